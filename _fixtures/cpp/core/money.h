@@ -0,0 +1,7 @@
+#pragma once
+
+namespace core {
+struct Money {
+    long cents;
+};
+}
@@ -0,0 +1,7 @@
+#include <string>
+#include "order.h"
+#include "core/money.h"
+
+core::Money Order::total() {
+    return core::Money{0};
+}
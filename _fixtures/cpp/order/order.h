@@ -0,0 +1,8 @@
+#pragma once
+
+#include "core/money.h"
+
+class Order {
+public:
+    core::Money total();
+};
@@ -0,0 +1,5 @@
+class Money {
+  final int cents;
+
+  Money(this.cents);
+}
@@ -0,0 +1,9 @@
+import 'package:flutter/material.dart';
+import 'package:shop/core/money.dart';
+import '../catalog/product.dart';
+
+class CartPage extends StatelessWidget {
+  Widget build(BuildContext context) {
+    return Container();
+  }
+}
@@ -0,0 +1,5 @@
+class Product {
+  final String name;
+
+  Product(this.name);
+}
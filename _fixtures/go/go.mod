@@ -0,0 +1,3 @@
+module github.com/inherd/shop
+
+go 1.16
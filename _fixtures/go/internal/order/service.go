@@ -0,0 +1,11 @@
+package order
+
+import "github.com/inherd/shop/internal/repo"
+
+type Service struct {
+	repo repo.Repository
+}
+
+func (s *Service) Find(id string) error {
+	return s.repo.Find(id)
+}
@@ -0,0 +1,5 @@
+package repo
+
+type Repository interface {
+	Find(id string) error
+}
@@ -0,0 +1,2 @@
+def money(amount):
+    return "%.2f" % amount
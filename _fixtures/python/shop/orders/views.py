@@ -0,0 +1,8 @@
+from django.views import View
+from .models import Order
+from ..core.utils import money
+
+
+class OrderView(View):
+    def get(self, request):
+        return money(Order().total())
@@ -0,0 +1,3 @@
+pub struct Cart {
+    pub size: usize,
+}
@@ -0,0 +1,5 @@
+pub mod service;
+
+pub struct Order {
+    pub id: String,
+}
@@ -0,0 +1,13 @@
+use crate::order::Order;
+use super::super::cart::Cart;
+use serde::Serialize;
+
+pub struct OrderService {
+    pub order: Order,
+}
+
+impl OrderService {
+    pub fn checkout(&self, cart: Cart) -> usize {
+        cart.size
+    }
+}
@@ -0,0 +1,7 @@
+import UIKit
+import Catalog
+
+class CartViewController: UIViewController {
+    func render(products: [Product]) {
+    }
+}
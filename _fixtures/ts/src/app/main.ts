@@ -0,0 +1,9 @@
+import { Component } from 'react';
+import { Logger } from '@shared/logger';
+import { login } from '../features/auth';
+
+export class App extends Component {
+  start() {
+    new Logger().info(login());
+  }
+}
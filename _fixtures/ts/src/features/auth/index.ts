@@ -0,0 +1,6 @@
+import { Logger } from '@shared/logger';
+
+export function login(): string {
+  new Logger().info('login');
+  return 'ok';
+}
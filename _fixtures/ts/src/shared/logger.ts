@@ -0,0 +1,5 @@
+export class Logger {
+  info(message: string) {
+    console.log(message);
+  }
+}
@@ -4,3 +4,11 @@
 #include <stdlib.h>
 
 char *from_string(const char *models, const char *rules);
+
+char *guarding_parse_rules(const char *rules);
+
+char *guarding_check_path(const char *rules, const char *path);
+
+const char *guarding_last_error(void);
+
+void guarding_free_string(char *string);
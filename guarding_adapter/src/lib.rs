@@ -1,35 +1,157 @@
+use std::cell::RefCell;
 use std::ffi::{CStr, CString};
 use std::os::raw::c_char;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::PathBuf;
+use std::ptr;
 
 use guarding_core::domain::code_file::CodeFile;
 use guarding_core::rule_executor::{RuleExecutor};
+pub use guarding_core::rule_executor::RuleSummary;
+use guarding_ident::ModelBuilder;
 use guarding_parser::parser;
 
+thread_local! {
+    static LAST_ERROR: RefCell<Option<CString>> = RefCell::new(None);
+}
+
+/// the errors of the rules on `models`, a JSON array of models, as JSON; NULL on errors, see
+/// `guarding_last_error`
 #[no_mangle]
 pub extern fn from_string(models: *const c_char, rules: *const c_char) -> *mut c_char {
-    let rule_str = unsafe {
-        assert!(!rules.is_null());
+    guarded("from_string", || {
+        let rules = parser::parse(read_str(rules, "rules")?).map_err(|err| err.to_string())?;
+        let code_files: Vec<CodeFile> = serde_json::from_str(read_str(models, "models")?)
+            .map_err(|err| format!("invalid models: {}", err))?;
+
+        let mut executor = RuleExecutor::new(code_files, rules);
+        executor.run();
+        serde_json::to_string(&executor.errors).map_err(|err| err.to_string())
+    })
+}
+
+/// the rules as a JSON array of their name, severity, level, tags and origin; NULL when they
+/// can't be parsed, see `guarding_last_error`
+#[no_mangle]
+pub extern fn guarding_parse_rules(rules: *const c_char) -> *mut c_char {
+    guarded("guarding_parse_rules", || read_str(rules, "rules").and_then(parse_rules))
+}
+
+/// the errors of the rules on the sources under `path` as JSON, like `from_string`; NULL on
+/// errors, see `guarding_last_error`
+#[no_mangle]
+pub extern fn guarding_check_path(rules: *const c_char, path: *const c_char) -> *mut c_char {
+    guarded("guarding_check_path", || check_path(read_str(rules, "rules")?, read_str(path, "path")?))
+}
 
-        CStr::from_ptr(rules)
-    };
+/// why the last call on this thread returned NULL, or NULL; owned by guarding, valid until the
+/// next call
+#[no_mangle]
+pub extern fn guarding_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ref().map(|err| err.as_ptr()).unwrap_or(ptr::null()))
+}
 
-    let rules = rule_str.to_str().unwrap();
-    let guard_rules = parser::parse(rules).unwrap();
+/// frees a string returned by guarding, NULL is ignored
+///
+/// # Safety
+///
+/// `string` is NULL or a string returned by guarding and not freed yet.
+#[no_mangle]
+pub unsafe extern fn guarding_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
 
-    let model = unsafe {
-        assert!(!models.is_null());
+fn parse_rules(rules: &str) -> Result<String, String> {
+    let rules = parser::parse(rules).map_err(|err| err.to_string())?;
+    let summaries: Vec<RuleSummary> = rules.iter().map(RuleSummary::of).collect();
 
-        CStr::from_ptr(models)
-    };
+    serde_json::to_string(&summaries).map_err(|err| err.to_string())
+}
 
-    let model_str = model.to_str().unwrap();
-    let code_files: Vec<CodeFile> = serde_json::from_str(model_str).unwrap();
+fn check_path(rules: &str, path: &str) -> Result<String, String> {
+    let rules = parser::parse(rules).map_err(|err| err.to_string())?;
+    let path = PathBuf::from(path);
+    if !path.exists() {
+        return Err(format!("{} doesn't exist", path.display()));
+    }
 
-    let mut executor = RuleExecutor::new(code_files, guard_rules);
+    let mut executor = RuleExecutor::from_project(ModelBuilder::build_project_by_dir(path), rules);
     executor.run();
+    serde_json::to_string(&executor.errors).map_err(|err| err.to_string())
+}
+
+fn read_str<'a>(string: *const c_char, name: &str) -> Result<&'a str, String> {
+    if string.is_null() {
+        return Err(format!("{} is NULL", name));
+    }
+
+    let string = unsafe { CStr::from_ptr(string) };
+    string.to_str().map_err(|_| format!("{} isn't UTF-8", name))
+}
+
+/// a panic of a grammar or a rule can't unwind into the host, it's an error of the call
+fn guarded(name: &str, call: impl FnOnce() -> Result<String, String>) -> *mut c_char {
+    let result = panic::catch_unwind(AssertUnwindSafe(call)).unwrap_or_else(|_| Err(format!("{} panicked", name)));
+    to_result(result)
+}
+
+fn to_result(result: Result<String, String>) -> *mut c_char {
+    let result = result.and_then(|json| CString::new(json).map_err(|err| err.to_string()));
+    match result {
+        Ok(json) => {
+            LAST_ERROR.with(|last| *last.borrow_mut() = None);
+            json.into_raw()
+        }
+        Err(err) => {
+            let err = CString::new(err.replace('\0', " ")).unwrap_or_default();
+            LAST_ERROR.with(|last| *last.borrow_mut() = Some(err));
+            ptr::null_mut()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+    use std::fs;
+
+    use crate::{from_string, guarded, guarding_check_path, guarding_free_string, guarding_last_error, guarding_parse_rules};
+
+    fn take(json: *mut std::os::raw::c_char) -> String {
+        let string = unsafe { CStr::from_ptr(json) }.to_str().unwrap().to_string();
+        unsafe { guarding_free_string(json) };
+        string
+    }
+
+    #[test]
+    fn should_check_a_path_through_the_c_abi() {
+        let dir = std::env::temp_dir().join("guarding").join("adapter");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Orders.java"), "package shop;\nclass Orders {}").unwrap();
+
+        let rules = CString::new("@name(\"naming\")\nclass::name should endsWith \"Service\";").unwrap();
+        assert!(take(guarding_parse_rules(rules.as_ptr())).contains("\"name\":\"naming\""));
+
+        let path = CString::new(dir.display().to_string()).unwrap();
+        assert!(take(guarding_check_path(rules.as_ptr(), path.as_ptr())).contains("Orders"));
+        assert!(guarding_last_error().is_null());
+
+        let invalid = CString::new("class::name should").unwrap();
+        assert!(guarding_parse_rules(invalid.as_ptr()).is_null());
+        assert!(!guarding_last_error().is_null());
+    }
+
+    #[test]
+    fn should_return_null_instead_of_panicking() {
+        let rules = CString::new("class::name should endsWith \"Service\";").unwrap();
+        let models = CString::new("not json").unwrap();
+        assert!(from_string(models.as_ptr(), rules.as_ptr()).is_null());
+        assert!(unsafe { CStr::from_ptr(guarding_last_error()) }.to_str().unwrap().starts_with("invalid models"));
 
-    let json = serde_json::to_string(&executor.errors).unwrap();
-    let c_str = CString::new(json).unwrap();
-    // println!("{:?}", c_str);
-    c_str.into_raw()
-}
\ No newline at end of file
+        assert!(guarded("check", || panic!("a grammar bug")).is_null());
+        assert_eq!("check panicked", unsafe { CStr::from_ptr(guarding_last_error()) }.to_str().unwrap());
+    }
+}
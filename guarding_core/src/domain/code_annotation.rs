@@ -1,15 +1,63 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::CodePoint;
+use crate::domain::Location;
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct CodeAnnotation {
     pub name: String,
-    pub key_values: Vec<AnnotationKeyValue>
+    pub key_values: Vec<AnnotationKeyValue>,
+    pub start: CodePoint,
+    pub end: CodePoint
+}
+
+impl Default for CodeAnnotation {
+    fn default() -> Self {
+        CodeAnnotation {
+            name: "".to_string(),
+            key_values: vec![],
+            start: Default::default(),
+            end: Default::default()
+        }
+    }
 }
 
+impl CodeAnnotation {
+    pub fn value_of(&self, key: &str) -> Option<&str> {
+        self.key_values.iter()
+            .find(|key_value| key_value.key == key)
+            .map(|key_value| key_value.value.as_str())
+    }
+}
+
+impl Location for CodeAnnotation {
+    fn set_start(&mut self, row: usize, column: usize) {
+        self.start.row = row;
+        self.start.column = column;
+    }
+
+    fn set_end(&mut self, row: usize, column: usize) {
+        self.end.row = row;
+        self.end.column = column;
+    }
+}
+
+/// `@RequestMapping(path = "/orders")` -> `path`: `/orders`; positional arguments are keyed by
+/// their index, except the single `value` of a Java-like annotation
 #[repr(C)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct AnnotationKeyValue {
     pub key: String,
-    pub values: Vec<AnnotationKeyValue>
+    pub value: String
+}
+
+impl AnnotationKeyValue {
+    pub fn new(key: &str, value: &str) -> AnnotationKeyValue {
+        AnnotationKeyValue {
+            key: key.to_string(),
+            value: value.to_string()
+        }
+    }
 }
@@ -1,17 +1,29 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::code_annotation::CodeAnnotation;
+use crate::domain::code_constructor::CodeConstructor;
+use crate::domain::code_field::CodeField;
 use crate::domain::code_function::CodeFunction;
 use crate::domain::CodePoint;
 
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct CodeClass {
     pub name: String,
     pub package: String,
+    /// `class`, `interface`, `enum`, `record`, `struct`, `trait` or `object`
+    pub kind: String,
+    /// abstract classes and the kinds without implementation, like interfaces and traits
+    pub is_abstract: bool,
     pub extends: Vec<String>,
     pub implements: Vec<String>,
     pub constant: Vec<ClassConstant>,
+    pub fields: Vec<CodeField>,
+    pub constructors: Vec<CodeConstructor>,
     pub functions: Vec<CodeFunction>,
+    pub annotations: Vec<CodeAnnotation>,
+    pub doc: String,
     pub start: CodePoint,
     pub end: CodePoint
 }
@@ -21,16 +33,41 @@ impl Default for CodeClass {
         CodeClass {
             name: "".to_string(),
             package: "".to_string(),
+            kind: "class".to_string(),
+            is_abstract: false,
             extends: vec![],
             implements: vec![],
             constant: vec![],
+            fields: vec![],
+            constructors: vec![],
             functions: vec![],
+            annotations: vec![],
+            doc: "".to_string(),
             start: Default::default(),
             end: Default::default()
         }
     }
 }
 
+impl CodeClass {
+    /// sets the kind, interfaces and traits are abstract by definition
+    pub fn set_kind(&mut self, kind: &str) {
+        self.kind = kind.to_string();
+        if kind == "interface" || kind == "trait" {
+            self.is_abstract = true;
+        }
+    }
+
+    /// `com.phodal.shop.Order`, or only the name of a class without package
+    pub fn full_name(&self) -> String {
+        if self.package.is_empty() {
+            self.name.clone()
+        } else {
+            format!("{}.{}", self.package, self.name)
+        }
+    }
+}
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct ClassConstant {
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::code_annotation::CodeAnnotation;
+use crate::domain::CodePoint;
+use crate::domain::Location;
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CodeConstructor {
+    pub parameters: Vec<CodeParameter>,
+    /// `public`, `protected`, `private` or `internal`, empty for the language default
+    pub visibility: String,
+    pub annotations: Vec<CodeAnnotation>,
+    /// the constructor called first, as written: `this`, `super` or `base`; empty when none is
+    pub delegates_to: String,
+    pub doc: String,
+    pub loc: usize,
+    pub complexity: usize,
+    pub start: CodePoint,
+    pub end: CodePoint
+}
+
+impl Default for CodeConstructor {
+    fn default() -> Self {
+        CodeConstructor {
+            parameters: vec![],
+            visibility: "".to_string(),
+            annotations: vec![],
+            delegates_to: "".to_string(),
+            doc: "".to_string(),
+            loc: 0,
+            complexity: 0,
+            start: Default::default(),
+            end: Default::default()
+        }
+    }
+}
+
+impl Location for CodeConstructor {
+    fn set_start(&mut self, row: usize, column: usize) {
+        self.start.row = row;
+        self.start.column = column;
+    }
+
+    fn set_end(&mut self, row: usize, column: usize) {
+        self.end.row = row;
+        self.end.column = column;
+    }
+}
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct CodeParameter {
+    pub name: String,
+    /// as written, empty for untyped languages
+    pub typ: String,
+}
+
+impl Default for CodeParameter {
+    fn default() -> Self {
+        CodeParameter {
+            name: "".to_string(),
+            typ: "".to_string()
+        }
+    }
+}
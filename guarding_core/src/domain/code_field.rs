@@ -0,0 +1,48 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::code_annotation::CodeAnnotation;
+use crate::domain::CodePoint;
+use crate::domain::Location;
+
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CodeField {
+    pub name: String,
+    pub typ: String,
+    /// `public`, `protected`, `private` or `internal`, empty for the language default
+    pub visibility: String,
+    pub annotations: Vec<CodeAnnotation>,
+    pub is_static: bool,
+    /// `final`, `val`, `const` or `readonly`
+    pub is_final: bool,
+    pub start: CodePoint,
+    pub end: CodePoint
+}
+
+impl Default for CodeField {
+    fn default() -> Self {
+        CodeField {
+            name: "".to_string(),
+            typ: "".to_string(),
+            visibility: "".to_string(),
+            annotations: vec![],
+            is_static: false,
+            is_final: false,
+            start: Default::default(),
+            end: Default::default()
+        }
+    }
+}
+
+impl Location for CodeField {
+    fn set_start(&mut self, row: usize, column: usize) {
+        self.start.row = row;
+        self.start.column = column;
+    }
+
+    fn set_end(&mut self, row: usize, column: usize) {
+        self.end.row = row;
+        self.end.column = column;
+    }
+}
@@ -2,16 +2,25 @@ use serde::{Deserialize, Serialize};
 
 use crate::domain::code_function::CodeFunction;
 use crate::domain::code_class::CodeClass;
+use crate::domain::code_import::CodeImport;
+use crate::domain::parse_warning::ParseWarning;
 
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct CodeFile {
     pub file_name: String,
     pub path: String,
     pub package: String,
-    pub imports: Vec<String>,
+    /// the name of the language which parsed it, like `python`, for the steps of that language
+    pub language: String,
+    pub imports: Vec<CodeImport>,
+    pub exports: Vec<String>,
     pub classes: Vec<CodeClass>,
     pub functions: Vec<CodeFunction>,
+    pub warnings: Vec<ParseWarning>,
+    /// why the file couldn't be identified, like a panicking identifier or an unreadable file
+    pub ident_error: Option<String>,
 }
 
 impl Default for CodeFile {
@@ -20,9 +29,52 @@ impl Default for CodeFile {
             file_name: "".to_string(),
             path: "".to_string(),
             package: "".to_string(),
+            language: "".to_string(),
             imports: vec![],
+            exports: vec![],
             classes: vec![],
             functions: vec![],
+            warnings: vec![],
+            ident_error: None,
         }
     }
 }
+
+impl CodeFile {
+    /// a file which couldn't be identified at all, so rules skip it but it's still reported
+    pub fn unparsed(message: &str) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.warnings.push(ParseWarning {
+            message: message.to_string(),
+            ..Default::default()
+        });
+        file
+    }
+
+    /// an unparsed file which failed, reported apart from the files with syntax errors
+    pub fn failed(message: &str) -> CodeFile {
+        let mut file = CodeFile::unparsed(message);
+        file.ident_error = Some(message.to_string());
+        file
+    }
+
+    /// drops the spare capacity left from extraction, the models of a project are kept until
+    /// its check is done
+    pub fn shrink_to_fit(&mut self) {
+        for class in self.classes.iter_mut() {
+            class.fields.shrink_to_fit();
+            class.constructors.shrink_to_fit();
+            class.functions.shrink_to_fit();
+            class.annotations.shrink_to_fit();
+        }
+        self.imports.shrink_to_fit();
+        self.exports.shrink_to_fit();
+        self.classes.shrink_to_fit();
+        self.functions.shrink_to_fit();
+        self.warnings.shrink_to_fit();
+    }
+
+    pub fn import_sources(&self) -> Vec<String> {
+        self.imports.iter().map(|import| import.source.clone()).collect()
+    }
+}
@@ -1,14 +1,23 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::code_annotation::CodeAnnotation;
+use crate::domain::code_lambda::CodeLambda;
 use crate::domain::CodePoint;
 use crate::domain::Location;
 
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct CodeFunction {
     pub name: String,
     // todo: thinking in access
     pub vars: Vec<String>,
+    pub annotations: Vec<CodeAnnotation>,
+    pub doc: String,
+    /// lines with code, blank and comment-only lines excluded
+    pub loc: usize,
+    pub complexity: usize,
+    pub lambdas: Vec<CodeLambda>,
     pub start: CodePoint,
     pub end: CodePoint
 }
@@ -18,6 +27,11 @@ impl Default for CodeFunction {
         CodeFunction {
             name: "".to_string(),
             vars: vec![],
+            annotations: vec![],
+            doc: "".to_string(),
+            loc: 0,
+            complexity: 0,
+            lambdas: vec![],
             start: Default::default(),
             end: Default::default()
         }
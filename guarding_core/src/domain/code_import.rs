@@ -1,9 +1,125 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::CodePoint;
+use crate::domain::Location;
+
 #[repr(C)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
 pub struct CodeImport {
-    pub name: String,
-    pub import: String,
+    /// the imported path rules match on, like `java.util.List`, `fmt` or a resolved `features.cart`
     pub source: String,
+    /// `java.util` of `java.util.List`, the whole source for module imports
+    pub package: String,
+    /// the imported symbol, empty when a whole module or package is imported
+    pub name: String,
+    pub is_wildcard: bool,
+    pub is_static: bool,
+    /// a third-party or standard library, not code of the project
+    pub is_external: bool,
+    /// the declared dependency an external import comes from, like `lodash` or `org.springframework`
+    pub library: String,
+    pub start: CodePoint,
+    pub end: CodePoint
+}
+
+impl Default for CodeImport {
+    fn default() -> Self {
+        CodeImport {
+            source: "".to_string(),
+            package: "".to_string(),
+            name: "".to_string(),
+            is_wildcard: false,
+            is_static: false,
+            is_external: false,
+            library: "".to_string(),
+            start: Default::default(),
+            end: Default::default()
+        }
+    }
+}
+
+impl CodeImport {
+    /// a whole module: `fmt`, `./cart`, `vector`
+    pub fn module(source: &str) -> CodeImport {
+        CodeImport {
+            source: source.to_string(),
+            package: source.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// `java.util.List` -> package `java.util`, name `List`
+    pub fn symbol(source: &str, separator: &str) -> CodeImport {
+        let (package, name) = match source.rfind(separator) {
+            Some(index) => (&source[..index], &source[index + separator.len()..]),
+            None => ("", source)
+        };
+
+        CodeImport {
+            source: source.to_string(),
+            package: package.to_string(),
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// `java.util.*` imports every symbol of `java.util`
+    pub fn wildcard(package: &str) -> CodeImport {
+        CodeImport {
+            is_wildcard: true,
+            ..CodeImport::module(package)
+        }
+    }
+
+    /// points the import to `source`, like a relative path resolved to a module, the symbol is kept
+    pub fn set_source(&mut self, source: String) {
+        self.package = match source.strip_suffix(self.name.as_str()) {
+            Some(package) if !self.name.is_empty() => {
+                package.trim_end_matches(|c| c == '.' || c == ':' || c == '/').to_string()
+            }
+            _ => source.clone()
+        };
+        self.source = source;
+    }
+}
+
+impl Location for CodeImport {
+    fn set_start(&mut self, row: usize, column: usize) {
+        self.start.row = row;
+        self.start.column = column;
+    }
+
+    fn set_end(&mut self, row: usize, column: usize) {
+        self.end.row = row;
+        self.end.column = column;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_import::CodeImport;
+
+    #[test]
+    fn should_split_symbol_import() {
+        let import = CodeImport::symbol("java.util.List", ".");
+        assert_eq!("java.util", import.package);
+        assert_eq!("List", import.name);
+
+        let import = CodeImport::symbol("crate::domain::CodeFile", "::");
+        assert_eq!("crate::domain", import.package);
+        assert_eq!("CodeFile", import.name);
+    }
+
+    #[test]
+    fn should_keep_symbol_when_source_changes() {
+        let mut import = CodeImport::symbol(".models.Order", ".");
+        import.set_source("shop.orders.models.Order".to_string());
+        assert_eq!("shop.orders.models", import.package);
+        assert_eq!("Order", import.name);
+
+        let mut import = CodeImport::module("./cart");
+        import.set_source("features.cart".to_string());
+        assert_eq!("features.cart", import.package);
+    }
 }
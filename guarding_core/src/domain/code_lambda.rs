@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::CodePoint;
+use crate::domain::Location;
+
+/// a lambda, closure or anonymous class inside a function
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct CodeLambda {
+    /// `lambda` or `anonymous_class`
+    pub kind: String,
+    /// the implemented type of an anonymous class, `Runnable` of `new Runnable() {}`, else empty
+    pub name: String,
+    /// the types used in the body, resolved to full names where known
+    pub references: Vec<String>,
+    pub start: CodePoint,
+    pub end: CodePoint
+}
+
+impl Default for CodeLambda {
+    fn default() -> Self {
+        CodeLambda {
+            kind: "".to_string(),
+            name: "".to_string(),
+            references: vec![],
+            start: Default::default(),
+            end: Default::default()
+        }
+    }
+}
+
+impl Location for CodeLambda {
+    fn set_start(&mut self, row: usize, column: usize) {
+        self.start.row = row;
+        self.start.column = column;
+    }
+
+    fn set_end(&mut self, row: usize, column: usize) {
+        self.end.row = row;
+        self.end.column = column;
+    }
+}
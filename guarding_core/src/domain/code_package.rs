@@ -1,11 +1,147 @@
 use serde::{Deserialize, Serialize};
 
 use crate::domain::code_class::CodeClass;
+use crate::domain::code_file::CodeFile;
 
+/// A package and its sub-packages, `com.phodal.shop` holds `com.phodal.shop.order` and so on.
+/// The root has an empty name and holds the files without package.
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
 pub struct CodePackage {
+    /// the last segment: `order` of `com.phodal.shop.order`
     pub name: String,
+    /// the full name: `com.phodal.shop.order`
     pub path: String,
-    pub class: Vec<CodeClass>,
+    pub files: Vec<String>,
+    pub classes: Vec<CodeClass>,
+    pub packages: Vec<CodePackage>,
+}
+
+impl Default for CodePackage {
+    fn default() -> Self {
+        CodePackage {
+            name: "".to_string(),
+            path: "".to_string(),
+            files: vec![],
+            classes: vec![],
+            packages: vec![],
+        }
+    }
+}
+
+impl CodePackage {
+    pub fn build(files: &[CodeFile]) -> CodePackage {
+        let mut root = CodePackage::default();
+        for file in files {
+            let package = root.ensure(&file.package);
+            package.files.push(file.path.clone());
+            package.classes.extend(file.classes.iter().cloned());
+        }
+
+        root.sort();
+        root
+    }
+
+    /// the package named `path`, created with its parents when missing
+    fn ensure(&mut self, path: &str) -> &mut CodePackage {
+        let mut current = self;
+        for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+            let index = match current.packages.iter().position(|package| package.name == segment) {
+                Some(index) => index,
+                None => {
+                    let path = if current.path.is_empty() {
+                        segment.to_string()
+                    } else {
+                        format!("{}.{}", current.path, segment)
+                    };
+                    current.packages.push(CodePackage { name: segment.to_string(), path, ..Default::default() });
+                    current.packages.len() - 1
+                }
+            };
+            current = &mut current.packages[index];
+        }
+
+        current
+    }
+
+    fn sort(&mut self) {
+        self.packages.sort_by(|a, b| a.name.cmp(&b.name));
+        for package in self.packages.iter_mut() {
+            package.sort();
+        }
+    }
+
+    pub fn find(&self, path: &str) -> Option<&CodePackage> {
+        let mut current = self;
+        for segment in path.split('.').filter(|segment| !segment.is_empty()) {
+            current = current.packages.iter().find(|package| package.name == segment)?;
+        }
+
+        Some(current)
+    }
+
+    /// this package and every package below it, parents first
+    pub fn descendants(&self) -> Vec<&CodePackage> {
+        let mut packages = vec![self];
+        for package in &self.packages {
+            packages.extend(package.descendants());
+        }
+
+        packages
+    }
+
+    /// the classes of this package and all sub-packages
+    pub fn all_classes(&self) -> Vec<&CodeClass> {
+        self.descendants().into_iter().flat_map(|package| package.classes.iter()).collect()
+    }
+
+    pub fn class_count(&self) -> usize {
+        self.descendants().iter().map(|package| package.classes.len()).sum()
+    }
+
+    pub fn file_count(&self) -> usize {
+        self.descendants().iter().map(|package| package.files.len()).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_package::CodePackage;
+
+    fn file(path: &str, package: &str, classes: &[&str]) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.path = path.to_string();
+        file.package = package.to_string();
+        for name in classes {
+            let mut class = CodeClass::default();
+            class.name = name.to_string();
+            file.classes.push(class);
+        }
+        file
+    }
+
+    #[test]
+    fn should_build_package_tree() {
+        let files = vec![
+            file("Order.java", "shop.order", &["Order"]),
+            file("OrderRepo.java", "shop.order.repo", &["OrderRepo", "OrderDao"]),
+            file("Cart.java", "shop.cart", &["Cart"]),
+            file("main.go", "", &[]),
+        ];
+        let root = CodePackage::build(&files);
+
+        assert_eq!(vec!["main.go"], root.files);
+        let shop = root.find("shop").unwrap();
+        assert_eq!(vec!["cart", "order"], shop.packages.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(4, shop.class_count());
+
+        let order = root.find("shop.order").unwrap();
+        assert_eq!("shop.order", order.path);
+        assert_eq!(1, order.classes.len());
+        assert_eq!(2, order.file_count());
+        assert!(root.find("shop.payment").is_none());
+    }
 }
@@ -0,0 +1,80 @@
+use crate::domain::code_class::CodeClass;
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_package::CodePackage;
+use crate::resolver::{DependencyGraph, SymbolTable};
+
+/// all the files of a project with what is derived from them: the package tree, the classes by
+/// full name and the package dependencies
+#[derive(Debug, Clone, Default)]
+pub struct CodeProject {
+    pub files: Vec<CodeFile>,
+    pub root: CodePackage,
+    pub symbols: SymbolTable,
+    pub dependencies: DependencyGraph,
+}
+
+impl CodeProject {
+    /// `files` are expected to be resolved already, as `ModelBuilder` does
+    pub fn new(files: Vec<CodeFile>) -> CodeProject {
+        CodeProject {
+            root: CodePackage::build(&files),
+            symbols: SymbolTable::build(&files),
+            dependencies: DependencyGraph::build(&files),
+            files,
+        }
+    }
+
+    pub fn class(&self, full_name: &str) -> Option<&CodeClass> {
+        self.symbols.class(&self.files, full_name)
+    }
+
+    pub fn file(&self, path: &str) -> Option<&CodeFile> {
+        self.files.iter().find(|file| file.path == path)
+    }
+
+    /// the file declaring the class
+    pub fn file_of(&self, full_name: &str) -> Option<&CodeFile> {
+        self.symbols.path_of(full_name).and_then(|path| self.file(path))
+    }
+
+    pub fn package(&self, path: &str) -> Option<&CodePackage> {
+        self.root.find(path)
+    }
+
+    /// the files of exactly this package, sub-packages excluded
+    pub fn files_in(&self, package: &str) -> Vec<&CodeFile> {
+        self.files.iter().filter(|file| file.package == package).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_import::CodeImport;
+    use crate::domain::code_project::CodeProject;
+
+    fn file(path: &str, package: &str, class: &str) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.path = path.to_string();
+        file.package = package.to_string();
+        let mut code_class = CodeClass::default();
+        code_class.name = class.to_string();
+        file.classes.push(code_class);
+        file
+    }
+
+    #[test]
+    fn should_lookup_by_name_path_and_package() {
+        let mut order = file("shop/order/Order.java", "shop.order", "Order");
+        order.imports.push(CodeImport::symbol("shop.cart.Cart", "."));
+        let project = CodeProject::new(vec![order, file("shop/cart/Cart.java", "shop.cart", "Cart")]);
+
+        assert_eq!("Cart", project.class("shop.cart.Cart").unwrap().name);
+        assert_eq!("shop/cart/Cart.java", project.file_of("shop.cart.Cart").unwrap().path);
+        assert_eq!("shop.order", project.file("shop/order/Order.java").unwrap().package);
+        assert_eq!(2, project.package("shop").unwrap().class_count());
+        assert_eq!(1, project.files_in("shop.cart").len());
+        assert_eq!(vec!["shop.cart"], project.dependencies.dependencies_of("shop.order"));
+    }
+}
@@ -5,10 +5,17 @@ use code_class::CodeClass;
 pub mod code_import;
 pub mod code_file;
 pub mod code_package;
+pub mod code_project;
 pub mod code_module;
 pub mod code_class;
 pub mod code_function;
+pub mod code_constructor;
+pub mod code_lambda;
+pub mod code_field;
 pub mod code_annotation;
+pub mod parse_warning;
+pub mod model_export;
+pub mod project_diff;
 
 impl Location for CodeClass {
     fn set_start(&mut self, row: usize, column: usize) {
@@ -28,7 +35,7 @@ pub trait Location {
 }
 
 #[repr(C)]
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct CodePoint {
     pub row: usize,
     pub column: usize
@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_project::CodeProject;
+
+/// bumped on every change of the exported fields, older readers can refuse what they don't know
+pub const MODEL_SCHEMA_VERSION: u32 = 1;
+
+/// the extracted model as written by `guarding_ident`, for tools which don't run the rules
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ModelExport {
+    pub schema_version: u32,
+    pub files: Vec<CodeFile>,
+    pub dependencies: Vec<PackageDependency>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PackageDependency {
+    pub from: String,
+    pub to: String,
+}
+
+impl ModelExport {
+    pub fn from_project(project: &CodeProject) -> ModelExport {
+        let dependencies = project.dependencies.edges().into_iter()
+            .map(|(from, to)| PackageDependency { from: from.to_string(), to: to.to_string() })
+            .collect();
+
+        ModelExport {
+            schema_version: MODEL_SCHEMA_VERSION,
+            files: project.files.clone(),
+            dependencies,
+        }
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    pub fn from_json(content: &str) -> Result<ModelExport, String> {
+        let export: ModelExport = serde_json::from_str(content).map_err(|err| err.to_string())?;
+        if export.schema_version > MODEL_SCHEMA_VERSION {
+            return Err(format!("unsupported model schema version {}, the latest known is {}", export.schema_version, MODEL_SCHEMA_VERSION));
+        }
+
+        Ok(export)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_import::CodeImport;
+    use crate::domain::code_project::CodeProject;
+    use crate::domain::model_export::{MODEL_SCHEMA_VERSION, ModelExport};
+
+    #[test]
+    fn should_round_trip_export() {
+        let mut file = CodeFile::default();
+        file.path = "shop/Order.java".to_string();
+        file.package = "shop".to_string();
+        file.imports.push(CodeImport::symbol("java.util.List", "."));
+        let export = ModelExport::from_project(&CodeProject::new(vec![file]));
+
+        let json = export.to_json();
+        assert!(json.contains("\"schema_version\": 1"));
+
+        let read = ModelExport::from_json(&json).unwrap();
+        assert_eq!(MODEL_SCHEMA_VERSION, read.schema_version);
+        assert_eq!("shop/Order.java", read.files[0].path);
+        assert_eq!("java.util", read.dependencies[0].to);
+    }
+
+    #[test]
+    fn should_refuse_newer_schema() {
+        let content = "{\"schema_version\": 99, \"files\": [], \"dependencies\": []}";
+        assert!(ModelExport::from_json(content).is_err());
+    }
+}
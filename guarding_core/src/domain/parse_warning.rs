@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+use crate::domain::CodePoint;
+use crate::domain::Location;
+
+/// something in a file that couldn't be parsed, the rest of the file is still identified
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct ParseWarning {
+    pub message: String,
+    pub start: CodePoint,
+    pub end: CodePoint
+}
+
+impl Default for ParseWarning {
+    fn default() -> Self {
+        ParseWarning {
+            message: "".to_string(),
+            start: Default::default(),
+            end: Default::default()
+        }
+    }
+}
+
+impl Location for ParseWarning {
+    fn set_start(&mut self, row: usize, column: usize) {
+        self.start.row = row;
+        self.start.column = column;
+    }
+
+    fn set_end(&mut self, row: usize, column: usize) {
+        self.end.row = row;
+        self.end.column = column;
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::code_function::CodeFunction;
+use crate::domain::code_project::CodeProject;
+use crate::domain::model_export::PackageDependency;
+
+/// what changed between two analyses of a project, like the base and the head of a pull request
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct ProjectDiff {
+    pub added_classes: Vec<String>,
+    pub removed_classes: Vec<String>,
+    pub added_dependencies: Vec<PackageDependency>,
+    pub removed_dependencies: Vec<PackageDependency>,
+    /// functions found in both analyses whose metrics changed
+    pub changed_functions: Vec<FunctionDelta>,
+    pub loc_delta: i64,
+    pub complexity_delta: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct FunctionDelta {
+    /// `full.ClassName#function` for methods, `path#function` for top-level functions
+    pub name: String,
+    pub loc_before: usize,
+    pub loc_after: usize,
+    pub complexity_before: usize,
+    pub complexity_after: usize,
+}
+
+impl ProjectDiff {
+    pub fn between(old: &CodeProject, new: &CodeProject) -> ProjectDiff {
+        let old_classes: BTreeSet<&str> = old.symbols.full_names().into_iter().collect();
+        let new_classes: BTreeSet<&str> = new.symbols.full_names().into_iter().collect();
+
+        let old_edges: BTreeSet<(&str, &str)> = old.dependencies.edges().into_iter().collect();
+        let new_edges: BTreeSet<(&str, &str)> = new.dependencies.edges().into_iter().collect();
+
+        let old_functions = ProjectDiff::functions(old);
+        let new_functions = ProjectDiff::functions(new);
+        let mut changed_functions = vec![];
+        for (name, after) in &new_functions {
+            if let Some(before) = old_functions.get(name) {
+                if before.loc != after.loc || before.complexity != after.complexity {
+                    changed_functions.push(FunctionDelta {
+                        name: name.clone(),
+                        loc_before: before.loc,
+                        loc_after: after.loc,
+                        complexity_before: before.complexity,
+                        complexity_after: after.complexity,
+                    });
+                }
+            }
+        }
+
+        ProjectDiff {
+            added_classes: new_classes.difference(&old_classes).map(|name| name.to_string()).collect(),
+            removed_classes: old_classes.difference(&new_classes).map(|name| name.to_string()).collect(),
+            added_dependencies: ProjectDiff::dependencies(new_edges.difference(&old_edges).collect()),
+            removed_dependencies: ProjectDiff::dependencies(old_edges.difference(&new_edges).collect()),
+            changed_functions,
+            loc_delta: ProjectDiff::total(&new_functions, |f| f.loc) - ProjectDiff::total(&old_functions, |f| f.loc),
+            complexity_delta: ProjectDiff::total(&new_functions, |f| f.complexity) - ProjectDiff::total(&old_functions, |f| f.complexity),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_classes.is_empty()
+            && self.removed_classes.is_empty()
+            && self.added_dependencies.is_empty()
+            && self.removed_dependencies.is_empty()
+            && self.changed_functions.is_empty()
+    }
+
+    /// every function by a name stable across analyses, the first one wins for overloads
+    fn functions(project: &CodeProject) -> BTreeMap<String, &CodeFunction> {
+        let mut functions = BTreeMap::new();
+        for file in &project.files {
+            for function in &file.functions {
+                functions.entry(format!("{}#{}", file.path, function.name)).or_insert(function);
+            }
+
+            for class in &file.classes {
+                let package = if class.package.is_empty() { &file.package } else { &class.package };
+                let owner = if package.is_empty() { class.name.clone() } else { format!("{}.{}", package, class.name) };
+                for function in &class.functions {
+                    functions.entry(format!("{}#{}", owner, function.name)).or_insert(function);
+                }
+            }
+        }
+
+        functions
+    }
+
+    fn dependencies(edges: Vec<&(&str, &str)>) -> Vec<PackageDependency> {
+        edges.into_iter().map(|(from, to)| PackageDependency { from: from.to_string(), to: to.to_string() }).collect()
+    }
+
+    fn total(functions: &BTreeMap<String, &CodeFunction>, metric: fn(&CodeFunction) -> usize) -> i64 {
+        functions.values().map(|function| metric(function) as i64).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_function::CodeFunction;
+    use crate::domain::code_import::CodeImport;
+    use crate::domain::code_project::CodeProject;
+    use crate::domain::project_diff::ProjectDiff;
+
+    fn file(classes: &[&str], complexity: usize, imports: &[&str]) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.path = "shop/Order.java".to_string();
+        file.package = "shop".to_string();
+        for name in classes {
+            let mut class = CodeClass::default();
+            class.name = name.to_string();
+            let mut function = CodeFunction::default();
+            function.name = "total".to_string();
+            function.loc = 10;
+            function.complexity = complexity;
+            class.functions.push(function);
+            file.classes.push(class);
+        }
+        for import in imports {
+            file.imports.push(CodeImport::symbol(import, "."));
+        }
+        file
+    }
+
+    #[test]
+    fn should_diff_classes_dependencies_and_metrics() {
+        let old = CodeProject::new(vec![file(&["Order", "Cart"], 2, &["shop.db.Table"])]);
+        let new = CodeProject::new(vec![file(&["Order", "Invoice"], 5, &["shop.http.Client"])]);
+
+        let diff = ProjectDiff::between(&old, &new);
+
+        assert_eq!(vec!["shop.Invoice"], diff.added_classes);
+        assert_eq!(vec!["shop.Cart"], diff.removed_classes);
+        assert_eq!("shop.http", diff.added_dependencies[0].to);
+        assert_eq!("shop.db", diff.removed_dependencies[0].to);
+        assert_eq!(1, diff.changed_functions.len());
+        assert_eq!("shop.Order#total", diff.changed_functions[0].name);
+        assert_eq!(6, diff.complexity_delta);
+        assert_eq!(0, diff.loc_delta);
+    }
+
+    #[test]
+    fn should_be_empty_for_same_project() {
+        let project = CodeProject::new(vec![file(&["Order"], 2, &[])]);
+        assert!(ProjectDiff::between(&project, &project).is_empty());
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+/// A name of an [`Interner`], like a package or a full class name; comparing and hashing it
+/// is comparing two numbers instead of two strings.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NameId(u32);
+
+impl NameId {
+    pub fn index(&self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Keeps every distinct name once, the thousands of imports of a package share its string.
+/// The ids are only valid for the interner which gave them out.
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(from = "Vec<String>", into = "Vec<String>")]
+pub struct Interner {
+    ids: HashMap<Arc<str>, NameId>,
+    names: Vec<Arc<str>>,
+}
+
+impl Interner {
+    pub fn intern(&mut self, name: &str) -> NameId {
+        if let Some(id) = self.ids.get(name) {
+            return *id;
+        }
+
+        let id = NameId(self.names.len() as u32);
+        let name: Arc<str> = Arc::from(name);
+        self.names.push(name.clone());
+        self.ids.insert(name, id);
+        id
+    }
+
+    /// the id of `name` if it was interned, without adding it
+    pub fn get(&self, name: &str) -> Option<NameId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn resolve(&self, id: NameId) -> &str {
+        &self.names[id.index()]
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+}
+
+impl From<Vec<String>> for Interner {
+    fn from(names: Vec<String>) -> Self {
+        let mut interner = Interner::default();
+        for name in names {
+            interner.intern(&name);
+        }
+        interner
+    }
+}
+
+impl From<Interner> for Vec<String> {
+    fn from(interner: Interner) -> Self {
+        interner.names.iter().map(|name| name.to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::intern::Interner;
+
+    #[test]
+    fn should_intern_each_name_once() {
+        let mut names = Interner::default();
+        let order = names.intern("shop.order");
+        let cart = names.intern("shop.cart");
+
+        assert_eq!(order, names.intern("shop.order"));
+        assert_ne!(order, cart);
+        assert_eq!("shop.cart", names.resolve(cart));
+        assert_eq!(Some(order), names.get("shop.order"));
+        assert_eq!(None, names.get("shop.user"));
+        assert_eq!(2, names.len());
+    }
+
+    #[test]
+    fn should_keep_ids_through_serde() {
+        let mut names = Interner::default();
+        names.intern("shop.order");
+        let cart = names.intern("shop.cart");
+
+        let json = serde_json::to_string(&names).unwrap();
+        assert_eq!("[\"shop.order\",\"shop.cart\"]", json);
+        let names: Interner = serde_json::from_str(&json).unwrap();
+        assert_eq!(Some(cart), names.get("shop.cart"));
+    }
+}
@@ -1,2 +1,4 @@
 pub mod rule_executor;
 pub mod domain;
+pub mod intern;
+pub mod resolver;
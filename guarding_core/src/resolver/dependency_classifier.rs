@@ -0,0 +1,124 @@
+use std::collections::BTreeSet;
+
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_import::CodeImport;
+use crate::resolver::symbol_table::SymbolTable;
+
+/// what the build files tell about the project: its own names and the declared dependencies
+#[derive(Debug, Clone, Default)]
+pub struct BuildMetadata {
+    /// the project itself, like a Go module, an npm package, a crate or a Maven group
+    pub names: Vec<String>,
+    /// declared dependencies, like `lodash`, `serde` or the group `org.springframework`
+    pub libraries: Vec<String>,
+}
+
+/// An import is internal when it's relative, points to a class or package of the project, or
+/// starts with one of the project names; everything else is external.
+pub struct DependencyClassifier {
+    symbols: SymbolTable,
+    packages: BTreeSet<String>,
+    metadata: BuildMetadata,
+}
+
+impl DependencyClassifier {
+    pub fn new(files: &[CodeFile], metadata: BuildMetadata) -> DependencyClassifier {
+        let packages = files.iter()
+            .map(|file| file.package.clone())
+            .filter(|package| !package.is_empty())
+            .collect();
+
+        DependencyClassifier { symbols: SymbolTable::build(files), packages, metadata }
+    }
+
+    pub fn classify_files(files: &mut Vec<CodeFile>, metadata: BuildMetadata) {
+        let classifier = DependencyClassifier::new(files, metadata);
+        for file in files.iter_mut() {
+            for import in file.imports.iter_mut() {
+                classifier.classify(import);
+            }
+        }
+    }
+
+    pub fn classify(&self, import: &mut CodeImport) {
+        import.is_external = !self.is_internal(import);
+        import.library = if import.is_external {
+            self.library_of(&import.source).unwrap_or_default()
+        } else {
+            "".to_string()
+        };
+    }
+
+    fn is_internal(&self, import: &CodeImport) -> bool {
+        let source = import.source.as_str();
+        // `./cart`, `.models` of Python and the paths of the own crate
+        let relative = ["crate::", "self::", "super::"];
+        if source.starts_with('.') || relative.iter().any(|prefix| source.starts_with(prefix)) {
+            return true;
+        }
+
+        if self.symbols.contains(source) || self.packages.contains(&import.package) || self.packages.contains(source) {
+            return true;
+        }
+
+        self.metadata.names.iter().any(|name| DependencyClassifier::is_under(source, name))
+    }
+
+    /// the longest declared library `source` belongs to
+    fn library_of(&self, source: &str) -> Option<String> {
+        self.metadata.libraries.iter()
+            .filter(|library| DependencyClassifier::is_under(source, library))
+            .max_by_key(|library| library.len())
+            .cloned()
+    }
+
+    /// `lodash/fp` is under `lodash`, `serde::Deserialize` under `serde`, `lodashx` isn't
+    fn is_under(source: &str, name: &str) -> bool {
+        if name.is_empty() || !source.starts_with(name) {
+            return false;
+        }
+
+        let rest = &source[name.len()..];
+        rest.is_empty() || rest.starts_with(|c| c == '.' || c == '/' || c == ':')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_import::CodeImport;
+    use crate::resolver::dependency_classifier::{BuildMetadata, DependencyClassifier};
+
+    #[test]
+    fn should_classify_imports() {
+        let mut order = CodeFile::default();
+        order.package = "shop.order".to_string();
+        let mut class = CodeClass::default();
+        class.name = "Order".to_string();
+        order.classes.push(class);
+
+        let mut service = CodeFile::default();
+        service.package = "shop.service".to_string();
+        service.imports = vec![
+            CodeImport::symbol("shop.order.Order", "."),
+            CodeImport::symbol("shop.util.Strings", "."),
+            CodeImport::symbol("org.springframework.stereotype.Service", "."),
+            CodeImport::symbol("java.util.List", "."),
+            CodeImport::module("./cart"),
+        ];
+
+        let metadata = BuildMetadata {
+            names: vec!["shop.util".to_string()],
+            libraries: vec!["org.springframework".to_string(), "org".to_string()],
+        };
+        let mut files = vec![order, service];
+        DependencyClassifier::classify_files(&mut files, metadata);
+
+        let imports = &files[1].imports;
+        let external: Vec<bool> = imports.iter().map(|import| import.is_external).collect();
+        assert_eq!(vec![false, false, true, true, false], external);
+        assert_eq!("org.springframework", imports[2].library);
+        assert_eq!("", imports[3].library);
+    }
+}
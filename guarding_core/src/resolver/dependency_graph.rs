@@ -0,0 +1,243 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_function::CodeFunction;
+use crate::intern::{Interner, NameId};
+
+/// which package uses which, from the imports and the resolved supertypes, field types and lambda references;
+/// the packages are interned, so the edges are pairs of ids
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DependencyGraph {
+    names: Interner,
+    edges: BTreeMap<NameId, BTreeSet<NameId>>,
+}
+
+impl DependencyGraph {
+    pub fn build(files: &[CodeFile]) -> DependencyGraph {
+        let mut graph = DependencyGraph::default();
+        for file in files {
+            let from = graph.names.intern(&file.package);
+            graph.edges.entry(from).or_insert_with(BTreeSet::new);
+
+            for import in &file.imports {
+                graph.add(from, &import.package);
+            }
+
+            for class in &file.classes {
+                let types = class.extends.iter()
+                    .chain(class.implements.iter())
+                    .chain(class.fields.iter().map(|field| &field.typ))
+                    .chain(DependencyGraph::lambda_references(&class.functions));
+
+                for typ in types {
+                    graph.add_type(from, typ);
+                }
+            }
+
+            for typ in DependencyGraph::lambda_references(&file.functions) {
+                graph.add_type(from, typ);
+            }
+        }
+
+        graph
+    }
+
+    /// only resolved types, `shop.Order` depends on `shop`, a plain `Order` on nothing known
+    fn add_type(&mut self, from: NameId, typ: &str) {
+        let head = typ.split(|c| c == '<' || c == '[').next().unwrap_or(typ);
+        if let Some(index) = head.rfind('.') {
+            self.add(from, &head[..index]);
+        }
+    }
+
+    fn lambda_references(functions: &[CodeFunction]) -> impl Iterator<Item=&String> {
+        functions.iter()
+            .flat_map(|function| function.lambdas.iter())
+            .flat_map(|lambda| lambda.references.iter())
+    }
+
+    fn add(&mut self, from: NameId, to: &str) {
+        if to.is_empty() {
+            return;
+        }
+        let to = self.names.intern(to);
+        if from == to {
+            return;
+        }
+
+        self.edges.entry(from).or_insert_with(BTreeSet::new).insert(to);
+    }
+
+    /// the packages of the project and everything they depend on
+    pub fn nodes(&self) -> BTreeSet<&str> {
+        self.node_ids().into_iter().map(|id| self.names.resolve(id)).collect()
+    }
+
+    pub fn edges(&self) -> Vec<(&str, &str)> {
+        let mut edges: Vec<(&str, &str)> = self.edges.iter()
+            .flat_map(|(from, targets)| targets.iter().map(move |to| (*from, *to)))
+            .map(|(from, to)| (self.names.resolve(from), self.names.resolve(to)))
+            .collect();
+        edges.sort();
+        edges
+    }
+
+    pub fn dependencies_of(&self, package: &str) -> Vec<&str> {
+        match self.names.get(package) {
+            Some(id) => self.sorted_names(self.targets_of(id)),
+            None => vec![]
+        }
+    }
+
+    pub fn dependents_of(&self, package: &str) -> Vec<&str> {
+        let id = match self.names.get(package) {
+            Some(id) => id,
+            None => return vec![]
+        };
+        let dependents = self.edges.iter()
+            .filter(|(_, targets)| targets.contains(&id))
+            .map(|(from, _)| *from);
+        self.sorted_names(dependents)
+    }
+
+    /// the groups of packages depending on each other, directly or through others, each one sorted
+    pub fn cycles(&self) -> Vec<Vec<&str>> {
+        let mut components = StrongComponents {
+            graph: self,
+            next_index: 0,
+            indices: BTreeMap::new(),
+            low_links: BTreeMap::new(),
+            stack: vec![],
+            components: vec![],
+        };
+        for node in self.node_ids() {
+            if !components.indices.contains_key(&node) {
+                components.connect(node);
+            }
+        }
+
+        let mut cycles: Vec<Vec<&str>> = components.components.into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|component| self.sorted_names(component))
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    fn node_ids(&self) -> BTreeSet<NameId> {
+        let mut nodes: BTreeSet<NameId> = self.edges.keys().copied().collect();
+        for targets in self.edges.values() {
+            nodes.extend(targets.iter().copied());
+        }
+
+        nodes
+    }
+
+    fn targets_of(&self, id: NameId) -> Vec<NameId> {
+        self.edges.get(&id).map(|targets| targets.iter().copied().collect()).unwrap_or_default()
+    }
+
+    /// the ids are in the order they were seen, the names are listed alphabetically
+    fn sorted_names(&self, ids: impl IntoIterator<Item=NameId>) -> Vec<&str> {
+        let mut names: Vec<&str> = ids.into_iter().map(|id| self.names.resolve(id)).collect();
+        names.sort();
+        names
+    }
+}
+
+/// Tarjan's algorithm, packages graphs are small enough for the recursion
+struct StrongComponents<'a> {
+    graph: &'a DependencyGraph,
+    next_index: usize,
+    indices: BTreeMap<NameId, usize>,
+    low_links: BTreeMap<NameId, usize>,
+    stack: Vec<NameId>,
+    components: Vec<Vec<NameId>>,
+}
+
+impl<'a> StrongComponents<'a> {
+    fn connect(&mut self, node: NameId) {
+        self.indices.insert(node, self.next_index);
+        self.low_links.insert(node, self.next_index);
+        self.next_index += 1;
+        self.stack.push(node);
+
+        let graph = self.graph;
+        for target in graph.targets_of(node) {
+            if !self.indices.contains_key(&target) {
+                self.connect(target);
+                let low_link = self.low_links[&node].min(self.low_links[&target]);
+                self.low_links.insert(node, low_link);
+            } else if self.stack.contains(&target) {
+                let low_link = self.low_links[&node].min(self.indices[&target]);
+                self.low_links.insert(node, low_link);
+            }
+        }
+
+        if self.low_links[&node] == self.indices[&node] {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_import::CodeImport;
+    use crate::resolver::dependency_graph::DependencyGraph;
+
+    #[test]
+    fn should_collect_package_dependencies() {
+        let mut order = CodeFile::default();
+        order.package = "shop.order".to_string();
+        order.imports.push(CodeImport::symbol("shop.core.Entity", "."));
+        order.imports.push(CodeImport::symbol("shop.order.OrderId", "."));
+        let mut class = CodeClass::default();
+        class.extends.push("shop.base.Aggregate<shop.order.OrderId>".to_string());
+        order.classes.push(class);
+
+        let mut core = CodeFile::default();
+        core.package = "shop.core".to_string();
+
+        let graph = DependencyGraph::build(&[order, core]);
+
+        assert_eq!(vec!["shop.base", "shop.core"], graph.dependencies_of("shop.order"));
+        assert_eq!(vec!["shop.order"], graph.dependents_of("shop.core"));
+        assert!(graph.dependencies_of("shop.core").is_empty());
+        assert_eq!(3, graph.nodes().len());
+        assert!(graph.cycles().is_empty());
+    }
+
+    #[test]
+    fn should_find_cycles() {
+        let file = |package: &str, imports: &[&str]| {
+            let mut file = CodeFile::default();
+            file.package = package.to_string();
+            for import in imports {
+                file.imports.push(CodeImport::symbol(import, "."));
+            }
+            file
+        };
+
+        let graph = DependencyGraph::build(&[
+            file("shop.order", &["shop.payment.Payment", "shop.core.Entity"]),
+            file("shop.payment", &["shop.invoice.Invoice"]),
+            file("shop.invoice", &["shop.order.Order"]),
+            file("shop.core", &[]),
+            file("shop.user", &["shop.auth.Token"]),
+            file("shop.auth", &["shop.user.User"]),
+        ]);
+
+        assert_eq!(vec![vec!["shop.auth", "shop.user"], vec!["shop.invoice", "shop.order", "shop.payment"]], graph.cycles());
+    }
+}
@@ -0,0 +1,9 @@
+pub use dependency_classifier::{BuildMetadata, DependencyClassifier};
+pub use dependency_graph::DependencyGraph;
+pub use symbol_table::SymbolTable;
+pub use type_resolver::TypeResolver;
+
+pub mod dependency_classifier;
+pub mod dependency_graph;
+pub mod symbol_table;
+pub mod type_resolver;
@@ -0,0 +1,143 @@
+use std::collections::HashMap;
+
+use crate::domain::code_class::CodeClass;
+use crate::domain::code_file::CodeFile;
+use crate::intern::{Interner, NameId};
+
+/// every class of the project by its full name, where it's in the files the table is built of;
+/// the classes aren't copied, only their interned names, paths and supertypes are kept
+#[derive(Debug, Clone, Default)]
+pub struct SymbolTable {
+    names: Interner,
+    /// the index of the file and of the class in the file
+    locations: HashMap<NameId, (usize, usize)>,
+    paths: HashMap<NameId, NameId>,
+    by_name: HashMap<NameId, Vec<NameId>>,
+    /// what the class extends or implements, as written
+    parents: HashMap<NameId, Vec<NameId>>,
+}
+
+impl SymbolTable {
+    pub fn build(files: &[CodeFile]) -> SymbolTable {
+        let mut table = SymbolTable::default();
+        for (file_index, file) in files.iter().enumerate() {
+            let path = table.names.intern(&file.path);
+            for (class_index, class) in file.classes.iter().enumerate() {
+                // not every language sets the package on its classes
+                let package = if class.package.is_empty() { &file.package } else { &class.package };
+                let full_name = if package.is_empty() { class.name.clone() } else { format!("{}.{}", package, class.name) };
+
+                let full_name = table.names.intern(&full_name);
+                let name = table.names.intern(&class.name);
+                let parents = class.extends.iter().chain(class.implements.iter()).map(|parent| table.names.intern(parent)).collect();
+                table.by_name.entry(name).or_insert_with(Vec::new).push(full_name);
+                table.paths.insert(full_name, path);
+                table.parents.insert(full_name, parents);
+                table.locations.insert(full_name, (file_index, class_index));
+            }
+        }
+
+        table
+    }
+
+    pub fn contains(&self, full_name: &str) -> bool {
+        self.location(full_name).is_some()
+    }
+
+    /// the index of the file declaring the class and of the class in it
+    pub fn location(&self, full_name: &str) -> Option<(usize, usize)> {
+        self.names.get(full_name).and_then(|id| self.locations.get(&id)).copied()
+    }
+
+    /// the class in `files`, the ones the table is built of; its package may be only on its file
+    pub fn class<'a>(&self, files: &'a [CodeFile], full_name: &str) -> Option<&'a CodeClass> {
+        self.location(full_name)
+            .and_then(|(file_index, class_index)| files.get(file_index)?.classes.get(class_index))
+    }
+
+    /// the file declaring the class
+    pub fn path_of(&self, full_name: &str) -> Option<&str> {
+        self.names.get(full_name)
+            .and_then(|id| self.paths.get(&id))
+            .map(|path| self.names.resolve(*path))
+    }
+
+    /// full names of the classes called `name`, in any package
+    pub fn named(&self, name: &str) -> Vec<&str> {
+        self.names.get(name)
+            .and_then(|id| self.by_name.get(&id))
+            .map(|names| names.iter().map(|name| self.names.resolve(*name)).collect())
+            .unwrap_or_default()
+    }
+
+    /// every full name, sorted
+    pub fn full_names(&self) -> Vec<&str> {
+        let mut names: Vec<&str> = self.locations.keys().map(|name| self.names.resolve(*name)).collect();
+        names.sort();
+        names
+    }
+
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// the classes `full_name` extends or implements, directly or not, as far as they are known
+    pub fn supertypes(&self, full_name: &str) -> Vec<String> {
+        let start = match self.names.get(full_name) {
+            Some(start) => start,
+            None => return vec![]
+        };
+
+        let mut supertypes: Vec<NameId> = vec![];
+        let mut pending = vec![start];
+        while let Some(current) = pending.pop() {
+            for parent in self.parents.get(&current).into_iter().flatten() {
+                if *parent != start && !supertypes.contains(parent) {
+                    supertypes.push(*parent);
+                    pending.push(*parent);
+                }
+            }
+        }
+
+        supertypes.into_iter().map(|name| self.names.resolve(name).to_string()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_file::CodeFile;
+    use crate::resolver::symbol_table::SymbolTable;
+
+    fn file(path: &str, package: &str, classes: &[(&str, &[&str])]) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.path = path.to_string();
+        file.package = package.to_string();
+        for (name, extends) in classes {
+            let mut class = CodeClass::default();
+            class.name = name.to_string();
+            class.extends = extends.iter().map(|name| name.to_string()).collect();
+            file.classes.push(class);
+        }
+        file
+    }
+
+    #[test]
+    fn should_point_into_the_files_without_copying_classes() {
+        let files = vec![
+            file("shop/Base.java", "shop", &[("Base", &[])]),
+            file("shop/Order.java", "shop", &[("Entity", &["shop.Base"]), ("Order", &["shop.Entity"])]),
+        ];
+        let table = SymbolTable::build(&files);
+
+        assert_eq!(Some((1, 1)), table.location("shop.Order"));
+        assert_eq!("Order", table.class(&files, "shop.Order").unwrap().name);
+        assert_eq!(Some("shop/Order.java"), table.path_of("shop.Entity"));
+        assert_eq!(vec!["shop.Entity", "shop.Base"], table.supertypes("shop.Order"));
+        assert_eq!(vec!["shop.Base", "shop.Entity", "shop.Order"], table.full_names());
+    }
+}
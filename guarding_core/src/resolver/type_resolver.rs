@@ -0,0 +1,173 @@
+use crate::domain::code_file::CodeFile;
+use crate::domain::code_function::CodeFunction;
+use crate::domain::code_import::CodeImport;
+use crate::resolver::symbol_table::SymbolTable;
+
+/// Turns type names as written in a file into full names: an explicit import wins, then a class of
+/// the same package, then the wildcard imports, and last a name which is already a known full name.
+pub struct TypeResolver {
+    pub table: SymbolTable,
+}
+
+impl TypeResolver {
+    pub fn new(files: &[CodeFile]) -> TypeResolver {
+        TypeResolver { table: SymbolTable::build(files) }
+    }
+
+    /// rewrites supertypes, field types and lambda references of every class to full names where they can be found,
+    /// unknown types are kept as written
+    pub fn resolve_files(files: &mut Vec<CodeFile>) {
+        let resolver = TypeResolver::new(files);
+        for file in files.iter_mut() {
+            let package = file.package.clone();
+            let imports = file.imports.clone();
+
+            for class in file.classes.iter_mut() {
+                let class_package = if class.package.is_empty() { &package } else { &class.package };
+                for name in class.extends.iter_mut().chain(class.implements.iter_mut()) {
+                    if let Some(full_name) = resolver.resolve_in(class_package, &imports, name) {
+                        *name = full_name;
+                    }
+                }
+
+                for field in class.fields.iter_mut() {
+                    if let Some(full_name) = resolver.resolve_in(class_package, &imports, &field.typ) {
+                        field.typ = full_name;
+                    }
+                }
+
+                for function in class.functions.iter_mut() {
+                    resolver.resolve_lambdas(class_package, &imports, function);
+                }
+            }
+
+            for function in file.functions.iter_mut() {
+                resolver.resolve_lambdas(&package, &imports, function);
+            }
+        }
+    }
+
+    fn resolve_lambdas(&self, package: &str, imports: &[CodeImport], function: &mut CodeFunction) {
+        for lambda in function.lambdas.iter_mut() {
+            for reference in lambda.references.iter_mut() {
+                if let Some(full_name) = self.resolve_in(package, imports, reference) {
+                    *reference = full_name;
+                }
+            }
+        }
+    }
+
+    pub fn resolve(&self, file: &CodeFile, name: &str) -> Option<String> {
+        self.resolve_in(&file.package, &file.imports, name)
+    }
+
+    /// `Repo<Order>` resolves `Repo` and keeps the type arguments
+    fn resolve_in(&self, package: &str, imports: &[CodeImport], name: &str) -> Option<String> {
+        let split = name.find(|c| c == '<' || c == '[' || c == '?').unwrap_or(name.len());
+        let (head, rest) = (name[..split].trim(), &name[split..]);
+        if head.is_empty() {
+            return None;
+        }
+
+        self.resolve_head(package, imports, head).map(|full_name| format!("{}{}", full_name, rest))
+    }
+
+    fn resolve_head(&self, package: &str, imports: &[CodeImport], head: &str) -> Option<String> {
+        // `Outer.Inner` is looked up by its first part
+        let (first, nested) = match head.find('.') {
+            Some(index) => (&head[..index], &head[index..]),
+            None => (head, "")
+        };
+
+        if let Some(import) = imports.iter().find(|import| !import.is_wildcard && import.name == first) {
+            return Some(format!("{}{}", import.source, nested));
+        }
+
+        let same_package = TypeResolver::join(package, head);
+        if self.table.contains(&same_package) {
+            return Some(same_package);
+        }
+
+        for import in imports.iter().filter(|import| import.is_wildcard) {
+            let candidate = TypeResolver::join(&import.package, head);
+            if self.table.contains(&candidate) {
+                return Some(candidate);
+            }
+        }
+
+        if self.table.contains(head) {
+            return Some(head.to_string());
+        }
+
+        None
+    }
+
+    fn join(package: &str, name: &str) -> String {
+        if package.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}.{}", package, name)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::domain::code_class::CodeClass;
+    use crate::domain::code_field::CodeField;
+    use crate::domain::code_file::CodeFile;
+    use crate::domain::code_import::CodeImport;
+    use crate::resolver::type_resolver::TypeResolver;
+
+    fn class(package: &str, name: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.package = package.to_string();
+        class.name = name.to_string();
+        class
+    }
+
+    fn file(package: &str, classes: Vec<CodeClass>, imports: Vec<CodeImport>) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.package = package.to_string();
+        file.classes = classes;
+        file.imports = imports;
+        file
+    }
+
+    #[test]
+    fn should_resolve_by_import_package_and_wildcard() {
+        let mut service = class("shop.order", "OrderService");
+        service.extends.push("BaseService".to_string());
+        service.implements.push("Finder<Order>".to_string());
+        service.implements.push("Closeable".to_string());
+        let mut field = CodeField::default();
+        field.typ = "OrderRepository".to_string();
+        service.fields.push(field);
+
+        let mut files = vec![
+            file("shop.order", vec![service, class("shop.order", "Order")], vec![
+                CodeImport::symbol("shop.core.BaseService", "."),
+                CodeImport::wildcard("shop.repo"),
+                CodeImport::symbol("java.io.Closeable", "."),
+            ]),
+            file("shop.order", vec![class("shop.order", "Finder")], vec![]),
+            file("shop.repo", vec![class("shop.repo", "OrderRepository")], vec![]),
+        ];
+
+        TypeResolver::resolve_files(&mut files);
+        let service = &files[0].classes[0];
+
+        assert_eq!(vec!["shop.core.BaseService"], service.extends);
+        assert_eq!(vec!["shop.order.Finder<Order>", "java.io.Closeable"], service.implements);
+        assert_eq!("shop.repo.OrderRepository", service.fields[0].typ);
+    }
+
+    #[test]
+    fn should_keep_unknown_types() {
+        let files = vec![file("shop", vec![class("shop", "Order")], vec![])];
+        let resolver = TypeResolver::new(&files);
+
+        assert_eq!(None, resolver.resolve(&files[0], "String"));
+        assert_eq!(Some("shop.Order[]".to_string()), resolver.resolve(&files[0], "Order[]"));
+    }
+}
@@ -1,17 +1,27 @@
+use std::collections::{BTreeSet, HashSet};
+use std::mem;
+
+use tracing::{debug, debug_span, info_span};
+
 use guarding_parser::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope};
 
 use crate::domain::code_class::CodeClass;
 use crate::domain::code_file::CodeFile;
-use crate::rule_executor::package_matcher::is_package_match;
-use crate::rule_executor::rule_error::{MismatchType, RuleErrorMsg};
+use crate::domain::code_import::CodeImport;
+use crate::domain::code_project::CodeProject;
+use crate::rule_executor::package_matcher::{is_package_match, is_type_match};
+use crate::rule_executor::rule_error::{ErrorLocation, MismatchType, RuleErrorMsg};
 
 #[derive(Debug, Clone)]
 pub struct RuleExecutor {
     pub errors: Vec<RuleErrorMsg>,
     pub rules: Vec<GuardRule>,
     pub models: Vec<CodeFile>,
+    /// without its files, they are moved to `models` instead of copied
+    pub project: CodeProject,
     pub filtered_models: Vec<CodeFile>,
-    pub filtered_classes: Vec<CodeClass>,
+    /// with the index of their file in `models`
+    pub filtered_classes: Vec<(usize, CodeClass)>,
 }
 
 impl Default for RuleExecutor {
@@ -20,6 +30,7 @@ impl Default for RuleExecutor {
             errors: Default::default(),
             rules: vec![],
             models: vec![],
+            project: Default::default(),
             filtered_models: vec![],
             filtered_classes: vec![],
         }
@@ -28,23 +39,39 @@ impl Default for RuleExecutor {
 
 impl RuleExecutor {
     pub fn new(models: Vec<CodeFile>, rules: Vec<GuardRule>) -> RuleExecutor {
+        RuleExecutor::from_project(CodeProject::new(models), rules)
+    }
+
+    pub fn from_project(mut project: CodeProject, rules: Vec<GuardRule>) -> RuleExecutor {
         RuleExecutor {
             errors: vec![],
             rules,
-            models,
+            models: mem::take(&mut project.files),
+            project,
             filtered_models: vec![],
             filtered_classes: vec![],
         }
     }
 
+    /// the files after the run, for reports, without copying them
+    pub fn into_files(self) -> Vec<CodeFile> {
+        self.models
+    }
+
     pub fn run(&mut self) {
-        self.rules
-            .clone()
-            .into_iter()
-            .enumerate()
-            .for_each(|(i, rule)| {
-                self.capture(rule, i);
-            });
+        self.run_with(&mut |_, _| {});
+    }
+
+    /// `run` calling `on_rule` with the errors of every rule once it ran, before the next one
+    pub fn run_with(&mut self, on_rule: &mut dyn FnMut(usize, Vec<RuleErrorMsg>)) {
+        let _span = info_span!("rules", count = self.rules.len()).entered();
+        for (i, rule) in self.rules.clone().into_iter().enumerate() {
+            let _rule = debug_span!("rule", index = i, origin = %rule.origin).entered();
+            self.capture(rule, i);
+
+            let errors = self.errors.iter().filter(|error| error.rule_index == i).cloned().collect();
+            on_rule(i, errors);
+        }
     }
 
     pub fn capture(&mut self, rule: GuardRule, index: usize) {
@@ -53,19 +80,35 @@ impl RuleExecutor {
                 self.capture_package(&rule, index)
             }
             RuleLevel::Function => {
-                println!("todo");
+                debug!("function rules are not run yet");
             }
             RuleLevel::Class => {
                 self.capture_class(&rule, index)
             }
             RuleLevel::Struct => {
-                println!("todo");
+                debug!("struct rules are not run yet");
             }
         };
 
         // todo: 3. run assert
     }
 
+    /// the classes in the scope of `rule` with where they are, for queries without an assert
+    pub fn select_classes(&mut self, rule: &GuardRule) -> Vec<(ErrorLocation, CodeClass)> {
+        self.filter_classes_by_scope(&rule);
+        self.filtered_classes.iter()
+            .map(|(file_index, class)| (self.class_location(*file_index, class), class.clone()))
+            .collect()
+    }
+
+    /// the files in the package scope of `rule`
+    pub fn select_files(&mut self, rule: &GuardRule) -> Vec<CodeFile> {
+        match &rule.scope {
+            RuleScope::PathDefine(path) if path != "." => self.filter_classes_by_package_identifier(path),
+            _ => self.models.clone()
+        }
+    }
+
     fn capture_class(&mut self, rule: &GuardRule, index: usize) {
         // - accessed(["..controller..", "..service.."]);
         // - dependBy ""
@@ -81,34 +124,42 @@ impl RuleExecutor {
     }
 
     fn filter_classes_by_scope(&mut self, rule: &&GuardRule) {
-        match &rule.scope {
-            RuleScope::PathDefine(str) => {
-                if str.as_str() == "." {
-                    for file in &self.models {
-                        self.filtered_classes.extend(file.classes.clone());
-                    }
-                } else {
-                    for file in &self.filter_classes_by_package_identifier(str) {
-                        self.filtered_classes.extend(file.classes.clone());
-                    }
+        let mut filtered_classes = vec![];
+        for (file_index, file) in self.models.iter().enumerate() {
+            for class in &file.classes {
+                if self.is_in_scope(&rule.scope, file, class) {
+                    filtered_classes.push((file_index, class.clone()));
                 }
             }
-            RuleScope::Implementation(str) => {
-                let mut filtered_classes = vec![];
-                let _ = &self.models.iter().for_each(|file| {
-                    let classes: Vec<CodeClass> = file.classes.iter()
-                        .filter(|class| {
-                            class.implements.contains(str)
-                        })
-                        .map(|s| s.clone())
-                        .collect();
-
-                    filtered_classes.extend(classes);
-                });
+        }
 
-                self.filtered_classes.extend(filtered_classes);
+        self.filtered_classes = filtered_classes;
+    }
+
+    fn is_in_scope(&self, scope: &RuleScope, file: &CodeFile, class: &CodeClass) -> bool {
+        match scope {
+            RuleScope::All => true,
+            RuleScope::PathDefine(str) => str.as_str() == "." || is_package_match(str.to_string(), file.package.as_str()),
+            RuleScope::Implementation(str) => class.implements.iter().any(|name| is_type_match(name, str)),
+            RuleScope::Extend(str) => class.extends.iter().any(|name| is_type_match(name, str)),
+            // direct or indirect subtypes, so the resolved full names are needed
+            RuleScope::Assignable(str) => {
+                let mut class_in_file = class.clone();
+                if class_in_file.package.is_empty() {
+                    class_in_file.package = file.package.clone();
+                }
+
+                let full_name = class_in_file.full_name();
+                self.project.symbols.supertypes(&full_name).iter().any(|name| is_type_match(name, str))
             }
-            _ => {}
+            RuleScope::Kind(kind) => {
+                let (excluded, kind) = match kind.strip_prefix('!') {
+                    Some(kind) => (true, kind),
+                    None => (false, kind.as_str())
+                };
+                (class.kind == kind) != excluded
+            }
+            _ => false
         }
     }
 
@@ -124,8 +175,14 @@ impl RuleExecutor {
                         let string = GuardRule::assert_string(&rule);
                         self.process_name(index, &rule.ops, string)
                     }
+                    "doc" => {
+                        self.process_doc(index, &rule.ops)
+                    }
+                    "function" if props.get(1).map(|prop| prop.as_str()) == Some("doc") => {
+                        self.process_function_doc(index, &rule.ops)
+                    }
                     _ => {
-                        println!("todo: expr {:?}", props[0].as_str());
+                        debug!("unchecked property: {:?}", props[0].as_str());
                     }
                 }
             }
@@ -136,11 +193,11 @@ impl RuleExecutor {
                         if has_capture {
                             self.process_package_captures(index, &rule.ops, ident)
                         } else {
-                            println!("Empty Identifier: {:?}", ident);
+                            debug!("package rule without a capture: {:?}", ident);
                         }
                     }
                     &_ => {
-                        println!("Expr::Identifier: {:?}", ident);
+                        debug!("unchecked identifier: {:?}", ident);
                     }
                 }
             }
@@ -152,7 +209,12 @@ impl RuleExecutor {
 
         let mut assert_models: Vec<CodeFile> = vec![];
 
-        let operator = &rule.ops[0];
+        // `should not accessed([...])` forbids the packages instead of allowing them
+        let (negated, operator) = match rule.ops.as_slice() {
+            [Operator::Not, operator, ..] => (true, operator),
+            [operator, ..] => (false, operator),
+            [] => return false
+        };
         match operator {
             Operator::Accessed => {
                 match &rule.assert {
@@ -190,11 +252,11 @@ impl RuleExecutor {
 
         match operator {
             Operator::Accessed => {
-                let paths = self.search_by_access(pkg_identifier);
+                let paths = self.search_by_access(pkg_identifier, negated);
                 if paths.len() > 0 {
                     assert_success = false;
-                    paths.iter().for_each(|p| {
-                        error.items.push(p.clone());
+                    paths.into_iter().for_each(|(path, location)| {
+                        error.push_item(path, location);
                     });
                 }
             }
@@ -208,21 +270,18 @@ impl RuleExecutor {
         has_capture_assert
     }
 
-    fn search_by_access(&mut self, pkg_identifier: String) -> Vec<String> {
+    fn search_by_access(&mut self, pkg_identifier: String, negated: bool) -> Vec<(String, ErrorLocation)> {
         let mut error_paths = vec![];
+        // once per rule instead of comparing every path for every import
+        let assert_paths: HashSet<&str> = self.filtered_models.iter().map(|file| file.path.as_str()).collect();
         self.models.iter().for_each(|clz| {
             for imp in &clz.imports {
-                let is_file_import = is_package_match(pkg_identifier.clone(), imp.as_str());
+                let is_file_import = is_package_match(pkg_identifier.clone(), imp.source.as_str());
                 if is_file_import {
-                    let mut has_file_in_assert = false;
-                    let _ = &self.filtered_models.iter().for_each(|file| {
-                        if file.path == clz.path {
-                            has_file_in_assert = true;
-                        }
-                    });
-
-                    if !has_file_in_assert {
-                        error_paths.push(clz.path.clone());
+                    let has_file_in_assert = assert_paths.contains(clz.path.as_str());
+                    if has_file_in_assert == negated {
+                        let location = ErrorLocation { path: clz.path.clone(), start: imp.start.clone(), end: imp.end.clone() };
+                        error_paths.push((clz.path.clone(), location));
                     }
                 }
             }
@@ -262,6 +321,36 @@ impl RuleExecutor {
                             &_ => {}
                         };
                     }
+                    "cycle" if props.get(1).map(|prop| prop.as_str()) == Some("len") => {
+                        let packages: BTreeSet<&str> = self.filtered_models.iter().map(|file| file.package.as_str()).collect();
+                        let cycles: Vec<String> = self.project.dependencies.cycles().into_iter()
+                            .filter(|cycle| cycle.iter().any(|package| packages.contains(package)))
+                            .map(|cycle| cycle.join(" <-> "))
+                            .collect();
+
+                        let size = GuardRule::assert_sized(&rule);
+                        let count = self.errors.len();
+                        self.process_len(index, size, &rule.ops, cycles.len());
+                        if let Some(error) = self.errors.get_mut(count) {
+                            error.items.extend(cycles);
+                        }
+                    }
+                    "external" if props.get(1).map(|prop| prop.as_str()) == Some("import") => {
+                        let imports: Vec<(String, CodeImport)> = self.filtered_models.iter()
+                            .flat_map(|file| file.imports.iter()
+                                .filter(|import| import.is_external)
+                                .map(move |import| (file.path.clone(), import.clone())))
+                            .collect();
+
+                        match props.get(2).map(|prop| prop.as_str()) {
+                            Some("len") => {
+                                let size = GuardRule::assert_sized(&rule);
+                                self.process_len(index, size, &rule.ops, imports.len())
+                            }
+                            None => self.process_external_imports(index, rule, &imports),
+                            _ => {}
+                        }
+                    }
                     &_ => {}
                 }
             }
@@ -269,6 +358,38 @@ impl RuleExecutor {
         }
     }
 
+    /// `package("..domain..")::external.import should inside(["org.slf4j.."])`, the patterns match
+    /// the import or the library it comes from
+    fn process_external_imports(&mut self, index: usize, rule: &GuardRule, imports: &[(String, CodeImport)]) {
+        let allowed = match &rule.assert {
+            RuleAssert::Stringed(pattern) => vec![pattern.clone()],
+            RuleAssert::ArrayStringed(patterns) => patterns.clone(),
+            _ => return
+        };
+        let has_not = matches!(rule.ops.first(), Some(Operator::Not));
+
+        let mut error = RuleErrorMsg::new(MismatchType::Access, index);
+        error.msg = format!("external imports inside: {:?}", allowed);
+        for (path, import) in imports {
+            let mut is_allowed = allowed.iter().any(|pattern| {
+                is_package_match(pattern.clone(), import.source.as_str())
+                    || (!import.library.is_empty() && is_package_match(pattern.clone(), import.library.as_str()))
+            });
+            if has_not {
+                is_allowed = !is_allowed;
+            }
+
+            if !is_allowed {
+                let location = ErrorLocation { path: path.clone(), start: import.start.clone(), end: import.end.clone() };
+                error.push_item(format!("path: {}, import: {}", path, import.source), location);
+            }
+        }
+
+        if !error.items.is_empty() {
+            self.errors.push(error);
+        }
+    }
+
     fn filter_classes_by_package_identifier(&mut self, str: &String) -> Vec<CodeFile> {
         self.models.iter()
             .filter(|s| { is_package_match(str.to_string(), s.package.as_str()) })
@@ -294,7 +415,7 @@ impl RuleExecutor {
             Operator::Inside |
             Operator::ResideIn => {
                 error.msg = format!("resideIn: {:?}", identifier);
-                self.filtered_classes.iter().for_each(|clz| {
+                self.filtered_classes.iter().for_each(|(file_index, clz)| {
                     let mut package_match = is_package_match(identifier.clone(), clz.package.as_str());
                     if has_not {
                         package_match = !package_match;
@@ -302,7 +423,7 @@ impl RuleExecutor {
 
                     if !package_match {
                         let item = format!("path: {}, name: {}", clz.package.clone(), clz.name.clone());
-                        error.items.push(item);
+                        error.push_item(item, self.class_location(*file_index, clz));
                         assert_success = false;
                     }
                 });
@@ -356,7 +477,7 @@ impl RuleExecutor {
             _ => { return; }
         }
 
-        self.filtered_classes.iter().for_each(|clz| {
+        self.filtered_classes.iter().for_each(|(file_index, clz)| {
             let mut is_starts_with = match_func(clz.name.clone(), &excepted);
             if has_not {
                 is_starts_with = !is_starts_with
@@ -364,7 +485,7 @@ impl RuleExecutor {
             if !is_starts_with {
                 assert_success = false;
                 let item = format!("path: {}, name: {}", clz.package.clone(), clz.name.clone());
-                error.items.push(item)
+                error.push_item(item, self.class_location(*file_index, clz))
             }
         });
 
@@ -373,6 +494,64 @@ impl RuleExecutor {
         }
     }
 
+    fn process_doc(&mut self, index: usize, all_ops: &Vec<Operator>) {
+        let (has_not, ops) = RuleExecutor::split_not(all_ops);
+        if ops != &Operator::Empty {
+            return;
+        }
+
+        let mut error = RuleErrorMsg::new(MismatchType::Documentation, index);
+        error.msg = if has_not { "doc: not empty".to_string() } else { "doc: empty".to_string() };
+
+        self.filtered_classes.iter().for_each(|(file_index, clz)| {
+            if clz.doc.is_empty() == has_not {
+                let item = format!("path: {}, name: {}", clz.package.clone(), clz.name.clone());
+                error.push_item(item, self.class_location(*file_index, clz))
+            }
+        });
+
+        if !error.items.is_empty() {
+            self.errors.push(error);
+        }
+    }
+
+    fn process_function_doc(&mut self, index: usize, all_ops: &Vec<Operator>) {
+        let (has_not, ops) = RuleExecutor::split_not(all_ops);
+        if ops != &Operator::Empty {
+            return;
+        }
+
+        let mut error = RuleErrorMsg::new(MismatchType::Documentation, index);
+        error.msg = if has_not { "function.doc: not empty".to_string() } else { "function.doc: empty".to_string() };
+
+        self.filtered_classes.iter().for_each(|(file_index, clz)| {
+            for function in &clz.functions {
+                if function.doc.is_empty() == has_not {
+                    let item = format!("path: {}, name: {}.{}", clz.package.clone(), clz.name.clone(), function.name.clone());
+                    let location = ErrorLocation { start: function.start.clone(), end: function.end.clone(), ..self.class_location(*file_index, clz) };
+                    error.push_item(item, location)
+                }
+            }
+        });
+
+        if !error.items.is_empty() {
+            self.errors.push(error);
+        }
+    }
+
+    /// where `class` of the file `file_index` is declared
+    fn class_location(&self, file_index: usize, class: &CodeClass) -> ErrorLocation {
+        ErrorLocation { path: self.models[file_index].path.clone(), start: class.start.clone(), end: class.end.clone() }
+    }
+
+    /// `should not empty` -> (true, Empty)
+    fn split_not(all_ops: &Vec<Operator>) -> (bool, &Operator) {
+        match &all_ops[0] {
+            Operator::Not => (true, &all_ops[1]),
+            ops => (false, ops)
+        }
+    }
+
     fn process_len(&mut self, index: usize, excepted_size: usize, all_ops: &Vec<Operator>, actual_size: usize) {
         let mut ops = &all_ops[0];
         let mut has_not = false;
@@ -1,6 +1,8 @@
 pub use executor::RuleExecutor;
 pub use rule_error::RuleErrorMsg;
+pub use rule_summary::RuleSummary;
 
 pub mod executor;
 pub mod package_matcher;
 pub mod rule_error;
+pub mod rule_summary;
@@ -23,6 +23,14 @@ pub fn is_package_match(package_identifier: String, text: &str) -> bool {
     regex.is_match(text)
 }
 
+/// a resolved `com.phodal.BaseParser` still matches a rule written with `BaseParser`
+pub fn is_type_match(type_name: &str, expected: &str) -> bool {
+    let class_name = expected.trim_end_matches(".class");
+    let type_name = type_name.split('<').next().unwrap_or(type_name);
+
+    type_name == class_name || type_name.ends_with(&format!(".{}", class_name))
+}
+
 pub fn is_assert_match(package_identifier: String, text: &str, assert_package: String) -> bool {
     let package = convert_to_regex(package_identifier);
     let regex = Regex::new(package.as_str())
@@ -54,7 +62,15 @@ pub fn convert_to_regex(package_identifier: String) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::rule_executor::package_matcher::{is_package_match, is_assert_match};
+    use crate::rule_executor::package_matcher::{is_package_match, is_assert_match, is_type_match};
+
+    #[test]
+    fn should_match_type_by_simple_or_full_name() {
+        assert!(is_type_match("com.phodal.BaseParser", "BaseParser"));
+        assert!(is_type_match("com.phodal.BaseParser", "com.phodal.BaseParser"));
+        assert!(is_type_match("com.phodal.Finder<Order>", "Finder"));
+        assert!(!is_type_match("com.phodal.MyBaseParser", "BaseParser"));
+    }
 
     #[test]
     fn should_match() {
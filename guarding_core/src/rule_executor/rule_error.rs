@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 
+use crate::domain::CodePoint;
+
 #[repr(C)]
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub enum MismatchType {
@@ -7,6 +9,7 @@ pub enum MismatchType {
     Access,
     FileName,
     FileSize,
+    Documentation,
 }
 
 #[repr(C)]
@@ -17,7 +20,22 @@ pub struct RuleErrorMsg {
     pub mismatch_type: MismatchType,
     pub msg: String,
     pub items: Vec<String>,
+    /// where each of `items` is, same order, empty for violations of a whole package
+    #[serde(default)]
+    pub locations: Vec<ErrorLocation>,
     pub rule_index: usize,
+    /// a fix of each of `items`, like a name following the rule, empty when there is none
+    #[serde(default)]
+    pub suggestions: Vec<String>,
+}
+
+/// a range in a file, 0-based like the models
+#[repr(C)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ErrorLocation {
+    pub path: String,
+    pub start: CodePoint,
+    pub end: CodePoint,
 }
 
 impl RuleErrorMsg {
@@ -28,9 +46,21 @@ impl RuleErrorMsg {
             mismatch_type,
             msg: "".to_string(),
             items: vec![],
-            rule_index: index
+            locations: vec![],
+            rule_index: index,
+            suggestions: vec![],
         }
     }
+
+    pub fn push_item(&mut self, item: String, location: ErrorLocation) {
+        self.items.push(item);
+        self.locations.push(location);
+    }
+
+    /// the location of `items[index]`, if known
+    pub fn location_of(&self, index: usize) -> Option<&ErrorLocation> {
+        self.locations.get(index).filter(|location| !location.path.is_empty())
+    }
 }
 
 impl Default for RuleErrorMsg {
@@ -41,7 +71,9 @@ impl Default for RuleErrorMsg {
             mismatch_type: MismatchType::None,
             msg: "".to_string(),
             items: vec![],
-            rule_index: 0
+            locations: vec![],
+            rule_index: 0,
+            suggestions: vec![],
         }
     }
 }
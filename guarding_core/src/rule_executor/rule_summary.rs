@@ -0,0 +1,26 @@
+use serde::{Deserialize, Serialize};
+
+use guarding_parser::ast::GuardRule;
+
+/// What the bindings tell of a parsed rule: its name, severity, level, tags and origin.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RuleSummary {
+    pub name: String,
+    pub severity: String,
+    pub level: String,
+    pub tags: Vec<String>,
+    /// the rule as written
+    pub origin: String,
+}
+
+impl RuleSummary {
+    pub fn of(rule: &GuardRule) -> RuleSummary {
+        RuleSummary {
+            name: rule.name.clone(),
+            severity: rule.severity.name().to_string(),
+            level: rule.level.name().to_string(),
+            tags: rule.tags.clone(),
+            origin: rule.origin.clone(),
+        }
+    }
+}
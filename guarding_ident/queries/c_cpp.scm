@@ -0,0 +1,10 @@
+(preproc_include
+    path: (_) @include)
+
+(class_specifier
+    name: (type_identifier) @class-name)
+
+(struct_specifier
+    name: (type_identifier) @class-name)
+
+(function_definition) @function
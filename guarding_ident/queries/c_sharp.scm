@@ -0,0 +1,20 @@
+(using_directive
+	(qualified_name) @import-name)
+
+(using_directive
+	(identifier) @import-name)
+
+(namespace_declaration
+    name: (_) @namespace-name)
+
+(class_declaration
+    name: (identifier) @class-name)
+
+(interface_declaration
+    name: (identifier) @class-name)
+
+(record_declaration
+    name: (identifier) @class-name)
+
+(struct_declaration
+    name: (identifier) @class-name)
@@ -0,0 +1,11 @@
+(import_specification
+    (configurable_uri (uri) @import-uri))
+
+(class_definition) @class
+
+(mixin_declaration) @class
+
+(extension_declaration) @class
+
+(source_file
+    (function_signature) @function)
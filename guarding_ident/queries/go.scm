@@ -0,0 +1,14 @@
+(package_clause
+    (package_identifier) @package-name)
+
+(import_spec
+    path: (interpreted_string_literal) @import-name)
+
+(type_spec
+    name: (type_identifier) @type-name)
+
+(method_declaration
+    name: (field_identifier) @method-name)
+
+(source_file (function_declaration
+    name: (identifier) @function-name))
@@ -0,0 +1,11 @@
+(package_declaration
+    (scoped_identifier) @package-name)
+
+(import_declaration
+    (scoped_identifier) @import-name)
+
+(class_declaration
+    name: (identifier) @class-name)
+
+(interface_declaration
+    name: (identifier) @class-name)
@@ -0,0 +1,25 @@
+(package_declaration
+	(scoped_identifier) @package-name)
+
+(import_declaration
+	(scoped_identifier) @import-name)
+
+(program
+    (class_declaration
+	    name: (identifier) @class-name
+        interfaces: (super_interfaces (interface_type_list (type_identifier)  @impl-name))?
+    )
+)
+
+(program
+    (interface_declaration
+        name: (identifier) @class-name
+    )
+)
+
+(program
+    (enum_declaration
+        name: (identifier) @class-name
+        interfaces: (super_interfaces (interface_type_list (type_identifier)  @impl-name))?
+    )
+)
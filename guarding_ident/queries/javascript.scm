@@ -0,0 +1,19 @@
+(import_specifier
+	name: (identifier) @import-name)
+(namespace_import (identifier) @import-name)
+(import_statement
+	source: (string) @source)
+(import_clause (identifier) @import-name)
+
+(class_declaration
+  name: (identifier) @class-name
+  body: (class_body
+    (method_definition
+      name: (property_identifier) @class-method-name
+      parameters: (formal_parameters (identifier)? @parameter)
+    )
+  )
+)
+
+(program (function_declaration
+      name: * @function-name))
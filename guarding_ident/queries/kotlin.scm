@@ -0,0 +1,16 @@
+(package_header
+    (identifier) @package-name)
+
+(import_header
+    (identifier) @import-name)
+
+(class_declaration
+    (type_identifier) @class-name)
+
+(object_declaration
+    (type_identifier) @class-name)
+
+(companion_object) @companion
+
+(source_file (function_declaration
+    (simple_identifier) @function-name))
@@ -0,0 +1,18 @@
+(import_statement
+    name: (dotted_name) @import-name)
+
+(import_statement
+    name: (aliased_import
+        name: (dotted_name) @import-name))
+
+(import_from_statement) @import-from
+
+(class_definition
+    name: (identifier) @class-name)
+
+(module (function_definition
+    name: (identifier) @function-name))
+
+(module (decorated_definition
+    definition: (function_definition
+        name: (identifier) @function-name)))
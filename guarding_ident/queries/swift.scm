@@ -0,0 +1,9 @@
+(import_declaration
+    (identifier) @import-name)
+
+(class_declaration) @class
+
+(protocol_declaration) @class
+
+(source_file
+    (function_declaration) @function)
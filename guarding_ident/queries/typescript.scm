@@ -0,0 +1,23 @@
+(import_statement
+    source: (string) @source)
+
+(export_statement
+    source: (string) @source)
+
+(export_statement) @export
+
+(class_declaration
+    name: (type_identifier) @class-name)
+
+(abstract_class_declaration
+    name: (type_identifier) @class-name)
+
+(interface_declaration
+    name: (type_identifier) @interface-name)
+
+(program (function_declaration
+    name: (identifier) @function-name))
+
+(program (export_statement
+    (function_declaration
+        name: (identifier) @function-name)))
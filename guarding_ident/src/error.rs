@@ -0,0 +1,27 @@
+use std::fmt;
+
+/// Why a file couldn't be identified; the file is still reported, and the others are identified.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdentError {
+    /// the grammar doesn't fit the tree-sitter library, like after a version mismatch
+    Language(String),
+    /// a built-in query or one of the query dir doesn't compile
+    Query(String),
+    /// tree-sitter gave no tree, like after a timeout
+    Cancelled,
+    /// a bug in an identifier
+    Panicked(String),
+}
+
+impl fmt::Display for IdentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IdentError::Language(message) => write!(f, "can't load the grammar: {}", message),
+            IdentError::Query(message) => write!(f, "can't compile the query: {}", message),
+            IdentError::Cancelled => write!(f, "parsing was cancelled"),
+            IdentError::Panicked(message) => write!(f, "identifier failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for IdentError {}
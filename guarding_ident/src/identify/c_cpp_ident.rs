@@ -0,0 +1,383 @@
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
+
+const C_CPP_QUERY: &'static str = include_str!("../../queries/c_cpp.scm");
+
+/// C is parsed with the C++ grammar, which is a superset for the declarations guarding needs
+pub struct CCppIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl CCppIdent {
+    pub fn new() -> Result<CCppIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_cpp::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "c_cpp", C_CPP_QUERY)?;
+
+        Ok(CCppIdent { parser, query })
+    }
+
+    /// `<vector>` and `"core/util.h"` -> `vector`, `core/util.h`
+    pub fn include_path(text: &str) -> String {
+        text.trim_matches(|c| c == '"' || c == '<' || c == '>').to_string()
+    }
+}
+
+impl CCppIdent {
+    fn do_parse(code: &str, ident: &mut CCppIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(CCppIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &CCppIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = CCppIdent::parse_warnings(tree.root_node(), code);
+        // `void Order::total() {}` defined outside of the class body
+        let mut out_of_class: Vec<(String, CodeFunction)> = vec![];
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if CCppIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "include" => {
+                    let include = capture.node.parent().unwrap_or(capture.node);
+                    let import = CodeImport::module(&CCppIdent::include_path(text));
+                    code_file.imports.push(CCppIdent::located(import, include));
+                }
+                "class-name" => {
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
+                    // `struct Foo;` and `struct Foo *p` only refer to the type
+                    if class_node.child_by_field_name("body").is_none() {
+                        continue;
+                    }
+
+                    let mut class = CCppIdent::create_class(class_node, text, code);
+                    class.package = CCppIdent::namespace_of(class_node, code);
+                    code_file.classes.push(class);
+                }
+                "function" => {
+                    if CCppIdent::is_member(capture.node) {
+                        continue;
+                    }
+
+                    let name = match CCppIdent::function_name(capture.node, code) {
+                        Some(name) => name,
+                        None => continue
+                    };
+
+                    let mut parts: Vec<&str> = name.split("::").collect();
+                    let mut function = CodeFunction::default();
+                    function.name = parts.pop().unwrap_or("").to_string();
+                    CCppIdent::insert_location(&mut function, capture.node);
+                    function.doc = CCppIdent::doc_comment(capture.node, code);
+                    CCppIdent::insert_metrics(&mut function, capture.node);
+                    function.lambdas = lambdas::lambdas_of(capture.node, code);
+
+                    match parts.pop() {
+                        Some(owner) => out_of_class.push((owner.to_string(), function)),
+                        None => code_file.functions.push(function)
+                    }
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        for (owner, function) in out_of_class {
+            match code_file.classes.iter_mut().find(|class| class.name == owner) {
+                Some(class) => class.functions.push(function),
+                // the class is declared in a header
+                None => code_file.functions.push(function)
+            }
+        }
+
+        code_file
+    }
+
+    fn create_class(node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        CCppIdent::insert_location(&mut class, node);
+        CCppIdent::insert_kind(&mut class, node, code);
+        class.doc = CCppIdent::doc_comment(node, code);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() != "base_class_clause" {
+                continue;
+            }
+
+            let mut base_cursor = child.walk();
+            for base in child.named_children(&mut base_cursor) {
+                match base.kind() {
+                    "type_identifier" | "qualified_identifier" | "template_type" => {
+                        class.extends.push(CCppIdent::node_text(base, code));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            // members of a class are private until an access specifier, the ones of a struct public
+            let mut visibility = if node.kind() == "class_specifier" { "private" } else { "public" }.to_string();
+            let mut body_cursor = body.walk();
+            for member in body.named_children(&mut body_cursor) {
+                if member.kind() == "access_specifier" {
+                    visibility = CCppIdent::node_text(member, code).trim_end_matches(':').trim().to_string();
+                    continue;
+                }
+
+                let is_method = match member.kind() {
+                    "function_definition" => true,
+                    "field_declaration" | "declaration" => {
+                        member.child_by_field_name("declarator")
+                            .map(|declarator| CCppIdent::unwrap_declarator(declarator).kind() == "function_declarator")
+                            .unwrap_or(false)
+                    }
+                    _ => false
+                };
+
+                if !is_method {
+                    if member.kind() == "field_declaration" {
+                        class.fields.extend(CCppIdent::create_fields(member, &visibility, code));
+                    }
+                    continue;
+                }
+
+                if let Some(name) = CCppIdent::function_name(member, code) {
+                    let mut function = CodeFunction::default();
+                    function.name = name;
+                    CCppIdent::insert_location(&mut function, member);
+                    function.doc = CCppIdent::doc_comment(member, code);
+                    CCppIdent::insert_metrics(&mut function, member);
+                    function.lambdas = lambdas::lambdas_of(member, code);
+                    class.functions.push(function);
+                }
+            }
+        }
+
+        class
+    }
+
+    /// `static const int a, *b;` is two fields
+    fn create_fields(member: Node, visibility: &str, code: &str) -> Vec<CodeField> {
+        let mut template = CodeField::default();
+        template.visibility = visibility.to_string();
+        template.typ = member.child_by_field_name("type")
+            .map(|typ| CCppIdent::node_text(typ, code))
+            .unwrap_or_default();
+        CCppIdent::insert_location(&mut template, member);
+
+        let mut fields = vec![];
+        let mut cursor = member.walk();
+        for child in member.named_children(&mut cursor) {
+            match child.kind() {
+                "storage_class_specifier" | "type_qualifier" => {
+                    CCppIdent::apply_modifier(&mut template, &CCppIdent::node_text(child, code));
+                }
+                "field_identifier" | "pointer_declarator" | "reference_declarator" | "array_declarator" => {
+                    let mut declarator = CCppIdent::unwrap_declarator(child);
+                    while declarator.kind() == "array_declarator" {
+                        match declarator.child_by_field_name("declarator") {
+                            Some(inner) => declarator = CCppIdent::unwrap_declarator(inner),
+                            None => break
+                        }
+                    }
+
+                    if declarator.kind() == "field_identifier" {
+                        let mut field = template.clone();
+                        field.name = CCppIdent::node_text(declarator, code);
+                        fields.push(field);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        fields
+    }
+
+    fn is_member(node: Node) -> bool {
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if current.kind() == "field_declaration_list" {
+                return true;
+            }
+            parent = current.parent();
+        }
+
+        false
+    }
+
+    /// peel pointer/reference declarators: `Order* find()` is still function `find`
+    fn unwrap_declarator(node: Node) -> Node {
+        let mut current = node;
+        while current.kind() == "pointer_declarator" || current.kind() == "reference_declarator" {
+            match current.child_by_field_name("declarator").or(current.named_child(0)) {
+                Some(inner) => current = inner,
+                None => break
+            }
+        }
+
+        current
+    }
+
+    fn function_name(node: Node, code: &str) -> Option<String> {
+        let declarator = CCppIdent::unwrap_declarator(node.child_by_field_name("declarator")?);
+        if declarator.kind() != "function_declarator" {
+            return None;
+        }
+
+        let name = declarator.child_by_field_name("declarator")?;
+        Some(CCppIdent::node_text(name, code))
+    }
+
+    /// `namespace shop { namespace order {} }` -> `shop.order`
+    fn namespace_of(node: Node, code: &str) -> String {
+        let mut names = vec![];
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if current.kind() == "namespace_definition" {
+                if let Some(name) = current.child_by_field_name("name") {
+                    names.insert(0, CCppIdent::node_text(name, code).replace("::", "."));
+                }
+            }
+            parent = current.parent();
+        }
+
+        names.join(".")
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for CCppIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = CCppIdent::new()?;
+        CCppIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = CCppIdent::new()?;
+        Ok(CCppIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::c_cpp_ident::CCppIdent;
+
+    #[test]
+    fn should_parse_includes() {
+        let source_code = "#include <vector>
+#include \"core/util.h\"
+";
+        let file = CCppIdent::parse(source_code).unwrap();
+        assert_eq!(vec!["vector", "core/util.h"], file.import_sources());
+    }
+
+    #[test]
+    fn should_parse_class_in_namespace() {
+        let source_code = "namespace shop {
+namespace order {
+
+class OrderService : public BaseService {
+public:
+    Order* find(int id);
+    void save() {}
+private:
+    int count;
+};
+
+}
+}
+";
+        let file = CCppIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("OrderService", class.name);
+        assert_eq!("shop.order", class.package);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(2, class.functions.len());
+        assert_eq!("find", class.functions[0].name);
+        assert_eq!(0, file.functions.len());
+    }
+
+    #[test]
+    fn should_parse_free_and_out_of_class_functions() {
+        let source_code = "struct Point {
+    int x;
+};
+
+struct Point *origin;
+
+int Point::length() { return 0; }
+
+int main(int argc, char **argv) {
+    return 0;
+}
+";
+        let file = CCppIdent::parse(source_code).unwrap();
+
+        assert_eq!(1, file.classes.len());
+        assert_eq!("length", file.classes[0].functions[0].name);
+        assert_eq!(1, file.functions.len());
+        assert_eq!("main", file.functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_fields_with_access() {
+        let source_code = "class Cart {
+    Item* first;
+public:
+    static const int limit, *counts;
+};
+";
+        let file = CCppIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!(3, fields.len());
+        assert_eq!("first", fields[0].name);
+        assert_eq!("private", fields[0].visibility);
+        assert_eq!("counts", fields[2].name);
+        assert_eq!("public", fields[2].visibility);
+        assert!(fields[2].is_static && fields[2].is_final);
+    }
+}
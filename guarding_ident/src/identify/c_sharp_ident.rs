@@ -1,32 +1,17 @@
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+use guarding_core::domain::code_annotation::CodeAnnotation;
 use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
 use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_constructor::CodeConstructor;
 use guarding_core::domain::code_function::CodeFunction;
 use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
 
-const C_SHARP_QUERY: &'static str = "
-(using_directive
-	(qualified_name) @import-name)
-
-(class_declaration
-    (attribute_list (attribute name: (identifier) @annotation.name))?
-    name: (identifier) @class-name
-    bases: (base_list ((identifier) @impl-name))?
-    body: (declaration_list
-    	(property_declaration
-        	type: (identifier) @prop-type
-            name: (identifier) @prop-name
-        )?
-        (method_declaration
-        	name: (identifier) @method-name
-            parameters: (parameter_list (parameter
-            	type: (identifier) @param-type
-                name: (identifier) @param-name
-            ))?
-        )?
-    ) @body
-)";
-
+const C_SHARP_QUERY: &'static str = include_str!("../../queries/c_sharp.scm");
 
 pub struct CSharpIdent {
     parser: Parser,
@@ -34,64 +19,79 @@ pub struct CSharpIdent {
 }
 
 impl CSharpIdent {
-    pub fn new() -> CSharpIdent {
+    pub fn new() -> Result<CSharpIdent, IdentError> {
         let mut parser = Parser::new();
         let language = tree_sitter_c_sharp::language();
-        parser.set_language(language).unwrap();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
 
-        let query = Query::new(language, &C_SHARP_QUERY)
-            .map_err(|e| println!("{}", format!("Query compilation failed: {:?}", e))).unwrap();
+        let query = queries::load_query(language, "c_sharp", C_SHARP_QUERY)?;
 
-        CSharpIdent {
+        Ok(CSharpIdent {
             parser,
             query
-        }
+        })
+    }
+
+    fn do_parse(code: &&str, ident: &mut CSharpIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(CSharpIdent::from_tree(code, &tree, ident))
     }
 
-    fn do_parse(code: &&str, ident: &mut CSharpIdent) -> CodeFile {
-        let tree = ident.parser.parse(code, None).unwrap();
+    fn from_tree(code: &&str, tree: &Tree, ident: &CSharpIdent) -> CodeFile {
         let text_callback = |n: Node| &code[n.byte_range()];
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
         let mut code_file = CodeFile::default();
-        let mut class = CodeClass::default();
+        code_file.warnings = CSharpIdent::parse_warnings(tree.root_node(), code);
 
         let capture_names = ident.query.capture_names();
 
         let mut iters = captures.into_iter();
         while let Some((mat, capture_index)) = iters.next() {
             let capture = mat.captures[capture_index];
+            if CSharpIdent::is_error_fragment(capture.node) {
+                continue;
+            }
             let capture_name: &str = &capture_names[capture.index as usize];
 
             let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
             match capture_name {
+                // a namespace, or a type with `using static`
                 "import-name" => {
-                    code_file.imports.push(text.to_string());
+                    let directive = capture.node.parent().unwrap_or(capture.node);
+                    let mut import = CodeImport::module(text);
+                    let mut cursor = directive.walk();
+                    import.is_static = directive.children(&mut cursor).any(|child| child.kind() == "static");
+                    code_file.imports.push(CSharpIdent::located(import, directive));
+                }
+                "namespace-name" => {
+                    // keep the innermost one of nested namespaces
+                    let namespace = CSharpIdent::namespace_of(capture.node.parent().unwrap_or(capture.node), code);
+                    if code_file.package.is_empty() || namespace.starts_with(&format!("{}.", code_file.package)) {
+                        code_file.package = namespace;
+                    }
                 }
                 "class-name" => {
-                    class.name = text.to_string();
-                    class.package = code_file.package.clone();
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
+                    let mut class = CSharpIdent::create_class(class_node, text, code);
+                    class.package = CSharpIdent::namespace_of(class_node, code);
+                    if class.package.is_empty() {
+                        class.package = CSharpIdent::file_scoped_namespace(tree.root_node(), code);
+                    }
 
-                    let class_node = capture.node.parent().unwrap();
-                    CSharpIdent::insert_location(&mut class, class_node);
-                }
-                "impl-name" => {
-                    class.implements.push(text.to_string());
-                }
-                "method-name" => {
-                    let mut function = CodeFunction::default();
-                    function.name = text.to_string();
-                    class.functions.push(function);
-
-                    if !class.name.is_empty() {
-                        code_file.classes.push(class.clone());
-                        class = CodeClass::default();
+                    if code_file.package.is_empty() {
+                        code_file.package = class.package.clone();
                     }
+                    code_file.classes.push(class);
                 }
                 &_ => {
-                    println!(
-                        "    pattern: {}, capture: {}, row: {}, text: {:?}",
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
                         mat.pattern_index,
                         capture_name,
                         capture.node.start_position().row,
@@ -101,19 +101,240 @@ impl CSharpIdent {
             }
         }
 
-        if class.name != "" {
-            code_file.classes.push(class.clone());
+        code_file
+    }
+
+    fn create_class(node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        CSharpIdent::insert_location(&mut class, node);
+        CSharpIdent::insert_kind(&mut class, node, code);
+        class.doc = CSharpIdent::doc_comment(node, code);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "attribute_list" => {
+                    class.annotations.extend(CSharpIdent::attributes_of(child, code));
+                }
+                // the syntax can't tell the base class from interfaces
+                "base_list" => {
+                    let mut base_cursor = child.walk();
+                    for base in child.named_children(&mut base_cursor) {
+                        class.implements.push(CSharpIdent::node_text(base, code));
+                    }
+                }
+                "declaration_list" => {
+                    CSharpIdent::fill_members(&mut class, child, code);
+                }
+                _ => {}
+            }
         }
 
-        code_file
+        class
+    }
+
+    fn fill_members(class: &mut CodeClass, body: Node, code: &str) {
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            match member.kind() {
+                "method_declaration" => {
+                    if let Some(name) = member.child_by_field_name("name") {
+                        let mut function = CodeFunction::default();
+                        function.name = CSharpIdent::node_text(name, code);
+                        function.annotations = CSharpIdent::member_attributes(member, code);
+                        CSharpIdent::insert_location(&mut function, member);
+                        function.doc = CSharpIdent::doc_comment(member, code);
+                        CSharpIdent::insert_metrics(&mut function, member);
+                        function.lambdas = lambdas::lambdas_of(member, code);
+                        class.functions.push(function);
+                    }
+                }
+                "constructor_declaration" => {
+                    class.constructors.push(CSharpIdent::create_cs_constructor(member, code));
+                }
+                "property_declaration" => {
+                    if let Some(name) = member.child_by_field_name("name") {
+                        let mut field = CSharpIdent::create_field(member, code);
+                        field.name = CSharpIdent::node_text(name, code);
+                        field.typ = member.child_by_field_name("type")
+                            .map(|typ| CSharpIdent::node_text(typ, code))
+                            .unwrap_or_default();
+                        class.fields.push(field);
+                    }
+                }
+                // `private readonly Repo repo, backup;`
+                "field_declaration" => {
+                    let declaration = match CSharpIdent::first_child_of_kind(member, "variable_declaration") {
+                        Some(declaration) => declaration,
+                        None => continue
+                    };
+                    let typ = declaration.child_by_field_name("type")
+                        .map(|typ| CSharpIdent::node_text(typ, code))
+                        .unwrap_or_default();
+
+                    let mut declarator_cursor = declaration.walk();
+                    for declarator in declaration.named_children(&mut declarator_cursor) {
+                        if declarator.kind() != "variable_declarator" {
+                            continue;
+                        }
+
+                        let name = declarator.child_by_field_name("name")
+                            .or(CSharpIdent::first_child_of_kind(declarator, "identifier"));
+                        if let Some(name) = name {
+                            let mut field = CSharpIdent::create_field(member, code);
+                            field.name = CSharpIdent::node_text(name, code);
+                            field.typ = typ.clone();
+                            class.fields.push(field);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn create_cs_constructor(member: Node, code: &str) -> CodeConstructor {
+        let mut constructor = CSharpIdent::create_constructor(member, code);
+        // modifiers and attributes are the same as for a field
+        let modifiers = CSharpIdent::create_field(member, code);
+        constructor.visibility = modifiers.visibility;
+        constructor.annotations = modifiers.annotations;
+
+        if let Some(parameters) = member.child_by_field_name("parameters") {
+            constructor.parameters = CSharpIdent::parameters_of(parameters, code, CSharpIdent::parameter_parts);
+        }
+
+        // `: base(name)` or `: this(name, 0)`
+        if let Some(initializer) = CSharpIdent::first_child_of_kind(member, "constructor_initializer") {
+            let mut cursor = initializer.walk();
+            let target = initializer.children(&mut cursor).find(|child| child.kind() == "base" || child.kind() == "this");
+            if let Some(target) = target {
+                constructor.delegates_to = CSharpIdent::node_text(target, code);
+            }
+        }
+
+        constructor
+    }
+
+    fn parameter_parts(parameter: Node) -> Option<(Node, Option<Node>)> {
+        if parameter.kind() != "parameter" {
+            return None;
+        }
+
+        Some((parameter.child_by_field_name("name")?, parameter.child_by_field_name("type")))
+    }
+
+    /// modifiers, attributes and location of a field or property, the name and type are set by the caller
+    fn create_field(member: Node, code: &str) -> CodeField {
+        let mut field = CodeField::default();
+        let mut cursor = member.walk();
+        for child in member.named_children(&mut cursor) {
+            if child.kind() == "modifier" {
+                CSharpIdent::apply_modifier(&mut field, &CSharpIdent::node_text(child, code));
+            }
+        }
+        field.annotations = CSharpIdent::member_attributes(member, code);
+        CSharpIdent::insert_location(&mut field, member);
+
+        field
+    }
+
+    fn member_attributes(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.kind() == "attribute_list" {
+                annotations.extend(CSharpIdent::attributes_of(child, code));
+            }
+        }
+
+        annotations
+    }
+
+    fn attributes_of(attribute_list: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut cursor = attribute_list.walk();
+        for attribute in attribute_list.named_children(&mut cursor) {
+            if attribute.kind() != "attribute" {
+                continue;
+            }
+
+            if let Some(name) = attribute.child_by_field_name("name") {
+                let mut annotation = CSharpIdent::annotation_at(attribute, &CSharpIdent::node_text(name, code));
+                if let Some(arguments) = CSharpIdent::first_child_of_kind(attribute, "attribute_argument_list") {
+                    annotation.key_values = CSharpIdent::argument_key_values(arguments, code, CSharpIdent::split_argument);
+                }
+                annotations.push(annotation);
+            }
+        }
+
+        annotations
+    }
+
+    /// `Name = "orders"` and `name: "orders"` are named
+    fn split_argument(argument: Node) -> (Option<Node>, Node) {
+        let value = argument.named_child(argument.named_child_count().saturating_sub(1)).unwrap_or(argument);
+        match argument.named_child(0) {
+            Some(name) if name.kind() == "name_equals" || name.kind() == "name_colon" => {
+                (name.named_child(0), value)
+            }
+            _ => (None, value)
+        }
+    }
+
+    /// nested `namespace A { namespace B { } }` is `A.B`
+    fn namespace_of(node: Node, code: &str) -> String {
+        let mut names = vec![];
+        let mut current = Some(node);
+        while let Some(parent) = current {
+            if parent.kind() == "namespace_declaration" {
+                if let Some(name) = parent.child_by_field_name("name") {
+                    names.insert(0, CSharpIdent::node_text(name, code));
+                }
+            }
+            current = parent.parent();
+        }
+
+        names.join(".")
+    }
+
+    /// C# 10 `namespace A.B;` applies to every type in the file
+    fn file_scoped_namespace(root: Node, code: &str) -> String {
+        let mut cursor = root.walk();
+        for child in root.named_children(&mut cursor) {
+            if child.kind() == "file_scoped_namespace_declaration" {
+                if let Some(name) = child.child_by_field_name("name") {
+                    return CSharpIdent::node_text(name, code);
+                }
+            }
+        }
+
+        "".to_string()
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
     }
 }
 
 impl CodeIdent for CSharpIdent {
-    fn parse(code: &str) -> CodeFile {
-        let mut ident = CSharpIdent::new();
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = CSharpIdent::new()?;
         CSharpIdent::do_parse(&code, &mut ident)
     }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = CSharpIdent::new()?;
+        Ok(CSharpIdent::from_tree(&code, tree, &ident))
+    }
 }
 
 #[cfg(test)]
@@ -125,7 +346,7 @@ mod tests {
     fn should_parse_import() {
         let source_code = "using Microsoft.CodeAnalysis;";
 
-        let file = CSharpIdent::parse(source_code);
+        let file = CSharpIdent::parse(source_code).unwrap();
         assert_eq!(1, file.imports.len());
     }
 
@@ -133,7 +354,7 @@ mod tests {
     fn should_parse_class_name() {
         let source_code = "public class SharpingClassVisitor { }";
 
-        let file = CSharpIdent::parse(source_code);
+        let file = CSharpIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
         assert_eq!("SharpingClassVisitor", file.classes[0].name);
     }
@@ -142,7 +363,7 @@ mod tests {
     fn should_parse_class_impl_name() {
         let source_code = "public class SharpingClassVisitor: CSharpSyntaxWalker, DemoInterface { }";
 
-        let file = CSharpIdent::parse(source_code);
+        let file = CSharpIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
 
         assert_eq!(2, file.classes[0].implements.len());
@@ -160,10 +381,93 @@ public class SharpingClassVisitor {
   }
 }";
 
-        let file = CSharpIdent::parse(source_code);
+        let file = CSharpIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
 
         assert_eq!(1, file.classes[0].functions.len());
         assert_eq!("VisitClassDeclaration", file.classes[0].functions[0].name);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_parse_constructors() {
+        let source_code = "public class OrderService : BaseService {
+  [Inject]
+  public OrderService(IRepository repo) : base(repo) {
+  }
+
+  public void Run() {}
+}";
+
+        let file = CSharpIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+        assert_eq!(1, class.functions.len());
+
+        let constructor = &class.constructors[0];
+        assert_eq!("public", constructor.visibility);
+        assert_eq!("Inject", constructor.annotations[0].name);
+        assert_eq!("repo", constructor.parameters[0].name);
+        assert_eq!("IRepository", constructor.parameters[0].typ);
+        assert_eq!("base", constructor.delegates_to);
+    }
+
+    #[test]
+    fn should_parse_attributes_and_properties() {
+        let source_code = "[ApiController]
+[Route(\"api/orders\")]
+public class OrderController {
+  public Domain domain { get; set; }
+
+  [HttpGet]
+  public IActionResult Get() { return Ok(); }
+}";
+
+        let file = CSharpIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!(2, class.annotations.len());
+        assert_eq!("Route", class.annotations[1].name);
+        assert_eq!(Some("api/orders"), class.annotations[1].value_of("0"));
+        assert_eq!(1, class.annotations[1].start.row);
+        assert_eq!("HttpGet", class.functions[0].annotations[0].name);
+        assert_eq!("domain", class.fields[0].name);
+        assert_eq!("Domain", class.fields[0].typ);
+    }
+
+    #[test]
+    fn should_parse_fields() {
+        let source_code = "public class OrderService {
+  [Inject]
+  private readonly IOrderRepository repo;
+  public static int Count { get; set; }
+}";
+
+        let file = CSharpIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!("repo", fields[0].name);
+        assert_eq!("IOrderRepository", fields[0].typ);
+        assert_eq!("private", fields[0].visibility);
+        assert!(fields[0].is_final);
+        assert_eq!("Inject", fields[0].annotations[0].name);
+        assert_eq!("Count", fields[1].name);
+        assert!(fields[1].is_static);
+    }
+
+    #[test]
+    fn should_parse_nested_namespaces() {
+        let source_code = "namespace Shop {
+  namespace Orders {
+    public interface IOrderRepository { }
+    public record OrderPlaced(string Id);
+  }
+}";
+
+        let file = CSharpIdent::parse(source_code).unwrap();
+
+        assert_eq!("Shop.Orders", file.package);
+        assert_eq!(2, file.classes.len());
+        assert_eq!("IOrderRepository", file.classes[0].name);
+        assert_eq!("Shop.Orders", file.classes[0].package);
+        assert_eq!("OrderPlaced", file.classes[1].name);
+    }
+}
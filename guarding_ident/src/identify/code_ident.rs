@@ -1,27 +1,220 @@
-use tree_sitter::{Node, QueryCapture};
+use tree_sitter::{Node, QueryCapture, Tree};
 
+use guarding_core::domain::code_annotation::{AnnotationKeyValue, CodeAnnotation};
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_constructor::{CodeConstructor, CodeParameter};
+use guarding_core::domain::code_field::CodeField;
 use guarding_core::domain::code_function::CodeFunction;
 use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::parse_warning::ParseWarning;
 use guarding_core::domain::Location;
+use crate::error::IdentError;
+use crate::support::{doc_comment, lambdas, metrics};
 
 pub trait CodeIdent {
-    fn parse(code: &str) -> CodeFile;
+    fn parse(code: &str) -> Result<CodeFile, IdentError>;
+
+    /// builds the model from a tree parsed before, like an incrementally re-parsed one
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError>;
 
     fn insert_location<T: Location>(model: &mut T, node: Node) {
         model.set_start(node.start_position().row, node.start_position().column);
         model.set_end(node.end_position().row, node.end_position().column);
     }
 
-    fn create_function( capture: QueryCapture, text: &str) -> CodeFunction {
+    /// `model` with the location of `node`, like an import at its whole statement
+    fn located<T: Location>(mut model: T, node: Node) -> T {
+        Self::insert_location(&mut model, node);
+        model
+    }
+
+    fn create_function(capture: QueryCapture, text: &str, code: &str) -> CodeFunction {
         let mut function = CodeFunction::default();
         function.name = text.to_string();
 
-        let node = capture.node.parent().unwrap();
+        let node = capture.node.parent().unwrap_or(capture.node);
         // RustIdent::insert_location(&mut function, node);
 
         function.set_start(node.start_position().row, node.start_position().column);
         function.set_end(node.end_position().row, node.end_position().column);
+        function.doc = Self::doc_comment(node, code);
+        Self::insert_metrics(&mut function, node);
+        function.lambdas = lambdas::lambdas_of(node, code);
 
         function
     }
-}
\ No newline at end of file
+
+    /// lines of code and cyclomatic complexity of the whole declaration `node`
+    fn insert_metrics(function: &mut CodeFunction, node: Node) {
+        function.loc = metrics::lines_of_code(node);
+        function.complexity = metrics::cyclomatic_complexity(node);
+    }
+
+    /// the kind from the declaration `node`, like `interface` of an `interface_declaration`, and
+    /// whether it's marked `abstract`
+    fn insert_kind(class: &mut CodeClass, node: Node, code: &str) {
+        let kind = match node.kind() {
+            kind if kind.contains("interface") || kind.contains("protocol") => "interface",
+            kind if kind.contains("enum") => "enum",
+            kind if kind.contains("record") => "record",
+            kind if kind.contains("struct") => "struct",
+            kind if kind.contains("trait") || kind.contains("mixin") => "trait",
+            kind if kind.contains("object") => "object",
+            _ => "class"
+        };
+        class.set_kind(kind);
+
+        if node.kind().starts_with("abstract") || Self::has_modifier(node, code, "abstract") {
+            class.is_abstract = true;
+        }
+    }
+
+    /// `keyword` is a direct child of `node` or inside one of its modifier lists
+    fn has_modifier(node: Node, code: &str, keyword: &str) -> bool {
+        let mut cursor = node.walk();
+        let children: Vec<Node> = node.children(&mut cursor).collect();
+        for child in children {
+            if child.child_count() == 0 {
+                if &code[child.byte_range()] == keyword {
+                    return true;
+                }
+            } else if child.kind().contains("modifier") && Self::has_modifier(child, code, keyword) {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// location, doc and metrics of the constructor declared by `node`, the rest is language specific
+    fn create_constructor(node: Node, code: &str) -> CodeConstructor {
+        let mut constructor = Self::located(CodeConstructor::default(), node);
+        constructor.doc = Self::doc_comment(node, code);
+        constructor.loc = metrics::lines_of_code(node);
+        constructor.complexity = metrics::cyclomatic_complexity(node);
+
+        constructor
+    }
+
+    /// `(Repo repo, int size)` like lists: `split` gives the name and the type of a parameter
+    fn parameters_of(parameters: Node, code: &str, split: fn(Node) -> Option<(Node, Option<Node>)>) -> Vec<CodeParameter> {
+        let mut cursor = parameters.walk();
+        let named: Vec<Node> = parameters.named_children(&mut cursor).collect();
+        named.into_iter()
+            .filter_map(split)
+            .map(|(name, typ)| CodeParameter {
+                name: code[name.byte_range()].to_string(),
+                typ: typ.map(|typ| code[typ.byte_range()].trim_start_matches(':').trim().to_string()).unwrap_or_default(),
+            })
+            .collect()
+    }
+
+    /// the first of `public`, `protected`, `private` and `internal` in `modifiers`
+    fn visibility_in(modifiers: Node, code: &str) -> String {
+        let mut field = CodeField::default();
+        Self::apply_modifiers(&mut field, modifiers, code);
+        field.visibility
+    }
+
+    /// Javadoc, `///` and other doc comments right before the declaration `node`
+    fn doc_comment(node: Node, code: &str) -> String {
+        doc_comment::preceding_doc(node, code, false)
+    }
+
+    fn annotation_at(node: Node, name: &str) -> CodeAnnotation {
+        let mut annotation = CodeAnnotation::default();
+        annotation.name = name.to_string();
+        Self::insert_location(&mut annotation, node);
+
+        annotation
+    }
+
+    /// literals lose their quotes: `"/orders"` -> `/orders`, other values are kept as written
+    fn annotation_value(node: Node, code: &str) -> String {
+        let text = code[node.byte_range()].trim();
+        let quotes: &[char] = &['"', '\'', '`'];
+        if text.len() >= 2 && text.starts_with(quotes) && text.ends_with(quotes) {
+            return text[1..text.len() - 1].to_string();
+        }
+
+        text.to_string()
+    }
+
+    /// `(a, key = b)` like arguments: `split` gives the key of a named one, the others are keyed by index
+    fn argument_key_values(arguments: Node, code: &str, split: fn(Node) -> (Option<Node>, Node)) -> Vec<AnnotationKeyValue> {
+        let mut key_values = vec![];
+        let mut cursor = arguments.walk();
+        let named = arguments.named_children(&mut cursor).filter(|argument| !argument.kind().contains("comment"));
+        for (index, argument) in named.enumerate() {
+            let (key, value) = split(argument);
+            let key = match key {
+                Some(key) => code[key.byte_range()].to_string(),
+                None => index.to_string()
+            };
+            key_values.push(AnnotationKeyValue::new(&key, &Self::annotation_value(value, code)));
+        }
+
+        key_values
+    }
+
+    /// keywords like `private`, `static` and `final`, the others are left alone
+    fn apply_modifier(field: &mut CodeField, keyword: &str) {
+        match keyword {
+            "public" | "protected" | "private" | "internal" => field.visibility = keyword.to_string(),
+            "static" => field.is_static = true,
+            "final" | "val" | "const" | "readonly" => field.is_final = true,
+            _ => {}
+        }
+    }
+
+    /// the keywords anywhere in a `modifiers` node, annotations excluded
+    fn apply_modifiers(field: &mut CodeField, modifiers: Node, code: &str) {
+        let mut cursor = modifiers.walk();
+        for child in modifiers.children(&mut cursor) {
+            if child.kind().contains("annotation") || child.kind().contains("attribute") {
+                continue;
+            }
+
+            if child.child_count() == 0 {
+                Self::apply_modifier(field, &code[child.byte_range()]);
+            } else {
+                Self::apply_modifiers(field, child, code);
+            }
+        }
+    }
+
+    /// a loose piece of a broken statement, like half of an import left in an `ERROR` node
+    fn is_error_fragment(node: Node) -> bool {
+        node.kind() == "ERROR" || node.is_missing() || node.parent().map(|parent| parent.kind() == "ERROR").unwrap_or(false)
+    }
+
+    /// every `ERROR` subtree and missing token, without looking into the `ERROR` ones
+    fn parse_warnings(root: Node, code: &str) -> Vec<ParseWarning> {
+        let mut warnings = vec![];
+        let mut nodes = vec![root];
+        while let Some(node) = nodes.pop() {
+            if node.kind() == "ERROR" || node.is_missing() {
+                let mut warning = ParseWarning::default();
+                warning.message = if node.is_missing() {
+                    format!("missing `{}`", node.kind())
+                } else {
+                    let text: String = code[node.byte_range()].chars().take(40).collect();
+                    format!("syntax error near `{}`", text.lines().next().unwrap_or("").trim())
+                };
+                Self::insert_location(&mut warning, node);
+                warnings.push(warning);
+                continue;
+            }
+
+            if !node.has_error() {
+                continue;
+            }
+
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.children(&mut cursor).collect();
+            nodes.extend(children.into_iter().rev());
+        }
+
+        warnings
+    }
+}
@@ -0,0 +1,240 @@
+use std::collections::BTreeSet;
+
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, metrics, queries};
+
+const DART_QUERY: &'static str = include_str!("../../queries/dart.scm");
+
+pub struct DartIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl DartIdent {
+    pub fn new() -> Result<DartIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_dart::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "dart", DART_QUERY)?;
+
+        Ok(DartIdent { parser, query })
+    }
+}
+
+impl DartIdent {
+    fn do_parse(code: &str, ident: &mut DartIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(DartIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &DartIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = DartIdent::parse_warnings(tree.root_node(), code);
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if DartIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "import-uri" => {
+                    let uri = text.trim_matches(|c| c == '\'' || c == '"');
+                    code_file.imports.push(DartIdent::located(CodeImport::module(uri), capture.node));
+                }
+                "class" => {
+                    if let Some(class) = DartIdent::create_class(capture.node, code) {
+                        code_file.classes.push(class);
+                    }
+                }
+                "function" => {
+                    if let Some(function) = DartIdent::create_method(capture.node, code) {
+                        code_file.functions.push(function);
+                    }
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        code_file
+    }
+
+    /// classes, mixins and extensions; `with` mixins are kept with the interfaces
+    fn create_class(node: Node, code: &str) -> Option<CodeClass> {
+        let name = node.child_by_field_name("name")
+            .or(DartIdent::first_child_of_kind(node, "identifier"))?;
+
+        let mut class = CodeClass::default();
+        class.name = DartIdent::node_text(name, code);
+        DartIdent::insert_location(&mut class, node);
+        DartIdent::insert_kind(&mut class, node, code);
+        class.doc = DartIdent::doc_comment(node, code);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "superclass" => {
+                    let mut super_cursor = child.walk();
+                    for typ in child.named_children(&mut super_cursor) {
+                        match typ.kind() {
+                            "mixins" => DartIdent::push_types(&mut class.implements, typ, code),
+                            "type_identifier" => class.extends.push(DartIdent::node_text(typ, code)),
+                            _ => {}
+                        }
+                    }
+                }
+                "mixins" | "interfaces" => {
+                    DartIdent::push_types(&mut class.implements, child, code);
+                }
+                "class_body" | "extension_body" => {
+                    let mut body_cursor = child.walk();
+                    for member in child.named_children(&mut body_cursor) {
+                        if member.kind() != "method_signature" && member.kind() != "declaration" {
+                            continue;
+                        }
+
+                        let signature = DartIdent::first_child_of_kind(member, "function_signature");
+                        if let Some(function) = signature.and_then(|signature| DartIdent::create_method(signature, code)) {
+                            class.functions.push(function);
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(class)
+    }
+
+    fn push_types(types: &mut Vec<String>, node: Node, code: &str) {
+        let mut cursor = node.walk();
+        for typ in node.named_children(&mut cursor) {
+            if typ.kind() == "type_identifier" {
+                types.push(DartIdent::node_text(typ, code));
+            }
+        }
+    }
+
+    fn create_method(signature: Node, code: &str) -> Option<CodeFunction> {
+        let name = signature.child_by_field_name("name")
+            .or(DartIdent::first_child_of_kind(signature, "identifier"))?;
+
+        let mut function = CodeFunction::default();
+        function.name = DartIdent::node_text(name, code);
+        DartIdent::insert_location(&mut function, signature);
+        function.doc = DartIdent::doc_comment(signature, code);
+
+        // the body is the next sibling of the signature
+        let mut rows = BTreeSet::new();
+        metrics::code_rows(signature, &mut rows);
+        function.complexity = metrics::cyclomatic_complexity(signature);
+        if let Some(body) = signature.next_named_sibling().filter(|body| body.kind() == "function_body") {
+            metrics::code_rows(body, &mut rows);
+            function.complexity += metrics::branch_count(body);
+            function.lambdas = lambdas::lambdas_of(body, code);
+        }
+        function.loc = rows.len();
+
+        Some(function)
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for DartIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = DartIdent::new()?;
+        DartIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = DartIdent::new()?;
+        Ok(DartIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::dart_ident::DartIdent;
+
+    #[test]
+    fn should_parse_imports() {
+        let source_code = "import 'package:flutter/material.dart';
+import '../catalog/product.dart' as catalog;
+";
+        let file = DartIdent::parse(source_code).unwrap();
+        assert_eq!(vec!["package:flutter/material.dart", "../catalog/product.dart"], file.import_sources());
+    }
+
+    #[test]
+    fn should_parse_class_with_mixins() {
+        let source_code = "class CartPage extends StatefulWidget with Logging implements Routable {
+  Widget build(BuildContext context) {
+    return Container();
+  }
+}
+";
+        let file = DartIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("CartPage", class.name);
+        assert_eq!(vec!["StatefulWidget"], class.extends);
+        assert_eq!(vec!["Logging", "Routable"], class.implements);
+        assert_eq!("build", class.functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_mixins_and_functions() {
+        let source_code = "mixin Logging {
+  void log(String message) {}
+}
+
+void main() {
+  runApp(App());
+}
+";
+        let file = DartIdent::parse(source_code).unwrap();
+
+        assert_eq!("Logging", file.classes[0].name);
+        assert_eq!("log", file.classes[0].functions[0].name);
+        assert_eq!("main", file.functions[0].name);
+    }
+}
@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{doc_comment, lambdas, queries};
+
+const GO_QUERY: &'static str = include_str!("../../queries/go.scm");
+
+pub struct GoIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl GoIdent {
+    pub fn new() -> Result<GoIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_go::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "go", GO_QUERY)?;
+
+        Ok(GoIdent { parser, query })
+    }
+
+    /// Go import paths use `/`, the package model uses `.`
+    pub fn import_to_package(import: &str) -> String {
+        import.trim_matches('"').replace('/', ".")
+    }
+}
+
+impl GoIdent {
+    fn do_parse(code: &str, ident: &mut GoIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(GoIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &GoIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = GoIdent::parse_warnings(tree.root_node(), code);
+        let mut receiver_methods: HashMap<String, Vec<CodeFunction>> = Default::default();
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if GoIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "package-name" => {
+                    code_file.package = text.to_string();
+                }
+                "import-name" => {
+                    let spec = capture.node.parent().unwrap_or(capture.node);
+                    let import = CodeImport::module(&GoIdent::import_to_package(text));
+                    code_file.imports.push(GoIdent::located(import, spec));
+                }
+                "type-name" => {
+                    let spec_node = capture.node.parent().unwrap_or(capture.node);
+                    if let Some(mut class) = GoIdent::create_type(spec_node, text, code) {
+                        class.package = code_file.package.clone();
+                        code_file.classes.push(class);
+                    }
+                }
+                "method-name" => {
+                    let method_node = capture.node.parent().unwrap_or(capture.node);
+                    let function = GoIdent::create_function(capture, text, code);
+                    match GoIdent::receiver_type(method_node, code) {
+                        Some(receiver) => {
+                            receiver_methods.entry(receiver).or_insert_with(Vec::new).push(function);
+                        }
+                        None => code_file.functions.push(function)
+                    }
+                }
+                "function-name" => {
+                    code_file.functions.push(GoIdent::create_function(capture, text, code));
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        for clz in code_file.classes.iter_mut() {
+            if let Some(methods) = receiver_methods.remove(clz.name.as_str()) {
+                clz.functions.extend(methods);
+            }
+        }
+
+        // receivers declared in another file of the package are still methods of the package
+        for (_, methods) in receiver_methods {
+            code_file.functions.extend(methods);
+        }
+
+        code_file.functions.sort_by_key(|function| function.start.row);
+        code_file
+    }
+
+    /// only structs and interfaces are classes, `type ID string` is not
+    fn create_type(spec_node: Node, name: &str, code: &str) -> Option<CodeClass> {
+        let type_node = spec_node.child_by_field_name("type")?;
+
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        GoIdent::insert_location(&mut class, spec_node);
+        class.doc = GoIdent::doc_comment(spec_node, code);
+
+        GoIdent::insert_kind(&mut class, type_node, code);
+        match type_node.kind() {
+            "struct_type" => {
+                let mut cursor = type_node.walk();
+                for list in type_node.named_children(&mut cursor) {
+                    let mut list_cursor = list.walk();
+                    for field in list.named_children(&mut list_cursor) {
+                        if field.kind() != "field_declaration" {
+                            continue;
+                        }
+
+                        // embedded struct: a field without name
+                        if field.child_by_field_name("name").is_none() {
+                            if let Some(embedded) = field.child_by_field_name("type") {
+                                class.extends.push(GoIdent::type_name(embedded, code));
+                            }
+                        } else {
+                            class.fields.extend(GoIdent::create_fields(field, code));
+                        }
+                    }
+                }
+            }
+            "interface_type" => {
+                // older grammars wrap the members in a `method_spec_list`
+                let mut members = vec![];
+                let mut cursor = type_node.walk();
+                for child in type_node.named_children(&mut cursor) {
+                    if child.kind() == "method_spec_list" {
+                        let mut list_cursor = child.walk();
+                        members.extend(child.named_children(&mut list_cursor));
+                    } else {
+                        members.push(child);
+                    }
+                }
+
+                for member in members {
+                    match member.kind() {
+                        "method_spec" | "method_elem" => {
+                            if let Some(name_node) = member.child_by_field_name("name") {
+                                let mut function = CodeFunction::default();
+                                function.name = name_node.utf8_text(code.as_bytes()).unwrap_or("").to_string();
+                                GoIdent::insert_location(&mut function, member);
+                                function.doc = GoIdent::doc_comment(member, code);
+                                GoIdent::insert_metrics(&mut function, member);
+                                function.lambdas = lambdas::lambdas_of(member, code);
+                                class.functions.push(function);
+                            }
+                        }
+                        "type_identifier" | "qualified_type" => {
+                            class.extends.push(GoIdent::type_name(member, code));
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            _ => return None
+        }
+
+        Some(class)
+    }
+
+    /// `func (s *OrderService) Find()` -> `OrderService`
+    fn receiver_type(method_node: Node, code: &str) -> Option<String> {
+        let receiver = method_node.child_by_field_name("receiver")?;
+        let parameter = receiver.named_child(0)?;
+        let type_node = parameter.child_by_field_name("type")?;
+
+        Some(GoIdent::type_name(type_node, code))
+    }
+
+    /// `X, Y int` is two fields, exported ones are public
+    fn create_fields(node: Node, code: &str) -> Vec<CodeField> {
+        let typ = node.child_by_field_name("type")
+            .map(|typ| typ.utf8_text(code.as_bytes()).unwrap_or("").to_string())
+            .unwrap_or_default();
+
+        let mut fields = vec![];
+        let mut cursor = node.walk();
+        for name in node.named_children(&mut cursor) {
+            if name.kind() != "field_identifier" {
+                continue;
+            }
+
+            let mut field = CodeField::default();
+            field.name = name.utf8_text(code.as_bytes()).unwrap_or("").to_string();
+            field.typ = typ.clone();
+            field.visibility = if field.name.starts_with(char::is_uppercase) { "public" } else { "private" }.to_string();
+            GoIdent::insert_location(&mut field, node);
+            fields.push(field);
+        }
+
+        fields
+    }
+
+    fn type_name(node: Node, code: &str) -> String {
+        let mut current = node;
+        loop {
+            match current.kind() {
+                "pointer_type" | "generic_type" => {
+                    match current.named_child(0) {
+                        Some(child) => current = child,
+                        None => break
+                    }
+                }
+                _ => break
+            }
+        }
+
+        current.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for GoIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = GoIdent::new()?;
+        GoIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = GoIdent::new()?;
+        Ok(GoIdent::from_tree(code, tree, &ident))
+    }
+
+    /// Go docs are the plain `//` lines right before the declaration
+    fn doc_comment(node: Node, code: &str) -> String {
+        doc_comment::preceding_doc(node, code, true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::go_ident::GoIdent;
+
+    #[test]
+    fn should_parse_package_and_imports() {
+        let source_code = "package service
+
+import \"fmt\"
+
+import (
+    \"github.com/inherd/shop/internal/repo\"
+    log \"github.com/sirupsen/logrus\"
+)
+";
+        let file = GoIdent::parse(source_code).unwrap();
+
+        assert_eq!("service", file.package);
+        assert_eq!(vec!["fmt", "github.com.inherd.shop.internal.repo", "github.com.sirupsen.logrus"], file.import_sources());
+    }
+
+    #[test]
+    fn should_attach_methods_to_receiver() {
+        let source_code = "package service
+
+type OrderService struct {
+    repo.Base
+    name string
+}
+
+func (s *OrderService) Find(id string) {}
+
+func (s OrderService) Save() {}
+
+func NewOrderService() *OrderService {
+    return &OrderService{}
+}
+";
+        let file = GoIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("OrderService", class.name);
+        assert_eq!("service", class.package);
+        assert_eq!(vec!["repo.Base"], class.extends);
+        assert_eq!(2, class.functions.len());
+        assert_eq!("Find", class.functions[0].name);
+        assert_eq!(1, file.functions.len());
+        assert_eq!("NewOrderService", file.functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_interface() {
+        let source_code = "package repo
+
+type Repository interface {
+    io.Closer
+    Find(id string) error
+}
+
+type ID string
+";
+        let file = GoIdent::parse(source_code).unwrap();
+
+        assert_eq!(1, file.classes.len());
+        assert_eq!("Repository", file.classes[0].name);
+        assert_eq!(vec!["io.Closer"], file.classes[0].extends);
+        assert_eq!("Find", file.classes[0].functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_struct_fields() {
+        let source_code = "package cart
+
+type Cart struct {
+    Items, gifts []Item
+    *Base
+}
+";
+        let file = GoIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!(2, fields.len());
+        assert_eq!("Items", fields[0].name);
+        assert_eq!("[]Item", fields[0].typ);
+        assert_eq!("public", fields[0].visibility);
+        assert_eq!("private", fields[1].visibility);
+        assert_eq!(vec!["Base"], file.classes[0].extends);
+    }
+
+    #[test]
+    fn should_capture_doc_lines() {
+        let source_code = "package cart
+
+// Cart holds the items
+// of an order.
+type Cart struct {}
+
+func total() int { return 0 }
+";
+        let file = GoIdent::parse(source_code).unwrap();
+
+        assert_eq!("Cart holds the items\nof an order.", file.classes[0].doc);
+        assert_eq!("", file.functions[0].doc);
+    }
+}
@@ -0,0 +1,341 @@
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_annotation::{AnnotationKeyValue, CodeAnnotation};
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
+
+const GROOVY_QUERY: &'static str = include_str!("../../queries/groovy.scm");
+
+/// the grammar follows the Java one, so packages and imports are the same as in Java
+pub struct GroovyIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl GroovyIdent {
+    pub fn new() -> Result<GroovyIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_groovy::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "groovy", GROOVY_QUERY)?;
+
+        Ok(GroovyIdent { parser, query })
+    }
+}
+
+impl GroovyIdent {
+    fn do_parse(code: &str, ident: &mut GroovyIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(GroovyIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &GroovyIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = GroovyIdent::parse_warnings(tree.root_node(), code);
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if GroovyIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "package-name" => {
+                    code_file.package = text.to_string();
+                }
+                "import-name" => {
+                    let statement = capture.node.parent().unwrap_or(capture.node);
+                    code_file.imports.push(GroovyIdent::located(GroovyIdent::create_import(statement, text), statement));
+                }
+                "class-name" => {
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
+                    let mut class = GroovyIdent::create_class(class_node, text, code);
+                    class.package = code_file.package.clone();
+                    code_file.classes.push(class);
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        code_file
+    }
+
+    fn create_class(node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        GroovyIdent::insert_location(&mut class, node);
+        GroovyIdent::insert_kind(&mut class, node, code);
+        class.doc = GroovyIdent::doc_comment(node, code);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "modifiers" => {
+                    class.annotations = GroovyIdent::annotations_of(child, code);
+                }
+                "superclass" => {
+                    if let Some(typ) = child.named_child(0) {
+                        class.extends.push(GroovyIdent::node_text(typ, code));
+                    }
+                }
+                "super_interfaces" | "extends_interfaces" => {
+                    if let Some(list) = child.named_child(0) {
+                        let mut list_cursor = list.walk();
+                        for typ in list.named_children(&mut list_cursor) {
+                            class.implements.push(GroovyIdent::node_text(typ, code));
+                        }
+                    }
+                }
+                "class_body" | "interface_body" => {
+                    let mut body_cursor = child.walk();
+                    for member in child.named_children(&mut body_cursor) {
+                        match member.kind() {
+                            "method_declaration" => {
+                                if let Some(function) = GroovyIdent::create_method(member, code) {
+                                    class.functions.push(function);
+                                }
+                            }
+                            "field_declaration" => {
+                                class.fields.extend(GroovyIdent::create_fields(member, code));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        class
+    }
+
+    /// Spock features are named by strings: `def "should place order"()`
+    fn create_method(node: Node, code: &str) -> Option<CodeFunction> {
+        let name = node.child_by_field_name("name")?;
+
+        let mut function = CodeFunction::default();
+        function.name = GroovyIdent::node_text(name, code)
+            .trim_matches(|c| c == '"' || c == '\'')
+            .to_string();
+        if let Some(modifiers) = GroovyIdent::first_child_of_kind(node, "modifiers") {
+            function.annotations = GroovyIdent::annotations_of(modifiers, code);
+        }
+        GroovyIdent::insert_location(&mut function, node);
+        function.doc = GroovyIdent::doc_comment(node, code);
+        GroovyIdent::insert_metrics(&mut function, node);
+        function.lambdas = lambdas::lambdas_of(node, code);
+
+        Some(function)
+    }
+
+    /// `private String name, title` declares two fields
+    fn create_fields(node: Node, code: &str) -> Vec<CodeField> {
+        let mut template = CodeField::default();
+        template.typ = node.child_by_field_name("type")
+            .map(|typ| GroovyIdent::node_text(typ, code))
+            .unwrap_or_default();
+        if let Some(modifiers) = GroovyIdent::first_child_of_kind(node, "modifiers") {
+            GroovyIdent::apply_modifiers(&mut template, modifiers, code);
+            template.annotations = GroovyIdent::annotations_of(modifiers, code);
+        }
+        GroovyIdent::insert_location(&mut template, node);
+
+        let mut fields = vec![];
+        let mut cursor = node.walk();
+        for declarator in node.named_children(&mut cursor) {
+            if declarator.kind() != "variable_declarator" {
+                continue;
+            }
+
+            if let Some(name) = declarator.child_by_field_name("name") {
+                let mut field = template.clone();
+                field.name = GroovyIdent::node_text(name, code);
+                fields.push(field);
+            }
+        }
+
+        fields
+    }
+
+    fn annotations_of(modifiers: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut cursor = modifiers.walk();
+        for node in modifiers.named_children(&mut cursor) {
+            if node.kind() != "annotation" && node.kind() != "marker_annotation" {
+                continue;
+            }
+
+            if let Some(name) = node.child_by_field_name("name") {
+                let mut annotation = GroovyIdent::annotation_at(node, &GroovyIdent::node_text(name, code));
+                if let Some(arguments) = node.child_by_field_name("arguments") {
+                    annotation.key_values = GroovyIdent::annotation_arguments(arguments, code);
+                }
+                annotations.push(annotation);
+            }
+        }
+
+        annotations
+    }
+
+    /// same as Java: pairs, or a single `value`
+    fn annotation_arguments(arguments: Node, code: &str) -> Vec<AnnotationKeyValue> {
+        let mut key_values = vec![];
+        let mut cursor = arguments.walk();
+        for argument in arguments.named_children(&mut cursor) {
+            if argument.kind().contains("comment") {
+                continue;
+            }
+
+            if argument.kind() == "element_value_pair" {
+                let key = argument.child_by_field_name("key");
+                let value = argument.child_by_field_name("value");
+                if let (Some(key), Some(value)) = (key, value) {
+                    let value = GroovyIdent::annotation_value(value, code);
+                    key_values.push(AnnotationKeyValue::new(&GroovyIdent::node_text(key, code), &value));
+                }
+            } else {
+                key_values.push(AnnotationKeyValue::new("value", &GroovyIdent::annotation_value(argument, code)));
+            }
+        }
+
+        key_values
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    /// `import static a.B.c;` and `import a.b.*;` keep their flags
+    fn create_import(statement: Node, text: &str) -> CodeImport {
+        let mut cursor = statement.walk();
+        let keywords: Vec<&str> = statement.children(&mut cursor).map(|child| child.kind()).collect();
+
+        let mut import = if keywords.contains(&"asterisk") {
+            CodeImport::wildcard(text)
+        } else {
+            CodeImport::symbol(text, ".")
+        };
+        import.is_static = keywords.contains(&"static");
+        import
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for GroovyIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = GroovyIdent::new()?;
+        GroovyIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = GroovyIdent::new()?;
+        Ok(GroovyIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::groovy_ident::GroovyIdent;
+
+    #[test]
+    fn should_parse_package_and_imports() {
+        let source_code = "package com.phodal.shop.order
+
+import com.phodal.shop.repo.OrderRepository
+import spock.lang.Specification
+";
+        let file = GroovyIdent::parse(source_code).unwrap();
+
+        assert_eq!("com.phodal.shop.order", file.package);
+        assert_eq!(vec!["com.phodal.shop.repo.OrderRepository", "spock.lang.Specification"], file.import_sources());
+    }
+
+    #[test]
+    fn should_parse_class_with_methods() {
+        let source_code = "package com.phodal.shop.order
+
+@Service
+class OrderService extends BaseService implements Finder {
+    Order find(String id) {
+        return repo.find(id)
+    }
+}
+";
+        let file = GroovyIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("OrderService", class.name);
+        assert_eq!("com.phodal.shop.order", class.package);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(vec!["Finder"], class.implements);
+        assert_eq!("Service", class.annotations[0].name);
+        assert_eq!("find", class.functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_spock_feature_methods() {
+        let source_code = "class OrderServiceSpec extends Specification {
+    def \"should place order\"() {
+        expect:
+        true
+    }
+}
+";
+        let file = GroovyIdent::parse(source_code).unwrap();
+
+        assert_eq!(vec!["Specification"], file.classes[0].extends);
+        assert_eq!("should place order", file.classes[0].functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_fields() {
+        let source_code = "class OrderService {
+    @Inject
+    private OrderRepository repo
+    static final int LIMIT = 10
+}
+";
+        let file = GroovyIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!("repo", fields[0].name);
+        assert_eq!("OrderRepository", fields[0].typ);
+        assert_eq!("private", fields[0].visibility);
+        assert_eq!("Inject", fields[0].annotations[0].name);
+        assert!(fields[1].is_static && fields[1].is_final);
+    }
+}
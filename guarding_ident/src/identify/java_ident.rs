@@ -1,33 +1,18 @@
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
 
+use guarding_core::domain::code_annotation::{AnnotationKeyValue, CodeAnnotation};
 use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
 use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_constructor::CodeConstructor;
+use guarding_core::domain::code_function::CodeFunction;
 use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
 
-const JAVA_QUERY: &'static str = "
-(package_declaration
-	(scoped_identifier) @package-name)
-
-(import_declaration
-	(scoped_identifier) @import-name)
-
-(method_declaration
-	(modifiers
-    	(annotation
-    		name: (identifier) @annotation.name
-            arguments: (annotation_argument_list)? @annotation.key_values
-    	)
-    )
-)
-
-(program
-    (class_declaration
-	    name: (identifier) @class-name
-        interfaces: (super_interfaces (interface_type_list (type_identifier)  @impl-name))?
-    )
-)
-
-";
+const JAVA_QUERY: &'static str = include_str!("../../queries/java.scm");
 
 pub struct JavaIdent {
     parser: Parser,
@@ -35,29 +20,37 @@ pub struct JavaIdent {
 }
 
 impl JavaIdent {
-    pub fn new() -> JavaIdent {
+    pub fn new() -> Result<JavaIdent, IdentError> {
         let mut parser = Parser::new();
         let language = tree_sitter_java::language();
-        parser.set_language(language).unwrap();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
 
-        let query = Query::new(language, &JAVA_QUERY)
-            .map_err(|e| println!("{}", format!("Query compilation failed: {:?}", e))).unwrap();
+        let query = queries::load_query(language, "java", JAVA_QUERY)?;
 
-        JavaIdent {
+        Ok(JavaIdent {
             parser,
             query
-        }
+        })
     }
 }
 
 impl JavaIdent {
-    fn do_parse(code: &&str, ident: &mut JavaIdent) -> CodeFile {
-        let tree = ident.parser.parse(code, None).unwrap();
+    fn do_parse(code: &&str, ident: &mut JavaIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(JavaIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &&str, tree: &Tree, ident: &JavaIdent) -> CodeFile {
         let text_callback = |n: Node| &code[n.byte_range()];
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
         let mut code_file = CodeFile::default();
+        code_file.warnings = JavaIdent::parse_warnings(tree.root_node(), code);
         let mut class = CodeClass::default();
         let mut is_last_node = false;
 
@@ -65,6 +58,9 @@ impl JavaIdent {
 
         for (mat, capture_index) in captures {
             let capture = mat.captures[capture_index];
+            if JavaIdent::is_error_fragment(capture.node) {
+                continue;
+            }
             let capture_name = &capture_names[capture.index as usize];
 
             let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
@@ -73,7 +69,8 @@ impl JavaIdent {
                     code_file.package = text.to_string();
                 }
                 "import-name" => {
-                    code_file.imports.push(text.to_string());
+                    let statement = capture.node.parent().unwrap_or(capture.node);
+                    code_file.imports.push(JavaIdent::located(JavaIdent::create_import(statement, text), statement));
                 }
                 "class-name" => {
                     if !class.name.is_empty() {
@@ -84,8 +81,16 @@ impl JavaIdent {
                     class.name = text.to_string();
                     class.package = code_file.package.clone();
 
-                    let class_node = capture.node.parent().unwrap();
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
                     JavaIdent::insert_location(&mut class, class_node);
+                    JavaIdent::insert_kind(&mut class, class_node, code);
+                    class.doc = JavaIdent::doc_comment(class_node, code);
+                    class.fields = JavaIdent::fields_of(class_node, code);
+                    class.functions = JavaIdent::methods_of(class_node, code);
+                    class.constructors = JavaIdent::constructors_of(class_node, code);
+                    if let Some(modifiers) = JavaIdent::first_child_of_kind(class_node, "modifiers") {
+                        class.annotations = JavaIdent::annotations_of(modifiers, code);
+                    }
                     if !is_last_node {
                         is_last_node = true;
                     }
@@ -95,8 +100,8 @@ impl JavaIdent {
                 }
                 "parameter" => {}
                 &_ => {
-                    println!(
-                        "    pattern: {}, capture: {}, row: {}, text: {:?}",
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
                         mat.pattern_index,
                         capture_name,
                         capture.node.start_position().row,
@@ -112,13 +117,200 @@ impl JavaIdent {
 
         code_file
     }
+
+    /// `private final Repo repo, backup;` is two fields
+    fn fields_of(class_node: Node, code: &str) -> Vec<CodeField> {
+        let mut fields = vec![];
+        let body = match class_node.child_by_field_name("body") {
+            Some(body) => body,
+            None => return fields
+        };
+
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            if member.kind() != "field_declaration" {
+                continue;
+            }
+
+            let mut template = CodeField::default();
+            template.typ = member.child_by_field_name("type")
+                .map(|typ| JavaIdent::node_text(typ, code))
+                .unwrap_or_default();
+            if let Some(modifiers) = JavaIdent::first_child_of_kind(member, "modifiers") {
+                JavaIdent::apply_modifiers(&mut template, modifiers, code);
+                template.annotations = JavaIdent::annotations_of(modifiers, code);
+            }
+            JavaIdent::insert_location(&mut template, member);
+
+            let mut declarator_cursor = member.walk();
+            for declarator in member.named_children(&mut declarator_cursor) {
+                if declarator.kind() != "variable_declarator" {
+                    continue;
+                }
+
+                if let Some(name) = declarator.child_by_field_name("name") {
+                    let mut field = template.clone();
+                    field.name = JavaIdent::node_text(name, code);
+                    fields.push(field);
+                }
+            }
+        }
+
+        fields
+    }
+
+    fn methods_of(class_node: Node, code: &str) -> Vec<CodeFunction> {
+        let mut functions = vec![];
+        let body = match class_node.child_by_field_name("body") {
+            Some(body) => body,
+            None => return functions
+        };
+
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            if member.kind() != "method_declaration" {
+                continue;
+            }
+
+            if let Some(name) = member.child_by_field_name("name") {
+                let mut function = CodeFunction::default();
+                function.name = JavaIdent::node_text(name, code);
+                if let Some(modifiers) = JavaIdent::first_child_of_kind(member, "modifiers") {
+                    function.annotations = JavaIdent::annotations_of(modifiers, code);
+                }
+                JavaIdent::insert_location(&mut function, member);
+                function.doc = JavaIdent::doc_comment(member, code);
+                JavaIdent::insert_metrics(&mut function, member);
+                function.lambdas = lambdas::lambdas_of(member, code);
+                functions.push(function);
+            }
+        }
+
+        functions
+    }
+
+    fn constructors_of(class_node: Node, code: &str) -> Vec<CodeConstructor> {
+        let mut constructors = vec![];
+        let body = match class_node.child_by_field_name("body") {
+            Some(body) => body,
+            None => return constructors
+        };
+
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            if member.kind() != "constructor_declaration" {
+                continue;
+            }
+
+            let mut constructor = JavaIdent::create_constructor(member, code);
+            if let Some(modifiers) = JavaIdent::first_child_of_kind(member, "modifiers") {
+                constructor.annotations = JavaIdent::annotations_of(modifiers, code);
+                constructor.visibility = JavaIdent::visibility_in(modifiers, code);
+            }
+            if let Some(parameters) = member.child_by_field_name("parameters") {
+                constructor.parameters = JavaIdent::parameters_of(parameters, code, JavaIdent::parameter_parts);
+            }
+
+            // `this(...)` or `super(...)` can only be the first statement
+            let invocation = member.child_by_field_name("body")
+                .and_then(|body| JavaIdent::first_child_of_kind(body, "explicit_constructor_invocation"));
+            if let Some(target) = invocation.and_then(|invocation| invocation.child_by_field_name("constructor")) {
+                constructor.delegates_to = JavaIdent::node_text(target, code);
+            }
+
+            constructors.push(constructor);
+        }
+
+        constructors
+    }
+
+    fn parameter_parts(parameter: Node) -> Option<(Node, Option<Node>)> {
+        match parameter.kind() {
+            "formal_parameter" => Some((parameter.child_by_field_name("name")?, parameter.child_by_field_name("type"))),
+            _ => None
+        }
+    }
+
+    fn annotations_of(modifiers: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut cursor = modifiers.walk();
+        for node in modifiers.named_children(&mut cursor) {
+            if node.kind() != "annotation" && node.kind() != "marker_annotation" {
+                continue;
+            }
+
+            if let Some(name) = node.child_by_field_name("name") {
+                let mut annotation = JavaIdent::annotation_at(node, &JavaIdent::node_text(name, code));
+                if let Some(arguments) = node.child_by_field_name("arguments") {
+                    annotation.key_values = JavaIdent::annotation_arguments(arguments, code);
+                }
+                annotations.push(annotation);
+            }
+        }
+
+        annotations
+    }
+
+    /// `@RequestMapping(value = "/a", method = GET)` has pairs, `@Named("a")` a single `value`
+    fn annotation_arguments(arguments: Node, code: &str) -> Vec<AnnotationKeyValue> {
+        let mut key_values = vec![];
+        let mut cursor = arguments.walk();
+        for argument in arguments.named_children(&mut cursor) {
+            if argument.kind().contains("comment") {
+                continue;
+            }
+
+            if argument.kind() == "element_value_pair" {
+                let key = argument.child_by_field_name("key");
+                let value = argument.child_by_field_name("value");
+                if let (Some(key), Some(value)) = (key, value) {
+                    let value = JavaIdent::annotation_value(value, code);
+                    key_values.push(AnnotationKeyValue::new(&JavaIdent::node_text(key, code), &value));
+                }
+            } else {
+                key_values.push(AnnotationKeyValue::new("value", &JavaIdent::annotation_value(argument, code)));
+            }
+        }
+
+        key_values
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    /// `import static a.B.c;` and `import a.b.*;` keep their flags
+    fn create_import(statement: Node, text: &str) -> CodeImport {
+        let mut cursor = statement.walk();
+        let keywords: Vec<&str> = statement.children(&mut cursor).map(|child| child.kind()).collect();
+
+        let mut import = if keywords.contains(&"asterisk") {
+            CodeImport::wildcard(text)
+        } else {
+            CodeImport::symbol(text, ".")
+        };
+        import.is_static = keywords.contains(&"static");
+        import
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
 }
 
 impl CodeIdent for JavaIdent {
-    fn parse(code: &str) -> CodeFile {
-        let mut ident = JavaIdent::new();
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = JavaIdent::new()?;
         JavaIdent::do_parse(&code, &mut ident)
     }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = JavaIdent::new()?;
+        Ok(JavaIdent::from_tree(&code, tree, &ident))
+    }
 }
 
 
@@ -133,10 +325,27 @@ mod tests {
 import java.io.InputStream;
 import payroll.Employee;
 ";
-        let file = JavaIdent::parse(source_code);
+        let file = JavaIdent::parse(source_code).unwrap();
         assert_eq!(3, file.imports.len());
     }
 
+    #[test]
+    fn should_parse_import_details() {
+        let source_code = "import java.util.List;
+import java.util.concurrent.*;
+import static java.lang.Math.max;
+";
+        let file = JavaIdent::parse(source_code).unwrap();
+
+        assert_eq!("java.util", file.imports[0].package);
+        assert_eq!("List", file.imports[0].name);
+        assert!(file.imports[1].is_wildcard);
+        assert_eq!("java.util.concurrent", file.imports[1].package);
+        assert!(file.imports[2].is_static);
+        assert_eq!("max", file.imports[2].name);
+        assert_eq!(2, file.imports[2].start.row);
+    }
+
     #[test]
     fn should_parse_impl_java_class() {
         let source_code = "class DateTimeImpl implements DateTime {
@@ -145,7 +354,7 @@ import payroll.Employee;
         return new Date();
     }
 }";
-        let file = JavaIdent::parse(source_code);
+        let file = JavaIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
         assert_eq!("DateTimeImpl", file.classes[0].name);
         assert_eq!(1, file.classes[0].implements.len());
@@ -159,7 +368,7 @@ import payroll.Employee;
         return new Date();
     }
 }";
-        let file = JavaIdent::parse(source_code);
+        let file = JavaIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
         assert_eq!("DateTimeImpl", file.classes[0].name);
     }
@@ -172,7 +381,7 @@ import payroll.Employee;
 class DateTimeImpl2 {
 }
 ";
-        let file = JavaIdent::parse(source_code);
+        let file = JavaIdent::parse(source_code).unwrap();
         assert_eq!(2, file.classes.len());
         assert_eq!("DateTimeImpl", file.classes[0].name);
         assert_eq!("DateTimeImpl2", file.classes[1].name);
@@ -182,7 +391,7 @@ class DateTimeImpl2 {
     fn should_support_package_name() {
         let source_code = "package com.phodal.pepper.powermock;
 ";
-        let file = JavaIdent::parse(source_code);
+        let file = JavaIdent::parse(source_code).unwrap();
         assert_eq!("com.phodal.pepper.powermock", file.package);
     }
 
@@ -196,14 +405,14 @@ class DateTimeImpl2 {
   }
 }";
 
-        let file = JavaIdent::parse(source_code);
+        let file = JavaIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
     }
 
-    #[ignore]
     #[test]
     fn should_support_annotation() {
-        let source_code = "public class HelloController {
+        let source_code = "@RestController
+public class HelloController {
   @RequestMapping(value = \"/ex/foos\", method = RequestMethod.GET)
   @ResponseBody
   public String getFoosBySimplePath() {
@@ -211,7 +420,158 @@ class DateTimeImpl2 {
   }
 }";
 
-        let file = JavaIdent::parse(source_code);
-        println!("{:?}", file);
+        let file = JavaIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+        assert_eq!("RestController", class.annotations[0].name);
+
+        let function = &class.functions[0];
+        assert_eq!("getFoosBySimplePath", function.name);
+        assert_eq!(2, function.annotations.len());
+
+        let mapping = &function.annotations[0];
+        assert_eq!("RequestMapping", mapping.name);
+        assert_eq!(Some("/ex/foos"), mapping.value_of("value"));
+        assert_eq!(Some("RequestMethod.GET"), mapping.value_of("method"));
+        assert_eq!(2, mapping.start.row);
+        assert_eq!("ResponseBody", function.annotations[1].name);
+    }
+
+    #[test]
+    fn should_capture_lambdas_in_methods() {
+        let source_code = "class OrderService {
+  void export() {
+    orders.stream().map(order -> OrderDto.from(order));
+  }
+}";
+
+        let file = JavaIdent::parse(source_code).unwrap();
+        let lambdas = &file.classes[0].functions[0].lambdas;
+        assert_eq!(1, lambdas.len());
+        assert_eq!(vec!["OrderDto"], lambdas[0].references);
+        assert_eq!(2, lambdas[0].start.row);
+    }
+
+    #[test]
+    fn should_parse_constructors() {
+        let source_code = "class OrderService {
+  private final OrderRepository repository;
+
+  @Inject
+  public OrderService(OrderRepository repository, int size) {
+    this.repository = repository;
+  }
+
+  OrderService() {
+    this(null, 0);
+  }
+
+  void run() {}
+}";
+
+        let file = JavaIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+        assert_eq!(1, class.functions.len());
+        assert_eq!(2, class.constructors.len());
+
+        let injected = &class.constructors[0];
+        assert_eq!("public", injected.visibility);
+        assert_eq!("Inject", injected.annotations[0].name);
+        assert_eq!(vec!["repository", "size"], injected.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!("OrderRepository", injected.parameters[0].typ);
+        assert_eq!("", injected.delegates_to);
+
+        assert_eq!("", class.constructors[1].visibility);
+        assert_eq!("this", class.constructors[1].delegates_to);
+    }
+
+    #[test]
+    fn should_set_class_kind() {
+        let source_code = "package shop;
+
+public interface Repository {}
+
+public abstract class BaseRepository implements Repository {}
+
+enum Status { OPEN, CLOSED }
+";
+
+        let file = JavaIdent::parse(source_code).unwrap();
+        let kinds: Vec<(&str, bool)> = file.classes.iter().map(|class| (class.kind.as_str(), class.is_abstract)).collect();
+
+        assert_eq!(vec![("interface", true), ("class", true), ("enum", false)], kinds);
+        assert_eq!(vec!["Repository"], file.classes[1].implements);
+    }
+
+    #[test]
+    fn should_parse_single_annotation_value() {
+        let source_code = "@Named(\"orders\")
+class OrderService {
+}";
+
+        let file = JavaIdent::parse(source_code).unwrap();
+        assert_eq!(Some("orders"), file.classes[0].annotations[0].value_of("value"));
+    }
+
+    #[test]
+    fn should_keep_parsable_parts_of_broken_file() {
+        let source_code = "import java.io.InputStream;
+import payroll.Employee;
+
+class Broken {
+    public void run( {
+    }
+}
+";
+        let file = JavaIdent::parse(source_code).unwrap();
+
+        assert_eq!(vec!["java.io.InputStream", "payroll.Employee"], file.import_sources());
+        assert!(file.warnings.len() > 0);
+        assert_eq!(4, file.warnings[0].start.row);
+    }
+
+    #[test]
+    fn should_capture_javadoc() {
+        let source_code = "package shop;
+
+/**
+ * Places orders.
+ */
+@Service
+public class OrderService {
+}
+
+class Undocumented {
+}
+";
+        let file = JavaIdent::parse(source_code).unwrap();
+
+        assert_eq!("Places orders.", file.classes[0].doc);
+        assert_eq!("", file.classes[1].doc);
+    }
+
+    #[test]
+    fn should_parse_fields() {
+        let source_code = "package shop;
+
+public class OrderService {
+    @Autowired
+    private final OrderRepository repo;
+    public static int count, limit = 10;
+    String name;
+}
+";
+        let file = JavaIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!(4, fields.len());
+        assert_eq!("repo", fields[0].name);
+        assert_eq!("OrderRepository", fields[0].typ);
+        assert_eq!("private", fields[0].visibility);
+        assert!(fields[0].is_final);
+        assert_eq!("Autowired", fields[0].annotations[0].name);
+        assert_eq!("limit", fields[2].name);
+        assert!(fields[2].is_static);
+        assert_eq!("", fields[3].visibility);
+        assert_eq!(6, fields[3].start.row);
     }
 }
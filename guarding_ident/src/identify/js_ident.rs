@@ -1,30 +1,15 @@
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
 
 use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
 use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_constructor::CodeConstructor;
 use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::queries;
 
-const JS_QUERY: &'static str = "
-(import_specifier
-	name: (identifier) @import-name)
-(namespace_import (identifier) @import-name)
-(import_statement
-	source: (string) @source)
-(import_clause (identifier) @import-name)
-
-(class_declaration
-  name: (identifier) @class-name
-  body: (class_body
-    (method_definition
-      name: (property_identifier) @class-method-name
-      parameters: (formal_parameters (identifier)? @parameter)
-    )
-  )
-)
-
-(program (function_declaration
-      name: * @function-name))
-";
+const JS_QUERY: &'static str = include_str!("../../queries/javascript.scm");
 
 pub struct JsIdent {
     parser: Parser,
@@ -33,56 +18,76 @@ pub struct JsIdent {
 
 
 impl JsIdent {
-    fn new() -> JsIdent {
+    fn new() -> Result<JsIdent, IdentError> {
         let mut parser = Parser::new();
 
         let language = tree_sitter_javascript::language();
-        parser.set_language(language).unwrap();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
 
-        let query = Query::new(language, &JS_QUERY)
-            .map_err(|e| println!("{}", format!("Query compilation failed: {:?}", e))).unwrap();
-        JsIdent { parser, query }
+        let query = queries::load_query(language, "javascript", JS_QUERY)?;
+        Ok(JsIdent { parser, query })
     }
 }
 
 impl JsIdent {
-    fn do_parse(code: &str, ident: &mut JsIdent) -> CodeFile {
-        let text_callback = |n: Node| &code[n.byte_range()];
-        let tree = ident.parser.parse(code, None).unwrap();
+    fn do_parse(code: &str, ident: &mut JsIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
 
+        Ok(JsIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &JsIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
         let mut code_file = CodeFile::default();
+        code_file.warnings = JsIdent::parse_warnings(tree.root_node(), code);
         let mut last_class_end_line = 0;
         let mut class = CodeClass::default();
 
         for (mat, capture_index) in captures {
             let capture = mat.captures[capture_index];
+            if JsIdent::is_error_fragment(capture.node) {
+                continue;
+            }
             let capture_name = &ident.query.capture_names()[capture.index as usize];
 
             let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
             match capture_name.as_str() {
                 "source" => {
-                    code_file.imports.push(text.to_string());
+                    let source = text.trim_matches(|c| c == '\'' || c == '"');
+                    let statement = capture.node.parent().unwrap_or(capture.node);
+                    code_file.imports.push(JsIdent::located(CodeImport::module(source), statement));
                 }
                 "class-name" => {
                     class.name = text.to_string();
-                    let class_node = capture.node.parent().unwrap();
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
                     last_class_end_line = class_node.end_position().row;
                     JsIdent::insert_location(&mut class, class_node);
+                    class.doc = JsIdent::doc_comment(class_node, code);
+                }
+                "class-method-name" if text == "constructor" => {
+                    // the method matches once per captured parameter
+                    let constructor = JsIdent::create_js_constructor(capture.node.parent().unwrap_or(capture.node), code);
+                    if !class.constructors.iter().any(|known| known.start == constructor.start) {
+                        class.constructors.push(constructor);
+                    }
                 }
                 "class-method-name" => {
-                    class.functions.push(JsIdent::create_function(capture, text));
+                    class.functions.push(JsIdent::create_function(capture, text, code));
                 }
                 "function-name" => {
-                    code_file.functions.push(JsIdent::create_function(capture, text));
+                    code_file.functions.push(JsIdent::create_function(capture, text, code));
                 }
                 "import-name" => {}
                 "parameter" => {}
                 &_ => {
-                    println!(
-                        "    pattern: {}, capture: {}, row: {}, text: {:?}",
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
                         mat.pattern_index,
                         capture_name,
                         capture.node.start_position().row,
@@ -101,13 +106,48 @@ impl JsIdent {
 
         code_file
     }
+
+    fn create_js_constructor(method: Node, code: &str) -> CodeConstructor {
+        let mut constructor = JsIdent::create_constructor(method, code);
+        if let Some(parameters) = method.child_by_field_name("parameters") {
+            constructor.parameters = JsIdent::parameters_of(parameters, code, JsIdent::parameter_parts);
+        }
+
+        if let Some(body) = method.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            let calls_super = body.named_children(&mut cursor)
+                .filter_map(|statement| statement.named_child(0))
+                .filter(|expression| expression.kind() == "call_expression")
+                .filter_map(|call| call.child_by_field_name("function"))
+                .any(|function| function.kind() == "super");
+            if calls_super {
+                constructor.delegates_to = "super".to_string();
+            }
+        }
+
+        constructor
+    }
+
+    /// `height` or `height = 1`
+    fn parameter_parts(parameter: Node) -> Option<(Node, Option<Node>)> {
+        match parameter.kind() {
+            "identifier" => Some((parameter, None)),
+            "assignment_pattern" => Some((parameter.child_by_field_name("left")?, None)),
+            _ => None
+        }
+    }
 }
 
 impl CodeIdent for JsIdent {
-    fn parse(code: &str) -> CodeFile {
-        let mut ident = JsIdent::new();
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = JsIdent::new()?;
         JsIdent::do_parse(code, &mut ident)
     }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = JsIdent::new()?;
+        Ok(JsIdent::from_tree(code, tree, &ident))
+    }
 }
 
 #[cfg(test)]
@@ -130,16 +170,18 @@ function abc() {
 
 }
 ";
-        let file = JsIdent::parse(source_code);
+        let file = JsIdent::parse(source_code).unwrap();
         let funcs = &file.functions[0];
         let class = &file.classes[0];
 
+        assert_eq!(vec!["./say.js"], file.import_sources());
         assert_eq!("Rectangle", class.name);
         assert_eq!(0, class.start.column);
         assert_eq!(2, class.start.row);
         assert_eq!(7, class.end.row);
         assert_eq!(1, class.end.column);
-        assert_eq!("constructor", class.functions[0].name);
+        assert_eq!(vec!["height", "width"], class.constructors[0].parameters.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>());
+        assert!(class.functions.is_empty());
         assert_eq!("abc", funcs.name);
     }
 
@@ -149,7 +191,7 @@ function abc() {
 
 }
 ";
-        let file = JsIdent::parse(source_code);
+        let file = JsIdent::parse(source_code).unwrap();
 
         let funcs = &file.functions[0];
         assert_eq!("abc", funcs.name);
@@ -0,0 +1,443 @@
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_annotation::CodeAnnotation;
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_constructor::CodeConstructor;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
+
+const KOTLIN_QUERY: &'static str = include_str!("../../queries/kotlin.scm");
+
+/// Kotlin shares the package model with Java: `package` header and fully qualified imports
+pub struct KotlinIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl KotlinIdent {
+    pub fn new() -> Result<KotlinIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_kotlin::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "kotlin", KOTLIN_QUERY)?;
+
+        Ok(KotlinIdent { parser, query })
+    }
+}
+
+impl KotlinIdent {
+    fn do_parse(code: &str, ident: &mut KotlinIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(KotlinIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &KotlinIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = KotlinIdent::parse_warnings(tree.root_node(), code);
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if KotlinIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "package-name" => {
+                    code_file.package = text.to_string();
+                }
+                "import-name" => {
+                    let header = capture.node.parent().unwrap_or(capture.node);
+                    let import = match KotlinIdent::first_child_of_kind(header, "wildcard_import") {
+                        Some(_) => CodeImport::wildcard(text),
+                        None => CodeImport::symbol(text, ".")
+                    };
+                    code_file.imports.push(KotlinIdent::located(import, header));
+                }
+                "class-name" => {
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
+                    let mut class = KotlinIdent::create_class(class_node, text, code);
+                    class.package = code_file.package.clone();
+                    code_file.classes.push(class);
+                }
+                "companion" => {
+                    let name = KotlinIdent::companion_name(capture.node, code);
+                    let mut class = KotlinIdent::create_class(capture.node, &name, code);
+                    class.package = code_file.package.clone();
+                    code_file.classes.push(class);
+                }
+                "function-name" => {
+                    let function_node = capture.node.parent().unwrap_or(capture.node);
+                    code_file.functions.push(KotlinIdent::create_method(function_node, text, code));
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        code_file
+    }
+
+    fn create_class(node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        KotlinIdent::insert_location(&mut class, node);
+        KotlinIdent::insert_kind(&mut class, node, code);
+        // interfaces and enums are `class_declaration`s with a keyword
+        if KotlinIdent::has_modifier(node, code, "interface") {
+            class.set_kind("interface");
+        } else if KotlinIdent::has_modifier(node, code, "enum") {
+            class.set_kind("enum");
+        }
+        class.doc = KotlinIdent::doc_comment(node, code);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "modifiers" => {
+                    class.annotations = KotlinIdent::annotations_of(child, code);
+                }
+                // a constructor call is the super class, plain types are interfaces
+                "delegation_specifier" => {
+                    if let Some(specifier) = child.named_child(0) {
+                        match specifier.kind() {
+                            "constructor_invocation" => {
+                                if let Some(user_type) = specifier.named_child(0) {
+                                    class.extends.push(KotlinIdent::node_text(user_type, code));
+                                }
+                            }
+                            "user_type" => {
+                                class.implements.push(KotlinIdent::node_text(specifier, code));
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                // `class Order(val id: String)` declares the property `id`
+                "primary_constructor" => {
+                    class.constructors.push(KotlinIdent::create_kotlin_constructor(child, code));
+                    let mut parameter_cursor = child.walk();
+                    for parameter in child.named_children(&mut parameter_cursor) {
+                        if parameter.kind() == "class_parameter" && KotlinIdent::binding_kind(parameter, code).is_some() {
+                            if let Some(field) = KotlinIdent::create_field(parameter, parameter, code) {
+                                class.fields.push(field);
+                            }
+                        }
+                    }
+                }
+                "class_body" | "enum_class_body" => {
+                    let mut body_cursor = child.walk();
+                    for member in child.named_children(&mut body_cursor) {
+                        match member.kind() {
+                            "function_declaration" => {
+                                if let Some(name) = KotlinIdent::first_child_of_kind(member, "simple_identifier") {
+                                    let name = KotlinIdent::node_text(name, code);
+                                    class.functions.push(KotlinIdent::create_method(member, &name, code));
+                                }
+                            }
+                            "secondary_constructor" => {
+                                class.constructors.push(KotlinIdent::create_kotlin_constructor(member, code));
+                            }
+                            "property_declaration" => {
+                                let declaration = KotlinIdent::first_child_of_kind(member, "variable_declaration");
+                                if let Some(field) = declaration.and_then(|declaration| KotlinIdent::create_field(member, declaration, code)) {
+                                    class.fields.push(field);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        class
+    }
+
+    /// a `primary_constructor` has its `class_parameter`s inline, a `secondary_constructor` a list
+    fn create_kotlin_constructor(node: Node, code: &str) -> CodeConstructor {
+        let mut constructor = KotlinIdent::create_constructor(node, code);
+        if let Some(modifiers) = KotlinIdent::first_child_of_kind(node, "modifiers") {
+            constructor.annotations = KotlinIdent::annotations_of(modifiers, code);
+            constructor.visibility = KotlinIdent::visibility_in(modifiers, code);
+        }
+
+        let parameters = KotlinIdent::first_child_of_kind(node, "function_value_parameters").unwrap_or(node);
+        constructor.parameters = KotlinIdent::parameters_of(parameters, code, KotlinIdent::parameter_parts);
+
+        // `constructor(id: String) : this(id, 0)`
+        if let Some(delegation) = KotlinIdent::first_child_of_kind(node, "constructor_delegation_call") {
+            if let Some(target) = delegation.child(0) {
+                constructor.delegates_to = KotlinIdent::node_text(target, code);
+            }
+        }
+
+        constructor
+    }
+
+    fn parameter_parts(parameter: Node) -> Option<(Node, Option<Node>)> {
+        if parameter.kind() != "class_parameter" && parameter.kind() != "parameter" {
+            return None;
+        }
+
+        let name = KotlinIdent::first_child_of_kind(parameter, "simple_identifier")?;
+        let mut cursor = parameter.walk();
+        let typ = parameter.named_children(&mut cursor).find(|child| child.kind().ends_with("_type"));
+        Some((name, typ))
+    }
+
+    fn create_method(node: Node, name: &str, code: &str) -> CodeFunction {
+        let mut function = CodeFunction::default();
+        function.name = name.to_string();
+        if let Some(modifiers) = KotlinIdent::first_child_of_kind(node, "modifiers") {
+            function.annotations = KotlinIdent::annotations_of(modifiers, code);
+        }
+        KotlinIdent::insert_location(&mut function, node);
+        function.doc = KotlinIdent::doc_comment(node, code);
+        KotlinIdent::insert_metrics(&mut function, node);
+        function.lambdas = lambdas::lambdas_of(node, code);
+
+        function
+    }
+
+    /// `node` has the modifiers and `val`/`var`, `declaration` the name and the type
+    fn create_field(node: Node, declaration: Node, code: &str) -> Option<CodeField> {
+        let name = KotlinIdent::first_child_of_kind(declaration, "simple_identifier")?;
+
+        let mut field = CodeField::default();
+        field.name = KotlinIdent::node_text(name, code);
+        let mut cursor = declaration.walk();
+        let typ = declaration.named_children(&mut cursor)
+            .find(|child| child.kind().ends_with("_type"));
+        field.typ = typ.map(|typ| KotlinIdent::node_text(typ, code)).unwrap_or_default();
+
+        if let Some(modifiers) = KotlinIdent::first_child_of_kind(node, "modifiers") {
+            KotlinIdent::apply_modifiers(&mut field, modifiers, code);
+            field.annotations = KotlinIdent::annotations_of(modifiers, code);
+        }
+        if let Some(kind) = KotlinIdent::binding_kind(node, code) {
+            KotlinIdent::apply_modifier(&mut field, &kind);
+        }
+        KotlinIdent::insert_location(&mut field, node);
+
+        Some(field)
+    }
+
+    /// `val` or `var`, which grammars keep as a `binding_pattern_kind` or a plain keyword
+    fn binding_kind(node: Node, code: &str) -> Option<String> {
+        let mut cursor = node.walk();
+        let kind = node.children(&mut cursor)
+            .map(|child| KotlinIdent::node_text(child, code))
+            .find(|text| text == "val" || text == "var");
+
+        kind
+    }
+
+    /// `companion object Factory` inside `Order` is `Order.Factory`, unnamed ones are `Order.Companion`
+    fn companion_name(node: Node, code: &str) -> String {
+        let name = KotlinIdent::first_child_of_kind(node, "type_identifier")
+            .map(|name| KotlinIdent::node_text(name, code))
+            .unwrap_or("Companion".to_string());
+
+        let mut parent = node.parent();
+        while let Some(current) = parent {
+            if current.kind() == "class_declaration" || current.kind() == "object_declaration" {
+                if let Some(outer) = KotlinIdent::first_child_of_kind(current, "type_identifier") {
+                    return format!("{}.{}", KotlinIdent::node_text(outer, code), name);
+                }
+            }
+            parent = current.parent();
+        }
+
+        name
+    }
+
+    fn annotations_of(modifiers: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut cursor = modifiers.walk();
+        for node in modifiers.named_children(&mut cursor) {
+            if node.kind() != "annotation" {
+                continue;
+            }
+
+            let invocation = KotlinIdent::first_child_of_kind(node, "constructor_invocation");
+            let user_type = KotlinIdent::first_child_of_kind(node, "user_type")
+                .or(invocation.and_then(|invocation| invocation.named_child(0)));
+
+            if let Some(user_type) = user_type {
+                let mut annotation = KotlinIdent::annotation_at(node, &KotlinIdent::node_text(user_type, code));
+                let arguments = invocation.and_then(|invocation| KotlinIdent::first_child_of_kind(invocation, "value_arguments"));
+                if let Some(arguments) = arguments {
+                    annotation.key_values = KotlinIdent::argument_key_values(arguments, code, KotlinIdent::split_argument);
+                }
+                annotations.push(annotation);
+            }
+        }
+
+        annotations
+    }
+
+    /// `name = "orders"` is named, the value is the last node either way
+    fn split_argument(argument: Node) -> (Option<Node>, Node) {
+        let mut cursor = argument.walk();
+        let is_named = argument.children(&mut cursor).any(|child| child.kind() == "=");
+        let value = argument.named_child(argument.named_child_count().saturating_sub(1)).unwrap_or(argument);
+
+        match argument.named_child(0) {
+            Some(key) if is_named && key.kind() == "simple_identifier" => (Some(key), value),
+            _ => (None, value)
+        }
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for KotlinIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = KotlinIdent::new()?;
+        KotlinIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = KotlinIdent::new()?;
+        Ok(KotlinIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::kotlin_ident::KotlinIdent;
+
+    #[test]
+    fn should_parse_package_and_imports() {
+        let source_code = "package com.phodal.shop.order
+
+import com.phodal.shop.repo.OrderRepository
+import org.springframework.stereotype.Service
+";
+        let file = KotlinIdent::parse(source_code).unwrap();
+
+        assert_eq!("com.phodal.shop.order", file.package);
+        assert_eq!(2, file.imports.len());
+        assert_eq!("org.springframework.stereotype.Service", file.imports[1].source);
+    }
+
+    #[test]
+    fn should_parse_class_with_supertypes() {
+        let source_code = "package com.phodal.shop.order
+
+@Service
+class OrderService(private val repo: OrderRepository) : BaseService(), Finder {
+    @Transactional(readOnly = true)
+    fun find(id: String): Order = repo.find(id)
+}
+";
+        let file = KotlinIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("OrderService", class.name);
+        assert_eq!("com.phodal.shop.order", class.package);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(vec!["Finder"], class.implements);
+        assert_eq!("Service", class.annotations[0].name);
+        assert_eq!("find", class.functions[0].name);
+        assert_eq!("Transactional", class.functions[0].annotations[0].name);
+        assert_eq!(Some("true"), class.functions[0].annotations[0].value_of("readOnly"));
+    }
+
+    #[test]
+    fn should_parse_data_class_object_and_companion() {
+        let source_code = "data class Order(val id: String)
+
+object OrderFactory {
+    fun create(): Order = Order(\"1\")
+}
+
+class OrderId {
+    companion object {
+        fun of(id: String) = OrderId()
+    }
+}
+
+fun main() {}
+";
+        let file = KotlinIdent::parse(source_code).unwrap();
+        let names: Vec<&str> = file.classes.iter().map(|class| class.name.as_str()).collect();
+
+        assert_eq!(vec!["Order", "OrderFactory", "OrderId", "OrderId.Companion"], names);
+        assert_eq!("of", file.classes[3].functions[0].name);
+        assert_eq!("main", file.functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_constructors() {
+        let source_code = "class Order private constructor(val id: String, count: Int) {
+    constructor(id: String) : this(id, 0)
+}
+";
+        let file = KotlinIdent::parse(source_code).unwrap();
+        let constructors = &file.classes[0].constructors;
+
+        assert_eq!(2, constructors.len());
+        assert_eq!("private", constructors[0].visibility);
+        assert_eq!("count", constructors[0].parameters[1].name);
+        assert_eq!("Int", constructors[0].parameters[1].typ);
+        assert_eq!(1, constructors[1].parameters.len());
+        assert_eq!("this", constructors[1].delegates_to);
+    }
+
+    #[test]
+    fn should_parse_properties() {
+        let source_code = "class OrderService(private val repo: OrderRepository, name: String) {
+    @Inject
+    lateinit var clock: Clock
+}
+";
+        let file = KotlinIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!(2, fields.len());
+        assert_eq!("repo", fields[0].name);
+        assert_eq!("OrderRepository", fields[0].typ);
+        assert_eq!("private", fields[0].visibility);
+        assert!(fields[0].is_final);
+        assert_eq!("clock", fields[1].name);
+        assert!(!fields[1].is_final);
+        assert_eq!("Inject", fields[1].annotations[0].name);
+    }
+}
@@ -1,5 +1,13 @@
 pub mod code_ident;
 pub mod js_ident;
+pub mod ts_ident;
+pub mod go_ident;
 pub mod java_ident;
+pub mod kotlin_ident;
+pub mod groovy_ident;
+pub mod swift_ident;
+pub mod dart_ident;
+pub mod python_ident;
 pub mod rust_ident;
 pub mod c_sharp_ident;
+pub mod c_cpp_ident;
@@ -0,0 +1,473 @@
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_annotation::CodeAnnotation;
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_constructor::CodeConstructor;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{doc_comment, lambdas, queries};
+
+const PYTHON_QUERY: &'static str = include_str!("../../queries/python.scm");
+
+pub struct PythonIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl PythonIdent {
+    pub fn new() -> Result<PythonIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_python::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "python", PYTHON_QUERY)?;
+
+        Ok(PythonIdent { parser, query })
+    }
+
+    /// resolve `from ..models import User` against the package of the importing module,
+    /// `package` is the dotted directory of the file, `myapp.views` for `myapp/views/list.py`
+    pub fn resolve_relative(package: &str, import: &str) -> String {
+        let dots = import.chars().take_while(|c| *c == '.').count();
+        if dots == 0 {
+            return import.to_string();
+        }
+
+        let mut parts: Vec<&str> = package.split('.').filter(|part| !part.is_empty()).collect();
+        for _ in 1..dots {
+            parts.pop();
+        }
+
+        let rest = &import[dots..];
+        if !rest.is_empty() {
+            parts.push(rest);
+        }
+
+        parts.join(".")
+    }
+}
+
+impl PythonIdent {
+    fn do_parse(code: &str, ident: &mut PythonIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(PythonIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &PythonIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = PythonIdent::parse_warnings(tree.root_node(), code);
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if PythonIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "import-name" => {
+                    let statement = capture.node.parent().unwrap_or(capture.node);
+                    code_file.imports.push(PythonIdent::located(CodeImport::module(text), statement));
+                }
+                "import-from" => {
+                    code_file.imports.extend(PythonIdent::from_imports(capture.node, code));
+                }
+                "class-name" => {
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
+                    code_file.classes.push(PythonIdent::create_class(class_node, text, code));
+                }
+                "function-name" => {
+                    let function_node = capture.node.parent().unwrap_or(capture.node);
+                    code_file.functions.push(PythonIdent::create_method(function_node, text, code));
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        code_file
+    }
+
+    /// `from x import y, z as w` -> `x.y`, `x.z`; `from x import *` -> `x`
+    fn from_imports(node: Node, code: &str) -> Vec<CodeImport> {
+        let module = match node.child_by_field_name("module_name") {
+            Some(module) => module,
+            None => return vec![]
+        };
+        let module_name = PythonIdent::node_text(module, code);
+
+        let mut imports = vec![];
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            if child.byte_range() == module.byte_range() {
+                continue;
+            }
+
+            let name = match child.kind() {
+                "dotted_name" => PythonIdent::node_text(child, code),
+                "aliased_import" => {
+                    match child.child_by_field_name("name") {
+                        Some(name) => PythonIdent::node_text(name, code),
+                        None => continue
+                    }
+                }
+                "wildcard_import" => {
+                    imports.push(PythonIdent::located(CodeImport::wildcard(&module_name), node));
+                    continue;
+                }
+                _ => continue
+            };
+
+            // `from . import views` is `.views`, not `..views`
+            let source = if module_name.ends_with('.') {
+                format!("{}{}", module_name, name)
+            } else {
+                format!("{}.{}", module_name, name)
+            };
+
+            let mut import = CodeImport::module(&source);
+            import.package = module_name.clone();
+            import.name = name;
+            imports.push(PythonIdent::located(import, node));
+        }
+
+        imports
+    }
+
+    fn create_class(node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        class.annotations = PythonIdent::decorators_of(node, code);
+        PythonIdent::insert_location(&mut class, node);
+        class.doc = PythonIdent::doc_comment(node, code);
+
+        if let Some(superclasses) = node.child_by_field_name("superclasses") {
+            let mut cursor = superclasses.walk();
+            for base in superclasses.named_children(&mut cursor) {
+                match base.kind() {
+                    // `metaclass=ABCMeta` is not a base class
+                    "keyword_argument" => {}
+                    _ => class.extends.push(PythonIdent::node_text(base, code))
+                }
+            }
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            for member in body.named_children(&mut cursor) {
+                let function_node = match member.kind() {
+                    // class attributes: `count: int = 0`
+                    "expression_statement" => {
+                        if let Some(field) = PythonIdent::create_field(member, code) {
+                            class.fields.push(field);
+                        }
+                        continue
+                    }
+                    "function_definition" => member,
+                    "decorated_definition" => {
+                        match member.child_by_field_name("definition") {
+                            Some(definition) if definition.kind() == "function_definition" => definition,
+                            _ => continue
+                        }
+                    }
+                    _ => continue
+                };
+
+                if let Some(name) = function_node.child_by_field_name("name") {
+                    let name = PythonIdent::node_text(name, code);
+                    if name == "__init__" {
+                        class.constructors.push(PythonIdent::create_init(function_node, code));
+                    } else {
+                        class.functions.push(PythonIdent::create_method(function_node, &name, code));
+                    }
+                }
+            }
+        }
+
+        class
+    }
+
+    fn create_field(statement: Node, code: &str) -> Option<CodeField> {
+        let assignment = statement.named_child(0).filter(|child| child.kind() == "assignment")?;
+        let name = assignment.child_by_field_name("left").filter(|left| left.kind() == "identifier")?;
+
+        let mut field = CodeField::default();
+        field.name = PythonIdent::node_text(name, code);
+        if let Some(typ) = assignment.child_by_field_name("type") {
+            field.typ = PythonIdent::node_text(typ, code);
+        }
+        PythonIdent::insert_location(&mut field, statement);
+
+        Some(field)
+    }
+
+    /// `__init__`, without its `self`
+    fn create_init(node: Node, code: &str) -> CodeConstructor {
+        let mut constructor = PythonIdent::create_constructor(node, code);
+        constructor.annotations = PythonIdent::decorators_of(node, code);
+        if let Some(parameters) = node.child_by_field_name("parameters") {
+            constructor.parameters = PythonIdent::parameters_of(parameters, code, PythonIdent::parameter_parts);
+            if constructor.parameters.first().map(|parameter| parameter.name == "self").unwrap_or(false) {
+                constructor.parameters.remove(0);
+            }
+        }
+
+        // `super().__init__(...)`
+        let calls_super = node.child_by_field_name("body")
+            .map(|body| code[body.byte_range()].contains("super("))
+            .unwrap_or(false);
+        if calls_super {
+            constructor.delegates_to = "super".to_string();
+        }
+
+        constructor
+    }
+
+    /// `name`, `name: int`, `name=1` or `name: int = 1`
+    fn parameter_parts(parameter: Node) -> Option<(Node, Option<Node>)> {
+        match parameter.kind() {
+            "identifier" => Some((parameter, None)),
+            "default_parameter" | "typed_default_parameter" => {
+                Some((parameter.child_by_field_name("name")?, parameter.child_by_field_name("type")))
+            }
+            "typed_parameter" => {
+                let name = PythonIdent::first_child_of_kind(parameter, "identifier")?;
+                Some((name, parameter.child_by_field_name("type")))
+            }
+            _ => None
+        }
+    }
+
+    fn create_method(node: Node, name: &str, code: &str) -> CodeFunction {
+        let mut function = CodeFunction::default();
+        function.name = name.to_string();
+        function.annotations = PythonIdent::decorators_of(node, code);
+        PythonIdent::insert_location(&mut function, node);
+        function.doc = PythonIdent::doc_comment(node, code);
+        PythonIdent::insert_metrics(&mut function, node);
+        function.lambdas = lambdas::lambdas_of(node, code);
+
+        function
+    }
+
+    /// decorators are siblings of the definition inside a `decorated_definition`
+    fn decorators_of(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let parent = match node.parent() {
+            Some(parent) if parent.kind() == "decorated_definition" => parent,
+            _ => return annotations
+        };
+
+        let mut cursor = parent.walk();
+        for child in parent.named_children(&mut cursor) {
+            if child.kind() != "decorator" {
+                continue;
+            }
+
+            let expr = match child.named_child(0) {
+                Some(expr) => expr,
+                None => continue
+            };
+            let name_node = match expr.kind() {
+                "call" => expr.child_by_field_name("function").unwrap_or(expr),
+                _ => expr
+            };
+
+            let mut annotation = PythonIdent::annotation_at(child, &PythonIdent::node_text(name_node, code));
+            if expr.kind() == "call" {
+                if let Some(arguments) = expr.child_by_field_name("arguments") {
+                    annotation.key_values = PythonIdent::argument_key_values(arguments, code, PythonIdent::split_argument);
+                }
+            }
+            annotations.push(annotation);
+        }
+
+        annotations
+    }
+
+    /// `url_prefix="/orders"` is a keyword argument
+    fn split_argument(argument: Node) -> (Option<Node>, Node) {
+        match argument.kind() {
+            "keyword_argument" => {
+                let value = argument.child_by_field_name("value").unwrap_or(argument);
+                (argument.child_by_field_name("name"), value)
+            }
+            _ => (None, argument)
+        }
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for PythonIdent {
+    /// the docstring, which is the first statement of the body
+    fn doc_comment(node: Node, code: &str) -> String {
+        let first = node.child_by_field_name("body").and_then(|body| body.named_child(0));
+        let string = match first {
+            Some(statement) if statement.kind() == "expression_statement" => statement.named_child(0),
+            _ => None
+        };
+
+        match string {
+            Some(string) if string.kind() == "string" => doc_comment::clean_docstring(&code[string.byte_range()]),
+            _ => "".to_string()
+        }
+    }
+
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = PythonIdent::new()?;
+        PythonIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = PythonIdent::new()?;
+        Ok(PythonIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::python_ident::PythonIdent;
+
+    #[test]
+    fn should_parse_imports() {
+        let source_code = "import os
+import os.path as osp
+from django.db import models, transaction as tx
+from . import views
+from ..core.utils import *
+";
+        let file = PythonIdent::parse(source_code).unwrap();
+        assert_eq!(vec!["os", "os.path", "django.db.models", "django.db.transaction", ".views", "..core.utils"], file.import_sources());
+        assert_eq!("django.db", file.imports[2].package);
+        assert_eq!("models", file.imports[2].name);
+    }
+
+    #[test]
+    fn should_parse_class_with_bases_and_methods() {
+        let source_code = "class OrderView(LoginRequiredMixin, generic.View, metaclass=Meta):
+    def get(self, request):
+        pass
+
+    @staticmethod
+    def create():
+        pass
+";
+        let file = PythonIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("OrderView", class.name);
+        assert_eq!(vec!["LoginRequiredMixin", "generic.View"], class.extends);
+        assert_eq!(2, class.functions.len());
+        assert_eq!("create", class.functions[1].name);
+        assert_eq!("staticmethod", class.functions[1].annotations[0].name);
+    }
+
+    #[test]
+    fn should_parse_init_as_constructor() {
+        let source_code = "class OrderView(View):
+    def __init__(self, repo: OrderRepository, size=10):
+        super().__init__()
+        self.repo = repo
+
+    def get(self, request):
+        pass
+";
+        let file = PythonIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!(1, class.functions.len());
+        let init = &class.constructors[0];
+        assert_eq!(vec!["repo", "size"], init.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!("OrderRepository", init.parameters[0].typ);
+        assert_eq!("super", init.delegates_to);
+    }
+
+    #[test]
+    fn should_parse_decorated_functions() {
+        let source_code = "@app.route('/orders')
+def list_orders():
+    pass
+
+def helper():
+    pass
+";
+        let file = PythonIdent::parse(source_code).unwrap();
+
+        assert_eq!(2, file.functions.len());
+        assert_eq!("list_orders", file.functions[0].name);
+        assert_eq!("app.route", file.functions[0].annotations[0].name);
+        assert_eq!(Some("/orders"), file.functions[0].annotations[0].value_of("0"));
+        assert_eq!(0, file.functions[1].annotations.len());
+    }
+
+    #[test]
+    fn should_resolve_relative_import() {
+        assert_eq!("myapp.views", PythonIdent::resolve_relative("myapp", ".views"));
+        assert_eq!("myapp.core.utils", PythonIdent::resolve_relative("myapp.orders", "..core.utils"));
+        assert_eq!("myapp", PythonIdent::resolve_relative("myapp", "."));
+        assert_eq!("django.db", PythonIdent::resolve_relative("myapp", "django.db"));
+    }
+
+    #[test]
+    fn should_parse_class_attributes() {
+        let source_code = "class Order:
+    \"\"\"An order.\"\"\"
+    total: int = 0
+    items = []
+";
+        let file = PythonIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!(2, fields.len());
+        assert_eq!("total", fields[0].name);
+        assert_eq!("int", fields[0].typ);
+        assert_eq!("items", fields[1].name);
+    }
+
+    #[test]
+    fn should_capture_docstrings() {
+        let source_code = "class Order:
+    \"\"\"An order of a cart.\"\"\"
+
+    def total(self):
+        return 0
+";
+        let file = PythonIdent::parse(source_code).unwrap();
+
+        assert_eq!("An order of a cart.", file.classes[0].doc);
+        assert_eq!("", file.classes[0].functions[0].doc);
+    }
+}
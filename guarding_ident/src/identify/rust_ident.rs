@@ -1,35 +1,18 @@
 use std::collections::HashMap;
+use std::path::Path;
 
-use tree_sitter::{Node, Parser, Query, QueryCursor};
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
 
 use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
 use guarding_core::domain::code_function::CodeFunction;
 use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
 use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::queries;
 
-const RUST_QUERY: &'static str = "
-(use_declaration
-	(scoped_identifier) @import-name)
-
-(struct_item
-	name: (type_identifier) @struct-name
-    body: (field_declaration_list
-    	(field_declaration
-			name: (field_identifier) @field-name
-            type: (type_identifier) @type-name
-    ))?
-)
-
-(impl_item
-    trait: (type_identifier)? @trait-name
-	type: (type_identifier) @impl-struct-name
-    body: (declaration_list (
-	    (function_item
-        	name: (identifier) @impl-function-name
-        ))
-    )
-)
-";
+const RUST_QUERY: &'static str = include_str!("../../queries/rust.scm");
 
 
 pub struct RustIdent {
@@ -38,27 +21,95 @@ pub struct RustIdent {
 }
 
 impl RustIdent {
-    fn new() -> RustIdent {
+    fn new() -> Result<RustIdent, IdentError> {
         let mut parser = Parser::new();
 
         let language = tree_sitter_rust::language();
-        parser.set_language(language).unwrap();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
 
-        let query = Query::new(language, &RUST_QUERY)
-            .map_err(|e| println!("{}", format!("Query compilation failed: {:?}", e))).unwrap();
-        RustIdent { parser, query }
+        let query = queries::load_query(language, "rust", RUST_QUERY)?;
+        Ok(RustIdent { parser, query })
     }
 }
 
 impl RustIdent {
-    fn do_parse(code: &str, ident: &mut RustIdent) -> CodeFile {
-        let text_callback = |n: Node| &code[n.byte_range()];
-        let tree = ident.parser.parse(code, None).unwrap();
+    /// the `[package]` name of a `Cargo.toml` as it's used in paths, `shop-core` is `shop_core`
+    pub fn crate_name(manifest: &str) -> Option<String> {
+        let mut section = "";
+        for line in manifest.lines() {
+            let line = line.trim();
+            if line.starts_with('[') {
+                section = line.trim_matches(|c| c == '[' || c == ']');
+                continue;
+            }
+
+            if let (true, Some((key, value))) = (section == "package", line.split_once('=')) {
+                if key.trim() == "name" {
+                    return Some(value.trim().trim_matches('"').replace('-', "_"));
+                }
+            }
+        }
+
+        None
+    }
+
+    /// the module of `src/order/service.rs` or `src/order/service/mod.rs`, `order.service`
+    /// under the crate; `lib.rs` and `main.rs` are the crate itself
+    pub fn module_of(crate_name: &str, relative: &Path) -> String {
+        let mut parts = vec![crate_name.to_string()];
+        parts.extend(relative.with_extension("").iter().filter_map(|part| part.to_str()).map(|part| part.to_string()));
+
+        let root_file = parts.len() == 2 && (parts[1] == "lib" || parts[1] == "main");
+        if root_file || parts.last().map(|part| part == "mod").unwrap_or(false) {
+            parts.pop();
+        }
+        parts.join(".")
+    }
+
+    /// `crate::order::Order`, `self::`, `super::` are resolved against the crate and the
+    /// module, every `::` becomes a `.` like in the packages: `serde::Deserialize` is `serde.Deserialize`
+    pub fn resolve_path(crate_name: &str, module: &str, path: &str) -> String {
+        let mut segments = path.split("::");
+        let mut parts: Vec<&str> = match segments.next() {
+            Some("crate") => vec![crate_name],
+            Some("self") => module.split('.').collect(),
+            Some("super") => {
+                let mut parts: Vec<&str> = module.split('.').collect();
+                if parts.len() > 1 {
+                    parts.pop();
+                }
+                parts
+            }
+            Some(first) => vec![first],
+            None => vec![]
+        };
+
+        for segment in segments {
+            match segment {
+                "super" if parts.len() > 1 => { parts.pop(); }
+                "super" | "self" => {}
+                segment => parts.push(segment),
+            }
+        }
+        parts.join(".")
+    }
+
+    fn do_parse(code: &str, ident: &mut RustIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(RustIdent::from_tree(code, &tree, ident))
+    }
 
+    fn from_tree(code: &str, tree: &Tree, ident: &RustIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
         let mut query_cursor = QueryCursor::new();
         let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
 
         let mut code_file = CodeFile::default();
+        code_file.warnings = RustIdent::parse_warnings(tree.root_node(), code);
         let mut last_class_end_line = 0;
         let mut class = CodeClass::default();
 
@@ -69,18 +120,27 @@ impl RustIdent {
 
         for (mat, capture_index) in captures {
             let capture = mat.captures[capture_index];
+            if RustIdent::is_error_fragment(capture.node) {
+                continue;
+            }
             let capture_name = &ident.query.capture_names()[capture.index as usize];
 
             let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
             match capture_name.as_str() {
                 "import-name" => {
-                    code_file.imports.push(text.to_string());
+                    let declaration = capture.node.parent().unwrap_or(capture.node);
+                    code_file.imports.push(RustIdent::located(CodeImport::symbol(text, "::"), declaration));
                 },
                 "struct-name" => {
                     class.name = text.to_string();
                     let struct_node = capture.node;
                     last_class_end_line = struct_node.end_position().row;
                     RustIdent::insert_location(&mut class, struct_node);
+                    class.set_kind("struct");
+                    if let Some(struct_item) = struct_node.parent() {
+                        class.doc = RustIdent::doc_comment(struct_item, code);
+                        class.fields = RustIdent::fields_of(struct_item, code);
+                    }
                 },
                 "impl-struct-name" => {
                     last_impl_struct_name = text.to_string();
@@ -90,7 +150,7 @@ impl RustIdent {
                     last_trait_name = "".to_string();
                 }
                 "impl-function-name" => {
-                    let function = RustIdent::create_function(capture, text);
+                    let function = RustIdent::create_function(capture, text, code);
                     impl_functions
                         .entry(last_impl_struct_name.clone())
                         .or_insert_with(Vec::new)
@@ -124,34 +184,125 @@ impl RustIdent {
 
         code_file
     }
+
+    /// named fields only, the ones of tuple structs have no name
+    fn fields_of(struct_item: Node, code: &str) -> Vec<CodeField> {
+        let mut fields = vec![];
+        let body = match struct_item.child_by_field_name("body") {
+            Some(body) if body.kind() == "field_declaration_list" => body,
+            _ => return fields
+        };
+
+        let mut attributes = vec![];
+        let mut cursor = body.walk();
+        for member in body.named_children(&mut cursor) {
+            match member.kind() {
+                // `#[serde(default)]` -> `serde`
+                "attribute_item" => {
+                    let text = RustIdent::node_text(member, code);
+                    let inner = text.trim_start_matches("#[").trim_end_matches(']');
+                    let name = inner.split(|c| c == '(' || c == '=').next().unwrap_or("").trim();
+                    attributes.push(RustIdent::annotation_at(member, name));
+                }
+                "field_declaration" => {
+                    let mut field = CodeField::default();
+                    if let Some(name) = member.child_by_field_name("name") {
+                        field.name = RustIdent::node_text(name, code);
+                    }
+                    if let Some(typ) = member.child_by_field_name("type") {
+                        field.typ = RustIdent::node_text(typ, code);
+                    }
+                    field.visibility = match member.named_child(0) {
+                        Some(visibility) if visibility.kind() == "visibility_modifier" => {
+                            match RustIdent::node_text(visibility, code).as_str() {
+                                "pub" => "public",
+                                _ => "internal"
+                            }
+                        }
+                        _ => "private"
+                    }.to_string();
+                    field.annotations = attributes.drain(..).collect();
+                    RustIdent::insert_location(&mut field, member);
+                    fields.push(field);
+                }
+                _ => {}
+            }
+        }
+
+        fields
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
 }
 
 impl CodeIdent for RustIdent {
-    fn parse(code: &str) -> CodeFile {
-        let mut ident = RustIdent::new();
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = RustIdent::new()?;
         RustIdent::do_parse(code, &mut ident)
     }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = RustIdent::new()?;
+        Ok(RustIdent::from_tree(code, tree, &ident))
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::path::Path;
+
     use crate::code_ident::CodeIdent;
     use crate::rust_ident::RustIdent;
 
+    #[test]
+    fn should_resolve_module_paths() {
+        assert_eq!(Some("shop_core".to_string()), RustIdent::crate_name("[package]\nname = \"shop-core\"\n\n[dependencies]\nname = \"1\""));
+        assert_eq!(None, RustIdent::crate_name("[workspace]\nmembers = [\"core\"]"));
+
+        assert_eq!("shop_core", RustIdent::module_of("shop_core", Path::new("lib.rs")));
+        assert_eq!("shop_core.order", RustIdent::module_of("shop_core", Path::new("order/mod.rs")));
+        assert_eq!("shop_core.order.service", RustIdent::module_of("shop_core", Path::new("order/service.rs")));
+
+        assert_eq!("shop_core.order.Order", RustIdent::resolve_path("shop_core", "shop_core.cart", "crate::order::Order"));
+        assert_eq!("shop_core.cart.item.Item", RustIdent::resolve_path("shop_core", "shop_core.cart", "self::item::Item"));
+        assert_eq!("shop_core.order.Order", RustIdent::resolve_path("shop_core", "shop_core.cart", "super::order::Order"));
+        assert_eq!("serde.Deserialize", RustIdent::resolve_path("shop_core", "shop_core.cart", "serde::Deserialize"));
+    }
+
     #[test]
     fn should_parse_import() {
         let source_code = "use crate::identify::rust_ident::RustIdent;
 ";
-        let file = RustIdent::parse(source_code);
+        let file = RustIdent::parse(source_code).unwrap();
         assert_eq!(1, file.imports.len());
     }
 
+    #[test]
+    fn should_measure_function() {
+        let source_code = "fn check(value: Option<u32>) -> bool {
+    /// only the code lines count
+
+    match value {
+        Some(v) if v > 1 => true,
+        _ => false
+    }
+}
+";
+        let file = RustIdent::parse(source_code).unwrap();
+        let function = &file.functions[0];
+
+        assert_eq!(6, function.loc);
+        assert_eq!(3, function.complexity);
+    }
+
     #[test]
     fn should_parse_basic_struct() {
         let source_code = "pub struct RustIdent {
 }
 ";
-        let file = RustIdent::parse(source_code);
+        let file = RustIdent::parse(source_code).unwrap();
         assert_eq!(1, file.classes.len());
     }
 
@@ -165,7 +316,7 @@ impl RustIdent {
     }
 }
 ";
-        let file = RustIdent::parse(source_code);
+        let file = RustIdent::parse(source_code).unwrap();
 
         assert_eq!(1, file.classes.len());
         assert_eq!("RustIdent", file.classes[0].name);
@@ -184,8 +335,49 @@ impl Default for RustIdent {
     }
 }
 ";
-        let file = RustIdent::parse(source_code);
+        let file = RustIdent::parse(source_code).unwrap();
 
         assert_eq!("Default", file.classes[0].implements[0]);
     }
+
+    #[test]
+    fn should_parse_struct_fields() {
+        let source_code = "pub struct CodeFile {
+    #[serde(default)]
+    pub path: String,
+    pub(crate) imports: Vec<String>,
+    size: usize,
+}
+";
+        let file = RustIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!(3, fields.len());
+        assert_eq!("path", fields[0].name);
+        assert_eq!("String", fields[0].typ);
+        assert_eq!("public", fields[0].visibility);
+        assert_eq!("serde", fields[0].annotations[0].name);
+        assert_eq!("internal", fields[1].visibility);
+        assert_eq!("private", fields[2].visibility);
+    }
+
+    #[test]
+    fn should_capture_rustdoc() {
+        let source_code = "/// models a source file
+#[derive(Debug)]
+pub struct RustIdent {}
+
+impl RustIdent {
+    /// parses `code`
+    /// into a file
+    pub fn parse(code: &str) -> CodeFile {
+        CodeFile::default()
+    }
+}
+";
+        let file = RustIdent::parse(source_code).unwrap();
+
+        assert_eq!("models a source file", file.classes[0].doc);
+        assert_eq!("parses `code`\ninto a file", file.classes[0].functions[0].doc);
+    }
 }
@@ -0,0 +1,230 @@
+use tracing::trace;
+use tree_sitter::{Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
+
+const SWIFT_QUERY: &'static str = include_str!("../../queries/swift.scm");
+
+pub struct SwiftIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl SwiftIdent {
+    pub fn new() -> Result<SwiftIdent, IdentError> {
+        let mut parser = Parser::new();
+        let language = tree_sitter_swift::language();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "swift", SWIFT_QUERY)?;
+
+        Ok(SwiftIdent { parser, query })
+    }
+}
+
+impl SwiftIdent {
+    fn do_parse(code: &str, ident: &mut SwiftIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(SwiftIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &SwiftIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = SwiftIdent::parse_warnings(tree.root_node(), code);
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if SwiftIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "import-name" => {
+                    let declaration = capture.node.parent().unwrap_or(capture.node);
+                    code_file.imports.push(SwiftIdent::located(CodeImport::module(text), declaration));
+                }
+                "class" => {
+                    if let Some(class) = SwiftIdent::create_class(capture.node, code) {
+                        code_file.classes.push(class);
+                    }
+                }
+                "function" => {
+                    if let Some(function) = SwiftIdent::create_method(capture.node, code) {
+                        code_file.functions.push(function);
+                    }
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        code_file
+    }
+
+    /// classes, structs, enums, extensions and protocols
+    fn create_class(node: Node, code: &str) -> Option<CodeClass> {
+        let name = node.child_by_field_name("name")?;
+
+        let mut class = CodeClass::default();
+        class.name = SwiftIdent::node_text(name, code);
+        SwiftIdent::insert_location(&mut class, node);
+        SwiftIdent::insert_kind(&mut class, node, code);
+        // structs, enums and extensions are `class_declaration`s too
+        if let Some(kind) = node.child_by_field_name("declaration_kind") {
+            match &code[kind.byte_range()] {
+                "struct" => class.set_kind("struct"),
+                "enum" => class.set_kind("enum"),
+                _ => {}
+            }
+        }
+        class.doc = SwiftIdent::doc_comment(node, code);
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                // a super class and protocols look the same in the syntax
+                "inheritance_specifier" => {
+                    let inherited = child.child_by_field_name("inherits_from").unwrap_or(child);
+                    class.implements.push(SwiftIdent::node_text(inherited, code));
+                }
+                "class_body" | "enum_class_body" | "protocol_body" => {
+                    let mut body_cursor = child.walk();
+                    for member in child.named_children(&mut body_cursor) {
+                        match member.kind() {
+                            "function_declaration" | "protocol_function_declaration" => {
+                                if let Some(function) = SwiftIdent::create_method(member, code) {
+                                    class.functions.push(function);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Some(class)
+    }
+
+    fn create_method(node: Node, code: &str) -> Option<CodeFunction> {
+        let name = node.child_by_field_name("name")
+            .or(SwiftIdent::first_child_of_kind(node, "simple_identifier"))?;
+
+        let mut function = CodeFunction::default();
+        function.name = SwiftIdent::node_text(name, code);
+        SwiftIdent::insert_location(&mut function, node);
+        function.doc = SwiftIdent::doc_comment(node, code);
+        SwiftIdent::insert_metrics(&mut function, node);
+        function.lambdas = lambdas::lambdas_of(node, code);
+
+        Some(function)
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+}
+
+impl CodeIdent for SwiftIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = SwiftIdent::new()?;
+        SwiftIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = SwiftIdent::new()?;
+        Ok(SwiftIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::swift_ident::SwiftIdent;
+
+    #[test]
+    fn should_parse_imports() {
+        let source_code = "import UIKit
+import Checkout
+";
+        let file = SwiftIdent::parse(source_code).unwrap();
+        assert_eq!(vec!["UIKit", "Checkout"], file.import_sources());
+    }
+
+    #[test]
+    fn should_parse_class_and_methods() {
+        let source_code = "class CartViewController: UIViewController, CartView {
+    func viewDidLoad() {
+    }
+
+    func render(cart: Cart) {
+    }
+}
+";
+        let file = SwiftIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("CartViewController", class.name);
+        assert_eq!(vec!["UIViewController", "CartView"], class.implements);
+        assert_eq!(2, class.functions.len());
+        assert_eq!("render", class.functions[1].name);
+    }
+
+    #[test]
+    fn should_parse_struct_protocol_extension_and_functions() {
+        let source_code = "protocol CartView {
+    func render(cart: Cart)
+}
+
+struct Cart {
+}
+
+extension Cart {
+    func total() -> Int { return 0 }
+}
+
+func makeCart() -> Cart {
+    return Cart()
+}
+";
+        let file = SwiftIdent::parse(source_code).unwrap();
+        let names: Vec<&str> = file.classes.iter().map(|class| class.name.as_str()).collect();
+
+        assert_eq!(vec!["CartView", "Cart", "Cart"], names);
+        assert_eq!("render", file.classes[0].functions[0].name);
+        assert_eq!("total", file.classes[2].functions[0].name);
+        assert_eq!("makeCart", file.functions[0].name);
+    }
+}
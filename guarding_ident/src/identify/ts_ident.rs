@@ -0,0 +1,520 @@
+use tracing::trace;
+use tree_sitter::{Language, Node, Parser, Query, QueryCursor, Tree};
+
+use guarding_core::domain::code_annotation::{AnnotationKeyValue, CodeAnnotation};
+use guarding_core::domain::code_class::CodeClass;
+use guarding_core::domain::code_field::CodeField;
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_import::CodeImport;
+use guarding_core::domain::code_constructor::CodeConstructor;
+use guarding_core::domain::code_function::CodeFunction;
+use crate::code_ident::CodeIdent;
+use crate::error::IdentError;
+use crate::support::{lambdas, queries};
+
+const TS_QUERY: &'static str = include_str!("../../queries/typescript.scm");
+
+pub struct TsIdent {
+    parser: Parser,
+    query: Query,
+}
+
+impl TsIdent {
+    pub fn new(language: Language) -> Result<TsIdent, IdentError> {
+        let mut parser = Parser::new();
+        parser.set_language(language).map_err(|err| IdentError::Language(format!("{:?}", err)))?;
+
+        let query = queries::load_query(language, "typescript", TS_QUERY)?;
+        Ok(TsIdent { parser, query })
+    }
+
+    /// `.tsx` files need the tsx grammar, the rest of extraction is the same
+    pub fn parse_tsx(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = TsIdent::new(tree_sitter_typescript::language_tsx())?;
+        TsIdent::do_parse(code, &mut ident)
+    }
+
+    pub fn parse_tsx_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = TsIdent::new(tree_sitter_typescript::language_tsx())?;
+        Ok(TsIdent::from_tree(code, tree, &ident))
+    }
+}
+
+impl TsIdent {
+    fn do_parse(code: &str, ident: &mut TsIdent) -> Result<CodeFile, IdentError> {
+        let tree = match ident.parser.parse(code, None) {
+            Some(tree) => tree,
+            None => return Err(IdentError::Cancelled),
+        };
+
+        Ok(TsIdent::from_tree(code, &tree, ident))
+    }
+
+    fn from_tree(code: &str, tree: &Tree, ident: &TsIdent) -> CodeFile {
+        let text_callback = |n: Node| &code[n.byte_range()];
+        let mut query_cursor = QueryCursor::new();
+        let captures = query_cursor.captures(&ident.query, tree.root_node(), text_callback);
+
+        let mut code_file = CodeFile::default();
+        code_file.warnings = TsIdent::parse_warnings(tree.root_node(), code);
+        let capture_names = ident.query.capture_names();
+
+        for (mat, capture_index) in captures {
+            let capture = mat.captures[capture_index];
+            if TsIdent::is_error_fragment(capture.node) {
+                continue;
+            }
+            let capture_name = &capture_names[capture.index as usize];
+
+            let text = capture.node.utf8_text((&code).as_ref()).unwrap_or("");
+            match capture_name.as_str() {
+                "source" => {
+                    let statement = capture.node.parent().unwrap_or(capture.node);
+                    let mut import = TsIdent::located(CodeImport::module(&TsIdent::unquote(text)), statement);
+                    // `import * as api from './api'`
+                    import.is_wildcard = TsIdent::node_text(statement, code).contains("* as");
+                    code_file.imports.push(import);
+                }
+                "export" => {
+                    code_file.exports.extend(TsIdent::export_names(capture.node, code));
+                }
+                "class-name" | "interface-name" => {
+                    let class_node = capture.node.parent().unwrap_or(capture.node);
+                    code_file.classes.push(TsIdent::create_class(class_node, text, code));
+                }
+                "function-name" => {
+                    code_file.functions.push(TsIdent::create_function(capture, text, code));
+                }
+                &_ => {
+                    trace!(
+                        "unknown capture, pattern: {}, capture: {}, row: {}, text: {:?}",
+                        mat.pattern_index,
+                        capture_name,
+                        capture.node.start_position().row,
+                        capture.node.utf8_text((&code).as_ref()).unwrap_or("")
+                    );
+                }
+            }
+        }
+
+        code_file
+    }
+
+    fn create_class(node: Node, name: &str, code: &str) -> CodeClass {
+        let mut class = CodeClass::default();
+        class.name = name.to_string();
+        TsIdent::insert_location(&mut class, node);
+        TsIdent::insert_kind(&mut class, node, code);
+        class.doc = TsIdent::doc_comment(node, code);
+
+        // `@Component() export class Foo {}` keeps the decorator on the export statement
+        if let Some(parent) = node.parent() {
+            if parent.kind() == "export_statement" {
+                class.annotations.extend(TsIdent::decorators_of(parent, code));
+            }
+        }
+        class.annotations.extend(TsIdent::decorators_of(node, code));
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "class_heritage" => {
+                    let mut heritage_cursor = child.walk();
+                    for clause in child.named_children(&mut heritage_cursor) {
+                        TsIdent::fill_heritage(&mut class, clause, code);
+                    }
+                }
+                "extends_clause" | "extends_type_clause" => {
+                    TsIdent::fill_heritage(&mut class, child, code);
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(body) = node.child_by_field_name("body") {
+            let mut body_cursor = body.walk();
+            for member in body.named_children(&mut body_cursor) {
+                match member.kind() {
+                    "method_definition" if TsIdent::is_constructor(member, code) => {
+                        class.constructors.push(TsIdent::create_ts_constructor(member, code));
+                    }
+                    "method_definition" | "method_signature" | "abstract_method_signature" => {
+                        if let Some(name_node) = member.child_by_field_name("name") {
+                            let mut function = CodeFunction::default();
+                            function.name = TsIdent::node_text(name_node, code);
+                            function.annotations = TsIdent::preceding_decorators(member, code);
+                            function.annotations.extend(TsIdent::decorators_of(member, code));
+                            TsIdent::insert_location(&mut function, member);
+                            function.doc = TsIdent::doc_comment(member, code);
+                            TsIdent::insert_metrics(&mut function, member);
+                            function.lambdas = lambdas::lambdas_of(member, code);
+                            class.functions.push(function);
+                        }
+                    }
+                    "public_field_definition" | "property_signature" => {
+                        if let Some(field) = TsIdent::create_field(member, code) {
+                            class.fields.push(field);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        class
+    }
+
+    fn is_constructor(member: Node, code: &str) -> bool {
+        member.child_by_field_name("name").map(|name| &code[name.byte_range()] == "constructor").unwrap_or(false)
+    }
+
+    fn create_ts_constructor(member: Node, code: &str) -> CodeConstructor {
+        let mut constructor = TsIdent::create_constructor(member, code);
+        constructor.annotations = TsIdent::preceding_decorators(member, code);
+        constructor.annotations.extend(TsIdent::decorators_of(member, code));
+        if let Some(modifier) = TsIdent::first_child_of_kind(member, "accessibility_modifier") {
+            constructor.visibility = TsIdent::node_text(modifier, code);
+        }
+        if let Some(parameters) = member.child_by_field_name("parameters") {
+            constructor.parameters = TsIdent::parameters_of(parameters, code, TsIdent::parameter_parts);
+        }
+
+        // `super(...)` has to come before any `this`, so it's a statement of the body itself
+        if let Some(body) = member.child_by_field_name("body") {
+            let mut cursor = body.walk();
+            let calls_super = body.named_children(&mut cursor)
+                .filter_map(|statement| statement.named_child(0))
+                .filter(|expression| expression.kind() == "call_expression")
+                .filter_map(|call| call.child_by_field_name("function"))
+                .any(|function| function.kind() == "super");
+            if calls_super {
+                constructor.delegates_to = "super".to_string();
+            }
+        }
+
+        constructor
+    }
+
+    /// `private repo: Repo`, `name?: string` or a plain `name` of JavaScript
+    fn parameter_parts(parameter: Node) -> Option<(Node, Option<Node>)> {
+        if parameter.kind() == "identifier" {
+            return Some((parameter, None));
+        }
+
+        let name = parameter.child_by_field_name("pattern").or_else(|| {
+            let mut cursor = parameter.walk();
+            let name = parameter.named_children(&mut cursor)
+                .find(|child| matches!(child.kind(), "identifier" | "this" | "object_pattern" | "array_pattern"));
+            name
+        })?;
+        Some((name, TsIdent::first_child_of_kind(parameter, "type_annotation")))
+    }
+
+    fn first_child_of_kind<'a>(node: Node<'a>, kind: &str) -> Option<Node<'a>> {
+        let mut cursor = node.walk();
+        let child = node.named_children(&mut cursor).find(|child| child.kind() == kind);
+
+        child
+    }
+
+    fn create_field(member: Node, code: &str) -> Option<CodeField> {
+        let name = member.child_by_field_name("name")?;
+
+        let mut field = CodeField::default();
+        field.name = TsIdent::node_text(name, code);
+        // `type_annotation` keeps the colon of `: Repo`
+        field.typ = member.child_by_field_name("type")
+            .map(|typ| TsIdent::node_text(typ, code).trim_start_matches(':').trim().to_string())
+            .unwrap_or_default();
+
+        // keywords are direct children, values may hold identifiers named like them
+        let mut cursor = member.walk();
+        for child in member.children(&mut cursor) {
+            match child.kind() {
+                "accessibility_modifier" | "static" | "readonly" => {
+                    TsIdent::apply_modifier(&mut field, &TsIdent::node_text(child, code));
+                }
+                _ => {}
+            }
+        }
+
+        field.annotations = TsIdent::preceding_decorators(member, code);
+        field.annotations.extend(TsIdent::decorators_of(member, code));
+        TsIdent::insert_location(&mut field, member);
+
+        Some(field)
+    }
+
+    fn fill_heritage(class: &mut CodeClass, clause: Node, code: &str) {
+        let mut names = vec![];
+        let mut cursor = clause.walk();
+        for child in clause.named_children(&mut cursor) {
+            let type_node = match child.kind() {
+                "generic_type" => child.named_child(0).unwrap_or(child),
+                "type_arguments" => continue,
+                _ => child,
+            };
+            names.push(TsIdent::node_text(type_node, code));
+        }
+
+        match clause.kind() {
+            "implements_clause" => class.implements.extend(names),
+            _ => class.extends.extend(names),
+        }
+    }
+
+    fn export_names(node: Node, code: &str) -> Vec<String> {
+        let mut names = vec![];
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "export_clause" => {
+                    let mut clause_cursor = child.walk();
+                    for specifier in child.named_children(&mut clause_cursor) {
+                        let exported = specifier.child_by_field_name("alias")
+                            .or(specifier.child_by_field_name("name"));
+                        if let Some(exported) = exported {
+                            names.push(TsIdent::node_text(exported, code));
+                        }
+                    }
+                }
+                "lexical_declaration" | "variable_declaration" => {
+                    let mut declaration_cursor = child.walk();
+                    for declarator in child.named_children(&mut declaration_cursor) {
+                        if let Some(name) = declarator.child_by_field_name("name") {
+                            names.push(TsIdent::node_text(name, code));
+                        }
+                    }
+                }
+                _ => {
+                    if let Some(name) = child.child_by_field_name("name") {
+                        names.push(TsIdent::node_text(name, code));
+                    }
+                }
+            }
+        }
+
+        if names.is_empty() && TsIdent::node_text(node, code).starts_with("export default") {
+            names.push("default".to_string());
+        }
+
+        names
+    }
+
+    fn decorators_of(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut cursor = node.walk();
+        let annotations: Vec<CodeAnnotation> = node.named_children(&mut cursor)
+            .filter(|child| child.kind() == "decorator")
+            .map(|child| TsIdent::create_annotation(child, code))
+            .collect();
+
+        annotations
+    }
+
+    /// class members keep their decorators as siblings inside the class body
+    fn preceding_decorators(node: Node, code: &str) -> Vec<CodeAnnotation> {
+        let mut annotations = vec![];
+        let mut sibling = node.prev_named_sibling();
+        while let Some(current) = sibling {
+            if current.kind() != "decorator" {
+                break;
+            }
+
+            annotations.insert(0, TsIdent::create_annotation(current, code));
+            sibling = current.prev_named_sibling();
+        }
+
+        annotations
+    }
+
+    fn create_annotation(node: Node, code: &str) -> CodeAnnotation {
+        let expr = node.named_child(0).unwrap_or(node);
+        let name_node = match expr.kind() {
+            "call_expression" => expr.child_by_field_name("function").unwrap_or(expr),
+            _ => expr,
+        };
+
+        let mut annotation = TsIdent::annotation_at(node, &TsIdent::node_text(name_node, code));
+        if expr.kind() == "call_expression" {
+            if let Some(arguments) = expr.child_by_field_name("arguments") {
+                annotation.key_values = TsIdent::decorator_arguments(arguments, code);
+            }
+        }
+
+        annotation
+    }
+
+    /// `@Component({ selector: 'app-root' })` keys the properties of its only object argument
+    fn decorator_arguments(arguments: Node, code: &str) -> Vec<AnnotationKeyValue> {
+        let only_object = match arguments.named_child(0) {
+            Some(object) if arguments.named_child_count() == 1 && object.kind() == "object" => object,
+            _ => return TsIdent::argument_key_values(arguments, code, |argument| (None, argument))
+        };
+
+        let mut key_values = vec![];
+        let mut cursor = only_object.walk();
+        for pair in only_object.named_children(&mut cursor) {
+            let key = pair.child_by_field_name("key");
+            let value = pair.child_by_field_name("value");
+            if let (Some(key), Some(value)) = (key, value) {
+                let key = TsIdent::annotation_value(key, code);
+                key_values.push(AnnotationKeyValue::new(&key, &TsIdent::annotation_value(value, code)));
+            }
+        }
+
+        key_values
+    }
+
+    fn node_text(node: Node, code: &str) -> String {
+        node.utf8_text(code.as_bytes()).unwrap_or("").to_string()
+    }
+
+    fn unquote(text: &str) -> String {
+        text.trim_matches(|c| c == '\'' || c == '"' || c == '`').to_string()
+    }
+}
+
+impl CodeIdent for TsIdent {
+    fn parse(code: &str) -> Result<CodeFile, IdentError> {
+        let mut ident = TsIdent::new(tree_sitter_typescript::language_typescript())?;
+        TsIdent::do_parse(code, &mut ident)
+    }
+
+    fn parse_tree(code: &str, tree: &Tree) -> Result<CodeFile, IdentError> {
+        let ident = TsIdent::new(tree_sitter_typescript::language_typescript())?;
+        Ok(TsIdent::from_tree(code, tree, &ident))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::code_ident::CodeIdent;
+    use crate::identify::ts_ident::TsIdent;
+
+    #[test]
+    fn should_parse_es_module_imports() {
+        let source_code = "import { Injectable } from '@angular/core';
+import * as path from 'path';
+import Default from \"./default\";
+export { helper } from './helper';
+";
+        let file = TsIdent::parse(source_code).unwrap();
+        assert_eq!(vec!["@angular/core", "path", "./default", "./helper"], file.import_sources());
+    }
+
+    #[test]
+    fn should_parse_exports() {
+        let source_code = "export const a = 1, b = 2;
+export function sum() {}
+export class Order {}
+const c = 3;
+export { c as d };
+";
+        let file = TsIdent::parse(source_code).unwrap();
+        assert_eq!(vec!["a", "b", "sum", "Order", "d"], file.exports);
+    }
+
+    #[test]
+    fn should_parse_class_with_heritage() {
+        let source_code = "class OrderService extends BaseService implements Service, Disposable {
+  constructor(private repo: Repo) {
+    super();
+  }
+
+  find(id: string): Order {
+    return this.repo.find(id);
+  }
+}
+";
+        let file = TsIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("OrderService", class.name);
+        assert_eq!(vec!["BaseService"], class.extends);
+        assert_eq!(vec!["Service", "Disposable"], class.implements);
+        assert_eq!(1, class.functions.len());
+        assert_eq!("find", class.functions[0].name);
+        assert_eq!(1, class.constructors.len());
+        assert_eq!("repo", class.constructors[0].parameters[0].name);
+        assert_eq!("Repo", class.constructors[0].parameters[0].typ);
+        assert_eq!("super", class.constructors[0].delegates_to);
+        assert_eq!(0, class.start.row);
+        assert_eq!(9, class.end.row);
+    }
+
+    #[test]
+    fn should_parse_fields() {
+        let source_code = "class OrderService {
+  @Inject()
+  private readonly repo: OrderRepository;
+  static count = 0;
+}
+";
+        let file = TsIdent::parse(source_code).unwrap();
+        let fields = &file.classes[0].fields;
+
+        assert_eq!("repo", fields[0].name);
+        assert_eq!("OrderRepository", fields[0].typ);
+        assert_eq!("private", fields[0].visibility);
+        assert!(fields[0].is_final);
+        assert_eq!("Inject", fields[0].annotations[0].name);
+        assert_eq!("count", fields[1].name);
+        assert!(fields[1].is_static);
+    }
+
+    #[test]
+    fn should_parse_interface() {
+        let source_code = "interface Repo extends Readable {
+  find(id: string): Order;
+}
+";
+        let file = TsIdent::parse(source_code).unwrap();
+
+        assert_eq!("Repo", file.classes[0].name);
+        assert_eq!("find", file.classes[0].functions[0].name);
+    }
+
+    #[test]
+    fn should_parse_decorators() {
+        let source_code = "@Component({ selector: 'app-root' })
+export class AppComponent {
+  @Input()
+  @Output()
+  change() {}
+}
+";
+        let file = TsIdent::parse(source_code).unwrap();
+        let class = &file.classes[0];
+
+        assert_eq!("Component", class.annotations[0].name);
+        assert_eq!(Some("app-root"), class.annotations[0].value_of("selector"));
+        assert_eq!(2, class.functions[0].annotations.len());
+        assert_eq!("Input", class.functions[0].annotations[0].name);
+    }
+
+    #[test]
+    fn should_parse_functions() {
+        let source_code = "function abc() {}
+export function def() {}
+";
+        let file = TsIdent::parse(source_code).unwrap();
+
+        assert_eq!(2, file.functions.len());
+        assert_eq!("abc", file.functions[0].name);
+        assert_eq!("def", file.functions[1].name);
+    }
+
+    #[test]
+    fn should_parse_tsx() {
+        let source_code = "import React from 'react';
+
+export function App() {
+  return <div>hello</div>;
+}
+";
+        let file = TsIdent::parse_tsx(source_code).unwrap();
+
+        assert_eq!(vec!["react"], file.import_sources());
+        assert_eq!("App", file.functions[0].name);
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use tree_sitter::{InputEdit, Parser, Point, Tree};
+
+use guarding_core::domain::code_file::CodeFile;
+use crate::error::IdentError;
+use crate::registry::LanguageRegistry;
+
+struct ParsedFile {
+    code: String,
+    tree: Tree,
+}
+
+/// Keeps the tree of every parsed file, so a changed file is re-parsed from its old tree
+/// and the edited range only, which is what watch mode needs for large files.
+pub struct IncrementalParser {
+    registry: LanguageRegistry,
+    parsed: HashMap<PathBuf, ParsedFile>,
+}
+
+impl Default for IncrementalParser {
+    fn default() -> Self {
+        IncrementalParser::new(LanguageRegistry::default())
+    }
+}
+
+impl IncrementalParser {
+    pub fn new(registry: LanguageRegistry) -> IncrementalParser {
+        IncrementalParser { registry, parsed: HashMap::new() }
+    }
+
+    /// models `code` as the content of `path`, `None` when no language supports the file
+    pub fn parse(&mut self, path: &Path, code: String) -> Option<CodeFile> {
+        let language = self.registry.detect(path)?;
+        let grammar = match language.grammar(path) {
+            Some(grammar) => grammar,
+            None => return Some(unwrap_ident(language.parse(&code, path), language.name())),
+        };
+
+        let old_tree = self.parsed.get(path).map(|previous| {
+            let mut tree = previous.tree.clone();
+            if let Some(edit) = compute_edit(&previous.code, &code) {
+                tree.edit(&edit);
+            }
+            tree
+        });
+
+        let mut parser = Parser::new();
+        if parser.set_language(grammar).is_err() {
+            return Some(unwrap_ident(language.parse(&code, path), language.name()));
+        }
+
+        let tree = match parser.parse(&code, old_tree.as_ref()) {
+            Some(tree) => tree,
+            None => return Some(unwrap_ident(Err(IdentError::Cancelled), language.name())),
+        };
+
+        let file = unwrap_ident(language.parse_tree(&code, &tree, path), language.name());
+        self.parsed.insert(path.to_path_buf(), ParsedFile { code, tree });
+        Some(file)
+    }
+
+    pub fn registry(&self) -> &LanguageRegistry {
+        &self.registry
+    }
+
+    pub fn forget(&mut self, path: &Path) {
+        self.parsed.remove(path);
+    }
+
+    pub fn is_cached(&self, path: &Path) -> bool {
+        self.parsed.contains_key(path)
+    }
+}
+
+fn unwrap_ident(file: Result<CodeFile, IdentError>, language: &str) -> CodeFile {
+    let mut file = file.unwrap_or_else(|err| CodeFile::failed(&err.to_string()));
+    file.language = language.to_string();
+    file
+}
+
+/// the single edit turning `old` into `new`: everything between their common prefix and suffix
+pub fn compute_edit(old: &str, new: &str) -> Option<InputEdit> {
+    if old == new {
+        return None;
+    }
+
+    let mut prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+    while !old.is_char_boundary(prefix) || !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old.bytes().rev().zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old.is_char_boundary(old.len() - suffix) || !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+
+    let old_end = old.len() - suffix;
+    let new_end = new.len() - suffix;
+
+    Some(InputEdit {
+        start_byte: prefix,
+        old_end_byte: old_end,
+        new_end_byte: new_end,
+        start_position: point_at(old, prefix),
+        old_end_position: point_at(old, old_end),
+        new_end_position: point_at(new, new_end),
+    })
+}
+
+/// row and byte column of `offset`, as tree-sitter counts them
+fn point_at(text: &str, offset: usize) -> Point {
+    let before = &text.as_bytes()[..offset];
+    let row = before.iter().filter(|byte| **byte == b'\n').count();
+    let column = match before.iter().rposition(|byte| *byte == b'\n') {
+        Some(newline) => offset - newline - 1,
+        None => offset,
+    };
+
+    Point::new(row, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::code_ident::CodeIdent;
+    use crate::identify::java_ident::JavaIdent;
+    use crate::incremental::{compute_edit, IncrementalParser};
+
+    #[test]
+    fn should_compute_edit_between_prefix_and_suffix() {
+        let edit = compute_edit("class A {\n  int a;\n}", "class A {\n  long a;\n}").unwrap();
+
+        assert_eq!(12, edit.start_byte);
+        assert_eq!(15, edit.old_end_byte);
+        assert_eq!(16, edit.new_end_byte);
+        assert_eq!(1, edit.start_position.row);
+        assert_eq!(2, edit.start_position.column);
+        assert!(compute_edit("same", "same").is_none());
+    }
+
+    #[test]
+    fn should_respect_char_boundaries() {
+        let edit = compute_edit("// é\n", "// è\n").unwrap();
+
+        assert_eq!(3, edit.start_byte);
+        assert_eq!(5, edit.old_end_byte);
+        assert_eq!(5, edit.new_end_byte);
+    }
+
+    #[test]
+    fn should_reparse_changed_file_like_a_fresh_parse() {
+        let path = Path::new("src/Order.java");
+        let mut parser = IncrementalParser::default();
+
+        let first = parser.parse(path, "package shop;\nimport shop.repo.Repo;\nclass Order {}\n".to_string()).unwrap();
+        assert_eq!("Order", first.classes[0].name);
+        assert!(parser.is_cached(path));
+
+        let changed = "package shop;\nimport shop.repo.Repo;\nimport shop.web.Api;\nclass OrderService {}\n";
+        let second = parser.parse(path, changed.to_string()).unwrap();
+        let fresh = JavaIdent::parse(changed).unwrap();
+
+        assert_eq!(fresh.imports, second.imports);
+        assert_eq!("OrderService", second.classes[0].name);
+        assert_eq!(fresh.classes[0].start.row, second.classes[0].start.row);
+    }
+}
@@ -1,10 +1,27 @@
+pub mod error;
 pub mod identify;
+pub mod incremental;
 pub mod model_builder;
+pub mod registry;
+pub mod scanner;
+pub mod support;
 
+pub use error::IdentError;
 pub use model_builder::ModelBuilder;
+pub use registry::{LanguageIdent, LanguageRegistry};
+pub use incremental::IncrementalParser;
+pub use scanner::{ProjectScanner, set_jobs};
 
 pub use identify::code_ident;
+pub use identify::go_ident;
 pub use identify::java_ident;
+pub use identify::kotlin_ident;
+pub use identify::groovy_ident;
+pub use identify::swift_ident;
+pub use identify::dart_ident;
+pub use identify::python_ident;
 pub use identify::js_ident;
+pub use identify::ts_ident;
 pub use identify::rust_ident;
 pub use identify::c_sharp_ident;
+pub use identify::c_cpp_ident;
@@ -1,74 +1,448 @@
-use std::fs;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::{Path, PathBuf};
 
-use walkdir::WalkDir;
+use tracing::info_span;
 
 use guarding_core::domain::code_file::CodeFile;
-use crate::identify::c_sharp_ident::CSharpIdent;
-use crate::identify::code_ident::CodeIdent;
-use crate::identify::java_ident::JavaIdent;
-use crate::identify::js_ident::JsIdent;
+use guarding_core::domain::code_project::CodeProject;
+use guarding_core::resolver::{DependencyClassifier, TypeResolver};
+use crate::error::IdentError;
+use crate::identify::go_ident::GoIdent;
+use crate::identify::python_ident::PythonIdent;
 use crate::identify::rust_ident::RustIdent;
+use crate::registry::{LanguageIdent, LanguageRegistry};
+use crate::scanner::{DEFAULT_MAX_FILE_SIZE, ProjectScanner};
+use crate::support::build_metadata::read_build_metadata;
+use crate::support::encoding::decode_source;
+use crate::support::ts_config::TsConfig;
 
 pub struct ModelBuilder {}
 
 impl ModelBuilder {
     pub fn build_models_by_dir(code_dir: PathBuf) -> Vec<CodeFile> {
-        let mut models = vec![];
-        for entry in WalkDir::new(code_dir) {
-            let entry = entry.unwrap();
-            if !entry.file_type().is_file() {
+        ModelBuilder::build_models_with(&LanguageRegistry::default(), code_dir)
+    }
+
+    pub fn build_project_by_dir(code_dir: PathBuf) -> CodeProject {
+        CodeProject::new(ModelBuilder::build_models_by_dir(code_dir))
+    }
+
+    pub fn build_models_with(registry: &LanguageRegistry, code_dir: PathBuf) -> Vec<CodeFile> {
+        ModelBuilder::build_models_by_scanner(&ProjectScanner::new(registry), code_dir)
+    }
+
+    /// with the languages and limits of `scanner`
+    pub fn build_models_by_scanner(scanner: &ProjectScanner, code_dir: PathBuf) -> Vec<CodeFile> {
+        let mut models = scanner.scan_and_parse(&code_dir);
+        ModelBuilder::resolve_models(scanner.registry(), &code_dir, &mut models);
+        models
+    }
+
+    /// the steps which need all the parsed files of `code_dir`: packages, imports and types
+    pub fn resolve_models(registry: &LanguageRegistry, code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let _span = info_span!("resolve", files = models.len()).entered();
+        for language in registry.languages() {
+            language.resolve(code_dir, models);
+        }
+
+        // packages and imports are final only now
+        TypeResolver::resolve_files(models);
+        DependencyClassifier::classify_files(models, read_build_metadata(code_dir));
+    }
+
+    /// Dart libraries live under `lib/`, so `lib/features/cart/cart_page.dart` is package
+    /// `features.cart`; `package:<own name>/...` and relative imports become module paths
+    /// like `features.catalog.product`, other packages and `dart:` libraries are kept as is
+    pub fn resolve_dart_modules(code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let root = match fs::canonicalize(code_dir) {
+            Ok(root) => root,
+            Err(_) => return
+        };
+
+        let pubspec = root.ancestors().map(|dir| dir.join("pubspec.yaml")).find(|path| path.is_file());
+        let project_root = pubspec.as_ref().and_then(|pubspec| pubspec.parent()).unwrap_or(&root).to_path_buf();
+        let lib = project_root.join("lib");
+        let own_package = pubspec
+            .and_then(|pubspec| fs::read_to_string(pubspec).ok())
+            .and_then(|content| content.lines()
+                .find(|line| line.starts_with("name:"))
+                .map(|line| line["name:".len()..].trim().trim_matches(|c| c == '\'' || c == '"').to_string()));
+
+        let package_of = |path: &Path| -> Option<String> {
+            let base = if path.starts_with(&lib) { &lib } else { &project_root };
+            ModelBuilder::module_path(base, path)
+        };
+
+        for file in models.iter_mut() {
+            let path = PathBuf::from(&file.path);
+            if file.language != "dart" {
                 continue;
             }
 
-            let path = entry.path();
-            if let None = path.extension() {
+            let path = match fs::canonicalize(&path) {
+                Ok(path) => path,
+                Err(_) => continue
+            };
+            let dir = path.parent().unwrap_or(&root).to_path_buf();
+
+            if let Some(package) = package_of(&dir) {
+                file.package = package;
+                for class in file.classes.iter_mut() {
+                    class.package = file.package.clone();
+                }
+            }
+
+            for import in file.imports.iter_mut() {
+                let uri = import.source.as_str();
+                let target = match &own_package {
+                    Some(name) if uri.starts_with(&format!("package:{}/", name)) => {
+                        Some(lib.join(&uri[format!("package:{}/", name).len()..]))
+                    }
+                    _ if !uri.contains(':') => Some(dir.join(uri)),
+                    _ => None
+                };
+
+                let module = target
+                    .and_then(|target| fs::canonicalize(target).ok())
+                    .and_then(|target| package_of(&ModelBuilder::strip_module_ext(&target)));
+
+                if let Some(module) = module {
+                    import.set_source(module);
+                }
+            }
+        }
+    }
+
+    /// Swift imports are module names, and a SwiftPM target is the directory under `Sources/`
+    /// or `Tests/`, so `Sources/Checkout/Cart/CartView.swift` is package `Checkout.Cart`
+    pub fn resolve_swift_modules(code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let root = match fs::canonicalize(code_dir) {
+            Ok(root) => root,
+            Err(_) => return
+        };
+
+        for file in models.iter_mut() {
+            if file.language != "swift" {
                 continue;
             }
+            let path = PathBuf::from(&file.path);
+
+            let package = fs::canonicalize(&path).ok()
+                .and_then(|path| path.parent().and_then(|dir| ModelBuilder::module_path(&root, dir)));
 
-            ModelBuilder::build_model_by_file(&mut models, path)
+            if let Some(package) = package {
+                let parts: Vec<&str> = package.split('.').collect();
+                file.package = match parts.iter().rposition(|part| *part == "Sources" || *part == "Tests") {
+                    Some(index) => parts[index + 1..].join("."),
+                    None => package.clone()
+                };
+
+                for class in file.classes.iter_mut() {
+                    class.package = file.package.clone();
+                }
+            }
         }
-        models
     }
 
-    pub fn build_model_by_file(models: &mut Vec<CodeFile>, path: &Path) {
-        let ext = path.extension().unwrap().to_str().unwrap();
-        let file_name = path.file_name().unwrap().to_str().unwrap();
+    /// the directory is the package of a C/C++ file, and `#include "core/util.h"` is an edge to
+    /// module `core.util` when it's found next to the including file or under the root
+    pub fn resolve_includes(code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let root = match fs::canonicalize(code_dir) {
+            Ok(root) => root,
+            Err(_) => return
+        };
 
-        match ext {
-            "java" => {
-                let mut file = JavaIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = ModelBuilder::format_path(path);
-                file.file_name = file_name.to_string();
-                models.push(file);
+        for file in models.iter_mut() {
+            if file.language != "c_cpp" {
+                continue;
             }
-            "js" => {
-                let mut file = JsIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = format!("{}", path.display());
-                file.file_name = file_name.to_string();
-                models.push(file);
+            let path = PathBuf::from(&file.path);
+
+            let path = match fs::canonicalize(&path) {
+                Ok(path) => path,
+                Err(_) => continue
+            };
+            let dir = path.parent().unwrap_or(&root).to_path_buf();
+
+            if let Some(package) = ModelBuilder::module_path(&root, &dir) {
+                file.package = package;
             }
-            "rs" => {
-                let mut file = RustIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = format!("{}", path.display());
-                file.file_name = file_name.to_string();
-                models.push(file);
+            for class in file.classes.iter_mut() {
+                if class.package.is_empty() {
+                    class.package = file.package.clone();
+                }
             }
-            "cs" => {
-                let mut file = CSharpIdent::parse(ModelBuilder::read_content(path).as_str());
-                file.path = format!("{}", path.display());
-                file.file_name = file_name.to_string();
-                models.push(file);
+
+            for import in file.imports.iter_mut() {
+                let module = [dir.join(&import.source), root.join(&import.source)].iter()
+                    .find(|candidate| candidate.is_file())
+                    .and_then(|candidate| fs::canonicalize(candidate).ok())
+                    .and_then(|found| ModelBuilder::module_path(&root, &ModelBuilder::strip_module_ext(&found)));
+
+                if let Some(module) = module {
+                    import.set_source(module);
+                }
             }
-            &_ => {}
         }
     }
 
-    fn read_content(path: &Path) -> String {
-        fs::read_to_string(path).expect("not such file")
+    /// the package clause only has the last segment, so with a `go.mod` the package becomes the
+    /// full import path: `module/internal/repo` -> `module.internal.repo`, same as the imports
+    pub fn resolve_go_modules(code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let root = match fs::canonicalize(code_dir) {
+            Ok(root) => root,
+            Err(_) => return
+        };
+
+        let go_mod = match root.ancestors().map(|dir| dir.join("go.mod")).find(|path| path.is_file()) {
+            Some(go_mod) => go_mod,
+            None => return
+        };
+        let module_root = match go_mod.parent() {
+            Some(dir) => dir.to_path_buf(),
+            None => return
+        };
+        let module = fs::read_to_string(&go_mod).unwrap_or_default()
+            .lines()
+            .find(|line| line.trim_start().starts_with("module "))
+            .map(|line| line.trim_start()["module ".len()..].trim().trim_matches('"').to_string());
+
+        let module = match module {
+            Some(module) => GoIdent::import_to_package(&module),
+            None => return
+        };
+
+        for file in models.iter_mut() {
+            if file.language != "go" {
+                continue;
+            }
+            let path = PathBuf::from(&file.path);
+
+            let dir = fs::canonicalize(&path).ok()
+                .and_then(|path| path.parent().and_then(|dir| ModelBuilder::module_path(&module_root, dir)));
+
+            if let Some(dir) = dir {
+                file.package = if dir.is_empty() { module.clone() } else { format!("{}.{}", module, dir) };
+                for class in file.classes.iter_mut() {
+                    class.package = file.package.clone();
+                }
+            }
+        }
+    }
+
+    /// Rust has no package declaration, so a file is the module its path is under the `src/` of
+    /// the closest `Cargo.toml`: `shop-core/src/order/service.rs` is `shop_core.order.service`,
+    /// and imports are paths of the same modules, `crate::order::Order` -> `shop_core.order.Order`
+    pub fn resolve_rust_modules(_code_dir: &Path, models: &mut Vec<CodeFile>) {
+        // the crate directory and name of each directory seen, `None` outside of a crate
+        let mut crates: HashMap<PathBuf, Option<(PathBuf, String)>> = HashMap::new();
+
+        for file in models.iter_mut() {
+            if file.language != "rust" {
+                continue;
+            }
+            let path = PathBuf::from(&file.path);
+
+            let path = match fs::canonicalize(&path) {
+                Ok(path) => path,
+                Err(_) => continue
+            };
+            let dir = match path.parent() {
+                Some(dir) => dir.to_path_buf(),
+                None => continue
+            };
+            let found = crates.entry(dir.clone()).or_insert_with(|| ModelBuilder::find_crate(&dir)).clone();
+            let (crate_dir, crate_name) = match found {
+                Some(found) => found,
+                None => continue
+            };
+
+            let src = crate_dir.join("src");
+            let relative = path.strip_prefix(&src).or_else(|_| path.strip_prefix(&crate_dir)).unwrap_or(&path);
+            file.package = RustIdent::module_of(&crate_name, relative);
+            for class in file.classes.iter_mut() {
+                class.package = file.package.clone();
+            }
+
+            for import in file.imports.iter_mut() {
+                let source = RustIdent::resolve_path(&crate_name, &file.package, import.source.as_str());
+                import.set_source(source);
+            }
+        }
+    }
+
+    /// the closest `Cargo.toml` with a `[package]`, a workspace manifest alone isn't a crate
+    fn find_crate(dir: &Path) -> Option<(PathBuf, String)> {
+        dir.ancestors()
+            .map(|dir| (dir, dir.join("Cargo.toml")))
+            .filter(|(_, manifest)| manifest.is_file())
+            .find_map(|(dir, manifest)| {
+                let name = RustIdent::crate_name(&fs::read_to_string(&manifest).ok()?)?;
+                Some((dir.to_path_buf(), name))
+            })
+    }
+
+    /// Python packages are directories, so `myapp/orders/models.py` is module `myapp.orders.models`,
+    /// relative imports are resolved against the directory package
+    pub fn resolve_python_modules(code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let root = match fs::canonicalize(code_dir) {
+            Ok(root) => root,
+            Err(_) => return
+        };
+
+        for file in models.iter_mut() {
+            if file.language != "python" {
+                continue;
+            }
+            let path = PathBuf::from(&file.path);
+
+            let path = match fs::canonicalize(&path) {
+                Ok(path) => path,
+                Err(_) => continue
+            };
+
+            let dir_package = match path.parent().and_then(|dir| ModelBuilder::module_path(&root, dir)) {
+                Some(package) => package,
+                None => continue
+            };
+
+            let stem = path.file_stem().and_then(|stem| stem.to_str()).unwrap_or("");
+            file.package = match (dir_package.is_empty(), stem) {
+                (_, "__init__") => dir_package.clone(),
+                (true, stem) => stem.to_string(),
+                (false, stem) => format!("{}.{}", dir_package, stem),
+            };
+
+            for class in file.classes.iter_mut() {
+                class.package = file.package.clone();
+            }
+
+            for import in file.imports.iter_mut() {
+                let source = PythonIdent::resolve_relative(&dir_package, import.source.as_str());
+                import.set_source(source);
+            }
+        }
+    }
+
+    /// TS/JS have no package declaration, so the directory is the package, and imports
+    /// (relative or tsconfig aliased) are mapped to the module path of the file they point to
+    pub fn resolve_ts_modules(code_dir: &Path, models: &mut Vec<CodeFile>) {
+        let root = match fs::canonicalize(code_dir) {
+            Ok(root) => root,
+            Err(_) => return
+        };
+        let ts_config = TsConfig::discover(&root).unwrap_or_default();
+
+        for file in models.iter_mut() {
+            if file.language != "typescript" && file.language != "javascript" {
+                continue;
+            }
+            let importer = PathBuf::from(&file.path);
+
+            let importer = match fs::canonicalize(&importer) {
+                Ok(importer) => importer,
+                Err(_) => continue
+            };
+
+            if let Some(package) = importer.parent().and_then(|dir| ModelBuilder::module_path(&root, dir)) {
+                file.package = package;
+                for class in file.classes.iter_mut() {
+                    class.package = file.package.clone();
+                }
+            }
+
+            for import in file.imports.iter_mut() {
+                let module = ts_config.resolve(import.source.as_str(), &importer)
+                    .and_then(|resolved| ModelBuilder::module_path(&root, &ModelBuilder::strip_module_ext(&resolved)));
+
+                if let Some(module) = module {
+                    import.set_source(module);
+                }
+            }
+        }
     }
 
-    fn format_path(path: &Path) -> String {
+    /// `features/auth/index.ts` -> `features/auth`, `shared/logger.d.ts` -> `shared/logger`
+    fn strip_module_ext(path: &Path) -> PathBuf {
+        let mut stripped = path.to_path_buf();
+        while stripped.extension().is_some() {
+            stripped.set_extension("");
+        }
+
+        if stripped.file_name().map(|name| name == "index").unwrap_or(false) {
+            stripped.pop();
+        }
+
+        stripped
+    }
+
+    /// `root/features/auth` -> `features.auth`
+    fn module_path(root: &Path, path: &Path) -> Option<String> {
+        let relative = path.strip_prefix(root).ok()?;
+        let parts: Vec<&str> = relative.iter()
+            .filter_map(|part| part.to_str())
+            .collect();
+
+        Some(parts.join("."))
+    }
+
+    pub fn build_model_by_file(models: &mut Vec<CodeFile>, path: &Path) {
+        ModelBuilder::build_model_with(&LanguageRegistry::default(), models, path)
+    }
+
+    pub fn build_model_with(registry: &LanguageRegistry, models: &mut Vec<CodeFile>, path: &Path) {
+        if let Some(file) = ModelBuilder::parse_file(registry, path, DEFAULT_MAX_FILE_SIZE) {
+            models.push(file);
+        }
+    }
+
+    /// the model of `path`, or `None` for files of unknown languages; files over `max_file_size`
+    /// bytes, mostly generated ones, are skipped with a warning instead of being read
+    pub fn parse_file(registry: &LanguageRegistry, path: &Path, max_file_size: u64) -> Option<CodeFile> {
+        let language = registry.detect(path)?;
+
+        let file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let mut file = match ModelBuilder::read_source(path, max_file_size) {
+            Ok(content) => ModelBuilder::identify(language, &content, path)
+                .unwrap_or_else(|err| CodeFile::failed(&err.to_string())),
+            Err(message) => CodeFile::unparsed(&message),
+        };
+        file.path = ModelBuilder::format_path(path);
+        file.file_name = file_name;
+        file.language = language.name().to_string();
+        Some(file)
+    }
+
+    /// a bug in one of the identifiers shouldn't stop the others, so a panic is an `Err` too
+    pub fn identify(language: &dyn LanguageIdent, code: &str, path: &Path) -> Result<CodeFile, IdentError> {
+        panic::catch_unwind(AssertUnwindSafe(|| language.parse(code, path)))
+            .unwrap_or_else(|_| Err(IdentError::Panicked(language.name().to_string())))
+    }
+
+    /// reads at most `max_file_size` bytes, so a file growing after the size check still can't
+    /// take more memory than that
+    pub fn read_source(path: &Path, max_file_size: u64) -> Result<String, String> {
+        let size = fs::metadata(path).map(|metadata| metadata.len()).unwrap_or(0);
+        let too_large = |size: u64| format!("skipped, {} bytes is over the limit of {} bytes", size, max_file_size);
+        if size > max_file_size {
+            return Err(too_large(size));
+        }
+
+        let file = File::open(path).map_err(|err| format!("can't read file: {}", err))?;
+        let mut bytes = vec![];
+        file.take(max_file_size + 1).read_to_end(&mut bytes)
+            .map_err(|err| format!("can't read file: {}", err))?;
+
+        if bytes.len() as u64 > max_file_size {
+            return Err(too_large(bytes.len() as u64));
+        }
+        decode_source(&bytes).map_err(|err| format!("can't decode file: {}", err))
+    }
+
+    pub fn format_path(path: &Path) -> String {
         format!("{}", path.display())
     }
 }
@@ -77,8 +451,33 @@ impl ModelBuilder {
 #[cfg(test)]
 mod tests {
     use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use guarding_core::domain::code_file::CodeFile;
+    use crate::error::IdentError;
+    use crate::registry::{LanguageIdent, LanguageRegistry};
     use crate::ModelBuilder;
 
+    struct BrokenIdent {}
+
+    impl LanguageIdent for BrokenIdent {
+        fn name(&self) -> &str {
+            "broken"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["broken", "failing"]
+        }
+
+        fn parse(&self, _code: &str, path: &Path) -> Result<CodeFile, IdentError> {
+            match path.extension().and_then(|ext| ext.to_str()) {
+                Some("broken") => panic!("no tree"),
+                _ => Err(IdentError::Cancelled)
+            }
+        }
+    }
+
     #[test]
     fn should_parse_current_dir() {
         let dir = env::current_dir().unwrap();
@@ -86,4 +485,134 @@ mod tests {
 
         assert!(models.len() > 0);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_resolve_ts_modules() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("ts").join("src");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let main = models.iter().find(|file| file.file_name == "main.ts").unwrap();
+        assert_eq!("app", main.package);
+        assert_eq!(vec!["react", "shared.logger", "features.auth"], main.import_sources());
+        assert_eq!("app", main.classes[0].package);
+    }
+
+    #[test]
+    fn should_resolve_mapped_extensions_and_scripts() {
+        let dir = env::temp_dir().join("guarding").join("mapped-resolve");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("shop").join("bin")).unwrap();
+        fs::write(dir.join("shop").join("orders.pyw"), "from .core import money\n\nclass Order:\n    pass\n").unwrap();
+        fs::write(dir.join("shop").join("bin").join("export"), "#!/usr/bin/env python3\nimport shop.orders\n").unwrap();
+
+        let mut registry = LanguageRegistry::default();
+        registry.map_extension("pyw", "python").unwrap();
+        let models = ModelBuilder::build_models_with(&registry, dir);
+
+        let orders = models.iter().find(|file| file.file_name == "orders.pyw").unwrap();
+        assert_eq!("python", orders.language);
+        assert_eq!("shop.orders", orders.package);
+        assert_eq!(vec!["shop.core.money"], orders.import_sources());
+        let script = models.iter().find(|file| file.file_name == "export").unwrap();
+        assert_eq!("shop.bin.export", script.package);
+    }
+
+    #[test]
+    fn should_resolve_python_modules() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("python");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let views = models.iter().find(|file| file.file_name == "views.py").unwrap();
+        assert_eq!("shop.orders.views", views.package);
+        assert_eq!(vec!["django.views.View", "shop.orders.models.Order", "shop.core.utils.money"], views.import_sources());
+        assert_eq!("shop.orders.views", views.classes[0].package);
+    }
+
+    #[test]
+    fn should_resolve_go_module_packages() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("go");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let service = models.iter().find(|file| file.file_name == "service.go").unwrap();
+        assert_eq!("github.com.inherd.shop.internal.order", service.package);
+        assert_eq!(vec!["github.com.inherd.shop.internal.repo"], service.import_sources());
+    }
+
+    #[test]
+    fn should_resolve_rust_modules() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("rust");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let service = models.iter().find(|file| file.file_name == "service.rs").unwrap();
+        assert_eq!("shop_core.order.service", service.package);
+        assert_eq!(vec!["shop_core.order.Order", "shop_core.cart.Cart", "serde.Serialize"], service.import_sources());
+        assert_eq!("shop_core.order.service", service.classes[0].package);
+        let order = models.iter().find(|file| file.file_name == "mod.rs").unwrap();
+        assert_eq!("shop_core.order", order.package);
+    }
+
+    #[test]
+    fn should_resolve_includes() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("cpp");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let order = models.iter().find(|file| file.file_name == "order.cpp").unwrap();
+        assert_eq!("order", order.package);
+        assert_eq!(vec!["string", "order.order", "core.money"], order.import_sources());
+    }
+
+    #[test]
+    fn should_resolve_swift_targets() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("swift");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let cart = models.iter().find(|file| file.file_name == "CartView.swift").unwrap();
+        assert_eq!("Checkout.Cart", cart.package);
+        assert_eq!(vec!["UIKit", "Catalog"], cart.import_sources());
+        assert_eq!("Checkout.Cart", cart.classes[0].package);
+    }
+
+    #[test]
+    fn should_resolve_dart_libraries() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures").join("dart");
+        let models = ModelBuilder::build_models_by_dir(dir);
+
+        let cart = models.iter().find(|file| file.file_name == "cart_page.dart").unwrap();
+        assert_eq!("features.cart", cart.package);
+        assert_eq!(vec!["package:flutter/material.dart", "core.money", "features.catalog.product"], cart.import_sources());
+    }
+
+    #[test]
+    fn should_keep_parsing_after_a_file_failed() {
+        let dir = env::temp_dir().join("guarding").join("ident_error");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("a.broken"), "").unwrap();
+        fs::write(dir.join("b.failing"), "").unwrap();
+        fs::write(dir.join("Order.java"), "package shop;\nclass Order {}").unwrap();
+
+        let mut registry = LanguageRegistry::default();
+        registry.register(BrokenIdent {});
+        let mut models = ModelBuilder::build_models_with(&registry, dir);
+        models.sort_by(|a, b| a.file_name.cmp(&b.file_name));
+
+        assert_eq!(Some("identifier failed: broken".to_string()), models[1].ident_error);
+        assert_eq!(Some("parsing was cancelled".to_string()), models[2].ident_error);
+        assert_eq!("Order", models[0].classes[0].name);
+        assert_eq!(None, models[0].ident_error);
+    }
+}
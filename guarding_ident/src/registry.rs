@@ -0,0 +1,397 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+
+use tree_sitter::{Language, Tree};
+
+use guarding_core::domain::code_file::CodeFile;
+use crate::error::IdentError;
+use crate::identify::c_cpp_ident::CCppIdent;
+use crate::identify::c_sharp_ident::CSharpIdent;
+use crate::identify::code_ident::CodeIdent;
+use crate::identify::dart_ident::DartIdent;
+use crate::identify::go_ident::GoIdent;
+use crate::identify::groovy_ident::GroovyIdent;
+use crate::identify::java_ident::JavaIdent;
+use crate::identify::js_ident::JsIdent;
+use crate::identify::kotlin_ident::KotlinIdent;
+use crate::identify::python_ident::PythonIdent;
+use crate::identify::rust_ident::RustIdent;
+use crate::identify::swift_ident::SwiftIdent;
+use crate::identify::ts_ident::TsIdent;
+use crate::ModelBuilder;
+
+/// A language guarding can build models for. Downstream crates implement it and
+/// [`LanguageRegistry::register`] it to support more languages, files are parsed on many threads.
+pub trait LanguageIdent: Send + Sync {
+    /// unique name, like `java` or `typescript`
+    fn name(&self) -> &str;
+
+    /// file extensions without the dot
+    fn extensions(&self) -> &[&str];
+
+    /// interpreters after `#!` for files without a known extension, like `python`
+    fn interpreters(&self) -> &[&str] {
+        &[]
+    }
+
+    /// an `Err` when the file can't be identified, the other files are still parsed
+    fn parse(&self, code: &str, path: &Path) -> Result<CodeFile, IdentError>;
+
+    /// the tree-sitter grammar for `path`, languages with one can be re-parsed incrementally
+    fn grammar(&self, _path: &Path) -> Option<Language> {
+        None
+    }
+
+    /// builds the model from a tree of [`LanguageIdent::grammar`]
+    fn parse_tree(&self, code: &str, _tree: &Tree, path: &Path) -> Result<CodeFile, IdentError> {
+        self.parse(code, path)
+    }
+
+    /// runs once after all files under `code_dir` are parsed, to fix up packages and imports
+    /// which need more than one file, like directory packages or path aliases
+    fn resolve(&self, _code_dir: &Path, _models: &mut Vec<CodeFile>) {}
+}
+
+/// the languages shipped with guarding, which only need functions
+pub struct BuiltinLanguage {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub interpreters: &'static [&'static str],
+    pub parse: fn(&str, &Path) -> Result<CodeFile, IdentError>,
+    pub grammar: fn(&Path) -> Language,
+    pub parse_tree: fn(&str, &Tree, &Path) -> Result<CodeFile, IdentError>,
+    pub resolve: Option<fn(&Path, &mut Vec<CodeFile>)>,
+}
+
+impl LanguageIdent for BuiltinLanguage {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn extensions(&self) -> &[&str] {
+        self.extensions
+    }
+
+    fn interpreters(&self) -> &[&str] {
+        self.interpreters
+    }
+
+    fn parse(&self, code: &str, path: &Path) -> Result<CodeFile, IdentError> {
+        (self.parse)(code, path)
+    }
+
+    fn grammar(&self, path: &Path) -> Option<Language> {
+        Some((self.grammar)(path))
+    }
+
+    fn parse_tree(&self, code: &str, tree: &Tree, path: &Path) -> Result<CodeFile, IdentError> {
+        (self.parse_tree)(code, tree, path)
+    }
+
+    fn resolve(&self, code_dir: &Path, models: &mut Vec<CodeFile>) {
+        if let Some(resolve) = self.resolve {
+            resolve(code_dir, models);
+        }
+    }
+}
+
+pub struct LanguageRegistry {
+    languages: Vec<Box<dyn LanguageIdent>>,
+    /// extension -> name of the language, over the extensions of the languages
+    extensions: BTreeMap<String, String>,
+}
+
+impl Default for LanguageRegistry {
+    fn default() -> Self {
+        let mut registry = LanguageRegistry::empty();
+        for language in builtin_languages() {
+            registry.register(language);
+        }
+
+        registry
+    }
+}
+
+impl LanguageRegistry {
+    pub fn empty() -> LanguageRegistry {
+        LanguageRegistry { languages: vec![], extensions: BTreeMap::new() }
+    }
+
+    /// a language registered later wins for the extensions and interpreters it shares with
+    /// an earlier one, so a built-in language can be replaced
+    pub fn register<L: LanguageIdent + 'static>(&mut self, language: L) {
+        self.languages.push(Box::new(language));
+    }
+
+    pub fn languages(&self) -> Vec<&dyn LanguageIdent> {
+        self.languages.iter().map(|language| language.as_ref()).collect()
+    }
+
+    pub fn by_name(&self, name: &str) -> Option<&dyn LanguageIdent> {
+        self.languages.iter().rev()
+            .find(|language| language.name() == name)
+            .map(|language| language.as_ref())
+    }
+
+    /// files ending with `.extension` are parsed as `language`, like `mjs` as `javascript` or
+    /// `jsp` as `java`
+    pub fn map_extension(&mut self, extension: &str, language: &str) -> Result<(), String> {
+        if self.by_name(language).is_none() {
+            let names: Vec<&str> = self.languages.iter().map(|language| language.name()).collect();
+            return Err(format!("unknown language {:?} for .{}, the languages are: {}", language, extension, names.join(", ")));
+        }
+
+        self.extensions.insert(extension.trim_start_matches('.').to_string(), language.to_string());
+        Ok(())
+    }
+
+    pub fn by_extension(&self, ext: &str) -> Option<&dyn LanguageIdent> {
+        if let Some(name) = self.extensions.get(ext) {
+            return self.by_name(name);
+        }
+
+        self.languages.iter().rev()
+            .find(|language| language.extensions().contains(&ext))
+            .map(|language| language.as_ref())
+    }
+
+    pub fn by_interpreter(&self, interpreter: &str) -> Option<&dyn LanguageIdent> {
+        self.languages.iter().rev()
+            .find(|language| language.interpreters().contains(&interpreter))
+            .map(|language| language.as_ref())
+    }
+
+    /// by extension first, then by the `#!` line
+    pub fn detect(&self, path: &Path) -> Option<&dyn LanguageIdent> {
+        let by_extension = path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.by_extension(ext));
+
+        match by_extension {
+            Some(language) => Some(language),
+            None => shebang_interpreter(path).and_then(|interpreter| self.by_interpreter(&interpreter))
+        }
+    }
+}
+
+/// `#!/usr/bin/env python3` and `#!/usr/bin/python3.9` are both `python`
+pub fn shebang_interpreter(path: &Path) -> Option<String> {
+    let file = File::open(path).ok()?;
+    let mut first_line = vec![];
+    BufReader::new(file).take(256).read_until(b'\n', &mut first_line).ok()?;
+
+    let first_line = String::from_utf8_lossy(&first_line);
+    parse_shebang(&first_line)
+}
+
+pub fn parse_shebang(line: &str) -> Option<String> {
+    let command = line.strip_prefix("#!")?;
+    let mut parts = command.split_whitespace();
+    let mut program = parts.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = parts.find(|part| !part.starts_with('-'))?;
+    }
+
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(name.to_string())
+}
+
+pub fn builtin_languages() -> Vec<BuiltinLanguage> {
+    vec![
+        BuiltinLanguage {
+            name: "java",
+            extensions: &["java"],
+            interpreters: &[],
+            parse: |code, _| JavaIdent::parse(code),
+            grammar: |_| tree_sitter_java::language(),
+            parse_tree: |code, tree, _| JavaIdent::parse_tree(code, tree),
+            resolve: None,
+        },
+        BuiltinLanguage {
+            name: "kotlin",
+            extensions: &["kt", "kts"],
+            interpreters: &[],
+            parse: |code, _| KotlinIdent::parse(code),
+            grammar: |_| tree_sitter_kotlin::language(),
+            parse_tree: |code, tree, _| KotlinIdent::parse_tree(code, tree),
+            resolve: None,
+        },
+        BuiltinLanguage {
+            name: "groovy",
+            extensions: &["groovy", "gradle"],
+            interpreters: &["groovy"],
+            parse: |code, _| GroovyIdent::parse(code),
+            grammar: |_| tree_sitter_groovy::language(),
+            parse_tree: |code, tree, _| GroovyIdent::parse_tree(code, tree),
+            resolve: None,
+        },
+        BuiltinLanguage {
+            name: "javascript",
+            extensions: &["js", "jsx", "mjs"],
+            interpreters: &["node"],
+            parse: |code, _| JsIdent::parse(code),
+            grammar: |_| tree_sitter_javascript::language(),
+            parse_tree: |code, tree, _| JsIdent::parse_tree(code, tree),
+            resolve: None,
+        },
+        // also resolves the JavaScript modules, they share `tsconfig.json` and directory packages
+        BuiltinLanguage {
+            name: "typescript",
+            extensions: &["ts", "tsx"],
+            interpreters: &["ts-node", "deno"],
+            parse: |code, path| {
+                match path.extension().and_then(|ext| ext.to_str()) {
+                    Some("tsx") => TsIdent::parse_tsx(code),
+                    _ => TsIdent::parse(code)
+                }
+            },
+            grammar: |path| {
+                match path.extension().and_then(|ext| ext.to_str()) {
+                    Some("tsx") => tree_sitter_typescript::language_tsx(),
+                    _ => tree_sitter_typescript::language_typescript()
+                }
+            },
+            parse_tree: |code, tree, path| {
+                match path.extension().and_then(|ext| ext.to_str()) {
+                    Some("tsx") => TsIdent::parse_tsx_tree(code, tree),
+                    _ => TsIdent::parse_tree(code, tree)
+                }
+            },
+            resolve: Some(ModelBuilder::resolve_ts_modules),
+        },
+        BuiltinLanguage {
+            name: "python",
+            extensions: &["py"],
+            interpreters: &["python"],
+            parse: |code, _| PythonIdent::parse(code),
+            grammar: |_| tree_sitter_python::language(),
+            parse_tree: |code, tree, _| PythonIdent::parse_tree(code, tree),
+            resolve: Some(ModelBuilder::resolve_python_modules),
+        },
+        BuiltinLanguage {
+            name: "go",
+            extensions: &["go"],
+            interpreters: &[],
+            parse: |code, _| GoIdent::parse(code),
+            grammar: |_| tree_sitter_go::language(),
+            parse_tree: |code, tree, _| GoIdent::parse_tree(code, tree),
+            resolve: Some(ModelBuilder::resolve_go_modules),
+        },
+        BuiltinLanguage {
+            name: "rust",
+            extensions: &["rs"],
+            interpreters: &[],
+            parse: |code, _| RustIdent::parse(code),
+            grammar: |_| tree_sitter_rust::language(),
+            parse_tree: |code, tree, _| RustIdent::parse_tree(code, tree),
+            resolve: Some(ModelBuilder::resolve_rust_modules),
+        },
+        BuiltinLanguage {
+            name: "c_sharp",
+            extensions: &["cs"],
+            interpreters: &[],
+            parse: |code, _| CSharpIdent::parse(code),
+            grammar: |_| tree_sitter_c_sharp::language(),
+            parse_tree: |code, tree, _| CSharpIdent::parse_tree(code, tree),
+            resolve: None,
+        },
+        BuiltinLanguage {
+            name: "c_cpp",
+            extensions: &["c", "h", "cc", "cpp", "cxx", "hpp", "hh", "hxx"],
+            interpreters: &[],
+            parse: |code, _| CCppIdent::parse(code),
+            grammar: |_| tree_sitter_cpp::language(),
+            parse_tree: |code, tree, _| CCppIdent::parse_tree(code, tree),
+            resolve: Some(ModelBuilder::resolve_includes),
+        },
+        BuiltinLanguage {
+            name: "swift",
+            extensions: &["swift"],
+            interpreters: &["swift"],
+            parse: |code, _| SwiftIdent::parse(code),
+            grammar: |_| tree_sitter_swift::language(),
+            parse_tree: |code, tree, _| SwiftIdent::parse_tree(code, tree),
+            resolve: Some(ModelBuilder::resolve_swift_modules),
+        },
+        BuiltinLanguage {
+            name: "dart",
+            extensions: &["dart"],
+            interpreters: &["dart"],
+            parse: |code, _| DartIdent::parse(code),
+            grammar: |_| tree_sitter_dart::language(),
+            parse_tree: |code, tree, _| DartIdent::parse_tree(code, tree),
+            resolve: Some(ModelBuilder::resolve_dart_modules),
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use guarding_core::domain::code_file::CodeFile;
+    use crate::error::IdentError;
+    use crate::registry::{LanguageIdent, LanguageRegistry, parse_shebang};
+
+    struct ProtoIdent {}
+
+    impl LanguageIdent for ProtoIdent {
+        fn name(&self) -> &str {
+            "proto"
+        }
+
+        fn extensions(&self) -> &[&str] {
+            &["proto"]
+        }
+
+        fn parse(&self, _code: &str, _path: &Path) -> Result<CodeFile, IdentError> {
+            let mut file = CodeFile::default();
+            file.package = "proto".to_string();
+            Ok(file)
+        }
+    }
+
+    #[test]
+    fn should_detect_by_extension() {
+        let registry = LanguageRegistry::default();
+
+        assert_eq!("typescript", registry.detect(Path::new("src/app.tsx")).unwrap().name());
+        assert_eq!("c_cpp", registry.detect(Path::new("include/order.hpp")).unwrap().name());
+        assert!(registry.detect(Path::new("README.md")).is_none());
+    }
+
+    #[test]
+    fn should_map_custom_extensions() {
+        let mut registry = LanguageRegistry::default();
+        registry.map_extension(".jsp", "java").unwrap();
+        registry.map_extension("js", "typescript").unwrap();
+
+        assert_eq!("java", registry.detect(Path::new("web/order.jsp")).unwrap().name());
+        assert_eq!("typescript", registry.detect(Path::new("src/app.js")).unwrap().name());
+        assert!(registry.map_extension("jsp", "cobol").is_err());
+    }
+
+    #[test]
+    fn should_parse_shebang() {
+        assert_eq!(Some("python".to_string()), parse_shebang("#!/usr/bin/env python3\n"));
+        assert_eq!(Some("python".to_string()), parse_shebang("#!/usr/bin/python3.9"));
+        assert_eq!(Some("node".to_string()), parse_shebang("#!/usr/bin/env -S node --inspect"));
+        assert_eq!(None, parse_shebang("import os"));
+    }
+
+    #[test]
+    fn should_register_downstream_language() {
+        let mut registry = LanguageRegistry::default();
+        registry.register(ProtoIdent {});
+
+        let proto = registry.by_extension("proto").unwrap();
+        assert_eq!("proto", proto.parse("", Path::new("order.proto")).unwrap().package);
+        assert!(registry.languages().iter().any(|language| language.name() == "java"));
+    }
+}
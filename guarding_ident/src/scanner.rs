@@ -0,0 +1,312 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::env;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use rayon::prelude::*;
+use tracing::{debug_span, warn};
+
+use guarding_core::domain::code_file::CodeFile;
+use crate::ModelBuilder;
+use crate::registry::LanguageRegistry;
+
+/// generated sources over 2 MiB are skipped
+pub const DEFAULT_MAX_FILE_SIZE: u64 = 2 * 1024 * 1024;
+
+/// env var for the max file size in bytes, when none is set on the scanner
+pub const MAX_FILE_SIZE_ENV: &'static str = "GUARDING_MAX_FILE_SIZE";
+
+/// the files parsed at once before their models are handed over, so a large project never has
+/// more sources, syntax trees and unconsumed models than that in memory
+pub const DEFAULT_BATCH_SIZE: usize = 256;
+
+/// gitignore syntax, for what only guarding should skip, like generated or vendored code
+pub const IGNORE_FILE: &'static str = ".guardingignore";
+
+/// the threads parsing files for every scanner of the process, 0 for one per core; the pool
+/// is set up on first use, so this only works before any parsing
+pub fn set_jobs(jobs: usize) -> Result<(), String> {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(jobs)
+        .build_global()
+        .map_err(|err| format!("can't use {} jobs: {}", jobs, err))
+}
+
+/// Walks a source tree like git sees it, skipping what `.gitignore`, `.ignore`, `.guardingignore`
+/// and hidden directories exclude, and parses the files of the known languages in parallel.
+pub struct ProjectScanner<'a> {
+    registry: &'a LanguageRegistry,
+    languages: Vec<String>,
+    excludes: Vec<String>,
+    max_file_size: u64,
+    batch_size: usize,
+}
+
+impl<'a> ProjectScanner<'a> {
+    pub fn new(registry: &'a LanguageRegistry) -> ProjectScanner<'a> {
+        let max_file_size = env::var(MAX_FILE_SIZE_ENV).ok()
+            .and_then(|size| size.parse().ok())
+            .unwrap_or(DEFAULT_MAX_FILE_SIZE);
+
+        ProjectScanner { registry, languages: vec![], excludes: vec![], max_file_size, batch_size: DEFAULT_BATCH_SIZE }
+    }
+
+    /// only the files of these languages, like `java` or `typescript`, all of them when empty
+    pub fn with_languages(mut self, languages: Vec<String>) -> ProjectScanner<'a> {
+        self.languages = languages;
+        self
+    }
+
+    /// gitignore-like globs relative to the scanned directory, like `generated/` or `**/*.pb.go`
+    pub fn with_excludes(mut self, excludes: Vec<String>) -> ProjectScanner<'a> {
+        self.excludes = excludes;
+        self
+    }
+
+    /// larger files are kept in the models as skipped, with a warning
+    pub fn with_max_file_size(mut self, max_file_size: u64) -> ProjectScanner<'a> {
+        self.max_file_size = max_file_size;
+        self
+    }
+
+    /// how many files `parse_each` parses in parallel before handing their models over
+    pub fn with_batch_size(mut self, batch_size: usize) -> ProjectScanner<'a> {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    pub fn registry(&self) -> &LanguageRegistry {
+        self.registry
+    }
+
+    pub fn max_file_size(&self) -> u64 {
+        self.max_file_size
+    }
+
+    /// the files to parse, sorted so the models keep the same order between runs
+    pub fn scan(&self, code_dir: &Path) -> Vec<PathBuf> {
+        let skipped = RefCell::new(BTreeMap::new());
+        let paths = self.walk(code_dir, |path| {
+            let accepted = self.accepts(path);
+            if !accepted && self.languages.is_empty() {
+                if let Some(ext) = path.extension() {
+                    *skipped.borrow_mut().entry(ext.to_string_lossy().to_string()).or_insert(0) += 1;
+                }
+            }
+            accepted
+        });
+
+        for (ext, count) in skipped.into_inner() {
+            warn!("skipped {} .{} files without a language, `extensions` in guarding.toml maps them to one", count, ext);
+        }
+        paths
+    }
+
+    /// the files ending with `.extension`, like the `guarding` rule files, with the same
+    /// ignore files and excludes as the sources
+    pub fn scan_extension(&self, code_dir: &Path, extension: &str) -> Vec<PathBuf> {
+        self.walk(code_dir, |path| path.extension().map(|ext| ext == extension).unwrap_or(false))
+    }
+
+    fn walk(&self, code_dir: &Path, accepts: impl Fn(&Path) -> bool) -> Vec<PathBuf> {
+        let mut walker = WalkBuilder::new(code_dir);
+        // a checkout without `.git`, like an unpacked archive, is still ignored the same way
+        walker.require_git(false);
+        walker.add_custom_ignore_filename(IGNORE_FILE);
+
+        let mut overrides = OverrideBuilder::new(code_dir);
+        for exclude in &self.excludes {
+            if let Err(err) = overrides.add(&format!("!{}", exclude)) {
+                warn!("invalid exclude {:?}: {}", exclude, err);
+            }
+        }
+        match overrides.build() {
+            Ok(overrides) => { walker.overrides(overrides); }
+            Err(err) => warn!("invalid excludes: {}", err),
+        }
+
+        let mut paths: Vec<PathBuf> = walker
+            .build()
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|typ| typ.is_file()).unwrap_or(false))
+            .map(|entry| entry.into_path())
+            .filter(|path| accepts(path))
+            .collect();
+
+        paths.sort();
+        paths
+    }
+
+    pub fn parse(&self, paths: &[PathBuf]) -> Vec<CodeFile> {
+        self.parse_with_progress(paths, &|_, _| {})
+    }
+
+    /// calls `progress` with the parsed and the total number of files after each file, from
+    /// the parsing threads
+    pub fn parse_with_progress(&self, paths: &[PathBuf], progress: &(dyn Fn(usize, usize) + Sync)) -> Vec<CodeFile> {
+        let mut models = Vec::with_capacity(paths.len());
+        self.parse_each(paths, progress, &mut |file| models.push(file));
+        models
+    }
+
+    /// streams the models of `paths` to `on_file` in their order, a batch at a time: the source
+    /// and syntax tree of a file are dropped as soon as its model is extracted, so consumers
+    /// which write models away, like the cache, only keep one batch in memory
+    pub fn parse_each(&self, paths: &[PathBuf], progress: &(dyn Fn(usize, usize) + Sync), on_file: &mut dyn FnMut(CodeFile)) {
+        let parsed = AtomicUsize::new(0);
+        for batch in paths.chunks(self.batch_size) {
+            let models: Vec<CodeFile> = batch.par_iter()
+                .filter_map(|path| {
+                    let _span = debug_span!("parse", path = %path.display()).entered();
+                    let file = ModelBuilder::parse_file(self.registry, path, self.max_file_size);
+                    progress(parsed.fetch_add(1, Ordering::Relaxed) + 1, paths.len());
+                    file.map(|mut file| {
+                        file.shrink_to_fit();
+                        file
+                    })
+                })
+                .collect();
+
+            for file in models {
+                on_file(file);
+            }
+        }
+    }
+
+    pub fn scan_and_parse(&self, code_dir: &Path) -> Vec<CodeFile> {
+        self.parse(&self.scan(code_dir))
+    }
+
+    fn accepts(&self, path: &Path) -> bool {
+        match self.registry.detect(path) {
+            Some(language) => self.languages.is_empty() || self.languages.iter().any(|name| name == language.name()),
+            None => false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::path::PathBuf;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crate::registry::LanguageRegistry;
+    use crate::scanner::ProjectScanner;
+
+    fn fixtures() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).parent().unwrap().join("_fixtures")
+    }
+
+    #[test]
+    fn should_scan_by_language() {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry).with_languages(vec!["python".to_string()]);
+
+        let paths = scanner.scan(&fixtures());
+        assert!(paths.len() > 0);
+        assert!(paths.iter().all(|path| path.extension().unwrap() == "py"));
+
+        let mut sorted = paths.clone();
+        sorted.sort();
+        assert_eq!(sorted, paths);
+    }
+
+    #[test]
+    fn should_parse_in_parallel_keeping_order() {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry);
+
+        let paths = scanner.scan(&fixtures().join("java"));
+        let models = scanner.parse(&paths);
+
+        assert_eq!(paths.len(), models.len());
+        let parsed: Vec<String> = models.iter().map(|file| file.path.clone()).collect();
+        let expected: Vec<String> = paths.iter().map(|path| format!("{}", path.display())).collect();
+        assert_eq!(expected, parsed);
+    }
+
+    #[test]
+    fn should_stream_models_batch_by_batch() {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry).with_batch_size(2);
+        let paths = scanner.scan(&fixtures().join("java"));
+        assert!(paths.len() > 2);
+
+        let mut streamed = vec![];
+        scanner.parse_each(&paths, &|_, _| {}, &mut |file| streamed.push(file.path));
+        let expected: Vec<String> = paths.iter().map(|path| format!("{}", path.display())).collect();
+        assert_eq!(expected, streamed);
+    }
+
+    #[test]
+    fn should_report_parse_progress() {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry);
+        let paths = scanner.scan(&fixtures().join("java"));
+
+        let last = AtomicUsize::new(0);
+        scanner.parse_with_progress(&paths, &|parsed, total| {
+            assert_eq!(paths.len(), total);
+            last.fetch_max(parsed, Ordering::Relaxed);
+        });
+        assert_eq!(paths.len(), last.load(Ordering::Relaxed));
+    }
+
+    #[test]
+    fn should_scan_by_extension() {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry);
+
+        let paths = scanner.scan_extension(&fixtures().join("java"), "guarding");
+        assert_eq!(vec![fixtures().join("java").join("size.guarding")], paths);
+    }
+
+    #[test]
+    fn should_skip_excluded_paths() {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry).with_excludes(vec!["powermock/".to_string(), "**/*Parser.java".to_string()]);
+
+        let paths = scanner.scan(&fixtures().join("java"));
+        assert!(paths.len() > 0);
+        assert!(paths.iter().all(|path| !path.to_string_lossy().contains("powermock")));
+        assert!(paths.iter().all(|path| !path.to_string_lossy().ends_with("Parser.java")));
+    }
+
+    #[test]
+    fn should_honor_guardingignore() {
+        let dir = env::temp_dir().join("guarding").join("guardingignore");
+        fs::create_dir_all(dir.join("generated")).unwrap();
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join(".guardingignore"), "generated/\n*.g.java\n").unwrap();
+        fs::write(dir.join("generated").join("Api.java"), "class Api {}").unwrap();
+        fs::write(dir.join("src").join("Order.java"), "class Order {}").unwrap();
+        fs::write(dir.join("src").join("Order.g.java"), "class OrderG {}").unwrap();
+
+        let registry = LanguageRegistry::default();
+        let paths = ProjectScanner::new(&registry).scan(&dir);
+
+        assert_eq!(vec![dir.join("src").join("Order.java")], paths);
+    }
+
+    #[test]
+    fn should_skip_too_large_files() {
+        let dir = env::temp_dir().join("guarding").join("large_files");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Small.java"), "class Small {}").unwrap();
+        fs::write(dir.join("Generated.java"), format!("class Generated {{ {} }}", "int a;".repeat(100))).unwrap();
+
+        let registry = LanguageRegistry::default();
+        let models = ProjectScanner::new(&registry).with_max_file_size(100).scan_and_parse(&dir);
+
+        let generated = models.iter().find(|file| file.file_name == "Generated.java").unwrap();
+        assert!(generated.classes.is_empty());
+        assert!(generated.warnings[0].message.starts_with("skipped"));
+
+        let small = models.iter().find(|file| file.file_name == "Small.java").unwrap();
+        assert_eq!("Small", small.classes[0].name);
+    }
+}
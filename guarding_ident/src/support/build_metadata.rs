@@ -0,0 +1,262 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use guarding_core::resolver::BuildMetadata;
+
+/// the build files of `code_dir` or of the closest parent having one: `pom.xml`, `build.gradle`,
+/// `package.json`, `Cargo.toml`, `go.mod`, `requirements.txt` and `pubspec.yaml`
+pub fn read_build_metadata(code_dir: &Path) -> BuildMetadata {
+    let mut metadata = BuildMetadata::default();
+    for path in find_build_files(code_dir) {
+        let parser = PARSERS.iter().find(|(file_name, _)| path.file_name().map(|name| name == *file_name).unwrap_or(false));
+        if let (Some((_, parse)), Ok(content)) = (parser, fs::read_to_string(&path)) {
+            parse(&content, &mut metadata);
+        }
+    }
+
+    metadata
+}
+
+/// the build files `read_build_metadata` reads
+pub fn find_build_files(code_dir: &Path) -> Vec<PathBuf> {
+    let root = match fs::canonicalize(code_dir) {
+        Ok(root) => root,
+        Err(_) => return vec![]
+    };
+
+    for dir in root.ancestors() {
+        let files: Vec<PathBuf> = PARSERS.iter()
+            .map(|(file_name, _)| dir.join(file_name))
+            .filter(|path| path.is_file())
+            .collect();
+        if !files.is_empty() {
+            return files;
+        }
+    }
+
+    vec![]
+}
+
+const PARSERS: &[(&str, fn(&str, &mut BuildMetadata))] = &[
+    ("pom.xml", parse_pom),
+    ("build.gradle", parse_gradle),
+    ("build.gradle.kts", parse_gradle),
+    ("package.json", parse_package_json),
+    ("Cargo.toml", parse_cargo_toml),
+    ("go.mod", parse_go_mod),
+    ("requirements.txt", parse_requirements),
+    ("pubspec.yaml", parse_pubspec),
+];
+
+fn push(names: &mut Vec<String>, name: &str) {
+    let name = name.trim();
+    if !name.is_empty() && !names.iter().any(|known| known == name) {
+        names.push(name.to_string());
+    }
+}
+
+/// the text of every `<tag>...</tag>` in `content`
+fn xml_values<'a>(content: &'a str, tag: &str) -> Vec<&'a str> {
+    let open = format!("<{}>", tag);
+    let close = format!("</{}>", tag);
+    let mut values = vec![];
+    let mut rest = content;
+    while let Some(start) = rest.find(&open) {
+        let after = &rest[start + open.len()..];
+        match after.find(&close) {
+            Some(end) => {
+                values.push(&after[..end]);
+                rest = &after[end + close.len()..];
+            }
+            None => break
+        }
+    }
+
+    values
+}
+
+/// the project group is the first `groupId` outside of `<parent>` and `<dependencies>`
+fn parse_pom(content: &str, metadata: &mut BuildMetadata) {
+    let mut project = content.to_string();
+    for tag in &["parent", "dependencies", "dependencyManagement", "build"] {
+        let open = format!("<{}>", tag);
+        let close = format!("</{}>", tag);
+        while let (Some(start), Some(end)) = (project.find(&open), project.find(&close)) {
+            if end < start {
+                break;
+            }
+            project.replace_range(start..end + close.len(), "");
+        }
+    }
+    if let Some(group) = xml_values(&project, "groupId").first() {
+        push(&mut metadata.names, group);
+    }
+
+    for dependency in xml_values(content, "dependency") {
+        if let Some(group) = xml_values(dependency, "groupId").first() {
+            push(&mut metadata.libraries, group);
+        }
+    }
+}
+
+/// `group = 'com.phodal'` and `implementation 'org.springframework:spring-core:5.3.1'`
+fn parse_gradle(content: &str, metadata: &mut BuildMetadata) {
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with("group") && line.contains('=') {
+            let value = line.splitn(2, '=').nth(1).unwrap_or("");
+            push(&mut metadata.names, value.trim().trim_matches(|c| c == '\'' || c == '"'));
+            continue;
+        }
+
+        for quote in &['\'', '"'] {
+            let parts: Vec<&str> = line.split(*quote).collect();
+            for coordinate in parts.iter().skip(1).step_by(2) {
+                let segments: Vec<&str> = coordinate.split(':').collect();
+                if segments.len() >= 2 && !segments[0].is_empty() && !segments[0].contains(' ') {
+                    push(&mut metadata.libraries, segments[0]);
+                }
+            }
+        }
+    }
+}
+
+fn parse_package_json(content: &str, metadata: &mut BuildMetadata) {
+    let json: serde_json::Value = match serde_json::from_str(content) {
+        Ok(json) => json,
+        Err(_) => return
+    };
+
+    if let Some(name) = json.get("name").and_then(|name| name.as_str()) {
+        push(&mut metadata.names, name);
+    }
+    for key in &["dependencies", "devDependencies", "peerDependencies", "optionalDependencies"] {
+        if let Some(dependencies) = json.get(key).and_then(|dependencies| dependencies.as_object()) {
+            for name in dependencies.keys() {
+                push(&mut metadata.libraries, name);
+            }
+        }
+    }
+}
+
+/// crates are imported with `_` where the manifest may have `-`
+fn parse_cargo_toml(content: &str, metadata: &mut BuildMetadata) {
+    let mut section = "";
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            section = line.trim_matches(|c| c == '[' || c == ']');
+            continue;
+        }
+
+        let key = match line.split('=').next() {
+            Some(key) if line.contains('=') => key.trim().trim_matches('"'),
+            _ => continue
+        };
+        if section == "package" && key == "name" {
+            let value = line.splitn(2, '=').nth(1).unwrap_or("").trim().trim_matches('"');
+            push(&mut metadata.names, &value.replace('-', "_"));
+        } else if section.ends_with("dependencies") {
+            push(&mut metadata.libraries, &key.replace('-', "_"));
+        }
+    }
+}
+
+fn parse_go_mod(content: &str, metadata: &mut BuildMetadata) {
+    let mut in_require = false;
+    for line in content.lines() {
+        let line = line.trim();
+        if let Some(module) = line.strip_prefix("module ") {
+            push(&mut metadata.names, module);
+        } else if line.starts_with("require (") {
+            in_require = true;
+        } else if in_require && line.starts_with(')') {
+            in_require = false;
+        } else if let Some(require) = line.strip_prefix("require ") {
+            push(&mut metadata.libraries, require.split_whitespace().next().unwrap_or(""));
+        } else if in_require && !line.starts_with("//") {
+            push(&mut metadata.libraries, line.split_whitespace().next().unwrap_or(""));
+        }
+    }
+}
+
+/// `Django>=3.2` is imported as `django`
+fn parse_requirements(content: &str, metadata: &mut BuildMetadata) {
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+            continue;
+        }
+
+        let name = line.split(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == '.')).next().unwrap_or("");
+        push(&mut metadata.libraries, &name.to_lowercase().replace('-', "_"));
+    }
+}
+
+fn parse_pubspec(content: &str, metadata: &mut BuildMetadata) {
+    let mut in_dependencies = false;
+    for line in content.lines() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+
+        let indented = line.starts_with(' ');
+        let key = line.trim().split(':').next().unwrap_or("").trim();
+        if !indented {
+            in_dependencies = key.ends_with("dependencies");
+            if key == "name" {
+                let value = line.splitn(2, ':').nth(1).unwrap_or("");
+                push(&mut metadata.names, &format!("package:{}", value.trim().trim_matches(|c| c == '\'' || c == '"')));
+            }
+        } else if in_dependencies && line.starts_with("  ") && !line.starts_with("   ") {
+            push(&mut metadata.libraries, &format!("package:{}", key));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::resolver::BuildMetadata;
+
+    use crate::support::build_metadata::{parse_cargo_toml, parse_go_mod, parse_gradle, parse_package_json, parse_pom};
+
+    #[test]
+    fn should_read_pom_and_gradle() {
+        let mut metadata = BuildMetadata::default();
+        parse_pom("<project>
+  <parent><groupId>org.springframework.boot</groupId></parent>
+  <groupId>com.phodal</groupId>
+  <dependencies>
+    <dependency><groupId>com.google.guava</groupId><artifactId>guava</artifactId></dependency>
+  </dependencies>
+</project>", &mut metadata);
+        parse_gradle("group = 'com.phodal'
+dependencies {
+    implementation 'org.projectlombok:lombok:1.18.20'
+}", &mut metadata);
+
+        assert_eq!(vec!["com.phodal"], metadata.names);
+        assert_eq!(vec!["com.google.guava", "org.projectlombok"], metadata.libraries);
+    }
+
+    #[test]
+    fn should_read_package_json_cargo_and_go_mod() {
+        let mut metadata = BuildMetadata::default();
+        parse_package_json("{\"name\": \"shop\", \"dependencies\": {\"lodash\": \"4\"}}", &mut metadata);
+        parse_cargo_toml("[package]
+name = \"guarding-core\"
+
+[dependencies]
+serde_json = \"1\"
+tree-sitter = \"0.19\"
+", &mut metadata);
+        parse_go_mod("module github.com/phodal/shop
+
+require (
+    github.com/gin-gonic/gin v1.7.0
+)", &mut metadata);
+
+        assert_eq!(vec!["shop", "guarding_core", "github.com/phodal/shop"], metadata.names);
+        assert_eq!(vec!["lodash", "serde_json", "tree_sitter", "github.com/gin-gonic/gin"], metadata.libraries);
+    }
+}
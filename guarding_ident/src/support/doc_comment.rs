@@ -0,0 +1,134 @@
+use tree_sitter::Node;
+
+/// nodes which only wrap a declaration, the doc comment is before them
+const WRAPPERS: [&'static str; 7] = [
+    "export_statement",
+    "type_declaration",
+    "decorated_definition",
+    "template_declaration",
+    "declaration",
+    "field_declaration",
+    "method_signature",
+];
+
+/// The doc comment right before `node`: a `/** */` block or `///` lines, Rust attributes
+/// between them and the node are skipped. With `plain_lines`, like Go, `//` lines count too.
+pub fn preceding_doc(node: Node, code: &str, plain_lines: bool) -> String {
+    let mut target = node;
+    loop {
+        let doc = doc_before(target, code, plain_lines);
+        if !doc.is_empty() {
+            return doc;
+        }
+
+        match target.parent() {
+            Some(parent) if WRAPPERS.contains(&parent.kind()) && is_first_declaration(parent, target) => {
+                target = parent;
+            }
+            _ => return "".to_string(),
+        }
+    }
+}
+
+/// the wrapped node, which may come after a keyword like `export` or `type`
+fn is_first_declaration(parent: Node, node: Node) -> bool {
+    let mut cursor = parent.walk();
+    let first = parent.named_children(&mut cursor)
+        .find(|child| !child.kind().contains("comment") && child.kind() != "decorator");
+
+    first.map(|first| first.start_byte() == node.start_byte()).unwrap_or(false)
+}
+
+fn doc_before(node: Node, code: &str, plain_lines: bool) -> String {
+    let mut lines: Vec<String> = vec![];
+    let mut next_row = node.start_position().row;
+    let mut sibling = node.prev_sibling();
+
+    while let Some(current) = sibling {
+        if current.kind() == "attribute_item" {
+            next_row = current.start_position().row;
+            sibling = current.prev_sibling();
+            continue;
+        }
+
+        if !current.kind().contains("comment") || current.end_position().row + 1 < next_row {
+            break;
+        }
+
+        let text = &code[current.byte_range()];
+        if is_block_doc(text) {
+            if lines.is_empty() {
+                return clean_block(text);
+            }
+            break;
+        }
+
+        if !is_line_doc(text, plain_lines) {
+            break;
+        }
+
+        lines.insert(0, clean_line(text));
+        next_row = current.start_position().row;
+        sibling = current.prev_sibling();
+    }
+
+    lines.join("\n").trim().to_string()
+}
+
+fn is_block_doc(text: &str) -> bool {
+    text.starts_with("/**") && text != "/**/"
+}
+
+fn is_line_doc(text: &str, plain_lines: bool) -> bool {
+    if text.starts_with("///") {
+        return !text.starts_with("////");
+    }
+
+    plain_lines && text.starts_with("//")
+}
+
+fn clean_line(text: &str) -> String {
+    text.trim_start_matches('/').trim().to_string()
+}
+
+/// `/** Order\n * of a cart\n */` -> `Order\nof a cart`
+pub fn clean_block(text: &str) -> String {
+    let inner = text.trim_start_matches("/**").trim_end_matches("*/");
+    let lines: Vec<&str> = inner.lines()
+        .map(|line| line.trim())
+        .map(|line| line.strip_prefix('*').unwrap_or(line).trim())
+        .collect();
+
+    lines.join("\n").trim().to_string()
+}
+
+/// `"""Order of a cart."""` -> `Order of a cart.`
+pub fn clean_docstring(text: &str) -> String {
+    let text = text.trim_start_matches(|c| c == 'r' || c == 'u' || c == 'R' || c == 'U');
+    let quote = if text.starts_with("\"\"\"") || text.starts_with("'''") { 3 } else { 1 };
+    if text.len() < quote * 2 {
+        return "".to_string();
+    }
+
+    let inner = &text[quote..text.len() - quote];
+    let lines: Vec<&str> = inner.lines().map(|line| line.trim()).collect();
+    lines.join("\n").trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::doc_comment::{clean_block, clean_docstring};
+
+    #[test]
+    fn should_clean_block_comment() {
+        assert_eq!("Order\nof a cart", clean_block("/**\n * Order\n * of a cart\n */"));
+        assert_eq!("Order", clean_block("/** Order */"));
+    }
+
+    #[test]
+    fn should_clean_docstring() {
+        assert_eq!("Order of a cart.", clean_docstring("\"\"\"Order of a cart.\"\"\""));
+        assert_eq!("Order\nof a cart", clean_docstring("'''\n    Order\n    of a cart\n    '''"));
+        assert_eq!("Order", clean_docstring("r\"Order\""));
+    }
+}
@@ -0,0 +1,64 @@
+/// The text of a source file for tree-sitter, which only reads UTF-8: a UTF-8 or UTF-16 BOM
+/// decides the encoding, files without one are UTF-8 or else latin-1. Binary content, like a
+/// NUL byte outside of UTF-16, can't be decoded.
+pub fn decode_source(bytes: &[u8]) -> Result<String, String> {
+    if let Some(rest) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+        return String::from_utf8(rest.to_vec()).map_err(|err| format!("invalid UTF-8: {}", err));
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+        return decode_utf16(rest, u16::from_le_bytes);
+    }
+    if let Some(rest) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+        return decode_utf16(rest, u16::from_be_bytes);
+    }
+
+    if bytes.contains(&0) {
+        return Err("binary content, not a text file".to_string());
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => Ok(text.to_string()),
+        // every byte is a latin-1 char, and latin-1 is the first block of unicode
+        Err(_) => Ok(bytes.iter().map(|byte| *byte as char).collect())
+    }
+}
+
+fn decode_utf16(bytes: &[u8], to_unit: fn([u8; 2]) -> u16) -> Result<String, String> {
+    if bytes.len() % 2 != 0 {
+        return Err("invalid UTF-16: odd number of bytes".to_string());
+    }
+
+    let units: Vec<u16> = bytes.chunks(2).map(|pair| to_unit([pair[0], pair[1]])).collect();
+    String::from_utf16(&units).map_err(|_| "invalid UTF-16: unpaired surrogate".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::support::encoding::decode_source;
+
+    #[test]
+    fn should_decode_by_bom() {
+        assert_eq!("class A {}", decode_source(b"\xEF\xBB\xBFclass A {}").unwrap());
+
+        let mut utf16 = vec![0xFF, 0xFE];
+        "class Café {}".encode_utf16().for_each(|unit| utf16.extend_from_slice(&unit.to_le_bytes()));
+        assert_eq!("class Café {}", decode_source(&utf16).unwrap());
+
+        let mut utf16 = vec![0xFE, 0xFF];
+        "class B {}".encode_utf16().for_each(|unit| utf16.extend_from_slice(&unit.to_be_bytes()));
+        assert_eq!("class B {}", decode_source(&utf16).unwrap());
+    }
+
+    #[test]
+    fn should_fall_back_to_latin1() {
+        assert_eq!("// café", decode_source(b"// caf\xE9").unwrap());
+        assert_eq!("// café", decode_source("// café".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn should_reject_undecodable_content() {
+        assert!(decode_source(b"\x7FELF\x02\x01\x00\x00").is_err());
+        assert!(decode_source(&[0xFF, 0xFE, 0x41]).is_err());
+        assert!(decode_source(&[0xFF, 0xFE, 0x00, 0xD8]).is_err());
+    }
+}
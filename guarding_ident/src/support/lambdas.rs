@@ -0,0 +1,124 @@
+use tree_sitter::Node;
+
+use guarding_core::domain::code_lambda::CodeLambda;
+use guarding_core::domain::Location;
+
+/// lambdas and closures over all the supported grammars
+const LAMBDA_KINDS: &[&str] = &[
+    "lambda_expression", "lambda_literal", "anonymous_function", "anonymous_method_expression",
+    "arrow_function", "function", "closure_expression", "func_literal", "lambda",
+];
+
+/// anonymous classes, Java ones are an `object_creation_expression` with a body
+const ANONYMOUS_CLASS_KINDS: &[&str] = &["object_literal", "class", "anonymous_object_creation_expression"];
+
+const TYPE_KINDS: &[&str] = &["type_identifier", "identifier", "simple_identifier", "user_type"];
+
+/// every lambda and anonymous class below `node`, nested ones included; `node` itself doesn't count
+pub fn lambdas_of(node: Node, code: &str) -> Vec<CodeLambda> {
+    let mut lambdas = vec![];
+    let mut cursor = node.walk();
+    let mut nodes: Vec<Node> = node.children(&mut cursor).collect();
+    nodes.reverse();
+
+    while let Some(current) = nodes.pop() {
+        if let Some(lambda) = lambda_at(current, code) {
+            lambdas.push(lambda);
+        }
+
+        let mut cursor = current.walk();
+        let children: Vec<Node> = current.children(&mut cursor).collect();
+        nodes.extend(children.into_iter().rev());
+    }
+
+    lambdas
+}
+
+fn lambda_at(node: Node, code: &str) -> Option<CodeLambda> {
+    if !node.is_named() {
+        return None;
+    }
+
+    let mut lambda = CodeLambda::default();
+    if LAMBDA_KINDS.contains(&node.kind()) {
+        lambda.kind = "lambda".to_string();
+    } else if is_anonymous_class(node) {
+        lambda.kind = "anonymous_class".to_string();
+        if let Some(typ) = node.child_by_field_name("type") {
+            lambda.name = code[typ.byte_range()].to_string();
+        }
+    } else {
+        return None;
+    }
+
+    lambda.references = references_of(node, code);
+    lambda.set_start(node.start_position().row, node.start_position().column);
+    lambda.set_end(node.end_position().row, node.end_position().column);
+
+    Some(lambda)
+}
+
+fn is_anonymous_class(node: Node) -> bool {
+    if ANONYMOUS_CLASS_KINDS.contains(&node.kind()) {
+        return true;
+    }
+
+    let mut cursor = node.walk();
+    let has_body = node.children(&mut cursor).any(|child| child.kind() == "class_body");
+    node.kind() == "object_creation_expression" && has_body
+}
+
+/// names looking like types, `Order` and `OrderRepository` but not `order`, each once
+fn references_of(node: Node, code: &str) -> Vec<String> {
+    let mut references: Vec<String> = vec![];
+    let mut nodes = vec![node];
+    while let Some(current) = nodes.pop() {
+        if TYPE_KINDS.contains(&current.kind()) {
+            let text = &code[current.byte_range()];
+            let is_type = text.chars().next().map(|c| c.is_uppercase()).unwrap_or(false);
+            if is_type && !references.iter().any(|reference| reference == text) {
+                references.push(text.to_string());
+            }
+            continue;
+        }
+
+        let mut cursor = current.walk();
+        let children: Vec<Node> = current.children(&mut cursor).collect();
+        nodes.extend(children.into_iter().rev());
+    }
+
+    references
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Parser;
+
+    use crate::support::lambdas::lambdas_of;
+
+    #[test]
+    fn should_find_java_lambda_and_anonymous_class() {
+        let source = "class A {
+    void run() {
+        orders.forEach(order -> Audit.log(order));
+        executor.submit(new Runnable() {
+            public void run() { Metrics.count(); }
+        });
+    }
+}";
+        let mut parser = Parser::new();
+        parser.set_language(tree_sitter_java::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let class_body = tree.root_node().child(0).unwrap().child_by_field_name("body").unwrap();
+        let method = class_body.named_child(0).unwrap();
+
+        let lambdas = lambdas_of(method, source);
+
+        assert_eq!(2, lambdas.len());
+        assert_eq!("lambda", lambdas[0].kind);
+        assert_eq!(vec!["Audit"], lambdas[0].references);
+        assert_eq!("anonymous_class", lambdas[1].kind);
+        assert_eq!("Runnable", lambdas[1].name);
+        assert!(lambdas[1].references.contains(&"Metrics".to_string()));
+    }
+}
@@ -0,0 +1,95 @@
+use std::collections::BTreeSet;
+
+use tree_sitter::Node;
+
+/// node kinds adding a path through a function, over all the supported grammars
+const BRANCH_KINDS: &[&str] = &[
+    "if_statement", "if_expression", "if_let_expression", "elif_clause", "guard_statement",
+    "for_statement", "for_in_statement", "enhanced_for_statement", "for_expression", "foreach_statement",
+    "while_statement", "while_expression", "while_let_expression", "loop_expression",
+    "do_statement", "do_while_statement", "repeat_while_statement",
+    "case_statement", "switch_case", "switch_label", "switch_section", "switch_entry", "switch_block_statement_group",
+    "expression_case", "type_case", "communication_case", "match_arm", "when_entry",
+    "catch_clause", "catch_block", "except_clause",
+    "conditional_expression", "ternary_expression", "conditional_access_expression",
+];
+
+/// short-circuit operators, some grammars only name the operator token
+const OPERATOR_KINDS: &[&str] = &["&&", "||", "??", "and", "or"];
+
+/// McCabe's number: one plus every branch below `node`
+pub fn cyclomatic_complexity(node: Node) -> usize {
+    1 + branch_count(node)
+}
+
+pub fn branch_count(node: Node) -> usize {
+    let mut count = 0;
+    let mut nodes = vec![node];
+    while let Some(current) = nodes.pop() {
+        let kind = current.kind();
+        if (current.is_named() && BRANCH_KINDS.contains(&kind)) || (!current.is_named() && OPERATOR_KINDS.contains(&kind)) {
+            count += 1;
+        }
+
+        let mut cursor = current.walk();
+        nodes.extend(current.children(&mut cursor));
+    }
+
+    count
+}
+
+/// the rows of `node` holding a token which isn't a comment
+pub fn code_rows(node: Node, rows: &mut BTreeSet<usize>) {
+    let mut nodes = vec![node];
+    while let Some(current) = nodes.pop() {
+        if current.kind().contains("comment") {
+            continue;
+        }
+
+        if current.child_count() == 0 {
+            rows.extend(current.start_position().row..=current.end_position().row);
+            continue;
+        }
+
+        let mut cursor = current.walk();
+        nodes.extend(current.children(&mut cursor));
+    }
+}
+
+/// lines of `node` with code on them, blank and comment-only lines not counted
+pub fn lines_of_code(node: Node) -> usize {
+    let mut rows = BTreeSet::new();
+    code_rows(node, &mut rows);
+
+    rows.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use tree_sitter::Parser;
+
+    use crate::support::metrics::{cyclomatic_complexity, lines_of_code};
+
+    #[test]
+    fn should_measure_java_method() {
+        let source = "class A {
+    void run(int a) {
+        // not counted
+
+        if (a > 1 && a < 10) {
+            for (int i = 0; i < a; i++) {}
+        } else {
+            a = a > 0 ? 1 : 0;
+        }
+    }
+}";
+        let mut parser = Parser::new();
+        parser.set_language(tree_sitter_java::language()).unwrap();
+        let tree = parser.parse(source, None).unwrap();
+        let class_body = tree.root_node().child(0).unwrap().child_by_field_name("body").unwrap();
+        let method = class_body.named_child(0).unwrap();
+
+        assert_eq!(5, cyclomatic_complexity(method));
+        assert_eq!(7, lines_of_code(method));
+    }
+}
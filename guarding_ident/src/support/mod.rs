@@ -0,0 +1,7 @@
+pub mod ts_config;
+pub mod queries;
+pub mod doc_comment;
+pub mod metrics;
+pub mod lambdas;
+pub mod build_metadata;
+pub mod encoding;
@@ -0,0 +1,89 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+use tracing::warn;
+use tree_sitter::{Language, Query};
+
+use crate::error::IdentError;
+
+/// env var for a directory of `<language>.scm` files replacing the built-in queries
+pub const QUERY_DIR_ENV: &'static str = "GUARDING_QUERY_DIR";
+
+static QUERY_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+
+/// use `<dir>/<language>.scm`, like `java.scm`, instead of the built-in query when it exists,
+/// takes precedence over `GUARDING_QUERY_DIR`
+pub fn set_query_dir(dir: Option<PathBuf>) {
+    if let Ok(mut query_dir) = QUERY_DIR.write() {
+        *query_dir = dir;
+    }
+}
+
+pub fn query_dir() -> Option<PathBuf> {
+    let configured = QUERY_DIR.read().ok().and_then(|dir| dir.clone());
+    configured.or(env::var_os(QUERY_DIR_ENV).map(PathBuf::from))
+}
+
+/// the overridden query source for `language`, if any
+pub fn query_override(dir: &Path, language: &str) -> Option<String> {
+    fs::read_to_string(dir.join(format!("{}.scm", language))).ok()
+}
+
+/// compiles the overridden query of `name`, falls back to the built-in one when there is no
+/// override or it doesn't compile
+pub fn load_query(language: Language, name: &str, builtin: &str) -> Result<Query, IdentError> {
+    load_query_from(query_dir().as_deref(), language, name, builtin)
+}
+
+pub fn load_query_from(dir: Option<&Path>, language: Language, name: &str, builtin: &str) -> Result<Query, IdentError> {
+    if let Some(source) = dir.and_then(|dir| query_override(dir, name)) {
+        match Query::new(language, &source) {
+            Ok(query) => return Ok(query),
+            Err(e) => warn!("query {}.scm compilation failed, use the built-in one: {:?}", name, e),
+        }
+    }
+
+    Query::new(language, builtin).map_err(|e| IdentError::Query(format!("{}.scm: {:?}", name, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::path::PathBuf;
+
+    use crate::support::queries::load_query_from;
+
+    const BUILTIN: &'static str = "(class_declaration name: (identifier) @class-name)";
+
+    fn query_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join("guarding").join(name);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn should_use_builtin_query_without_override() {
+        let query = load_query_from(None, tree_sitter_java::language(), "java", BUILTIN).unwrap();
+        assert_eq!(vec!["class-name"], query.capture_names());
+    }
+
+    #[test]
+    fn should_override_query_from_dir() {
+        let dir = query_dir("query_override");
+        fs::write(dir.join("java.scm"), "(import_declaration (scoped_identifier) @import-name)").unwrap();
+
+        let query = load_query_from(Some(&dir), tree_sitter_java::language(), "java", BUILTIN).unwrap();
+        assert_eq!(vec!["import-name"], query.capture_names());
+    }
+
+    #[test]
+    fn should_fallback_when_override_is_broken() {
+        let dir = query_dir("query_broken");
+        fs::write(dir.join("java.scm"), "(no_such_node) @oops").unwrap();
+
+        let query = load_query_from(Some(&dir), tree_sitter_java::language(), "java", BUILTIN).unwrap();
+        assert_eq!(vec!["class-name"], query.capture_names());
+    }
+}
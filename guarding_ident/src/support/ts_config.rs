@@ -0,0 +1,294 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+const EXTENSIONS: [&'static str; 5] = ["ts", "tsx", "d.ts", "js", "jsx"];
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RawConfig {
+    extends: Option<String>,
+    compiler_options: Option<RawCompilerOptions>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+struct RawCompilerOptions {
+    base_url: Option<String>,
+    paths: Option<HashMap<String, Vec<String>>>,
+}
+
+/// module resolution part of `tsconfig.json`: `baseUrl` and `paths` aliases
+#[derive(Debug, Clone, Default)]
+pub struct TsConfig {
+    pub base_url: Option<PathBuf>,
+    /// alias pattern to targets, longest prefix first like tsc does
+    pub paths: Vec<(String, Vec<String>)>,
+    /// `paths` targets are relative to baseUrl, or to the config file without it
+    pub paths_base: PathBuf,
+}
+
+impl TsConfig {
+    /// find `tsconfig.json` in `dir` or the nearest parent
+    pub fn discover(dir: &Path) -> Option<TsConfig> {
+        let dir = fs::canonicalize(dir).ok()?;
+        let config_path = dir.ancestors()
+            .map(|parent| parent.join("tsconfig.json"))
+            .find(|path| path.is_file())?;
+
+        TsConfig::from_file(&config_path)
+    }
+
+    pub fn from_file(path: &Path) -> Option<TsConfig> {
+        TsConfig::load(path, 0)
+    }
+
+    fn load(path: &Path, depth: usize) -> Option<TsConfig> {
+        let content = fs::read_to_string(path).ok()?;
+        let raw: RawConfig = serde_json::from_str(&strip_json_comments(&content)).ok()?;
+        let config_dir = path.parent().unwrap_or(Path::new(".")).to_path_buf();
+
+        let mut config = TsConfig {
+            base_url: None,
+            paths: vec![],
+            paths_base: config_dir.clone(),
+        };
+
+        if let Some(parent) = &raw.extends {
+            // presets like `@tsconfig/node14` live in node_modules, only relative files are followed
+            if parent.starts_with('.') && depth < 8 {
+                let parent = if parent.ends_with(".json") { parent.to_string() } else { format!("{}.json", parent) };
+                if let Some(parent_config) = TsConfig::load(&config_dir.join(parent), depth + 1) {
+                    config = parent_config;
+                }
+            }
+        }
+
+        if let Some(options) = raw.compiler_options {
+            if let Some(base_url) = options.base_url {
+                let base_url = config_dir.join(base_url);
+                config.paths_base = base_url.clone();
+                config.base_url = Some(base_url);
+            }
+
+            if let Some(paths) = options.paths {
+                let mut paths: Vec<(String, Vec<String>)> = paths.into_iter().collect();
+                paths.sort_by(|a, b| {
+                    prefix_len(&b.0).cmp(&prefix_len(&a.0)).then(a.0.cmp(&b.0))
+                });
+
+                config.paths = paths;
+                config.paths_base = config.base_url.clone().unwrap_or(config_dir.clone());
+            }
+        }
+
+        Some(config)
+    }
+
+    /// resolve an import specifier to the source file it points to, `None` for packages
+    pub fn resolve(&self, import: &str, importer: &Path) -> Option<PathBuf> {
+        if import == "." || import == ".." || import.starts_with("./") || import.starts_with("../") {
+            let dir = importer.parent()?;
+            return probe(&dir.join(import));
+        }
+
+        for (pattern, targets) in &self.paths {
+            if let Some(matched) = match_pattern(pattern, import) {
+                for target in targets {
+                    let candidate = self.paths_base.join(target.replacen('*', matched, 1));
+                    if let Some(found) = probe(&candidate) {
+                        return Some(found);
+                    }
+                }
+            }
+        }
+
+        self.base_url.as_ref().and_then(|base_url| probe(&base_url.join(import)))
+    }
+}
+
+fn prefix_len(pattern: &str) -> usize {
+    pattern.find('*').unwrap_or(pattern.len())
+}
+
+fn match_pattern<'a>(pattern: &str, import: &'a str) -> Option<&'a str> {
+    match pattern.find('*') {
+        Some(index) => {
+            let prefix = &pattern[..index];
+            let suffix = &pattern[index + 1..];
+            if import.len() >= prefix.len() + suffix.len()
+                && import.starts_with(prefix)
+                && import.ends_with(suffix) {
+                Some(&import[prefix.len()..import.len() - suffix.len()])
+            } else {
+                None
+            }
+        }
+        None => {
+            if pattern == import { Some("") } else { None }
+        }
+    }
+}
+
+fn probe(candidate: &Path) -> Option<PathBuf> {
+    if candidate.is_file() {
+        return fs::canonicalize(candidate).ok();
+    }
+
+    let text = candidate.to_string_lossy();
+    for ext in EXTENSIONS.iter() {
+        let with_ext = PathBuf::from(format!("{}.{}", text, ext));
+        if with_ext.is_file() {
+            return fs::canonicalize(with_ext).ok();
+        }
+    }
+
+    // ESM style `import './foo.js'` points to `foo.ts`
+    if candidate.extension().map(|ext| ext == "js").unwrap_or(false) {
+        for ext in ["ts", "tsx"].iter() {
+            let with_ext = candidate.with_extension(ext);
+            if with_ext.is_file() {
+                return fs::canonicalize(with_ext).ok();
+            }
+        }
+    }
+
+    for ext in EXTENSIONS.iter() {
+        let index = candidate.join(format!("index.{}", ext));
+        if index.is_file() {
+            return fs::canonicalize(index).ok();
+        }
+    }
+
+    None
+}
+
+/// tsconfig is JSONC, it allows comments and trailing commas
+pub fn strip_json_comments(content: &str) -> String {
+    let chars: Vec<char> = content.chars().collect();
+    let mut without_comments = String::with_capacity(content.len());
+    let mut in_string = false;
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index];
+        if in_string {
+            without_comments.push(c);
+            if c == '\\' && index + 1 < chars.len() {
+                without_comments.push(chars[index + 1]);
+                index += 1;
+            } else if c == '"' {
+                in_string = false;
+            }
+            index += 1;
+            continue;
+        }
+
+        match c {
+            '"' => {
+                in_string = true;
+                without_comments.push(c);
+                index += 1;
+            }
+            '/' if chars.get(index + 1) == Some(&'/') => {
+                while index < chars.len() && chars[index] != '\n' {
+                    index += 1;
+                }
+            }
+            '/' if chars.get(index + 1) == Some(&'*') => {
+                index += 2;
+                while index + 1 < chars.len() && !(chars[index] == '*' && chars[index + 1] == '/') {
+                    index += 1;
+                }
+                index += 2;
+            }
+            _ => {
+                without_comments.push(c);
+                index += 1;
+            }
+        }
+    }
+
+    let chars: Vec<char> = without_comments.chars().collect();
+    let mut output = String::with_capacity(without_comments.len());
+    let mut in_string = false;
+    for (index, c) in chars.iter().enumerate() {
+        if in_string {
+            if *c == '"' && chars[index - 1] != '\\' {
+                in_string = false;
+            }
+        } else if *c == '"' {
+            in_string = true;
+        } else if *c == ',' {
+            let next = chars[index + 1..].iter().find(|next| !next.is_whitespace());
+            if next == Some(&'}') || next == Some(&']') {
+                continue;
+            }
+        }
+
+        output.push(*c);
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::support::ts_config::{match_pattern, strip_json_comments, TsConfig};
+
+    fn fixture_dir() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .parent().unwrap()
+            .join("_fixtures")
+            .join("ts")
+    }
+
+    #[test]
+    fn should_strip_comments_and_trailing_commas() {
+        let content = "{
+  // line comment
+  \"a\": \"http://b\", /* block */
+  \"c\": [1, 2,],
+}";
+        let value: serde_json::Value = serde_json::from_str(&strip_json_comments(content)).unwrap();
+        assert_eq!("http://b", value["a"]);
+        assert_eq!(2, value["c"].as_array().unwrap().len());
+    }
+
+    #[test]
+    fn should_match_alias_pattern() {
+        assert_eq!(Some("logger"), match_pattern("@shared/*", "@shared/logger"));
+        assert_eq!(Some(""), match_pattern("config", "config"));
+        assert_eq!(None, match_pattern("@shared/*", "@app/main"));
+    }
+
+    #[test]
+    fn should_resolve_path_alias() {
+        let config = TsConfig::discover(&fixture_dir().join("src")).unwrap();
+        let importer = fixture_dir().join("src").join("app").join("main.ts");
+
+        let resolved = config.resolve("@shared/logger", &importer).unwrap();
+        assert!(resolved.ends_with("shared/logger.ts"));
+    }
+
+    #[test]
+    fn should_resolve_relative_index() {
+        let config = TsConfig::default();
+        let importer = fixture_dir().join("src").join("app").join("main.ts");
+
+        let resolved = config.resolve("../features/auth", &importer).unwrap();
+        assert!(resolved.ends_with("features/auth/index.ts"));
+    }
+
+    #[test]
+    fn should_keep_packages_unresolved() {
+        let config = TsConfig::discover(&fixture_dir()).unwrap();
+        let importer = fixture_dir().join("src").join("app").join("main.ts");
+
+        assert_eq!(None, config.resolve("react", &importer));
+    }
+}
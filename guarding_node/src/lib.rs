@@ -0,0 +1,123 @@
+use napi::{Env, Error, Result, Task};
+use napi::bindgen_prelude::AsyncTask;
+use napi_derive::napi;
+
+use guarding::{CheckReport, GuardRule, Guarding, RuleSummary};
+
+/// a parsed rule, like `guarding rules list` shows it
+#[napi(object)]
+pub struct Rule {
+    pub name: String,
+    pub severity: String,
+    pub level: String,
+    pub tags: Vec<String>,
+    /// the rule as written
+    pub origin: String,
+}
+
+/// one violated item of a rule, `path` and `line` when it's known where it is
+#[napi(object)]
+pub struct Violation {
+    pub rule: String,
+    pub severity: String,
+    pub message: String,
+    pub item: Option<String>,
+    pub path: Option<String>,
+    /// 1-based
+    pub line: Option<u32>,
+    pub suggestion: Option<String>,
+    pub fingerprint: String,
+}
+
+#[napi(object)]
+pub struct Report {
+    pub rules: Vec<Rule>,
+    pub violations: Vec<Violation>,
+    pub summary: String,
+    /// like `guarding check`, only error violations fail
+    pub failed: bool,
+}
+
+/// `rules` or `rulesFile` replace the rule files of the project
+#[napi(object)]
+pub struct CheckOptions {
+    pub rules: Option<String>,
+    pub rules_file: Option<String>,
+}
+
+/// the rules of a guarding file's content, throws on a syntax error
+#[napi]
+pub fn parse_rules(rules: String) -> Result<Vec<Rule>> {
+    let rules = guarding::parse_rules(&rules).map_err(Error::from_reason)?;
+    Ok(rules.iter().map(to_rule).collect())
+}
+
+/// checks the project at `path` like `guarding check` off the main thread, with its `guarding.toml`
+#[napi(ts_return_type = "Promise<Report>")]
+pub fn check(path: String, options: Option<CheckOptions>) -> Result<AsyncTask<CheckTask>> {
+    Ok(AsyncTask::new(CheckTask { guarding: guarding_of(path, options)? }))
+}
+
+/// `check` blocking until the report is done
+#[napi]
+pub fn check_sync(path: String, options: Option<CheckOptions>) -> Result<Report> {
+    let report = guarding_of(path, options)?.check().map_err(Error::from_reason)?;
+    Ok(to_report(&report))
+}
+
+pub struct CheckTask {
+    guarding: Guarding,
+}
+
+impl Task for CheckTask {
+    type Output = CheckReport;
+    type JsValue = Report;
+
+    fn compute(&mut self) -> Result<CheckReport> {
+        self.guarding.check().map_err(Error::from_reason)
+    }
+
+    fn resolve(&mut self, _env: Env, report: CheckReport) -> Result<Report> {
+        Ok(to_report(&report))
+    }
+}
+
+fn guarding_of(path: String, options: Option<CheckOptions>) -> Result<Guarding> {
+    let mut guarding = Guarding::new(path);
+    if let Some(options) = options {
+        if let Some(rules) = options.rules {
+            guarding = guarding.with_rules(&rules).map_err(Error::from_reason)?;
+        }
+        if let Some(rules_file) = options.rules_file {
+            guarding = guarding.with_rules_file(rules_file);
+        }
+    }
+    Ok(guarding)
+}
+
+fn to_report(report: &CheckReport) -> Report {
+    let violations = report.violations().into_iter()
+        .map(|violation| Violation {
+            rule: violation.rule,
+            severity: violation.severity.name().to_string(),
+            message: violation.message,
+            item: violation.item,
+            path: violation.location.as_ref().map(|location| location.path.clone()),
+            line: violation.location.as_ref().map(|location| location.start.row as u32 + 1),
+            suggestion: violation.suggestion,
+            fingerprint: violation.fingerprint,
+        })
+        .collect();
+
+    Report {
+        rules: report.rules.iter().map(to_rule).collect(),
+        violations,
+        summary: report.summary(),
+        failed: report.has_failures(),
+    }
+}
+
+fn to_rule(rule: &GuardRule) -> Rule {
+    let RuleSummary { name, severity, level, tags, origin } = RuleSummary::of(rule);
+    Rule { name, severity, level, tags, origin }
+}
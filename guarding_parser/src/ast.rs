@@ -1,8 +1,18 @@
 use std::collections::HashMap;
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+use serde::{Deserialize, Serialize};
+
+/// also what a YAML or TOML rule file is read into, fields left out get their default
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct GuardRule {
     pub origin: String,
+    /// from `@name("...")`, empty for unnamed rules
+    pub name: String,
+    pub severity: Severity,
+    pub tags: Vec<String>,
+    /// from `@because("...")`, why the rule exists
+    pub because: String,
     pub ty: RuleType,
     pub level: RuleLevel,
     pub scope: RuleScope,
@@ -32,6 +42,10 @@ impl Default for GuardRule {
     fn default() -> Self {
         GuardRule {
             origin: "".to_string(),
+            name: "".to_string(),
+            severity: Severity::Error,
+            tags: vec![],
+            because: "".to_string(),
             ty: RuleType::Normal,
             level: RuleLevel::Class,
             scope: RuleScope::All,
@@ -82,13 +96,43 @@ impl GuardRule {
     }
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+/// by default only violations of `Error` rules fail a check, ordered from the least severe
+#[derive(Clone, Copy, Debug, Eq, PartialEq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn from_name(name: &str) -> Option<Severity> {
+        match name.to_lowercase().as_str() {
+            "info" => Some(Severity::Info),
+            "warning" | "warn" => Some(Severity::Warning),
+            "error" => Some(Severity::Error),
+            _ => None
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RuleType {
     Normal,
     Layer,
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum RuleLevel {
     Package,
     Function,
@@ -96,18 +140,39 @@ pub enum RuleLevel {
     Struct,
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+impl RuleLevel {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RuleLevel::Package => "package",
+            RuleLevel::Function => "function",
+            RuleLevel::Class => "class",
+            RuleLevel::Struct => "struct",
+        }
+    }
+}
+
+/// named like in the DSL in YAML and TOML rules, `{ resideIn = "..service.." }`
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum RuleScope {
     All,
+    #[serde(rename = "resideIn")]
     PathDefine(String),
+    #[serde(rename = "extends")]
     Extend(String),
     Assignable(String),
     Implementation(String),
+    /// `interface`, `enum`..., a leading `!` excludes the kind
+    Kind(String),
+    #[serde(rename = "match")]
     MatchRegex(String),
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// `{ props = ["name"] }` in YAML and TOML rules
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum Expr {
+    #[serde(rename = "props")]
     PropsCall(Vec<String>),
     Identifier(String)
 }
@@ -139,7 +204,8 @@ impl Default for FunctionCall {
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum Operator {
     /// >
     Gt,
@@ -163,6 +229,7 @@ pub enum Operator {
 
     // string assert operator
     StartsWith,
+    #[serde(rename = "endsWith")]
     Endswith,
     Contains,
 
@@ -170,14 +237,23 @@ pub enum Operator {
     Inside,
     ResideIn,
     Accessed,
-    DependBy
+    DependBy,
+
+    // property has no value, like a class without doc
+    Empty
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+/// `{ string = "Service" }`, `{ leveled = ["package", "..domain.."] }`, `{ strings = [...] }` or
+/// `{ size = 20 }` in YAML and TOML rules
+#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
 pub enum RuleAssert {
     Empty,
+    #[serde(rename = "string")]
     Stringed(String),
     Leveled(RuleLevel, String),
+    #[serde(rename = "strings")]
     ArrayStringed(Vec<String>),
+    #[serde(rename = "size")]
     Sized(usize),
 }
\ No newline at end of file
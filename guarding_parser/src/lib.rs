@@ -10,3 +10,4 @@ pub mod validator;
 pub mod parser;
 pub mod errors;
 pub mod support;
+pub mod syntax;
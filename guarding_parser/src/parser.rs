@@ -1,13 +1,14 @@
 use pest::iterators::{Pair, Pairs};
 use pest::Parser;
+use tracing::{debug, warn};
 
 use crate::errors::{Error, Result as GuardingResult};
-use crate::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope};
+use crate::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope, RuleType, Severity};
 use crate::support::str_support;
 
 #[derive(Parser)]
 #[grammar = "guarding.pest"]
-struct IdentParser;
+pub(crate) struct IdentParser;
 
 pub fn parse(code: &str) -> GuardingResult<Vec<GuardRule>> {
     match IdentParser::parse(Rule::start, code) {
@@ -30,13 +31,28 @@ pub fn parse(code: &str) -> GuardingResult<Vec<GuardRule>> {
     }
 }
 
+/// the level, scope and property of a query, the other parts of the rule are empty
+pub fn parse_query(code: &str) -> GuardingResult<GuardRule> {
+    match IdentParser::parse(Rule::query, code.trim()) {
+        Err(e) => Err(Error::msg(e)),
+        Ok(mut pairs) => match pairs.next() {
+            Some(pair) => Ok(parse_normal_rule(pair)),
+            None => Err(Error::msg("empty query"))
+        }
+    }
+}
+
 fn consume_rules_with_spans(pairs: Pairs<Rule>) -> Vec<GuardRule> {
     pairs.filter(|pair| {
         return pair.as_rule() == Rule::declaration;
     }).map(|pair| {
         let mut rule: GuardRule = Default::default();
+        let mut metas = vec![];
         for p in pair.into_inner() {
             match p.as_rule() {
+                Rule::rule_meta => {
+                    metas.push(parse_rule_meta(p));
+                }
                 Rule::normal_rule => {
                     rule = parse_normal_rule(p);
                 }
@@ -47,18 +63,68 @@ fn consume_rules_with_spans(pairs: Pairs<Rule>) -> Vec<GuardRule> {
             };
         }
 
+        for (key, value) in metas {
+            apply_rule_meta(&mut rule, &key, value);
+        }
+
         return rule;
     })
         .collect::<Vec<GuardRule>>()
 }
 
-fn parse_layer_rule(_pair: Pair<Rule>) -> GuardRule {
-    println!("todo: in processed");
-    GuardRule::default()
+fn parse_rule_meta(pair: Pair<Rule>) -> (String, String) {
+    let mut key = "".to_string();
+    let mut value = "".to_string();
+    for p in pair.into_inner() {
+        match p.as_rule() {
+            Rule::identifier => {
+                key = p.as_str().to_string();
+            }
+            Rule::string => {
+                value = str_support::replace_string_markers(p.as_str());
+            }
+            _ => {}
+        }
+    }
+
+    (key, value)
+}
+
+fn apply_rule_meta(rule: &mut GuardRule, key: &str, value: String) {
+    match key {
+        "name" => {
+            rule.name = value;
+        }
+        "severity" => {
+            match Severity::from_name(&value) {
+                Some(severity) => rule.severity = severity,
+                None => warn!("unknown severity: {:?}, use error", value)
+            }
+        }
+        "tag" => {
+            rule.tags.push(value);
+        }
+        "because" => {
+            rule.because = value;
+        }
+        _ => {
+            debug!("unknown rule meta: {:?}", key);
+        }
+    }
+}
+
+fn parse_layer_rule(pair: Pair<Rule>) -> GuardRule {
+    debug!("layer rules are not run yet");
+    GuardRule {
+        origin: pair.as_str().trim().to_string(),
+        ty: RuleType::Layer,
+        ..Default::default()
+    }
 }
 
 fn parse_normal_rule(pair: Pair<Rule>) -> GuardRule {
     let mut guard_rule = GuardRule::default();
+    guard_rule.origin = pair.as_str().trim().to_string();
 
     for p in pair.into_inner() {
         match p.as_rule() {
@@ -71,7 +137,7 @@ fn parse_normal_rule(pair: Pair<Rule>) -> GuardRule {
             Rule::expression => {
                 guard_rule.expr = parse_expr(p);
             }
-            Rule::operator => {
+            Rule::operator | Rule::empty_operator => {
                 guard_rule.ops = parse_operator(p);
             }
             Rule::assert => {
@@ -86,8 +152,9 @@ fn parse_normal_rule(pair: Pair<Rule>) -> GuardRule {
             Rule::only => {
                 // should do nothing
             }
+            Rule::EOI => {}
             _ => {
-                println!("implementing rule: {:?}, level: {:?}", p.as_rule(), p.as_span());
+                debug!("unhandled rule: {:?}, at: {:?}", p.as_rule(), p.as_span());
             }
         }
     }
@@ -135,6 +202,8 @@ fn parse_operator(parent: Pair<Rule>) -> Vec<Operator> {
         Rule::op_resideIn => { Operator::ResideIn }
         Rule::op_accessed => { Operator::Accessed }
         Rule::op_dependBy => { Operator::DependBy }
+
+        Rule::op_empty => { Operator::Empty }
         _ => {
             panic!("implementing ops: {:?}, text: {:?}", pair.as_rule(), pair.as_span())
         }
@@ -237,6 +306,10 @@ fn parse_scope(parent: Pair<Rule>) -> RuleScope {
             let string = str_support::unescape(without_markers.as_str()).expect("incorrect string literal");
             RuleScope::PathDefine(string)
         }
+        Rule::reside_scope => {
+            let string = string_from_pair(pair);
+            RuleScope::PathDefine(string)
+        }
         Rule::assignable_scope => {
             let string = string_from_pair(pair);
             RuleScope::Assignable(string)
@@ -253,8 +326,12 @@ fn parse_scope(parent: Pair<Rule>) -> RuleScope {
             let string = string_from_pair(pair);
             RuleScope::Implementation(string)
         }
+        Rule::kind_scope => {
+            let string = string_from_pair(pair);
+            RuleScope::Kind(string)
+        }
         _ => {
-            println!("implementing scope: {:?}, text: {:?}", pair.as_rule(), pair.as_span());
+            debug!("unhandled scope: {:?}, at: {:?}", pair.as_rule(), pair.as_span());
             RuleScope::All
         }
     }
@@ -276,8 +353,8 @@ fn string_from_pair(pair: Pair<Rule>) -> String {
 
 #[cfg(test)]
 mod tests {
-    use crate::ast::{Expr, Operator, RuleAssert, RuleLevel, RuleScope};
-    use crate::parser::parse;
+    use crate::ast::{Expr, Operator, RuleAssert, RuleLevel, RuleScope, Severity};
+    use crate::parser::{parse, parse_query};
 
     #[test]
     fn should_parse_string_assert() {
@@ -341,6 +418,44 @@ mod tests {
         assert_eq!(RuleAssert::Leveled(RuleLevel::Package, "..persistence.".to_string()), vec[0].assert);
     }
 
+    #[test]
+    fn should_parse_kind_scope() {
+        let code = "class(kind \"!enum\")::name should endsWith \"Service\";";
+        let vec = parse(code).unwrap();
+        assert_eq!(RuleScope::Kind("!enum".to_string()), vec[0].scope);
+    }
+
+    #[test]
+    fn should_parse_rule_meta() {
+        let code = "@name(\"service-naming\")
+@severity(\"warning\")
+@tag(\"naming\") @tag(\"service\")
+@because(\"services are found by name\")
+class(\"..service..\")::name should endsWith \"Service\";
+class::len should < 20;";
+        let vec = parse(code).unwrap();
+
+        assert_eq!("service-naming", vec[0].name);
+        assert_eq!(Severity::Warning, vec[0].severity);
+        assert_eq!(vec!["naming".to_string(), "service".to_string()], vec[0].tags);
+        assert_eq!("services are found by name", vec[0].because);
+        assert_eq!("class(\"..service..\")::name should endsWith \"Service\";", vec[0].origin);
+
+        assert_eq!("", vec[1].name);
+        assert_eq!(Severity::Error, vec[1].severity);
+    }
+
+    #[test]
+    fn should_parse_query() {
+        let query = parse_query("class(resideIn \"..service..\")::function").unwrap();
+        assert_eq!(RuleLevel::Class, query.level);
+        assert_eq!(RuleScope::PathDefine("..service..".to_string()), query.scope);
+        assert_eq!(Expr::PropsCall(vec!["function".to_string()]), query.expr);
+        assert!(query.ops.is_empty());
+
+        assert!(parse_query("class::name should endsWith \"Service\"").is_err());
+    }
+
     #[test]
     fn should_parse_package_regex() {
         let code = "package(match(\"^/app\")) endsWith \"Connection\";";
@@ -393,7 +508,10 @@ function -> name.len should < 30;
     ::adapter(\"com.phodal.com\", \"zero\");
 
 ";
-        parse(code).unwrap();
+        let rules = parse(code).unwrap();
+
+        assert_eq!(RuleType::Layer, rules[0].ty);
+        assert!(rules[0].origin.starts_with("layer(\"onion\")"));
     }
 
     #[test]
@@ -417,4 +535,23 @@ function -> name.len should < 30;
 
         parse(code).unwrap();
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn should_parse_doc_empty() {
+        let code = "class(resideIn \"..api..\")::doc should not empty;";
+        let vec = parse(code).unwrap();
+
+        assert_eq!(RuleScope::PathDefine("..api..".to_string()), vec[0].scope);
+        assert_eq!(Expr::PropsCall(vec!["doc".to_string()]), vec[0].expr);
+        assert_eq!(vec![Operator::Not, Operator::Empty], vec[0].ops);
+        assert_eq!(RuleAssert::Empty, vec[0].assert);
+    }
+
+    #[test]
+    fn should_reject_string_operator_without_argument() {
+        let code = "class(resideIn \"..api..\")::name should endsWith;";
+
+        assert!(parse(code).is_err());
+        assert!(parse("class(resideIn \"..api..\")::name should not contains;").is_err());
+    }
+}
@@ -0,0 +1,251 @@
+use pest::iterators::Pair;
+use pest::Parser;
+
+use crate::errors::{Error, Result as GuardingResult};
+use crate::parser::{IdentParser, Rule};
+
+const LEVELS: [&str; 6] = ["package", "class", "struct", "function", "file", "layer"];
+const KEYWORDS: [&str; 2] = ["should", "only"];
+const SCOPES: [&str; 6] = ["resideIn", "implementation", "extends", "assignable", "kind", "match"];
+const OPERATORS: [&str; 9] = ["not", "contains", "endsWith", "startsWith", "inside", "resideIn", "accessed", "dependBy", "empty"];
+const SYMBOLS: [&str; 9] = ["::", "->", "<=", ">=", "!=", "<", ">", "=", "!"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// a level like `class`, `should` or `only`
+    Keyword,
+    /// like `resideIn` in `class(resideIn "..service..")`
+    Scope,
+    /// like `endsWith`, `not` or `<=`
+    Operator,
+    String,
+    Number,
+    /// a property like `name` in `class::name`
+    Identifier,
+    /// like `@name`
+    Meta,
+    /// brackets, `,`, `;` and `.`
+    Punctuation,
+    Comment,
+    Whitespace,
+    /// what the DSL doesn't have, like `#`
+    Unknown,
+}
+
+/// A part of a rule file; the tokens of a file are all of its text, even when it doesn't parse.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    /// byte offsets
+    pub start: usize,
+    pub end: usize,
+    /// 0-based, the column in characters
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Token {
+    pub fn text<'a>(&self, code: &'a str) -> &'a str {
+        &code[self.start..self.end]
+    }
+}
+
+/// A rule of the grammar, like `normal_rule` or `scope`, with where it is; the children are in
+/// the order of the text, without whitespace and comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxNode {
+    pub kind: String,
+    pub start: usize,
+    pub end: usize,
+    pub children: Vec<SyntaxNode>,
+}
+
+impl SyntaxNode {
+    /// from the innermost node around `offset` to the file, for structural selection
+    pub fn path_to(&self, offset: usize) -> Vec<&SyntaxNode> {
+        let mut path = vec![];
+        let mut node = self;
+        if offset < node.start || offset > node.end {
+            return path;
+        }
+        loop {
+            path.push(node);
+            match node.children.iter().find(|child| child.start <= offset && offset <= child.end) {
+                Some(child) => node = child,
+                None => break
+            }
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+/// splits `code` into tokens without parsing it, for highlighting while typing
+pub fn tokenize(code: &str) -> Vec<Token> {
+    let mut tokens: Vec<Token> = vec![];
+    let (mut line, mut column) = (0, 0);
+    let mut offset = 0;
+
+    while offset < code.len() {
+        let rest = &code[offset..];
+        let (kind, length) = next_token(rest, previous_kind(code, &tokens));
+        tokens.push(Token { kind, start: offset, end: offset + length, line, column });
+
+        for c in rest[..length].chars() {
+            if c == '\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+        offset += length;
+    }
+
+    tokens
+}
+
+/// the tree of the rules of `code`, an `Err` with the syntax error like `parse`
+pub fn syntax_tree(code: &str) -> GuardingResult<SyntaxNode> {
+    let pairs = IdentParser::parse(Rule::start, code).map_err(Error::msg)?;
+    let children = pairs.filter(|pair| pair.as_rule() != Rule::EOI).map(to_node).collect();
+    Ok(SyntaxNode { kind: "file".to_string(), start: 0, end: code.len(), children })
+}
+
+fn to_node(pair: Pair<Rule>) -> SyntaxNode {
+    let span = pair.as_span();
+    SyntaxNode {
+        kind: format!("{:?}", pair.as_rule()),
+        start: span.start(),
+        end: span.end(),
+        children: pair.into_inner().map(to_node).collect(),
+    }
+}
+
+/// the kind and byte length of the token `rest` starts with
+fn next_token(rest: &str, previous: Option<(TokenKind, &str)>) -> (TokenKind, usize) {
+    let first = rest.chars().next().unwrap();
+
+    if first.is_whitespace() {
+        return (TokenKind::Whitespace, rest.find(|c: char| !c.is_whitespace()).unwrap_or(rest.len()));
+    }
+    if rest.starts_with("//") {
+        return (TokenKind::Comment, rest.find('\n').unwrap_or(rest.len()));
+    }
+    if rest.starts_with("/*") {
+        return (TokenKind::Comment, block_comment_len(rest));
+    }
+    if first == '"' || first == '\'' {
+        // an unclosed string ends with its line
+        let end = rest[1..].find(|c: char| c == first || c == '\n')
+            .map(|index| if rest[1 + index..].starts_with(first) { index + 2 } else { index + 1 })
+            .unwrap_or(rest.len());
+        return (TokenKind::String, end);
+    }
+    if first.is_ascii_digit() || (first == '-' && rest[1..].starts_with(|c: char| c.is_ascii_digit())) {
+        return (TokenKind::Number, 1 + rest[1..].find(|c: char| !c.is_ascii_digit()).unwrap_or(rest.len() - 1));
+    }
+
+    let is_word = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    if first == '@' {
+        return (TokenKind::Meta, 1 + rest[1..].find(|c: char| !is_word(c)).unwrap_or(rest.len() - 1));
+    }
+    if is_word(first) {
+        let length = rest.find(|c: char| !is_word(c)).unwrap_or(rest.len());
+        let word = &rest[..length];
+        let after_paren = previous == Some((TokenKind::Punctuation, "("));
+        let kind = if LEVELS.contains(&word) || KEYWORDS.contains(&word) {
+            TokenKind::Keyword
+        } else if SCOPES.contains(&word) && (after_paren || !OPERATORS.contains(&word)) {
+            TokenKind::Scope
+        } else if OPERATORS.contains(&word) {
+            TokenKind::Operator
+        } else {
+            TokenKind::Identifier
+        };
+        return (kind, length);
+    }
+
+    if let Some(symbol) = SYMBOLS.iter().find(|symbol| rest.starts_with(*symbol)) {
+        return (TokenKind::Operator, symbol.len());
+    }
+    if "()[],;.".contains(first) {
+        return (TokenKind::Punctuation, 1);
+    }
+    (TokenKind::Unknown, first.len_utf8())
+}
+
+/// nested like the grammar, an unclosed one goes to the end
+fn block_comment_len(rest: &str) -> usize {
+    let mut depth = 0;
+    let mut index = 0;
+    while index < rest.len() {
+        if rest[index..].starts_with("/*") {
+            depth += 1;
+            index += 2;
+        } else if rest[index..].starts_with("*/") {
+            depth -= 1;
+            index += 2;
+            if depth == 0 {
+                return index;
+            }
+        } else {
+            index += rest[index..].chars().next().unwrap().len_utf8();
+        }
+    }
+    rest.len()
+}
+
+/// the last token which isn't whitespace or a comment, with its text
+fn previous_kind<'a>(code: &'a str, tokens: &[Token]) -> Option<(TokenKind, &'a str)> {
+    tokens.iter().rev()
+        .find(|token| token.kind != TokenKind::Whitespace && token.kind != TokenKind::Comment)
+        .map(|token| (token.kind, token.text(code)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::syntax::{syntax_tree, tokenize, TokenKind};
+
+    #[test]
+    fn should_tokenize_every_part_of_a_rule() {
+        let code = "// naming\n@name(\"service\")\nclass(resideIn \"..service..\")::name should not endsWith \"Service\";";
+        let tokens = tokenize(code);
+        assert_eq!(code, tokens.iter().map(|token| token.text(code)).collect::<String>());
+
+        let kinds: Vec<(TokenKind, &str)> = tokens.iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text(code)))
+            .collect();
+        assert_eq!((TokenKind::Comment, "// naming"), kinds[0]);
+        assert_eq!((TokenKind::Meta, "@name"), kinds[1]);
+        assert_eq!((TokenKind::Keyword, "class"), kinds[5]);
+        assert_eq!((TokenKind::Scope, "resideIn"), kinds[7]);
+        assert_eq!((TokenKind::Operator, "::"), kinds[10]);
+        assert_eq!((TokenKind::Identifier, "name"), kinds[11]);
+        assert_eq!((TokenKind::Operator, "not"), kinds[13]);
+        assert_eq!((TokenKind::Operator, "endsWith"), kinds[14]);
+        assert_eq!(2, tokens.iter().find(|token| token.kind == TokenKind::Keyword).unwrap().line);
+    }
+
+    #[test]
+    fn should_tokenize_broken_rules() {
+        let code = "class::name should endsWith \"Serv";
+        let tokens = tokenize(code);
+        assert_eq!(TokenKind::String, tokens.last().unwrap().kind);
+        assert_eq!(code.len(), tokens.last().unwrap().end);
+    }
+
+    #[test]
+    fn should_select_the_nodes_around_an_offset() {
+        let code = "class(resideIn \"..service..\")::name should endsWith \"Service\";";
+        let tree = syntax_tree(code).unwrap();
+        let path: Vec<&str> = tree.path_to(18).iter().map(|node| node.kind.as_str()).collect();
+        assert_eq!("string", path[0]);
+        assert_eq!("file", *path.last().unwrap());
+        assert!(path.contains(&"scope"));
+
+        assert!(syntax_tree("class::name should").is_err());
+    }
+}
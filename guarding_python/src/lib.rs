@@ -0,0 +1,149 @@
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use guarding_rs::{CheckReport, GuardRule, Guarding, RuleSummary};
+
+/// A parsed rule, like `guarding rules list` shows it.
+#[pyclass(module = "guarding")]
+#[derive(Clone)]
+pub struct Rule {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub severity: String,
+    #[pyo3(get)]
+    pub level: String,
+    #[pyo3(get)]
+    pub tags: Vec<String>,
+    /// the rule as written
+    #[pyo3(get)]
+    pub origin: String,
+}
+
+#[pymethods]
+impl Rule {
+    fn __repr__(&self) -> String {
+        format!("Rule({:?})", self.origin)
+    }
+}
+
+/// One violated item of a rule, `path` and `line` when it's known where it is.
+#[pyclass(module = "guarding")]
+#[derive(Clone)]
+pub struct Violation {
+    #[pyo3(get)]
+    pub rule: String,
+    #[pyo3(get)]
+    pub severity: String,
+    #[pyo3(get)]
+    pub message: String,
+    #[pyo3(get)]
+    pub item: Option<String>,
+    #[pyo3(get)]
+    pub path: Option<String>,
+    /// 1-based
+    #[pyo3(get)]
+    pub line: Option<usize>,
+    #[pyo3(get)]
+    pub suggestion: Option<String>,
+    #[pyo3(get)]
+    pub fingerprint: String,
+}
+
+#[pymethods]
+impl Violation {
+    fn __repr__(&self) -> String {
+        match &self.item {
+            Some(item) => format!("Violation({}: {}, {})", self.rule, self.message, item),
+            None => format!("Violation({}: {})", self.rule, self.message),
+        }
+    }
+}
+
+/// What `check` found.
+#[pyclass(module = "guarding")]
+pub struct Report {
+    report: CheckReport,
+}
+
+#[pymethods]
+impl Report {
+    #[getter]
+    fn violations(&self) -> Vec<Violation> {
+        self.report.violations().into_iter()
+            .map(|violation| Violation {
+                rule: violation.rule,
+                severity: violation.severity.name().to_string(),
+                message: violation.message,
+                item: violation.item,
+                path: violation.location.as_ref().map(|location| location.path.clone()),
+                line: violation.location.as_ref().map(|location| location.start.row + 1),
+                suggestion: violation.suggestion,
+                fingerprint: violation.fingerprint,
+            })
+            .collect()
+    }
+
+    #[getter]
+    fn rules(&self) -> Vec<Rule> {
+        self.report.rules.iter().map(to_rule).collect()
+    }
+
+    /// like `guarding check`, only error violations fail
+    fn has_failures(&self) -> bool {
+        self.report.has_failures()
+    }
+
+    fn summary(&self) -> String {
+        self.report.summary()
+    }
+
+    fn __len__(&self) -> usize {
+        self.report.violations().len()
+    }
+
+    fn __str__(&self) -> String {
+        self.report.to_text()
+    }
+}
+
+/// the rules of a guarding file's content, `ValueError` on a syntax error
+#[pyfunction]
+#[pyo3(text_signature = "(rules)")]
+fn parse_rules(rules: &str) -> PyResult<Vec<Rule>> {
+    let rules = guarding_rs::parse_rules(rules).map_err(PyValueError::new_err)?;
+    Ok(rules.iter().map(to_rule).collect())
+}
+
+/// checks the project at `path` like `guarding check`, with its `guarding.toml`; `rules` or
+/// `rules_file` replace the rule files of the project
+#[pyfunction(rules = "None", rules_file = "None")]
+#[pyo3(text_signature = "(path, rules=None, rules_file=None)")]
+fn check(py: Python, path: &str, rules: Option<&str>, rules_file: Option<&str>) -> PyResult<Report> {
+    let mut guarding = Guarding::new(path);
+    if let Some(rules) = rules {
+        guarding = guarding.with_rules(rules).map_err(PyValueError::new_err)?;
+    }
+    if let Some(rules_file) = rules_file {
+        guarding = guarding.with_rules_file(rules_file);
+    }
+
+    // parsing the project takes a while, other python threads can run meanwhile
+    let report = py.allow_threads(|| guarding.check()).map_err(PyValueError::new_err)?;
+    Ok(Report { report })
+}
+
+fn to_rule(rule: &GuardRule) -> Rule {
+    let RuleSummary { name, severity, level, tags, origin } = RuleSummary::of(rule);
+    Rule { name, severity, level, tags, origin }
+}
+
+#[pymodule]
+fn guarding(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<Rule>()?;
+    m.add_class::<Violation>()?;
+    m.add_class::<Report>()?;
+    m.add_function(wrap_pyfunction!(parse_rules, m)?)?;
+    m.add_function(wrap_pyfunction!(check, m)?)?;
+    Ok(())
+}
@@ -0,0 +1,21 @@
+import pytest
+
+import guarding
+
+
+def test_parse_rules():
+    rules = guarding.parse_rules('@name("naming")\nclass::name should endsWith "Service";')
+    assert [rule.name for rule in rules] == ["naming"]
+    assert rules[0].level == "class"
+
+    with pytest.raises(ValueError):
+        guarding.parse_rules("class::name should")
+
+
+def test_check(tmp_path):
+    (tmp_path / "Orders.java").write_text("package shop;\nclass Orders {}")
+    report = guarding.check(str(tmp_path), rules='class::name should endsWith "Service";')
+
+    assert len(report) == 1
+    assert report.violations[0].path.endswith("Orders.java")
+    assert report.has_failures()
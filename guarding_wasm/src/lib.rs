@@ -0,0 +1,99 @@
+use wasm_bindgen::prelude::*;
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::rule_executor::RuleExecutor;
+pub use guarding_core::rule_executor::RuleSummary;
+use guarding_parser::ast::RuleLevel;
+use guarding_parser::parser;
+
+/// the rules of `rules` as a JSON array of `RuleSummary`, or the syntax error
+#[wasm_bindgen]
+pub fn parse(rules: &str) -> Result<String, JsValue> {
+    parse_rules(rules).map_err(|err| JsValue::from_str(&err))
+}
+
+/// runs `rules` on `models`, a JSON array of the models `guarding_ident` makes, and returns the
+/// errors as JSON like the C adapter
+#[wasm_bindgen]
+pub fn evaluate(rules: &str, models: &str) -> Result<String, JsValue> {
+    evaluate_models(rules, models, false).map_err(|err| JsValue::from_str(&err))
+}
+
+/// the model of `code`, in the language the extension of `path` is, as JSON
+#[cfg(feature = "analyzer")]
+#[wasm_bindgen]
+pub fn model(path: &str, code: &str) -> Result<String, JsValue> {
+    analyzer::parse_file(path, code)
+        .and_then(|file| serde_json::to_string(&file).map_err(|err| err.to_string()))
+        .map_err(|err| JsValue::from_str(&err))
+}
+
+/// like `evaluate` for the model of pasted code; package rules need the other files of the
+/// package and are left out
+#[cfg(feature = "analyzer")]
+#[wasm_bindgen]
+pub fn analyze(rules: &str, path: &str, code: &str) -> Result<String, JsValue> {
+    analyzer::parse_file(path, code)
+        .and_then(|file| serde_json::to_string(&[file]).map_err(|err| err.to_string()))
+        .and_then(|models| evaluate_models(rules, &models, true))
+        .map_err(|err| JsValue::from_str(&err))
+}
+
+fn parse_rules(rules: &str) -> Result<String, String> {
+    let rules = parser::parse(rules).map_err(|err| err.to_string())?;
+    let summaries: Vec<RuleSummary> = rules.iter().map(RuleSummary::of).collect();
+
+    serde_json::to_string(&summaries).map_err(|err| err.to_string())
+}
+
+fn evaluate_models(rules: &str, models: &str, single_file: bool) -> Result<String, String> {
+    let mut rules = parser::parse(rules).map_err(|err| err.to_string())?;
+    let models: Vec<CodeFile> = serde_json::from_str(models).map_err(|err| format!("invalid models: {}", err))?;
+    if single_file {
+        rules.retain(|rule| rule.level != RuleLevel::Package);
+    }
+
+    let mut executor = RuleExecutor::new(models, rules);
+    executor.run();
+    serde_json::to_string(&executor.errors).map_err(|err| err.to_string())
+}
+
+#[cfg(feature = "analyzer")]
+mod analyzer {
+    use std::path::Path;
+
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_ident::LanguageRegistry;
+
+    /// by the extension only, there is no file to read a shebang from
+    pub fn parse_file(path: &str, code: &str) -> Result<CodeFile, String> {
+        let registry = LanguageRegistry::default();
+        let path = Path::new(path);
+        let language = path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| registry.by_extension(ext))
+            .ok_or_else(|| format!("no language for {}", path.display()))?;
+
+        let mut file = language.parse(code, path).map_err(|err| err.to_string())?;
+        file.path = path.display().to_string();
+        file.file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        file.language = language.name().to_string();
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{evaluate_models, parse_rules};
+
+    #[test]
+    fn should_evaluate_pasted_models() {
+        let summaries = parse_rules("@name(\"naming\")\nclass::name should endsWith \"Service\";").unwrap();
+        assert!(summaries.contains("\"name\":\"naming\""));
+        assert!(parse_rules("class::name should").is_err());
+
+        let models = "[{\"path\":\"Orders.java\",\"package\":\"shop\",\"classes\":[{\"name\":\"Orders\",\"package\":\"shop\"}]}]";
+        let errors = evaluate_models("class::name should endsWith \"Service\";", models, false).unwrap();
+        assert!(errors.contains("Orders"));
+    }
+}
@@ -0,0 +1,149 @@
+use std::path::PathBuf;
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_parser::ast::GuardRule;
+
+use crate::config::GuardingConfig;
+use crate::custom::{CustomRule, CustomRules};
+use crate::handler::{check_project_with, ViolationHandler};
+use crate::parse_rules;
+use crate::report::{CheckReport, Violation};
+
+/// what a check found, see [`CheckReport::violations`]
+pub type AnalysisReport = CheckReport;
+
+/// Guarding as a library, for tools embedding it instead of running the binary:
+///
+/// ```no_run
+/// use guarding::Guarding;
+///
+/// let report = Guarding::new("path/to/project")
+///     .with_rules_file("path/to/project/guarding.guarding")
+///     .check()
+///     .unwrap();
+/// for violation in report.violations() {
+///     println!("{}: {}", violation.rule, violation.message);
+/// }
+/// ```
+///
+/// The `guarding.toml` of the project or its parents is used like by `guarding check`, without
+/// one every `*.guarding` file of the project is loaded.
+#[derive(Debug, Clone, Default)]
+pub struct Guarding {
+    root: PathBuf,
+    config: Option<GuardingConfig>,
+    rule_files: Vec<PathBuf>,
+    rules: Vec<GuardRule>,
+    custom_rules: CustomRules,
+}
+
+impl Guarding {
+    pub fn new(project_root: impl Into<PathBuf>) -> Guarding {
+        Guarding { root: project_root.into(), ..Default::default() }
+    }
+
+    /// instead of the discovered `guarding.toml`
+    pub fn with_config(mut self, config: GuardingConfig) -> Guarding {
+        self.config = Some(config);
+        self
+    }
+
+    /// instead of the rule files of the config, can be called more than once
+    pub fn with_rules_file(mut self, path: impl Into<PathBuf>) -> Guarding {
+        self.rule_files.push(path.into());
+        self
+    }
+
+    /// rules on top of the files, like ones of [`crate::rules::classes`]
+    pub fn with_rule(mut self, rule: GuardRule) -> Guarding {
+        self.rules.push(rule);
+        self
+    }
+
+    /// a check in Rust, run after the other rules
+    pub fn with_custom_rule(mut self, rule: impl CustomRule + 'static) -> Guarding {
+        self.custom_rules.register(rule);
+        self
+    }
+
+    /// rules written in the guarding DSL, on top of the files
+    pub fn with_rules(mut self, content: &str) -> Result<Guarding, String> {
+        self.rules.extend(parse_rules(content)?);
+        Ok(self)
+    }
+
+    pub fn config(&self) -> Result<GuardingConfig, String> {
+        match &self.config {
+            Some(config) => Ok(config.clone()),
+            None => match GuardingConfig::discover(&self.root) {
+                Some(path) => GuardingConfig::load(&path),
+                None => Ok(GuardingConfig { dir: self.root.clone(), ..Default::default() })
+            }
+        }
+    }
+
+    /// the models of the sources the rules run on
+    pub fn project(&self) -> Result<CodeProject, String> {
+        let config = self.config()?;
+        Ok(config.build_project(&self.roots(&config)))
+    }
+
+    pub fn check(&self) -> Result<AnalysisReport, String> {
+        self.check_with(&mut |_: &Violation| {})
+    }
+
+    /// `check` handing every violation to `handler` as soon as its rule ran
+    pub fn check_with(&self, handler: &mut dyn ViolationHandler) -> Result<AnalysisReport, String> {
+        let (config, rules, project) = self.prepare()?;
+        let custom = self.custom_rules.evaluate(&config, &project);
+        let mut report = check_project_with(rules, project, handler);
+        custom.append_to(&mut report, handler);
+        Ok(report)
+    }
+
+    fn prepare(&self) -> Result<(GuardingConfig, Vec<GuardRule>, CodeProject), String> {
+        let config = self.config()?;
+        // rules in code replace the discovered files, like `-r` rules
+        let in_code = !self.rules.is_empty() || !self.custom_rules.is_empty();
+        let mut rules: Vec<GuardRule> = match (self.rule_files.is_empty(), !in_code) {
+            (true, false) => vec![],
+            (true, true) => config.load_rules()?.into_iter().map(|(_, rule)| rule).collect(),
+            (false, _) => config.load_rule_files(&self.rule_files)?.into_iter().map(|(_, rule)| rule).collect(),
+        };
+        let mut extra = self.rules.clone();
+        config.apply_rule_settings(&mut extra);
+        rules.extend(extra);
+
+        let project = config.build_project(&self.roots(&config));
+        Ok((config, rules, project))
+    }
+
+    /// the project root, unless the config names source roots
+    fn roots(&self, config: &GuardingConfig) -> Vec<PathBuf> {
+        if config.roots.is_empty() { vec![self.root.clone()] } else { config.source_roots() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::api::Guarding;
+
+    #[test]
+    fn should_check_a_project_as_library() {
+        let dir = std::env::temp_dir().join("guarding").join("api");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("shop/service")).unwrap();
+        fs::write(dir.join("shop/service/Orders.java"), "package shop.service;\nclass Orders {}").unwrap();
+        fs::write(dir.join("naming.guarding"), "@name(\"naming\")\nclass(resideIn \"..service..\")::name should endsWith \"Service\";").unwrap();
+
+        let report = Guarding::new(&dir).with_rules_file(dir.join("naming.guarding")).check().unwrap();
+        assert_eq!(1, report.violations().len());
+        assert_eq!("naming", report.violations()[0].rule);
+
+        let report = Guarding::new(&dir).with_rules("package(\".\")::file.len should < 1;").unwrap().check().unwrap();
+        assert_eq!(1, report.rules.len());
+        assert_eq!(1, Guarding::new(&dir).project().unwrap().files.len());
+    }
+}
@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::format::relative_path;
+use crate::report::CheckReport;
+
+pub const BASELINE_FILE: &'static str = "guarding-baseline.json";
+
+/// The violations a project accepts for now, by fingerprint, so a check only fails on new
+/// ones while the old ones are fixed step by step.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct Baseline {
+    pub version: u32,
+    pub entries: Vec<BaselineEntry>,
+}
+
+/// the rule, message and place are only there to read and review the file
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct BaselineEntry {
+    pub fingerprint: String,
+    pub rule: String,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub item: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl Baseline {
+    pub fn of_report(report: &CheckReport) -> Baseline {
+        let mut entries: Vec<BaselineEntry> = report.violations().into_iter()
+            .map(|violation| BaselineEntry {
+                rule: report.rule_id(violation.rule_index),
+                message: violation.message,
+                item: violation.item,
+                path: violation.location.map(|location| relative_path(&location.path)),
+                fingerprint: violation.fingerprint,
+            })
+            .collect();
+        entries.sort_by(|a, b| (&a.path, &a.rule, &a.fingerprint).cmp(&(&b.path, &b.rule, &b.fingerprint)));
+
+        Baseline { version: 1, entries }
+    }
+
+    pub fn load(path: &Path) -> Result<Baseline, String> {
+        let content = fs::read_to_string(path).map_err(|err| format!("can't read baseline {}: {}", path.display(), err))?;
+        serde_json::from_str(&content).map_err(|err| format!("invalid baseline {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = format!("{}\n", serde_json::to_string_pretty(self).unwrap());
+        fs::write(path, content).map_err(|err| format!("can't write baseline {}: {}", path.display(), err))
+    }
+
+    /// the report without the baselined violations; a fingerprint in the baseline twice
+    /// accepts two of its violations
+    pub fn filter(&self, mut report: CheckReport) -> CheckReport {
+        let mut accepted = self.counts();
+        let fingerprints: Vec<String> = report.violations().into_iter().map(|violation| violation.fingerprint).collect();
+        let mut fingerprints = fingerprints.into_iter();
+        let mut is_new = |fingerprint: Option<String>| match fingerprint.and_then(|fingerprint| accepted.get_mut(&fingerprint)) {
+            Some(count) if *count > 0 => {
+                *count -= 1;
+                false
+            }
+            _ => true
+        };
+
+        // `violations` has one entry per item, or one for an error without items
+        report.errors = report.errors.into_iter()
+            .filter_map(|mut error| {
+                if error.items.is_empty() {
+                    return if is_new(fingerprints.next()) { Some(error) } else { None };
+                }
+
+                let kept: Vec<usize> = (0..error.items.len()).filter(|_| is_new(fingerprints.next())).collect();
+                if kept.is_empty() {
+                    return None;
+                }
+                error.items = kept.iter().map(|index| error.items[*index].clone()).collect();
+                error.locations = kept.iter().filter_map(|index| error.locations.get(*index).cloned()).collect();
+                Some(error)
+            })
+            .collect();
+        report
+    }
+
+    /// only the entries still violated, and how many were dropped
+    pub fn prune(&self, report: &CheckReport) -> (Baseline, usize) {
+        let mut found: BTreeMap<String, usize> = BTreeMap::new();
+        for violation in report.violations() {
+            *found.entry(violation.fingerprint).or_insert(0) += 1;
+        }
+
+        let entries: Vec<BaselineEntry> = self.entries.iter()
+            .filter(|entry| match found.get_mut(&entry.fingerprint) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    true
+                }
+                _ => false
+            })
+            .cloned()
+            .collect();
+
+        let pruned = self.entries.len() - entries.len();
+        (Baseline { version: self.version, entries }, pruned)
+    }
+
+    fn counts(&self) -> BTreeMap<String, usize> {
+        let mut counts = BTreeMap::new();
+        for entry in &self.entries {
+            *counts.entry(entry.fingerprint.clone()).or_insert(0) += 1;
+        }
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::GuardRule;
+
+    use crate::baseline::Baseline;
+    use crate::report::CheckReport;
+
+    fn report(items: &[&str], size: bool) -> CheckReport {
+        let mut naming = RuleErrorMsg::new(MismatchType::FileName, 0);
+        naming.msg = "endsWith: \"Service\"".to_string();
+        for (row, item) in items.iter().enumerate() {
+            naming.push_item(item.to_string(), ErrorLocation {
+                path: format!("src/{}.java", item),
+                start: CodePoint { row, column: 0 },
+                end: CodePoint::default(),
+            });
+        }
+
+        let mut errors = vec![naming];
+        if size {
+            let mut error = RuleErrorMsg::new(MismatchType::FileSize, 1);
+            error.msg = "file.len = 3".to_string();
+            errors.push(error);
+        }
+        CheckReport { rules: vec![GuardRule::default(), GuardRule::default()], errors, files: vec![] }
+    }
+
+    #[test]
+    fn should_fail_only_on_new_violations() {
+        let baseline = Baseline::of_report(&report(&["Order", "User"], true));
+        assert_eq!(3, baseline.entries.len());
+        assert_eq!(Some("src/Order.java".to_string()), baseline.entries[1].path);
+
+        let filtered = baseline.filter(report(&["Order", "Payment", "User"], true));
+        assert_eq!(1, filtered.errors.len());
+        assert_eq!(vec!["Payment".to_string()], filtered.errors[0].items);
+
+        let filtered = baseline.filter(report(&["User"], false));
+        assert!(filtered.errors.is_empty());
+    }
+
+    #[test]
+    fn should_prune_fixed_violations() {
+        let baseline = Baseline::of_report(&report(&["Order", "User"], true));
+        let (pruned, removed) = baseline.prune(&report(&["User", "Payment"], true));
+
+        assert_eq!(1, removed);
+        assert_eq!(2, pruned.entries.len());
+        assert!(pruned.entries.iter().all(|entry| entry.item.as_deref() != Some("Order")));
+    }
+}
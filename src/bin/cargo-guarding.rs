@@ -0,0 +1,10 @@
+//! `cargo install guarding` installs this next to `guarding`, so `cargo guarding check` checks
+//! the workspace it runs in
+
+use std::{env, process};
+
+use guarding::cli::{self, Invocation};
+
+fn main() {
+    process::exit(cli::run(env::args().collect(), Invocation::Cargo));
+}
@@ -3,7 +3,9 @@ use std::path::PathBuf;
 
 use clap::{AppSettings, Clap};
 
-use guarding_ident::ModelBuilder;
+use guarding_core::domain::model_export::ModelExport;
+use guarding::init_logging;
+use guarding_ident::{LanguageRegistry, ModelBuilder};
 
 #[derive(Clap)]
 #[clap(version = "1.0", author = "Inherd Group <group@inherd.org>")]
@@ -14,14 +16,25 @@ struct Opts {
 
     #[clap(short, long, default_value = "guard-ident.json")]
     output: String,
+
+    /// list the supported languages and their file extensions
+    #[clap(long)]
+    languages: bool,
 }
 
 fn main() {
     let opts: Opts = Opts::parse();
+    init_logging();
+    if opts.languages {
+        for language in LanguageRegistry::default().languages() {
+            println!("{}: {}", language.name(), language.extensions().join(", "));
+        }
+        return;
+    }
 
     let code_dir = PathBuf::from(opts.path);
 
-    let models = ModelBuilder::build_models_by_dir(code_dir);
-    let content = serde_json::to_string_pretty(&models).unwrap();
+    let project = ModelBuilder::build_project_by_dir(code_dir);
+    let content = ModelExport::from_project(&project).to_json();
     let _ = fs::write(opts.output, content);
 }
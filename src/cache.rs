@@ -0,0 +1,349 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_ident::ProjectScanner;
+use guarding_ident::support::queries::{query_dir, query_override};
+
+use crate::report::fnv1a;
+
+const MODELS_DIR: &'static str = "models";
+const STATS_FILE: &'static str = "stats.json";
+
+/// The parsed models of the source files on disk, by the hash of their path and content, so an
+/// unchanged file isn't parsed again by the next check.
+pub struct ModelCache {
+    dir: PathBuf,
+    /// in bytes, the oldest models are removed over it
+    max_size: Option<u64>,
+}
+
+/// the hits and misses of one or more checks
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub struct CacheCounts {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+impl CacheCounts {
+    /// the share of hits in percent, 0 without lookups
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 { 0.0 } else { self.hits as f64 / lookups as f64 * 100.0 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct CacheStats {
+    pub runs: usize,
+    pub last: CacheCounts,
+    pub total: CacheCounts,
+}
+
+/// What `guarding cache status` shows.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct CacheStatus {
+    pub dir: String,
+    pub entries: usize,
+    /// the models which can't be read anymore, like after a crash or an upgrade
+    pub broken: usize,
+    pub size: u64,
+    pub max_size: Option<u64>,
+    pub stats: CacheStats,
+}
+
+impl CacheStatus {
+    pub fn to_text(&self) -> String {
+        let limit = self.max_size.map(|max_size| format!(" of {}", megabytes(max_size))).unwrap_or_default();
+        let mut text = format!("{}: {} models, {}{}\n", self.dir, self.entries, megabytes(self.size), limit);
+        text.push_str(&format!("last run: {} hits, {} misses ({:.0}%)\n", self.stats.last.hits, self.stats.last.misses, self.stats.last.hit_rate()));
+        text.push_str(&format!("{} runs: {} hits, {} misses ({:.0}%)\n", self.stats.runs, self.stats.total.hits, self.stats.total.misses, self.stats.total.hit_rate()));
+        if self.broken > 0 {
+            text.push_str(&format!("{} broken models, `guarding cache clear` removes them\n", self.broken));
+        }
+        text
+    }
+}
+
+impl ModelCache {
+    pub fn new(dir: PathBuf, max_size: Option<u64>) -> ModelCache {
+        ModelCache { dir, max_size }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// like `ProjectScanner::parse_with_progress`, the models of unchanged files from the
+    /// cache; `progress` only counts the parsed ones
+    pub fn parse(&self, scanner: &ProjectScanner, paths: &[PathBuf], progress: &(dyn Fn(usize, usize) + Sync), counts: &mut CacheCounts) -> Vec<CodeFile> {
+        let mut found: BTreeMap<PathBuf, CodeFile> = BTreeMap::new();
+        let mut keys: BTreeMap<PathBuf, String> = BTreeMap::new();
+        let mut missing = vec![];
+        let query_dir = query_dir();
+        let mut query_hashes: BTreeMap<&str, u64> = BTreeMap::new();
+        for path in paths {
+            let key = scanner.registry().detect(path)
+                .map(|language| language.name())
+                .and_then(|language| {
+                    let hash = *query_hashes.entry(language).or_insert_with(|| query_hash(query_dir.as_deref(), language));
+                    self.key_of(path, scanner.max_file_size(), language, hash)
+                });
+            let key = match key {
+                Some(key) => key,
+                None => {
+                    missing.push(path.clone());
+                    continue;
+                }
+            };
+            match self.load(&key) {
+                Some(file) => {
+                    found.insert(path.clone(), file);
+                }
+                None => {
+                    keys.insert(path.clone(), key);
+                    missing.push(path.clone());
+                }
+            }
+        }
+        counts.hits += found.len();
+        counts.misses += missing.len();
+
+        scanner.parse_each(&missing, progress, &mut |file| {
+            let path = PathBuf::from(&file.path);
+            if let Some(key) = keys.get(&path) {
+                self.store(key, &file);
+            }
+            found.insert(path, file);
+        });
+
+        // in the order of `paths`, like the scanner
+        paths.iter().filter_map(|path| found.remove(path)).collect()
+    }
+
+    /// adds the counts of a check to the stats and removes the oldest models over the limit
+    pub fn record(&self, counts: &CacheCounts) {
+        let mut stats = self.stats();
+        stats.runs += 1;
+        stats.last = *counts;
+        stats.total.hits += counts.hits;
+        stats.total.misses += counts.misses;
+        let written = fs::create_dir_all(&self.dir)
+            .and_then(|_| fs::write(self.dir.join(STATS_FILE), serde_json::to_string_pretty(&stats).unwrap()));
+        if let Err(err) = written {
+            warn!("can't write the cache stats to {}: {}", self.dir.display(), err);
+        }
+
+        if let Some(max_size) = self.max_size {
+            self.evict(max_size);
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        fs::read_to_string(self.dir.join(STATS_FILE)).ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn status(&self) -> CacheStatus {
+        let entries = self.entries();
+        CacheStatus {
+            dir: self.dir.display().to_string(),
+            entries: entries.len(),
+            broken: entries.iter().filter(|(path, _, _)| read_model(path).is_none()).count(),
+            size: entries.iter().map(|(_, size, _)| size).sum(),
+            max_size: self.max_size,
+            stats: self.stats(),
+        }
+    }
+
+    /// removes the models and the stats, returns how many models there were
+    pub fn clear(&self) -> Result<usize, String> {
+        let entries = self.entries().len();
+        if self.dir.exists() {
+            fs::remove_dir_all(&self.dir).map_err(|err| format!("can't remove {}: {}", self.dir.display(), err))?;
+        }
+        Ok(entries)
+    }
+
+    /// the path and content, with the version as models change between versions, and the language
+    /// parsing it with the hash of its overridden query; `None` for files the scanner skips anyway
+    fn key_of(&self, path: &Path, max_file_size: u64, language: &str, query_hash: u64) -> Option<String> {
+        let size = fs::metadata(path).ok()?.len();
+        if size > max_file_size {
+            return None;
+        }
+
+        let mut bytes = format!("{}\0{}\0{}\0{:016x}\0", env!("CARGO_PKG_VERSION"), path.display(), language, query_hash).into_bytes();
+        bytes.extend(fs::read(path).ok()?);
+        Some(format!("{:016x}", fnv1a(&bytes)))
+    }
+
+    fn model_path(&self, key: &str) -> PathBuf {
+        self.dir.join(MODELS_DIR).join(format!("{}.json", key))
+    }
+
+    fn load(&self, key: &str) -> Option<CodeFile> {
+        let path = self.model_path(key);
+        if !path.is_file() {
+            return None;
+        }
+
+        let model = read_model(&path);
+        if model.is_none() {
+            debug!("broken cached model {}, parsing again", path.display());
+            let _ = fs::remove_file(&path);
+        }
+        model
+    }
+
+    fn store(&self, key: &str, file: &CodeFile) {
+        let path = self.model_path(key);
+        let written = fs::create_dir_all(self.dir.join(MODELS_DIR))
+            .and_then(|_| fs::write(&path, serde_json::to_string(file).unwrap()));
+        if let Err(err) = written {
+            warn!("can't cache {}: {}", path.display(), err);
+        }
+    }
+
+    /// every model with its size and when it was written
+    fn entries(&self) -> Vec<(PathBuf, u64, SystemTime)> {
+        let dir = match fs::read_dir(self.dir.join(MODELS_DIR)) {
+            Ok(dir) => dir,
+            Err(_) => return vec![]
+        };
+
+        dir.filter_map(|entry| entry.ok())
+            .filter_map(|entry| {
+                let metadata = entry.metadata().ok()?;
+                Some((entry.path(), metadata.len(), metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH)))
+            })
+            .collect()
+    }
+
+    fn evict(&self, max_size: u64) {
+        let mut entries = self.entries();
+        let mut size: u64 = entries.iter().map(|(_, size, _)| size).sum();
+        entries.sort_by_key(|(_, _, modified)| *modified);
+
+        for (path, entry_size, _) in entries {
+            if size <= max_size {
+                break;
+            }
+            if fs::remove_file(&path).is_ok() {
+                size -= entry_size;
+            }
+        }
+    }
+}
+
+/// of the `<language>.scm` in the query dir, 0 with the built-in query
+fn query_hash(query_dir: Option<&Path>, language: &str) -> u64 {
+    query_dir.and_then(|dir| query_override(dir, language))
+        .map(|query| fnv1a(query.as_bytes()))
+        .unwrap_or(0)
+}
+
+fn read_model(path: &Path) -> Option<CodeFile> {
+    fs::read_to_string(path).ok().and_then(|content| serde_json::from_str(&content).ok())
+}
+
+fn megabytes(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / 1024.0 / 1024.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use guarding_ident::{LanguageRegistry, ProjectScanner};
+
+    use crate::cache::{CacheCounts, ModelCache, query_hash};
+
+    #[test]
+    fn should_parse_changed_files_only() {
+        let dir = std::env::temp_dir().join("guarding").join("cache");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/Order.java"), "class Order {}").unwrap();
+        fs::write(dir.join("src/User.java"), "class User {}").unwrap();
+
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry);
+        let cache = ModelCache::new(dir.join("cache"), None);
+        let paths = scanner.scan(&dir.join("src"));
+
+        let mut counts = CacheCounts::default();
+        cache.parse(&scanner, &paths, &|_, _| {}, &mut counts);
+        cache.record(&counts);
+        assert_eq!(CacheCounts { hits: 0, misses: 2 }, counts);
+
+        fs::write(dir.join("src/User.java"), "class User { void save() {} }").unwrap();
+        let mut counts = CacheCounts::default();
+        let files = cache.parse(&scanner, &paths, &|_, _| {}, &mut counts);
+        cache.record(&counts);
+        assert_eq!(CacheCounts { hits: 1, misses: 1 }, counts);
+        assert_eq!(vec!["Order", "User"], files.iter().map(|file| file.classes[0].name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(1, files[1].classes[0].functions.len());
+
+        let status = cache.status();
+        assert_eq!(3, status.entries);
+        assert_eq!(2, status.stats.runs);
+        assert_eq!(4, status.stats.total.hits + status.stats.total.misses);
+
+        assert_eq!(3, cache.clear().unwrap());
+        assert_eq!(0, cache.status().entries);
+    }
+
+    #[test]
+    fn should_recover_from_broken_models() {
+        let dir = std::env::temp_dir().join("guarding").join("cache-broken");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src/Order.java"), "class Order {}").unwrap();
+
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry);
+        let cache = ModelCache::new(dir.join("cache"), None);
+        let paths = scanner.scan(&dir.join("src"));
+        cache.parse(&scanner, &paths, &|_, _| {}, &mut CacheCounts::default());
+
+        for entry in fs::read_dir(dir.join("cache/models")).unwrap() {
+            fs::write(entry.unwrap().path(), "{ broken").unwrap();
+        }
+        assert_eq!(1, cache.status().broken);
+
+        let mut counts = CacheCounts::default();
+        let files = cache.parse(&scanner, &paths, &|_, _| {}, &mut counts);
+        assert_eq!(CacheCounts { hits: 0, misses: 1 }, counts);
+        assert_eq!("Order", files[0].classes[0].name);
+        assert_eq!(0, cache.status().broken);
+    }
+
+    #[test]
+    fn should_key_by_language_and_query() {
+        let dir = std::env::temp_dir().join("guarding").join("cache-query");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("queries")).unwrap();
+        fs::write(dir.join("Order.java"), "class Order {}").unwrap();
+
+        let cache = ModelCache::new(dir.join("cache"), None);
+        let path = dir.join("Order.java");
+        let queries = dir.join("queries");
+        let builtin = cache.key_of(&path, u64::MAX, "java", query_hash(Some(&queries), "java")).unwrap();
+        assert_eq!(0, query_hash(Some(&queries), "java"));
+        assert_ne!(builtin, cache.key_of(&path, u64::MAX, "groovy", 0).unwrap());
+
+        fs::write(queries.join("java.scm"), "(class_declaration name: (identifier) @class-name)").unwrap();
+        let overridden = cache.key_of(&path, u64::MAX, "java", query_hash(Some(&queries), "java")).unwrap();
+        assert_ne!(builtin, overridden);
+        fs::write(queries.join("java.scm"), "(interface_declaration name: (identifier) @class-name)").unwrap();
+        assert_ne!(overridden, cache.key_of(&path, u64::MAX, "java", query_hash(Some(&queries), "java")).unwrap());
+    }
+}
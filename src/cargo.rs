@@ -0,0 +1,119 @@
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::Value;
+
+use crate::config::GuardingConfig;
+
+/// The cargo workspace `cargo guarding` runs in, as `cargo metadata` describes it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CargoWorkspace {
+    pub root: PathBuf,
+    /// the crate name and directory of every member, in the order of the workspace
+    pub members: Vec<(String, PathBuf)>,
+}
+
+impl CargoWorkspace {
+    /// the workspace of `dir`, with the cargo running `cargo guarding`
+    pub fn discover(dir: &Path) -> Result<CargoWorkspace, String> {
+        let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let output = Command::new(cargo)
+            .args(&["metadata", "--no-deps", "--format-version", "1"])
+            .current_dir(dir)
+            .output()
+            .map_err(|err| format!("can't run cargo metadata: {}", err))?;
+        if !output.status.success() {
+            return Err(format!("cargo metadata failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+
+        CargoWorkspace::from_metadata(&String::from_utf8_lossy(&output.stdout))
+    }
+
+    /// from the JSON of `cargo metadata --no-deps`, whose packages are the members
+    pub fn from_metadata(metadata: &str) -> Result<CargoWorkspace, String> {
+        let metadata: Value = serde_json::from_str(metadata).map_err(|err| format!("invalid cargo metadata: {}", err))?;
+        let root = metadata["workspace_root"].as_str()
+            .ok_or_else(|| "cargo metadata has no workspace_root".to_string())?;
+
+        let members = metadata["packages"].as_array().map(|packages| packages.as_slice()).unwrap_or(&[]).iter()
+            .filter_map(|package| {
+                let name = package["name"].as_str()?;
+                let dir = Path::new(package["manifest_path"].as_str()?).parent()?;
+                Some((name.to_string(), dir.to_path_buf()))
+            })
+            .collect();
+
+        Ok(CargoWorkspace { root: PathBuf::from(root), members })
+    }
+
+    /// the closest `guarding.toml` from `dir`, or the defaults at the workspace root, with the
+    /// members as the source roots when it has none
+    pub fn config(&self, dir: &Path) -> Result<GuardingConfig, String> {
+        let mut config = match GuardingConfig::discover(dir) {
+            Some(path) => GuardingConfig::load(&path)?,
+            None => GuardingConfig { dir: self.root.clone(), ..Default::default() }
+        };
+        if config.roots.is_empty() {
+            config.roots = self.member_roots(&config.dir);
+        }
+
+        Ok(config)
+    }
+
+    /// the member directories relative to `dir`, without the ones inside another member like
+    /// the crates under a root package, they would be parsed twice
+    pub fn member_roots(&self, dir: &Path) -> Vec<String> {
+        let dirs: Vec<&PathBuf> = self.members.iter().map(|(_, dir)| dir).collect();
+        dirs.iter()
+            .filter(|member| !dirs.iter().any(|other| other != *member && member.starts_with(other)))
+            .map(|member| match member.strip_prefix(dir) {
+                Ok(relative) if relative.as_os_str().is_empty() => ".".to_string(),
+                Ok(relative) => relative.display().to_string(),
+                Err(_) => member.display().to_string()
+            })
+            .collect()
+    }
+}
+
+/// cargo runs `cargo guarding check` as `cargo-guarding guarding check`, the arguments without
+/// that `guarding`
+pub fn cargo_args(mut args: Vec<String>) -> Vec<String> {
+    if args.get(1).map(|arg| arg == "guarding").unwrap_or(false) {
+        args.remove(1);
+    }
+    args
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::cargo::{cargo_args, CargoWorkspace};
+
+    #[test]
+    fn should_use_the_members_as_roots() {
+        let metadata = r#"{
+            "packages": [
+                { "name": "shop", "manifest_path": "/work/shop/Cargo.toml" },
+                { "name": "shop-core", "manifest_path": "/work/shop/core/Cargo.toml" },
+                { "name": "billing", "manifest_path": "/work/billing/Cargo.toml" }
+            ],
+            "workspace_root": "/work"
+        }"#;
+        let workspace = CargoWorkspace::from_metadata(metadata).unwrap();
+        assert_eq!(PathBuf::from("/work"), workspace.root);
+        assert_eq!(("shop-core".to_string(), PathBuf::from("/work/shop/core")), workspace.members[1]);
+        assert_eq!(vec!["shop".to_string(), "billing".to_string()], workspace.member_roots(Path::new("/work")));
+        assert_eq!(vec![".".to_string(), "/work/billing".to_string()], workspace.member_roots(Path::new("/work/shop")));
+
+        assert!(CargoWorkspace::from_metadata("{}").is_err());
+    }
+
+    #[test]
+    fn should_drop_the_subcommand_name() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<String>>();
+        assert_eq!(args(&["cargo-guarding", "check"]), cargo_args(args(&["cargo-guarding", "guarding", "check"])));
+        assert_eq!(args(&["cargo-guarding", "check"]), cargo_args(args(&["cargo-guarding", "check"])));
+    }
+}
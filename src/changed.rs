@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_ident::ModelBuilder;
+
+use crate::config::GuardingConfig;
+use crate::report::CheckReport;
+
+/// The files git sees as changed, to check only them and what they depend on, like in a
+/// pre-commit hook.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ChangedFiles {
+    paths: BTreeSet<PathBuf>,
+}
+
+impl ChangedFiles {
+    /// the uncommitted and untracked files, and with a `base` like `main` the files changed
+    /// since the branch left it
+    pub fn from_git(dir: &Path, base: Option<&str>) -> Result<ChangedFiles, String> {
+        let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+
+        let since = match base {
+            Some(base) => git(&top, &["merge-base", base, "HEAD"])?.trim().to_string(),
+            None => "HEAD".to_string()
+        };
+        let mut names = git(&top, &["diff", "--name-only", "--diff-filter=d", &since])?;
+        names.push_str(&git(&top, &["ls-files", "--others", "--exclude-standard"])?);
+
+        Ok(ChangedFiles::of_paths(names.lines().filter(|name| !name.is_empty()).map(|name| top.join(name))))
+    }
+
+    pub fn of_paths(paths: impl IntoIterator<Item=PathBuf>) -> ChangedFiles {
+        ChangedFiles { paths: paths.into_iter().map(|path| canonical(&path)).collect() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.paths.contains(&canonical(path))
+    }
+
+    /// parses the changed files of the roots, then the other files of their directories and
+    /// of the packages they import, so package rules still see whole packages
+    pub fn build_project(&self, config: &GuardingConfig, roots: &[PathBuf]) -> CodeProject {
+        config.use_query_dir();
+        let registry = config.registry();
+        let scanner = config.scanner(&registry);
+
+        let mut project_files = vec![];
+        for root in roots {
+            let paths = scanner.scan(root);
+            let (changed, others): (Vec<PathBuf>, Vec<PathBuf>) = paths.into_iter().partition(|path| self.contains(path));
+            if changed.is_empty() {
+                continue;
+            }
+
+            let mut files = scanner.parse(&changed);
+            let dirs: BTreeSet<&Path> = changed.iter().filter_map(|path| path.parent()).collect();
+            let package_dirs: BTreeSet<PathBuf> = files.iter()
+                .flat_map(|file| file.imports.iter())
+                .filter(|import| !import.package.is_empty())
+                .map(|import| import.package.split('.').collect())
+                .collect();
+
+            let neighbors: Vec<PathBuf> = others.into_iter()
+                .filter(|path| path.parent().map(|dir| dirs.contains(dir) || package_dirs.iter().any(|package| dir.ends_with(package))).unwrap_or(false))
+                .collect();
+            files.extend(scanner.parse(&neighbors));
+
+            ModelBuilder::resolve_models(&registry, root, &mut files);
+            project_files.extend(files);
+        }
+
+        CodeProject::new(project_files)
+    }
+
+    /// only the violations in changed files, and the ones of whole packages
+    pub fn restrict(&self, mut report: CheckReport) -> CheckReport {
+        report.errors = report.errors.into_iter()
+            .filter_map(|mut error| {
+                if error.locations.is_empty() {
+                    return Some(error);
+                }
+
+                let kept: Vec<usize> = (0..error.items.len())
+                    .filter(|index| error.location_of(*index).map(|location| self.contains(Path::new(&location.path))).unwrap_or(true))
+                    .collect();
+                if kept.is_empty() {
+                    return None;
+                }
+                error.items = kept.iter().map(|index| error.items[*index].clone()).collect();
+                error.locations = kept.iter().filter_map(|index| error.locations.get(*index).cloned()).collect();
+                Some(error)
+            })
+            .collect();
+        report
+    }
+}
+
+pub(crate) fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git").arg("-C").arg(dir).args(args).output()
+        .map_err(|err| format!("can't run git: {}", err))?;
+    if !output.status.success() {
+        return Err(format!("git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn canonical(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+
+    use crate::changed::ChangedFiles;
+    use crate::config::GuardingConfig;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_parse_changed_files_and_their_neighbors() {
+        let dir = std::env::temp_dir().join("guarding").join("changed");
+        let _ = fs::remove_dir_all(&dir);
+        for package in &["order", "core", "user"] {
+            fs::create_dir_all(dir.join("shop").join(package)).unwrap();
+        }
+        fs::write(dir.join("shop/order/Order.java"), "package shop.order;\nimport shop.core.Entity;\nclass Order {}").unwrap();
+        fs::write(dir.join("shop/order/OrderLine.java"), "package shop.order;\nclass OrderLine {}").unwrap();
+        fs::write(dir.join("shop/core/Entity.java"), "package shop.core;\nclass Entity {}").unwrap();
+        fs::write(dir.join("shop/user/User.java"), "package shop.user;\nclass User {}").unwrap();
+
+        let changed = ChangedFiles::of_paths(vec![dir.join("shop/order/Order.java")]);
+        let config = GuardingConfig::from_toml("", dir.clone()).unwrap();
+        let project = changed.build_project(&config, &[dir.clone()]);
+
+        let mut names: Vec<String> = project.files.iter().map(|file| file.file_name.clone()).collect();
+        names.sort();
+        assert_eq!(vec!["Entity.java", "Order.java", "OrderLine.java"], names);
+    }
+
+    #[test]
+    fn should_keep_violations_of_changed_files() {
+        let location = |path: &str| ErrorLocation { path: path.to_string(), start: CodePoint::default(), end: CodePoint::default() };
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.push_item("Order".to_string(), location("/repo/Order.java"));
+        error.push_item("User".to_string(), location("/repo/User.java"));
+        let mut other = RuleErrorMsg::new(MismatchType::FileName, 0);
+        other.push_item("User".to_string(), location("/repo/User.java"));
+        let package = RuleErrorMsg::new(MismatchType::FileSize, 1);
+
+        let changed = ChangedFiles::of_paths(vec![PathBuf::from("/repo/Order.java")]);
+        let report = changed.restrict(CheckReport { rules: vec![], errors: vec![error, other, package], files: vec![] });
+
+        assert_eq!(2, report.errors.len());
+        assert_eq!(vec!["Order".to_string()], report.errors[0].items);
+        assert_eq!(MismatchType::FileSize, report.errors[1].mismatch_type);
+    }
+}
@@ -0,0 +1,1327 @@
+use std::{env, fs, io};
+use std::io::Read;
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use clap::{AppSettings, Clap};
+use crate::{check_project, check_source, exec_guarding, init_logging};
+use crate::baseline::{Baseline, BASELINE_FILE};
+use crate::cache::ModelCache;
+use crate::cargo::{cargo_args, CargoWorkspace};
+use crate::changed::ChangedFiles;
+use crate::daemon;
+use crate::diff_base::DiffBase;
+use crate::config::GuardingConfig;
+use crate::report::{CheckReport, ExitCodes, FailPolicy};
+use crate::export::{export_rules, ExportOptions};
+use crate::fitness::{FitnessHistory, RunSummary, HISTORY_FILE};
+use crate::format::{csv, diagnostic, OutputFormat};
+use crate::graph::{ArchGraph, GraphLevel};
+use crate::grouping::{group_report, GroupBy, SortOrder};
+use crate::import::import_file;
+use crate::init::ProjectSurvey;
+use crate::lint::lint_rule_file;
+use crate::lsp::serve;
+use crate::monorepo::check_projects;
+use crate::packs::RulePack;
+use crate::query::run_query;
+use crate::repl::{repl, ReplSession};
+use crate::server::{self, HttpServer};
+use crate::rule_list::{list_rules, to_sources, to_table};
+use crate::stats::ProjectStats;
+use crate::structured::parse_rule_file;
+use crate::suggest::add_suggestions;
+use crate::timings::{PhaseTimings, progress_bar};
+use crate::watch::{watch, WatchSession};
+use guarding_parser::ast::{GuardRule, Severity};
+
+#[derive(Clap)]
+#[clap(version = "1.0", author = "Inherd Group <group@inherd.org>")]
+#[clap(setting = AppSettings::ColoredHelp)]
+struct Opts {
+    #[clap(short, long, default_value = "guarding.guarding")]
+    config: String,
+
+    #[clap(short, long, default_value = "src")]
+    path: String,
+
+    #[clap(short, long, default_value = "guard.json")]
+    output: String,
+
+    /// prints only the summary line of the text report, and no progress or notes
+    #[clap(short, long, global = true)]
+    quiet: bool,
+
+    /// no colors, also when stdout is a terminal
+    #[clap(long, global = true)]
+    no_color: bool,
+
+    /// for scripts: a line per violation in the text report, no colors and no progress
+    #[clap(long, global = true)]
+    machine: bool,
+
+    #[clap(subcommand)]
+    subcmd: Option<SubCommand>,
+}
+
+#[derive(Clap)]
+enum SubCommand {
+    /// runs the rules on a source tree, fails when a rule of error severity is violated
+    Check(CheckOpts),
+    /// creates a starter `guarding.guarding` for the languages and packages of a project
+    Init(InitOpts),
+    /// checks a rule file without running it: syntax, operators, duplicate names, scopes and conflicts
+    Validate(ValidateOpts),
+    /// writes a report to share, like a standalone HTML page
+    Report(ReportOpts),
+    /// shows the loaded rules
+    Rules(RulesOpts),
+    /// prints what a rule selects, like `guarding query "class(resideIn '..service..')"`
+    Query(QueryOpts),
+    /// parses the project once, then prints the violations of each typed rule or the matches of each scope
+    Repl(ReplOpts),
+    /// exports the dependencies, with the ones violating access rules highlighted
+    Graph(GraphOpts),
+    /// prints the shortest dependency paths from one package or class to another
+    Why(WhyOpts),
+    /// prints the sizes, coupling and cycles of the packages, without rules
+    Stats(StatsOpts),
+    /// scores the fitness of the architecture, adds it to the history and prints the trend since the previous runs
+    Fitness(FitnessOpts),
+    /// accepts the current violations, so checks only fail on new ones
+    Baseline(BaselineOpts),
+    /// shows or clears the cached models of `cache_dir`
+    Cache(CacheOpts),
+    /// fetches the rule packs of `guarding.toml` and prints the commit each is at, `--update` fetches them again
+    Packs(PacksOpts),
+    /// serves the language server of rule files on stdin and stdout, for editors
+    Lsp(LspOpts),
+    /// keeps the project parsed and answers `check` and `query` requests as JSON-RPC on stdin and stdout, for IDE plugins and hooks
+    Daemon(DaemonOpts),
+    /// serves the violations, stats, dependency graph and rule status as JSON over HTTP, for dashboards
+    Serve(ServeOpts),
+    /// converts the config of another architecture tool to guarding rules, like `guarding import import-linter .importlinter`
+    Import(ImportOpts),
+    /// writes the rules another architecture tool can check as its config, like `guarding export archunit`
+    Export(ExportOpts),
+}
+
+/// paths and rules not given are taken from the closest `guarding.toml`
+#[derive(Clap)]
+struct SourceOpts {
+    path: Option<String>,
+
+    #[clap(short, long)]
+    rules: Option<String>,
+
+    /// instead of the discovered `guarding.toml`
+    #[clap(long)]
+    config: Option<String>,
+
+    /// gitignore-like globs to skip, added to the `exclude` of `guarding.toml`
+    #[clap(long)]
+    exclude: Vec<String>,
+
+    /// threads parsing the files, one per core by default
+    #[clap(short, long)]
+    jobs: Option<usize>,
+
+    /// the rule files, tags and severities of a profile of `guarding.toml`, like `ci`
+    #[clap(long)]
+    profile: Option<String>,
+
+    /// only the rules of this `@name`, can be repeated
+    #[clap(long = "rule")]
+    only_rules: Vec<String>,
+
+    /// only the rules with this tag, instead of the `tags` of `guarding.toml`
+    #[clap(long = "tag")]
+    tags: Vec<String>,
+
+    /// not the rules of this `@name`, can be repeated
+    #[clap(long = "skip-rule")]
+    skip_rules: Vec<String>,
+
+    /// parses the files of an extension as a language, like `jsp=java`, can be repeated
+    #[clap(long = "language")]
+    extensions: Vec<String>,
+}
+
+#[derive(Clap)]
+struct CheckOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    /// `text` (`json` with `--stdin`), `json`, `sarif`, `junit`, `html`, `gitlab`, `teamcity`, `mermaid` or `csv`
+    #[clap(short, long)]
+    format: Option<String>,
+
+    /// writes the report to this file instead of the terminal
+    #[clap(short, long)]
+    output: Option<String>,
+
+    /// checks again on every change of the sources or rules, printing the new and fixed violations
+    #[clap(short, long)]
+    watch: bool,
+
+    /// the least severity failing the check: `error` (the default), `warning` or `info`
+    #[clap(long)]
+    fail_on: Option<String>,
+
+    /// fails only with more violations than this, to adopt rules gradually
+    #[clap(long)]
+    max_violations: Option<usize>,
+
+    /// checks the content of stdin as the file given by `--path`, for editors
+    #[clap(long, requires = "stdin-path")]
+    stdin: bool,
+
+    /// where the content of stdin is from, the language is detected by its extension
+    #[clap(long = "path", name = "stdin-path")]
+    stdin_path: Option<String>,
+
+    /// only the files git sees as changed and the packages around them, for pre-commit hooks
+    #[clap(long)]
+    changed: bool,
+
+    /// with `--changed`, the files changed since the branch left this one too, like `main`
+    #[clap(long, requires = "changed")]
+    base: Option<String>,
+
+    /// only the violations the commits since the branch left this revision add, like `origin/main` for a pull request
+    #[clap(long)]
+    diff_base: Option<String>,
+
+    /// prints how long scanning, parsing, resolving and running the rules took
+    #[clap(long)]
+    timings: bool,
+
+    /// adds a name following the rule to each violation of a naming rule
+    #[clap(long)]
+    suggest: bool,
+
+    #[clap(flatten)]
+    grouping: GroupOpts,
+}
+
+/// the order of the violations, the same in every format
+#[derive(Clap)]
+struct GroupOpts {
+    /// `rule` (the default), `file`, `package` or `severity`
+    #[clap(long)]
+    group_by: Option<String>,
+
+    /// the groups by `key`, like the file path, or the most violations first with `count`
+    #[clap(long, default_value = "key")]
+    sort: String,
+}
+
+impl GroupOpts {
+    fn apply(&self, report: CheckReport) -> Result<CheckReport, String> {
+        let sort = SortOrder::from_name(&self.sort).ok_or_else(|| format!("unknown sort {:?}, expected key or count", self.sort))?;
+        let by = match &self.group_by {
+            Some(name) => GroupBy::from_name(name).ok_or_else(|| format!("unknown group {:?}, expected one of: {}", name, GroupBy::names().join(", ")))?,
+            None if sort == SortOrder::Count => GroupBy::Rule,
+            None => return Ok(report)
+        };
+        Ok(group_report(report, by, sort))
+    }
+}
+
+#[derive(Clap)]
+struct ReportOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    #[clap(short, long, default_value = "html")]
+    format: String,
+
+    #[clap(short, long, default_value = "guarding-report.html")]
+    output: String,
+
+    #[clap(flatten)]
+    grouping: GroupOpts,
+}
+
+#[derive(Clap)]
+struct QueryOpts {
+    query: String,
+
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    /// `text` or `json`
+    #[clap(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Clap)]
+struct ReplOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+}
+
+#[derive(Clap)]
+struct LspOpts {}
+
+#[derive(Clap)]
+struct DaemonOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+}
+
+#[derive(Clap)]
+struct ServeOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+    #[clap(long, default_value = "127.0.0.1")]
+    host: String,
+    #[clap(long, default_value = "7878")]
+    port: u16,
+    /// checks the project again on a request once the last check is this many seconds old
+    #[clap(long, default_value = "60")]
+    refresh_secs: u64,
+}
+
+#[derive(Clap)]
+struct ImportOpts {
+    /// `import-linter`, `dependency-cruiser` or `deptrac`
+    tool: String,
+
+    /// its config, like `.importlinter`, `pyproject.toml`, `.dependency-cruiser.json` or `deptrac.yaml`
+    file: String,
+
+    /// the rule file to write, instead of stdout
+    #[clap(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Clap)]
+struct ExportOpts {
+    /// `dependency-cruiser` or `archunit`
+    tool: String,
+
+    #[clap(default_value = "guarding.guarding")]
+    rules: String,
+
+    /// the package of the ArchUnit test class and of the classes it checks
+    #[clap(long, default_value = "")]
+    package: String,
+
+    /// the name of the ArchUnit test class
+    #[clap(long, default_value = "GuardingArchitectureTest")]
+    class_name: String,
+
+    /// the config to write, instead of stdout
+    #[clap(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Clap)]
+struct GraphOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    /// `dot` or `mermaid`
+    #[clap(short, long, default_value = "dot")]
+    format: String,
+
+    /// `package` or `class`
+    #[clap(long, default_value = "package")]
+    level: String,
+
+    /// only the dependencies of the packages or classes matching this, like `..service..`
+    #[clap(long)]
+    scope: Option<String>,
+
+    #[clap(short, long)]
+    output: Option<String>,
+}
+
+#[derive(Clap)]
+struct WhyOpts {
+    /// the package or class depending, a class by its name when only one has it
+    from: String,
+
+    /// the package or class depended on
+    to: String,
+
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    /// `package` or `class`, by default packages when both are one
+    #[clap(long)]
+    level: Option<String>,
+
+    #[clap(long, default_value = "10")]
+    max_paths: usize,
+}
+
+#[derive(Clap)]
+struct StatsOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    /// `text`, `json` or `csv`, a row per package
+    #[clap(short, long, default_value = "text")]
+    format: String,
+
+    /// how many of the largest classes and functions
+    #[clap(long, default_value = "10")]
+    top: usize,
+}
+
+#[derive(Clap)]
+struct BaselineOpts {
+    #[clap(subcommand)]
+    subcmd: BaselineCommand,
+}
+
+#[derive(Clap)]
+enum BaselineCommand {
+    /// writes every current violation into the baseline
+    Generate(BaselineFileOpts),
+    /// checks like `guarding check`, failing only on the violations not in the baseline
+    Check(BaselineCheckOpts),
+    /// removes the violations which were fixed from the baseline
+    Update(BaselineFileOpts),
+}
+
+#[derive(Clap)]
+struct FitnessOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    #[clap(long, default_value = HISTORY_FILE)]
+    history: String,
+
+    /// only prints the score and the trend, without adding the run to the history
+    #[clap(long)]
+    dry_run: bool,
+
+    /// how many of the latest runs the history keeps
+    #[clap(long, default_value = "200")]
+    keep: usize,
+
+    /// `text` or `json`, the run and the history
+    #[clap(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Clap)]
+struct BaselineFileOpts {
+    #[clap(flatten)]
+    source: SourceOpts,
+
+    #[clap(long, default_value = BASELINE_FILE)]
+    baseline: String,
+}
+
+#[derive(Clap)]
+struct BaselineCheckOpts {
+    #[clap(flatten)]
+    file: BaselineFileOpts,
+
+    #[clap(short, long, default_value = "text")]
+    format: String,
+
+    #[clap(short, long)]
+    output: Option<String>,
+
+    #[clap(flatten)]
+    grouping: GroupOpts,
+}
+
+#[derive(Clap)]
+struct CacheOpts {
+    #[clap(subcommand)]
+    subcmd: CacheCommand,
+}
+
+#[derive(Clap)]
+enum CacheCommand {
+    /// prints the location, size and hit rate of the cache
+    Status(CacheStatusOpts),
+    /// removes every cached model, like after the cache broke
+    Clear(CacheClearOpts),
+}
+
+#[derive(Clap)]
+struct CacheStatusOpts {
+    #[clap(long)]
+    config: Option<String>,
+
+    /// `text` or `json`
+    #[clap(short, long, default_value = "text")]
+    format: String,
+}
+
+#[derive(Clap)]
+struct CacheClearOpts {
+    #[clap(long)]
+    config: Option<String>,
+}
+
+#[derive(Clap)]
+struct PacksOpts {
+    #[clap(long)]
+    config: Option<String>,
+
+    /// fetches the packs already checked out again, for packs following a branch
+    #[clap(long)]
+    update: bool,
+}
+
+#[derive(Clap)]
+struct RulesOpts {
+    #[clap(subcommand)]
+    subcmd: RulesCommand,
+}
+
+#[derive(Clap)]
+enum RulesCommand {
+    /// every rule of the rule files with its level, scope, severity and tags
+    List(RulesListOpts),
+}
+
+#[derive(Clap)]
+struct RulesListOpts {
+    /// instead of the rule files of `guarding.toml`
+    #[clap(short, long)]
+    rules: Option<String>,
+
+    #[clap(long)]
+    config: Option<String>,
+
+    #[clap(long)]
+    profile: Option<String>,
+
+    /// `table` or `json`
+    #[clap(short, long, default_value = "table")]
+    format: String,
+}
+
+#[derive(Clap)]
+struct InitOpts {
+    #[clap(default_value = "guarding.guarding")]
+    rules: String,
+
+    /// the sources to look at
+    #[clap(short, long, default_value = ".")]
+    path: String,
+}
+
+#[derive(Clap)]
+struct ValidateOpts {
+    #[clap(default_value = "guarding.guarding")]
+    rules: String,
+}
+
+/// How the command line is run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Invocation {
+    /// as `guarding`
+    Direct,
+    /// as `cargo guarding`, which takes the config and the roots of the cargo workspace
+    Cargo,
+}
+
+/// set by `run`, for the config to be discovered like the invocation wants
+static CARGO_SUBCOMMAND: AtomicBool = AtomicBool::new(false);
+
+/// runs the command line `args`, the program first, and returns the exit code; a usage error
+/// exits with the help of clap
+pub fn run(args: Vec<String>, invocation: Invocation) -> i32 {
+    let args = match invocation {
+        Invocation::Direct => args,
+        Invocation::Cargo => cargo_args(args),
+    };
+    CARGO_SUBCOMMAND.store(invocation == Invocation::Cargo, Ordering::Relaxed);
+
+    let opts = Opts::parse_from(args);
+    init_logging();
+    let mode = OutputMode::of(&opts);
+
+    match opts.subcmd {
+        Some(SubCommand::Check(check_opts)) => run_check(check_opts, mode),
+        Some(SubCommand::Init(init_opts)) => run_init(init_opts, mode),
+        Some(SubCommand::Validate(validate_opts)) => run_validate(validate_opts, mode),
+        Some(SubCommand::Report(report_opts)) => run_report(report_opts, mode),
+        Some(SubCommand::Query(query_opts)) => run_query_command(query_opts),
+        Some(SubCommand::Repl(repl_opts)) => run_repl(repl_opts, mode),
+        Some(SubCommand::Graph(graph_opts)) => run_graph(graph_opts),
+        Some(SubCommand::Why(why_opts)) => run_why(why_opts),
+        Some(SubCommand::Stats(stats_opts)) => run_stats(stats_opts),
+        Some(SubCommand::Fitness(fitness_opts)) => run_fitness(fitness_opts, mode),
+        Some(SubCommand::Baseline(baseline_opts)) => match baseline_opts.subcmd {
+            BaselineCommand::Generate(file_opts) => run_baseline_generate(file_opts, mode),
+            BaselineCommand::Check(check_opts) => run_baseline_check(check_opts, mode),
+            BaselineCommand::Update(file_opts) => run_baseline_update(file_opts, mode),
+        },
+        Some(SubCommand::Cache(cache_opts)) => match cache_opts.subcmd {
+            CacheCommand::Status(status_opts) => run_cache_status(status_opts),
+            CacheCommand::Clear(clear_opts) => run_cache_clear(clear_opts, mode),
+        },
+        Some(SubCommand::Packs(packs_opts)) => run_packs(packs_opts),
+        Some(SubCommand::Rules(rules_opts)) => match rules_opts.subcmd {
+            RulesCommand::List(list_opts) => run_rules_list(list_opts),
+        },
+        Some(SubCommand::Lsp(_)) => run_lsp(),
+        Some(SubCommand::Daemon(daemon_opts)) => run_daemon(daemon_opts),
+        Some(SubCommand::Serve(serve_opts)) => run_serve(serve_opts),
+        Some(SubCommand::Import(import_opts)) => run_import(import_opts),
+        Some(SubCommand::Export(export_opts)) => run_export(export_opts, mode),
+        // the flags before subcommands existed, kept for scripts using them
+        None => {
+            let buf = PathBuf::from(opts.path);
+            let conf = PathBuf::from(opts.config);
+            let content = fs::read_to_string(conf).unwrap();
+
+            let errors = exec_guarding(content, buf);
+            let content = serde_json::to_string_pretty(&errors).unwrap();
+            let _ = fs::write(opts.output, content);
+            0
+        }
+    }
+}
+
+fn read_rules(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|err| format!("can't read rules {}: {}", path, err))
+}
+
+fn output_format(name: &str) -> Result<OutputFormat, String> {
+    OutputFormat::from_name(name)
+        .ok_or_else(|| format!("unknown format {:?}, expected one of: {}", name, OutputFormat::names().join(", ")))
+}
+
+/// the config from the working directory, or of the cargo workspace for `cargo guarding`
+fn discover_config() -> Result<GuardingConfig, String> {
+    if !CARGO_SUBCOMMAND.load(Ordering::Relaxed) {
+        return GuardingConfig::discover_from_cwd();
+    }
+
+    let cwd = env::current_dir().map_err(|err| format!("{}", err))?;
+    CargoWorkspace::discover(&cwd)?.config(&cwd)
+}
+
+fn load_config(opts: &SourceOpts) -> Result<GuardingConfig, String> {
+    let mut config = match &opts.config {
+        Some(path) => GuardingConfig::load(Path::new(path))?,
+        None => discover_config()?
+    };
+    if let Some(profile) = &opts.profile {
+        config = config.with_profile(profile)?;
+    }
+    config.exclude.extend(opts.exclude.clone());
+    if !opts.tags.is_empty() {
+        config.tags = opts.tags.clone();
+    }
+    for mapping in &opts.extensions {
+        match mapping.split_once('=') {
+            Some((extension, language)) => config.map_extension(extension, language)?,
+            None => return Err(format!("--language {:?} isn't like jsp=java", mapping))
+        }
+    }
+    config.only_rules.extend(opts.only_rules.clone());
+    config.skip_rules.extend(opts.skip_rules.clone());
+    if opts.jobs.is_some() {
+        config.jobs = opts.jobs;
+    }
+    config.use_jobs()?;
+    Ok(config)
+}
+
+fn roots_of(opts: &SourceOpts) -> Option<Vec<PathBuf>> {
+    opts.path.as_ref().map(|path| vec![PathBuf::from(path)])
+}
+
+fn build_report(config: &GuardingConfig, opts: &SourceOpts) -> Result<CheckReport, String> {
+    build_report_timed(config, opts, &mut PhaseTimings::default(), &|_, _| {})
+}
+
+fn build_report_timed(config: &GuardingConfig, opts: &SourceOpts, timings: &mut PhaseTimings, progress: &(dyn Fn(usize, usize) + Sync)) -> Result<CheckReport, String> {
+    let rules = load_rules(config, opts)?;
+    let roots = roots_of(opts).unwrap_or_else(|| config.source_roots());
+    let project = config.build_project_timed(&roots, timings, progress);
+    Ok(timings.time("rules", || check_project(rules, project)))
+}
+
+/// parsing fewer files is too fast for a progress bar
+const PROGRESS_MIN_FILES: usize = 500;
+
+/// a bar on stderr for larger projects, redrawn every percent and cleared at the end
+fn parse_progress(shown: &AtomicBool, mode: OutputMode) -> impl Fn(usize, usize) + Sync + '_ {
+    let enabled = mode.progress();
+    move |parsed, total| {
+        if !enabled || total < PROGRESS_MIN_FILES {
+            return;
+        }
+
+        shown.store(true, Ordering::Relaxed);
+        if parsed == total {
+            eprint!("\r{}\r", " ".repeat(60));
+        } else if parsed % (total / 100).max(1) == 0 {
+            eprint!("\r{}", progress_bar(parsed, total, 30));
+        }
+    }
+}
+
+fn check_stdin(config: &GuardingConfig, opts: &SourceOpts, path: &Path) -> Result<CheckReport, String> {
+    let mut code = String::new();
+    io::stdin().read_to_string(&mut code).map_err(|err| format!("can't read stdin: {}", err))?;
+
+    check_source(&config.registry(), load_rules(config, opts)?, path, &code)
+}
+
+fn check_changed(config: &GuardingConfig, opts: &SourceOpts, base: Option<&str>) -> Result<CheckReport, String> {
+    let dir = opts.path.clone().unwrap_or_else(|| ".".to_string());
+    let changed = ChangedFiles::from_git(Path::new(&dir), base)?;
+    let rules = load_rules(config, opts)?;
+    if changed.is_empty() {
+        return Ok(CheckReport { rules, errors: vec![], files: vec![] });
+    }
+
+    let roots = roots_of(opts).unwrap_or_else(|| config.source_roots());
+    Ok(changed.restrict(check_project(rules, changed.build_project(config, &roots))))
+}
+
+/// the rules of `--rules` or `guarding.toml`, with its severities
+fn load_rules(config: &GuardingConfig, opts: &SourceOpts) -> Result<Vec<GuardRule>, String> {
+    match &opts.rules {
+        // YAML and TOML rule files too, like the ones of `guarding.toml`
+        Some(rules) => Ok(config.load_rule_files(&[PathBuf::from(rules)])?.into_iter().map(|(_, rule)| rule).collect()),
+        None => Ok(config.load_rules()?.into_iter().map(|(_, rule)| rule).collect())
+    }
+}
+
+/// What to print besides the reports, and how, the same in every command.
+#[derive(Clone, Copy)]
+struct OutputMode {
+    quiet: bool,
+    no_color: bool,
+    machine: bool,
+}
+
+impl OutputMode {
+    fn of(opts: &Opts) -> OutputMode {
+        OutputMode { quiet: opts.quiet, no_color: opts.no_color || env::var_os("NO_COLOR").is_some(), machine: opts.machine }
+    }
+
+    /// colors for a terminal only
+    fn color(&self, output: &Option<String>) -> bool {
+        output.is_none() && !self.no_color && !self.machine && atty::is(atty::Stream::Stdout)
+    }
+
+    fn progress(&self) -> bool {
+        !self.quiet && !self.machine && !self.no_color && atty::is(atty::Stream::Stderr)
+    }
+
+    /// the text report is the one shortened, the others are read by tools anyway
+    fn render(&self, format: &OutputFormat, report: &CheckReport, output: &Option<String>) -> String {
+        match format {
+            OutputFormat::Text if self.quiet => format!("{}\n", report.summary()),
+            OutputFormat::Text if self.machine => diagnostic::render_machine(report),
+            _ => format.render_colored(report, self.color(output))
+        }
+    }
+
+    fn note(&self, text: &str) {
+        if !self.quiet {
+            println!("{}", text);
+        }
+    }
+}
+
+fn write_output(output: &Option<String>, content: String) -> Result<(), String> {
+    match output {
+        Some(output) => fs::write(output, content).map_err(|err| format!("can't write {}: {}", output, err)),
+        None => {
+            print!("{}", content);
+            Ok(())
+        }
+    }
+}
+
+fn run_check(opts: CheckOpts, mode: OutputMode) -> i32 {
+    if opts.watch {
+        let session = load_config(&opts.source)
+            .and_then(|config| WatchSession::new(config, roots_of(&opts.source), opts.source.rules.as_ref().map(PathBuf::from)));
+        return match session.and_then(watch) {
+            Ok(_) => 0,
+            Err(err) => {
+                eprintln!("{}", err);
+                2
+            }
+        };
+    }
+
+    let config = match load_config(&opts.source) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCodes::default().rule_error;
+        }
+    };
+    let codes = config.exit_codes;
+
+    // a panic still prints its message, but ends with the code of internal errors
+    let checked = panic::catch_unwind(AssertUnwindSafe(|| check_with_opts(&config, &opts, mode)));
+    match checked {
+        Ok(Ok((report, policy))) => {
+            let code = codes.of_report(&report, &policy);
+            if report.fails(&policy) {
+                if let Some(max_violations) = policy.max_violations {
+                    eprintln!("{} violations, over the limit of {}", report.failing_count(&policy), max_violations);
+                }
+            } else if code != 0 {
+                eprintln!("{} files with parse errors", report.unparsed_count());
+            }
+            code
+        }
+        Ok(Err(err)) => {
+            eprintln!("{}", err);
+            codes.rule_error
+        }
+        Err(_) => codes.internal_error
+    }
+}
+
+fn check_with_opts(config: &GuardingConfig, opts: &CheckOpts, mode: OutputMode) -> Result<(CheckReport, FailPolicy), String> {
+    let default_format = if opts.stdin { "json" } else { "text" };
+    let format = output_format(opts.format.as_deref().unwrap_or(default_format))?;
+    let mut policy = config.fail_policy();
+    if let Some(fail_on) = &opts.fail_on {
+        policy.fail_on = Severity::from_name(fail_on).ok_or_else(|| format!("unknown severity {:?} of --fail-on", fail_on))?;
+    }
+    if opts.max_violations.is_some() {
+        policy.max_violations = opts.max_violations;
+    }
+
+    let mut timings = PhaseTimings::default();
+    let shown = AtomicBool::new(false);
+    let report = match &opts.stdin_path {
+        Some(path) if opts.stdin => check_stdin(config, &opts.source, Path::new(path))?,
+        _ if opts.changed => check_changed(config, &opts.source, opts.base.as_deref())?,
+        // `--path` or `--rules` check a single project of a monorepo
+        _ if !config.projects.is_empty() && opts.source.path.is_none() && opts.source.rules.is_none() => check_projects(config)?,
+        _ => build_report_timed(config, &opts.source, &mut timings, &parse_progress(&shown, mode))?
+    };
+    let report = match &opts.diff_base {
+        Some(base) => timings.time("diff-base", || new_since(config, &opts.source, base, report))?,
+        None => report
+    };
+    if opts.timings {
+        eprint!("{}", timings.to_text());
+    } else if shown.load(Ordering::Relaxed) {
+        eprintln!("{}", timings.summary());
+    }
+    let mut report = opts.grouping.apply(report)?;
+    if opts.suggest {
+        add_suggestions(&mut report);
+    }
+    write_output(&opts.output, mode.render(&format, &report, &opts.output))?;
+    Ok((report, policy))
+}
+
+/// the violations of `report` the base revision doesn't have, checked with the same rules
+fn new_since(config: &GuardingConfig, opts: &SourceOpts, base: &str, report: CheckReport) -> Result<CheckReport, String> {
+    let roots = roots_of(opts).unwrap_or_else(|| config.source_roots());
+    let diff_base = DiffBase::from_git(&config.dir, base)?;
+    let base_report = diff_base.check(config, &roots, load_rules(config, opts)?)?;
+    Ok(DiffBase::new_violations(&base_report, report))
+}
+
+fn run_report(opts: ReportOpts, mode: OutputMode) -> i32 {
+    let written = output_format(&opts.format).and_then(|format| {
+        let report = opts.grouping.apply(build_report(&load_config(&opts.source)?, &opts.source)?)?;
+        write_output(&Some(opts.output.clone()), format.render(&report))
+    });
+
+    match written {
+        Ok(_) => {
+            mode.note(&format!("wrote {}", opts.output));
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_query_command(opts: QueryOpts) -> i32 {
+    let queried = load_config(&opts.source).and_then(|config| {
+        let roots = roots_of(&opts.source).unwrap_or_else(|| config.source_roots());
+        let matches = run_query(&opts.query, config.build_project(&roots))?;
+        match opts.format.as_str() {
+            "text" => Ok(matches.iter().map(|found| format!("{}\n", found.to_text())).collect::<String>()),
+            "json" => Ok(format!("{}\n", serde_json::to_string_pretty(&matches).unwrap())),
+            _ => Err(format!("unknown format {:?}, expected text or json", opts.format))
+        }
+    });
+
+    match queried {
+        Ok(text) => {
+            print!("{}", text);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_repl(opts: ReplOpts, mode: OutputMode) -> i32 {
+    let session = load_config(&opts.source).map(|config| ReplSession::new(config, roots_of(&opts.source)));
+    match session.and_then(|session| repl(session, mode.color(&None))) {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_lsp() -> i32 {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    match serve(&mut stdin.lock(), &mut stdout.lock()) {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_daemon(opts: DaemonOpts) -> i32 {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    let served = load_config(&opts.source)
+        .and_then(|config| WatchSession::new(config, roots_of(&opts.source), opts.source.rules.as_ref().map(PathBuf::from)))
+        .and_then(|session| daemon::serve(session, &mut stdin.lock(), &mut stdout.lock()));
+    match served {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_serve(opts: ServeOpts) -> i32 {
+    let address = format!("{}:{}", opts.host, opts.port);
+    let served = load_config(&opts.source)
+        .and_then(|config| WatchSession::new(config, roots_of(&opts.source), opts.source.rules.as_ref().map(PathBuf::from)))
+        .and_then(|session| server::serve(HttpServer::new(session, Duration::from_secs(opts.refresh_secs)), &address));
+    match served {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_import(opts: ImportOpts) -> i32 {
+    let imported = import_file(&opts.tool, Path::new(&opts.file))
+        .and_then(|imported| write_output(&opts.output, imported.to_guarding()));
+    match imported {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_export(opts: ExportOpts, mode: OutputMode) -> i32 {
+    let exported = read_rules(&opts.rules)
+        .and_then(|content| parse_rule_file(Path::new(&opts.rules), &content))
+        .and_then(|rules| {
+            let options = ExportOptions { package: opts.package.clone(), class_name: opts.class_name.clone(), source: opts.rules.clone() };
+            export_rules(&opts.tool, &rules, &options)
+        });
+    let exported = exported.and_then(|exported| {
+        // on stderr, the config can be on stdout
+        if !mode.quiet {
+            for skipped in &exported.skipped {
+                eprintln!("skipped, {} has no equivalent: {}", opts.tool, skipped);
+            }
+        }
+        write_output(&opts.output, exported.content)
+    });
+    match exported {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_graph(opts: GraphOpts) -> i32 {
+    let exported = load_config(&opts.source).and_then(|config| {
+        let level = GraphLevel::from_name(&opts.level).ok_or_else(|| format!("unknown level {:?}, expected package or class", opts.level))?;
+        let render: fn(&ArchGraph) -> String = match opts.format.as_str() {
+            "dot" => ArchGraph::to_dot,
+            "mermaid" => ArchGraph::to_mermaid,
+            _ => return Err(format!("unknown format {:?}, expected dot or mermaid", opts.format))
+        };
+
+        let report = graph_report(&config, &opts.source)?;
+        let graph = ArchGraph::from_report(&report, level, opts.scope.as_deref());
+        write_output(&opts.output, render(&graph))
+    });
+
+    match exported {
+        Ok(_) => 0,
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+/// without rules there is nothing to highlight, but still a graph
+fn graph_report(config: &GuardingConfig, opts: &SourceOpts) -> Result<CheckReport, String> {
+    let rules = match &opts.rules {
+        Some(_) => Some(load_rules(config, opts)?),
+        None => config.load_rules().ok().map(|rules| rules.into_iter().map(|(_, rule)| rule).collect())
+    };
+    let roots = roots_of(opts).unwrap_or_else(|| config.source_roots());
+    let project = config.build_project(&roots);
+    Ok(match rules {
+        Some(rules) => check_project(rules, project),
+        None => CheckReport { rules: vec![], errors: vec![], files: project.files }
+    })
+}
+
+fn run_why(opts: WhyOpts) -> i32 {
+    let explained = load_config(&opts.source).and_then(|config| {
+        let report = graph_report(&config, &opts.source)?;
+        let graph = match opts.level.as_deref() {
+            Some(name) => {
+                let level = GraphLevel::from_name(name).ok_or_else(|| format!("unknown level {:?}, expected package or class", name))?;
+                ArchGraph::from_report(&report, level, None)
+            }
+            None => {
+                let packages = ArchGraph::from_report(&report, GraphLevel::Package, None);
+                if packages.find_node(&opts.from).is_some() && packages.find_node(&opts.to).is_some() {
+                    packages
+                } else {
+                    ArchGraph::from_report(&report, GraphLevel::Class, None)
+                }
+            }
+        };
+
+        let from = graph.find_node(&opts.from).ok_or_else(|| format!("no package or class {:?}, or more than one ending with it", opts.from))?;
+        let to = graph.find_node(&opts.to).ok_or_else(|| format!("no package or class {:?}, or more than one ending with it", opts.to))?;
+        Ok((graph.shortest_paths(from, to, opts.max_paths), graph.violated.clone(), from.to_string(), to.to_string()))
+    });
+
+    let (paths, violated, from, to) = match explained {
+        Ok(explained) => explained,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+    if paths.is_empty() {
+        println!("{} doesn't depend on {}", from, to);
+        return 1;
+    }
+
+    println!("{} shortest paths of {} steps from {} to {}:", paths.len(), paths[0].len() - 1, from, to);
+    for path in &paths {
+        println!();
+        println!("{}", path[0]);
+        for step in path.windows(2) {
+            if violated.contains(&(step[0].clone(), step[1].clone())) {
+                println!("  -> {}  (violates an access rule)", step[1]);
+            } else {
+                println!("  -> {}", step[1]);
+            }
+        }
+    }
+    0
+}
+
+fn run_stats(opts: StatsOpts) -> i32 {
+    let printed = load_config(&opts.source).and_then(|config| {
+        let roots = roots_of(&opts.source).unwrap_or_else(|| config.source_roots());
+        let stats = ProjectStats::of_files(&config.build_project(&roots).files, opts.top);
+        match opts.format.as_str() {
+            "text" => Ok(stats.to_text()),
+            "json" => Ok(format!("{}\n", serde_json::to_string_pretty(&stats).unwrap())),
+            "csv" => Ok(csv::render_stats(&stats)),
+            _ => Err(format!("unknown format {:?}, expected text, json or csv", opts.format))
+        }
+    });
+
+    match printed {
+        Ok(text) => {
+            print!("{}", text);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_fitness(opts: FitnessOpts, mode: OutputMode) -> i32 {
+    let scored = load_config(&opts.source).and_then(|config| {
+        let run = RunSummary::of_report(&build_report(&config, &opts.source)?);
+        let path = Path::new(&opts.history);
+        let mut history = FitnessHistory::load(path)?;
+        let text = match opts.format.as_str() {
+            "text" => history.trend_of(&run),
+            "json" => format!("{}\n", serde_json::to_string_pretty(&serde_json::json!({ "run": run, "history": history.runs })).unwrap()),
+            _ => return Err(format!("unknown format {:?}, expected text or json", opts.format))
+        };
+
+        if !opts.dry_run {
+            history.push(run, opts.keep);
+            history.save(path)?;
+        }
+        Ok(text)
+    });
+
+    match scored {
+        Ok(text) => {
+            print!("{}", text);
+            if !opts.dry_run {
+                mode.note(&format!("added the run to {}", opts.history));
+            }
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_baseline_generate(opts: BaselineFileOpts, mode: OutputMode) -> i32 {
+    let generated = load_config(&opts.source).and_then(|config| {
+        let baseline = Baseline::of_report(&build_report(&config, &opts.source)?);
+        baseline.save(Path::new(&opts.baseline))?;
+        Ok(baseline.entries.len())
+    });
+
+    match generated {
+        Ok(count) => {
+            mode.note(&format!("wrote {} violations to {}", count, opts.baseline));
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_baseline_check(opts: BaselineCheckOpts, mode: OutputMode) -> i32 {
+    let config = match load_config(&opts.file.source) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCodes::default().rule_error;
+        }
+    };
+    let codes = config.exit_codes;
+
+    let checked = panic::catch_unwind(AssertUnwindSafe(|| {
+        let format = output_format(&opts.format)?;
+        let baseline = Baseline::load(Path::new(&opts.file.baseline))?;
+        let report = opts.grouping.apply(baseline.filter(build_report(&config, &opts.file.source)?))?;
+        write_output(&opts.output, mode.render(&format, &report, &opts.output))?;
+        Ok::<_, String>(report)
+    }));
+
+    match checked {
+        Ok(Ok(report)) => codes.of_report(&report, &config.fail_policy()),
+        Ok(Err(err)) => {
+            eprintln!("{}", err);
+            codes.rule_error
+        }
+        Err(_) => codes.internal_error
+    }
+}
+
+fn run_baseline_update(opts: BaselineFileOpts, mode: OutputMode) -> i32 {
+    let updated = load_config(&opts.source).and_then(|config| {
+        let path = Path::new(&opts.baseline);
+        let (baseline, pruned) = Baseline::load(path)?.prune(&build_report(&config, &opts.source)?);
+        baseline.save(path)?;
+        Ok((baseline.entries.len(), pruned))
+    });
+
+    match updated {
+        Ok((count, pruned)) => {
+            mode.note(&format!("removed {} fixed violations, {} left in {}", pruned, count, opts.baseline));
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn model_cache(config: &Option<String>) -> Result<ModelCache, String> {
+    let config = match config {
+        Some(path) => GuardingConfig::load(Path::new(path))?,
+        None => discover_config()?
+    };
+    config.model_cache().ok_or_else(|| "no cache_dir in guarding.toml, nothing is cached".to_string())
+}
+
+fn run_cache_status(opts: CacheStatusOpts) -> i32 {
+    let status = model_cache(&opts.config).and_then(|cache| {
+        let status = cache.status();
+        match opts.format.as_str() {
+            "text" => Ok(status.to_text()),
+            "json" => Ok(format!("{}\n", serde_json::to_string_pretty(&status).unwrap())),
+            _ => Err(format!("unknown format {:?}, expected text or json", opts.format))
+        }
+    });
+
+    match status {
+        Ok(text) => {
+            print!("{}", text);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_cache_clear(opts: CacheClearOpts, mode: OutputMode) -> i32 {
+    let cleared = model_cache(&opts.config).and_then(|cache| cache.clear().map(|count| (count, cache.dir().display().to_string())));
+    match cleared {
+        Ok((count, dir)) => {
+            mode.note(&format!("removed {} cached models from {}", count, dir));
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_packs(opts: PacksOpts) -> i32 {
+    let config = match &opts.config {
+        Some(path) => GuardingConfig::load(Path::new(path)),
+        None => discover_config()
+    };
+    let listed = config.and_then(|config| {
+        let mut text = String::new();
+        for (name, checkout) in config.fetch_packs(opts.update)? {
+            let pack = &config.packs[&name];
+            let files = pack.rule_files(&checkout)?;
+            text.push_str(&format!("{}  {}@{}  {}  {} rule files\n", name, pack.git,
+                                   pack.git_ref.as_deref().unwrap_or("HEAD"), RulePack::revision(&checkout)?, files.len()));
+        }
+        Ok(text)
+    });
+
+    match listed {
+        Ok(text) => {
+            print!("{}", text);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_rules_list(opts: RulesListOpts) -> i32 {
+    let listed = load_config(&SourceOpts {
+        path: None,
+        rules: None,
+        config: opts.config.clone(),
+        exclude: vec![],
+        jobs: None,
+        profile: opts.profile.clone(),
+        only_rules: vec![],
+        tags: vec![],
+        skip_rules: vec![],
+        extensions: vec![],
+    })
+        .and_then(|mut config| {
+            if let Some(rules) = &opts.rules {
+                config.rules = vec![rules.clone()];
+                config.dir = PathBuf::from(".");
+            }
+            config.load_rules()
+        })
+        .and_then(|rules| {
+            let entries = list_rules(&rules);
+            match opts.format.as_str() {
+                "table" => Ok(format!("{}\n{}", to_table(&entries), to_sources(&entries))),
+                "json" => Ok(format!("{}\n", serde_json::to_string_pretty(&entries).unwrap())),
+                _ => Err(format!("unknown format {:?}, expected table or json", opts.format))
+            }
+        });
+
+    match listed {
+        Ok(text) => {
+            print!("{}", text);
+            0
+        }
+        Err(err) => {
+            eprintln!("{}", err);
+            2
+        }
+    }
+}
+
+fn run_init(opts: InitOpts, mode: OutputMode) -> i32 {
+    if Path::new(&opts.rules).exists() {
+        eprintln!("{} already exists", opts.rules);
+        return 2;
+    }
+
+    let survey = ProjectSurvey::of_dir(Path::new(&opts.path));
+    match fs::write(&opts.rules, survey.starter_rules()) {
+        Ok(_) => {
+            mode.note(&format!("created {}", opts.rules));
+            0
+        }
+        Err(err) => {
+            eprintln!("can't write {}: {}", opts.rules, err);
+            2
+        }
+    }
+}
+
+fn run_validate(opts: ValidateOpts, mode: OutputMode) -> i32 {
+    let issues = match read_rules(&opts.rules).and_then(|content| lint_rule_file(Path::new(&opts.rules), &content)) {
+        Ok(issues) => issues,
+        Err(err) => {
+            eprintln!("{}", err);
+            return 2;
+        }
+    };
+
+    if !mode.quiet {
+        for issue in &issues {
+            println!("{}", issue.to_text());
+        }
+    }
+
+    let errors = issues.iter().filter(|issue| issue.severity == Severity::Error).count();
+    println!("{}: {} errors, {} warnings", opts.rules, errors, issues.len() - errors);
+    if errors > 0 { 1 } else { 0 }
+}
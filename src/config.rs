@@ -0,0 +1,661 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_ident::{LanguageRegistry, ModelBuilder, ProjectScanner, set_jobs};
+use guarding_ident::support::queries::set_query_dir;
+use guarding_parser::ast::{GuardRule, Severity};
+
+use crate::cache::{CacheCounts, ModelCache};
+use crate::packs::{namespace_rules, RulePack};
+use crate::report::{ExitCodes, FailPolicy};
+use crate::structured::parse_rule_file;
+use crate::timings::PhaseTimings;
+
+pub const CONFIG_FILE: &'static str = "guarding.toml";
+pub const RULES_FILE: &'static str = "guarding.guarding";
+
+/// `guarding.toml`, every path in it is relative to the directory of the file:
+///
+/// ```toml
+/// roots = ["src"]
+/// languages = ["java", "kotlin"]
+/// rules = ["guarding.guarding"]
+/// cache_dir = ".guarding"
+///
+/// [severity]
+/// service-naming = "warning"
+///
+/// [packs.clean]
+/// git = "https://github.com/acme/guarding-rules"
+/// ref = "v1.2.0"
+///
+/// [profiles.pre-commit]
+/// tags = ["naming"]
+///
+/// [exit_codes]
+/// violations = 1
+/// rule_error = 3
+/// ```
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct GuardingConfig {
+    /// source roots, the directory of the config when empty
+    pub roots: Vec<String>,
+    /// gitignore-like globs of excluded paths, like `generated/`
+    pub exclude: Vec<String>,
+    /// languages to analyze, like `java` or `typescript`, all of them when empty
+    pub languages: Vec<String>,
+    /// rule files, every `*.guarding` under the directory of the config when empty
+    pub rules: Vec<String>,
+    /// `@name` of a rule -> `error`, `warning` or `info`
+    pub severity: BTreeMap<String, String>,
+    /// parsed models of unchanged files are taken from here instead of parsing them again
+    pub cache_dir: Option<String>,
+    /// in bytes, the oldest cached models are removed over it
+    pub max_cache_size: Option<u64>,
+    /// overrides the built-in tree-sitter queries, see `GUARDING_QUERY_DIR`
+    pub query_dir: Option<String>,
+    /// files over this size in bytes are skipped
+    pub max_file_size: Option<u64>,
+    /// the least severity failing a check, `error` when not set
+    pub fail_on: Option<String>,
+    /// a check fails only with more violations than this
+    pub max_violations: Option<usize>,
+    /// threads parsing the files, one per core when not set
+    pub jobs: Option<usize>,
+    /// extension -> language, for files the languages don't know like `jsp = "java"`
+    pub extensions: BTreeMap<String, String>,
+    /// only the rules with one of these tags, all of them when empty
+    pub tags: Vec<String>,
+    /// only the rules with one of these `@name`s, all of them when empty
+    pub only_rules: Vec<String>,
+    /// the `@name`s of rules not to run
+    pub skip_rules: Vec<String>,
+    /// shared rule packs by name, checked after the rule files, see [`RulePack`]
+    pub packs: BTreeMap<String, RulePack>,
+    /// the project directories of a monorepo, checked each with its own `guarding.toml` over
+    /// this one, like `services/*` for every directory of `services`
+    pub projects: Vec<String>,
+    /// named variants of the settings, like `ci` or `strict`, chosen with `--profile`
+    pub profiles: BTreeMap<String, Profile>,
+    /// the exit codes of `guarding check`
+    pub exit_codes: ExitCodes,
+    /// where the config was found
+    #[serde(skip)]
+    pub dir: PathBuf,
+}
+
+impl Default for GuardingConfig {
+    fn default() -> Self {
+        GuardingConfig {
+            roots: vec![],
+            exclude: vec![],
+            languages: vec![],
+            rules: vec![],
+            severity: Default::default(),
+            cache_dir: None,
+            max_cache_size: None,
+            query_dir: None,
+            max_file_size: None,
+            fail_on: None,
+            max_violations: None,
+            jobs: None,
+            extensions: Default::default(),
+            tags: vec![],
+            only_rules: vec![],
+            skip_rules: vec![],
+            packs: Default::default(),
+            projects: vec![],
+            profiles: Default::default(),
+            exit_codes: Default::default(),
+            dir: PathBuf::from("."),
+        }
+    }
+}
+
+/// What a profile changes of the config, what it doesn't set stays.
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct Profile {
+    /// instead of the rule files of the config
+    pub rules: Vec<String>,
+    /// instead of the tags of the config
+    pub tags: Vec<String>,
+    /// on top of the severities of the config
+    pub severity: BTreeMap<String, String>,
+    pub fail_on: Option<String>,
+    pub max_violations: Option<usize>,
+}
+
+impl GuardingConfig {
+    /// the closest `guarding.toml` in `start` or its parents
+    pub fn discover(start: &Path) -> Option<PathBuf> {
+        let start = fs::canonicalize(start).ok()?;
+        start.ancestors()
+            .map(|dir| dir.join(CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
+
+    /// from the working directory, the defaults without a config file
+    pub fn discover_from_cwd() -> Result<GuardingConfig, String> {
+        let cwd = env::current_dir().map_err(|err| format!("{}", err))?;
+        match GuardingConfig::discover(&cwd) {
+            Some(path) => GuardingConfig::load(&path),
+            None => Ok(GuardingConfig { dir: cwd, ..Default::default() })
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<GuardingConfig, String> {
+        let content = fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+        let dir = path.parent().map(|dir| dir.to_path_buf()).unwrap_or_else(|| PathBuf::from("."));
+        GuardingConfig::from_toml(&content, dir)
+            .map_err(|err| format!("invalid {}: {}", path.display(), err))
+    }
+
+    pub fn from_toml(content: &str, dir: PathBuf) -> Result<GuardingConfig, String> {
+        let mut config: GuardingConfig = toml::from_str(content).map_err(|err| format!("{}", err))?;
+        config.dir = dir;
+
+        check_severities(&config.severity, config.fail_on.as_ref())?;
+        let mut registry = LanguageRegistry::default();
+        for (extension, language) in &config.extensions {
+            registry.map_extension(extension, language)?;
+        }
+        for (name, pack) in &config.packs {
+            if name.is_empty() || name.contains('/') {
+                return Err(format!("invalid rule pack name {:?}, it can't be empty or have a `/`", name));
+            }
+            if pack.git.is_empty() {
+                return Err(format!("the rule pack {:?} has no `git` URL", name));
+            }
+            // git would take them for options
+            if pack.git.starts_with('-') || pack.git_ref.as_deref().map(|git_ref| git_ref.starts_with('-')).unwrap_or(false) {
+                return Err(format!("the `git` and `ref` of the rule pack {:?} can't start with a `-`", name));
+            }
+        }
+        for (name, profile) in &config.profiles {
+            check_severities(&profile.severity, profile.fail_on.as_ref()).map_err(|err| format!("{}, in profile {:?}", err, name))?;
+        }
+
+        Ok(config)
+    }
+
+    /// the config with the settings of the profile `name`
+    pub fn with_profile(mut self, name: &str) -> Result<GuardingConfig, String> {
+        let profile = match self.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None => {
+                let names: Vec<&str> = self.profiles.keys().map(|name| name.as_str()).collect();
+                return Err(format!("unknown profile {:?}, the profiles are: {}", name, names.join(", ")));
+            }
+        };
+
+        if !profile.rules.is_empty() {
+            self.rules = profile.rules;
+        }
+        if !profile.tags.is_empty() {
+            self.tags = profile.tags;
+        }
+        self.severity.extend(profile.severity);
+        if profile.fail_on.is_some() {
+            self.fail_on = profile.fail_on;
+        }
+        if profile.max_violations.is_some() {
+            self.max_violations = profile.max_violations;
+        }
+        Ok(self)
+    }
+
+    pub fn source_roots(&self) -> Vec<PathBuf> {
+        if self.roots.is_empty() {
+            return vec![self.dir.clone()];
+        }
+
+        self.roots.iter().map(|root| self.dir.join(root)).collect()
+    }
+
+    pub fn rule_files(&self) -> Vec<PathBuf> {
+        if self.rules.is_empty() {
+            return self.discover_rule_files();
+        }
+
+        self.rules.iter().map(|rules| self.dir.join(rules)).collect()
+    }
+
+    /// the `*.guarding` files the scanner doesn't ignore but the packs and projects, `guarding.guarding` of the directory
+    /// first, then by depth and name; `guarding.guarding` still when there is none
+    fn discover_rule_files(&self) -> Vec<PathBuf> {
+        let registry = self.registry();
+        let main = self.dir.join(RULES_FILE);
+        let packs_dir = self.packs_dir();
+        let projects = self.project_dirs();
+        let mut files = self.scanner(&registry).scan_extension(&self.dir, "guarding");
+        files.retain(|path| !path.starts_with(&packs_dir) && !projects.iter().any(|project| path.starts_with(project)));
+        if files.is_empty() {
+            return vec![main];
+        }
+
+        files.sort_by_key(|path| (path != &main, path.components().count(), path.clone()));
+        files
+    }
+
+    /// the directories of `projects`, a `*` at the end is every directory in it, by name
+    pub fn project_dirs(&self) -> Vec<PathBuf> {
+        let mut dirs = vec![];
+        for project in &self.projects {
+            match project.strip_suffix('*') {
+                Some(parent) => {
+                    let mut children: Vec<PathBuf> = fs::read_dir(self.dir.join(parent)).into_iter()
+                        .flatten()
+                        .flatten()
+                        .map(|entry| entry.path())
+                        .filter(|path| path.is_dir())
+                        .collect();
+                    children.sort();
+                    dirs.extend(children);
+                }
+                None => dirs.push(self.dir.join(project)),
+            }
+        }
+        dirs
+    }
+
+    /// the config of the project in `dir`, its `guarding.toml` over this one: its rule files come
+    /// before the ones of the repo so its `@name`s replace theirs, its severities and packs replace
+    /// the ones of the same name, excluded paths and skipped rules add up, and what it doesn't set
+    /// is the repo's; the exit codes and `jobs` are always the repo's
+    pub fn for_project(&self, dir: &Path) -> Result<GuardingConfig, String> {
+        let path = dir.join(CONFIG_FILE);
+        let local = if path.is_file() {
+            GuardingConfig::load(&path)?
+        } else {
+            GuardingConfig { dir: dir.to_path_buf(), ..Default::default() }
+        };
+        if local.jobs.is_some() {
+            return Err(format!("{}: jobs is only read from the repo's config, the threads are sized once", path.display()));
+        }
+
+        let mut merged = local.clone();
+        merged.rules = local.existing_rule_files().into_iter()
+            .chain(self.existing_rule_files())
+            .map(|path| path.display().to_string())
+            .collect();
+        merged.exclude = self.exclude.iter().chain(&local.exclude).cloned().collect();
+        if merged.languages.is_empty() {
+            merged.languages = self.languages.clone();
+        }
+        merged.severity = self.severity.clone();
+        merged.severity.extend(local.severity);
+        let absolute = |path: &Option<String>| path.as_ref().map(|path| self.dir.join(path).display().to_string());
+        merged.cache_dir = local.cache_dir.or_else(|| absolute(&self.cache_dir));
+        merged.query_dir = local.query_dir.or_else(|| absolute(&self.query_dir));
+        merged.max_cache_size = local.max_cache_size.or(self.max_cache_size);
+        merged.max_file_size = local.max_file_size.or(self.max_file_size);
+        merged.fail_on = local.fail_on.or_else(|| self.fail_on.clone());
+        merged.max_violations = local.max_violations.or(self.max_violations);
+        merged.jobs = self.jobs;
+        merged.extensions = self.extensions.clone();
+        merged.extensions.extend(local.extensions);
+        if merged.tags.is_empty() {
+            merged.tags = self.tags.clone();
+        }
+        if merged.only_rules.is_empty() {
+            merged.only_rules = self.only_rules.clone();
+        }
+        merged.skip_rules = self.skip_rules.iter().chain(&local.skip_rules).cloned().collect();
+        merged.packs = self.packs.clone();
+        merged.packs.extend(local.packs);
+        merged.projects = vec![];
+        merged.exit_codes = self.exit_codes;
+        Ok(merged)
+    }
+
+    /// the rule files, the discovered ones only when they exist
+    fn existing_rule_files(&self) -> Vec<PathBuf> {
+        let files = self.rule_files();
+        if self.rules.is_empty() {
+            return files.into_iter().filter(|path| path.is_file()).collect();
+        }
+        files
+    }
+
+    pub fn cache_dir(&self) -> Option<PathBuf> {
+        self.cache_dir.as_ref().map(|dir| self.dir.join(dir))
+    }
+
+    pub fn model_cache(&self) -> Option<ModelCache> {
+        self.cache_dir().map(|dir| ModelCache::new(dir, self.max_cache_size))
+    }
+
+    /// the rules of every rule file then of the packs with their configured severity, in the
+    /// order `check` runs them
+    pub fn load_rules(&self) -> Result<Vec<(PathBuf, GuardRule)>, String> {
+        let mut loaded = self.load_rule_files(&self.rule_files())?;
+        self.load_pack_rules(&mut loaded)?;
+        Ok(loaded)
+    }
+
+    /// the rules of `paths` in their order, a `@name` of an earlier file wins over the same name
+    /// in a later one
+    pub fn load_rule_files(&self, paths: &[PathBuf]) -> Result<Vec<(PathBuf, GuardRule)>, String> {
+        let mut loaded: Vec<(PathBuf, GuardRule)> = vec![];
+        for path in paths {
+            self.load_rule_file(path, None, &mut loaded)?;
+        }
+
+        Ok(loaded)
+    }
+
+    /// adds the rules of the packs, fetched when they aren't yet; a rule of `loaded` wins over a
+    /// pack rule of the same `@name`
+    pub fn load_pack_rules(&self, loaded: &mut Vec<(PathBuf, GuardRule)>) -> Result<(), String> {
+        for (name, checkout) in self.fetch_packs(false)? {
+            for path in self.packs[&name].rule_files(&checkout).map_err(|err| format!("{}, in rule pack {:?}", err, name))? {
+                self.load_rule_file(&path, Some(&name), loaded)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the checkouts of the packs by name, `update` fetches the ones already checked out again
+    pub fn fetch_packs(&self, update: bool) -> Result<Vec<(String, PathBuf)>, String> {
+        let packs_dir = self.packs_dir();
+        self.packs.iter()
+            .map(|(name, pack)| pack.fetch(name, &packs_dir, update).map(|checkout| (name.clone(), checkout)))
+            .collect()
+    }
+
+    /// under `cache_dir`, `.guarding/packs` without one
+    pub fn packs_dir(&self) -> PathBuf {
+        self.cache_dir().unwrap_or_else(|| self.dir.join(".guarding")).join("packs")
+    }
+
+    fn load_rule_file(&self, path: &Path, pack: Option<&str>, loaded: &mut Vec<(PathBuf, GuardRule)>) -> Result<(), String> {
+        let content = fs::read_to_string(path).map_err(|err| format!("can't read rules {}: {}", path.display(), err))?;
+        let mut rules = parse_rule_file(path, &content).map_err(|err| format!("{}: {}", path.display(), err))?;
+        // the second rule would never run, like `guarding validate` tells
+        for (index, rule) in rules.iter().enumerate() {
+            if !rule.name.is_empty() && rules[..index].iter().any(|earlier| earlier.name == rule.name) {
+                return Err(format!("{}: duplicate rule name {:?}", path.display(), rule.name));
+            }
+        }
+        // named before the settings, so `skip_rules` and `severity` take the pack names
+        if let Some(pack) = pack {
+            namespace_rules(pack, &mut rules);
+        }
+        self.apply_rule_settings(&mut rules);
+
+        for rule in rules {
+            let earlier = loaded.iter().find(|(_, loaded)| !rule.name.is_empty() && loaded.name == rule.name).map(|(file, _)| file.clone());
+            match earlier {
+                Some(file) => debug_or_warn(pack, &rule.name, path, &file),
+                None => loaded.push((path.to_path_buf(), rule)),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// keeps the rules of `tags` and `only_rules` but not of `skip_rules`, and sets the
+    /// severities of `severity`
+    pub fn apply_rule_settings(&self, rules: &mut Vec<GuardRule>) {
+        if !self.tags.is_empty() {
+            rules.retain(|rule| rule.tags.iter().any(|tag| self.tags.contains(tag)));
+        }
+        if !self.only_rules.is_empty() {
+            rules.retain(|rule| self.only_rules.contains(&rule.name));
+        }
+        rules.retain(|rule| !self.skip_rules.contains(&rule.name));
+        for rule in rules.iter_mut() {
+            if let Some(severity) = self.severity.get(&rule.name).and_then(|name| Severity::from_name(name)) {
+                rule.severity = severity;
+            }
+        }
+    }
+
+    pub fn fail_policy(&self) -> FailPolicy {
+        FailPolicy {
+            fail_on: self.fail_on.as_ref().and_then(|name| Severity::from_name(name)).unwrap_or(Severity::Error),
+            max_violations: self.max_violations,
+        }
+    }
+
+    /// sizes the parsing threads by `jobs`, once per process and before parsing
+    pub fn use_jobs(&self) -> Result<(), String> {
+        match self.jobs {
+            Some(jobs) => set_jobs(jobs),
+            None => Ok(())
+        }
+    }
+
+    /// the languages, with the files of `extensions` mapped to theirs
+    pub fn registry(&self) -> LanguageRegistry {
+        let mut registry = LanguageRegistry::default();
+        for (extension, language) in &self.extensions {
+            if let Err(err) = registry.map_extension(extension, language) {
+                warn!("{}", err);
+            }
+        }
+        registry
+    }
+
+    /// like `extensions` in `guarding.toml`, failing for unknown languages
+    pub fn map_extension(&mut self, extension: &str, language: &str) -> Result<(), String> {
+        LanguageRegistry::default().map_extension(extension, language)?;
+        self.extensions.insert(extension.trim_start_matches('.').to_string(), language.to_string());
+        Ok(())
+    }
+
+    pub fn scanner<'a>(&self, registry: &'a LanguageRegistry) -> ProjectScanner<'a> {
+        let scanner = ProjectScanner::new(registry)
+            .with_languages(self.languages.clone())
+            .with_excludes(self.exclude.clone());
+
+        match self.max_file_size {
+            Some(max_file_size) => scanner.with_max_file_size(max_file_size),
+            None => scanner
+        }
+    }
+
+    pub(crate) fn use_query_dir(&self) {
+        set_query_dir(self.query_dir.as_ref().map(|dir| self.dir.join(dir)));
+    }
+
+    /// the models of every source root in one project
+    pub fn build_project(&self, roots: &[PathBuf]) -> CodeProject {
+        self.build_project_timed(roots, &mut PhaseTimings::default(), &|_, _| {})
+    }
+
+    /// timing the scan, parse and resolve phases, `progress` is called with the parsed and the
+    /// total files of a root
+    pub fn build_project_timed(&self, roots: &[PathBuf], timings: &mut PhaseTimings, progress: &(dyn Fn(usize, usize) + Sync)) -> CodeProject {
+        self.use_query_dir();
+        let registry = self.registry();
+        let scanner = self.scanner(&registry);
+        let cache = self.model_cache();
+        let mut counts = CacheCounts::default();
+
+        let mut files = vec![];
+        for root in roots {
+            let paths = timings.time("scan", || scanner.scan(root));
+            let mut models = timings.time("parse", || match &cache {
+                Some(cache) => cache.parse(&scanner, &paths, progress, &mut counts),
+                None => scanner.parse_with_progress(&paths, progress),
+            });
+            timings.time("resolve", || ModelBuilder::resolve_models(&registry, root, &mut models));
+            timings.files += models.len();
+            files.extend(models);
+        }
+
+        if let Some(cache) = &cache {
+            cache.record(&counts);
+        }
+        CodeProject::new(files)
+    }
+}
+
+/// replacing a pack rule is on purpose, the same name in two files of the project likely isn't
+fn debug_or_warn(pack: Option<&str>, name: &str, path: &Path, earlier: &Path) {
+    match pack {
+        Some(pack) => debug!("rule {:?} of the pack {} is replaced by {}", name, pack, earlier.display()),
+        None => warn!("rule {:?} of {} is already in {}, skipped", name, path.display(), earlier.display()),
+    }
+}
+
+fn check_severities(severity: &BTreeMap<String, String>, fail_on: Option<&String>) -> Result<(), String> {
+    for (name, severity) in severity {
+        if Severity::from_name(severity).is_none() {
+            return Err(format!("unknown severity {:?} of rule {:?}", severity, name));
+        }
+    }
+    if let Some(fail_on) = fail_on {
+        if Severity::from_name(fail_on).is_none() {
+            return Err(format!("unknown severity {:?} of fail_on", fail_on));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use crate::config::GuardingConfig;
+    use crate::parse_rules;
+    use crate::report::{ExitCodes, FailPolicy};
+
+    #[test]
+    fn should_read_config() {
+        let content = "roots = [\"src\", \"lib\"]
+languages = [\"java\"]
+cache_dir = \".guarding\"
+fail_on = \"warning\"
+max_violations = 10
+jobs = 2
+
+[exit_codes]
+parse_error = 3
+
+[extensions]
+jsp = \"java\"
+
+[severity]
+service-naming = \"warning\"
+";
+        let config = GuardingConfig::from_toml(content, PathBuf::from("project")).unwrap();
+
+        assert_eq!(vec![PathBuf::from("project/src"), PathBuf::from("project/lib")], config.source_roots());
+        assert_eq!(vec![PathBuf::from("project/guarding.guarding")], config.rule_files());
+        assert_eq!(Some(PathBuf::from("project/.guarding")), config.cache_dir());
+        assert_eq!(FailPolicy { fail_on: Severity::Warning, max_violations: Some(10) }, config.fail_policy());
+        assert_eq!(Some(2), config.jobs);
+        assert_eq!(ExitCodes { parse_error: 3, ..Default::default() }, config.exit_codes);
+        assert_eq!("java", config.registry().detect(Path::new("web/order.jsp")).unwrap().name());
+        assert!(GuardingConfig::from_toml("[extensions]\njsp = \"cobol\"", PathBuf::from(".")).is_err());
+
+        let mut rule = GuardRule::default();
+        rule.name = "service-naming".to_string();
+        let mut rules = vec![rule, GuardRule::default()];
+        config.apply_rule_settings(&mut rules);
+        assert_eq!(Severity::Warning, rules[0].severity);
+        assert_eq!(Severity::Error, rules[1].severity);
+    }
+
+    #[test]
+    fn should_apply_profile() {
+        let content = "rules = [\"guarding.guarding\"]
+fail_on = \"error\"
+
+[profiles.pre-commit]
+tags = [\"naming\"]
+
+[profiles.strict]
+rules = [\"guarding.guarding\", \"strict.guarding\"]
+fail_on = \"warning\"
+
+[profiles.strict.severity]
+enough-files = \"error\"
+";
+        let config = GuardingConfig::from_toml(content, PathBuf::from(".")).unwrap();
+        assert!(config.clone().with_profile("nightly").is_err());
+
+        let strict = config.clone().with_profile("strict").unwrap();
+        assert_eq!(2, strict.rule_files().len());
+        assert_eq!(Severity::Warning, strict.fail_policy().fail_on);
+
+        let pre_commit = config.with_profile("pre-commit").unwrap();
+        let mut rules = parse_rules("@tag(\"naming\")
+class::name should endsWith \"Service\";
+package(\".\")::file.len should < 200;").unwrap();
+        pre_commit.apply_rule_settings(&mut rules);
+        assert_eq!(1, rules.len());
+        assert_eq!(vec!["naming".to_string()], rules[0].tags);
+    }
+
+    #[test]
+    fn should_discover_rule_files() {
+        let dir = std::env::temp_dir().join("guarding").join("discover");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("rules/web")).unwrap();
+        fs::create_dir_all(dir.join("generated")).unwrap();
+        fs::write(dir.join(".guardingignore"), "generated/\n").unwrap();
+        fs::write(dir.join("generated/old.guarding"), "class::name should endsWith \"Old\";").unwrap();
+        fs::write(dir.join("rules/web/naming.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Controller\";").unwrap();
+        fs::write(dir.join("rules/layers.guarding"), "package(\".\")::file.len should < 200;").unwrap();
+        fs::write(dir.join("guarding.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Service\";").unwrap();
+
+        let config = GuardingConfig::from_toml("", dir.clone()).unwrap();
+        assert_eq!(vec![dir.join("guarding.guarding"), dir.join("rules/layers.guarding"), dir.join("rules/web/naming.guarding")], config.rule_files());
+
+        let rules = config.load_rules().unwrap();
+        assert_eq!(2, rules.len());
+        assert_eq!(dir.join("guarding.guarding"), rules[0].0);
+        assert!(rules[0].1.origin.contains("Service"));
+
+        fs::write(dir.join("rules/layers.guarding"), "@name(\"size\")\nclass::name should endsWith \"Service\";\n@name(\"size\")\npackage(\".\")::file.len should < 200;").unwrap();
+        let err = config.load_rules().unwrap_err();
+        assert!(err.contains("layers.guarding: duplicate rule name \"size\""));
+    }
+
+    #[test]
+    fn should_reject_unknown_severity() {
+        let content = "[severity]\nnaming = \"fatal\"\n";
+        assert!(GuardingConfig::from_toml(content, PathBuf::from(".")).is_err());
+    }
+
+    #[test]
+    fn should_filter_rules_by_name() {
+        let rules = || parse_rules("@name(\"service-naming\")
+@tag(\"naming\")
+class::name should endsWith \"Service\";
+@name(\"controller-naming\")
+@tag(\"naming\")
+class::name should endsWith \"Controller\";
+@name(\"enough-files\")
+package(\".\")::file.len should < 200;").unwrap();
+
+        let mut config = GuardingConfig::default();
+        config.tags = vec!["naming".to_string()];
+        config.skip_rules = vec!["service-naming".to_string()];
+        let mut kept = rules();
+        config.apply_rule_settings(&mut kept);
+        assert_eq!(vec!["controller-naming"], kept.iter().map(|rule| rule.name.as_str()).collect::<Vec<&str>>());
+
+        let mut config = GuardingConfig::default();
+        config.only_rules = vec!["enough-files".to_string(), "service-naming".to_string()];
+        let mut kept = rules();
+        config.apply_rule_settings(&mut kept);
+        assert_eq!(vec!["service-naming", "enough-files"], kept.iter().map(|rule| rule.name.as_str()).collect::<Vec<&str>>());
+    }
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+use std::sync::Arc;
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_core::rule_executor::RuleErrorMsg;
+use guarding_core::rule_executor::rule_error::MismatchType;
+use guarding_parser::ast::{GuardRule, RuleLevel, Severity};
+
+use crate::config::GuardingConfig;
+use crate::handler::{hand_over, ViolationHandler};
+use crate::report::{CheckReport, Violation};
+
+/// A check written in Rust for what the DSL can't express, run with the rules of the DSL and in
+/// the same reports. Its violations only need a message and their items, see `Violation::new`.
+pub trait CustomRule: Send + Sync {
+    /// like the `@name` of a rule, for `--rule`, `[severity]` and the reports
+    fn name(&self) -> &str;
+
+    /// what it checks, for the listings
+    fn level(&self) -> RuleLevel {
+        RuleLevel::Class
+    }
+
+    /// unless `[severity]` of the config sets another one
+    fn severity(&self) -> Severity {
+        Severity::Error
+    }
+
+    /// for `--tag`
+    fn tags(&self) -> Vec<String> {
+        vec![]
+    }
+
+    fn evaluate(&self, project: &CodeProject) -> Vec<Violation>;
+}
+
+/// The registered custom rules, see `Guarding::with_custom_rule`.
+#[derive(Clone, Default)]
+pub struct CustomRules {
+    rules: Vec<Arc<dyn CustomRule>>,
+}
+
+impl fmt::Debug for CustomRules {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
+
+/// what the custom rules found, until it's added to the report of the other rules
+pub struct CustomResults {
+    results: Vec<(GuardRule, Vec<Violation>)>,
+}
+
+impl CustomRules {
+    pub fn register(&mut self, rule: impl CustomRule + 'static) {
+        self.rules.push(Arc::new(rule));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    pub fn names(&self) -> Vec<&str> {
+        self.rules.iter().map(|rule| rule.name()).collect()
+    }
+
+    /// runs the rules `config` doesn't filter out, with its severities
+    pub fn evaluate(&self, config: &GuardingConfig, project: &CodeProject) -> CustomResults {
+        let mut results = vec![];
+        for custom in &self.rules {
+            let mut rules = vec![GuardRule {
+                origin: format!("custom rule {}", custom.name()),
+                name: custom.name().to_string(),
+                severity: custom.severity(),
+                tags: custom.tags(),
+                level: custom.level(),
+                ..Default::default()
+            }];
+            config.apply_rule_settings(&mut rules);
+            if let Some(rule) = rules.pop() {
+                results.push((rule, custom.evaluate(project)));
+            }
+        }
+
+        CustomResults { results }
+    }
+}
+
+impl CustomResults {
+    /// adds the rules after the ones of `report`, handing their violations to `handler` rule by rule
+    pub fn append_to(self, report: &mut CheckReport, handler: &mut dyn ViolationHandler) {
+        for (rule, violations) in self.results {
+            let index = report.rules.len();
+            report.rules.push(rule);
+
+            let errors = to_errors(index, violations);
+            hand_over(report, index, &errors, handler);
+            report.errors.extend(errors);
+        }
+    }
+}
+
+/// the violations with the same message as one error with their items, like the rules of the DSL
+fn to_errors(index: usize, violations: Vec<Violation>) -> Vec<RuleErrorMsg> {
+    let mut errors: Vec<RuleErrorMsg> = vec![];
+    for violation in violations {
+        let item = match violation.item {
+            Some(item) => item,
+            None => {
+                let mut error = RuleErrorMsg::new(MismatchType::None, index);
+                error.msg = violation.message;
+                errors.push(error);
+                continue;
+            }
+        };
+
+        let position = errors.iter().position(|error| error.msg == violation.message && !error.items.is_empty());
+        let error = match position {
+            Some(position) => &mut errors[position],
+            None => {
+                let mut error = RuleErrorMsg::new(MismatchType::None, index);
+                error.msg = violation.message;
+                errors.push(error);
+                errors.last_mut().unwrap()
+            }
+        };
+        error.push_item(item, violation.location.unwrap_or_default());
+        error.suggestions.push(violation.suggestion.unwrap_or_default());
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::code_class::CodeClass;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_project::CodeProject;
+    use guarding_parser::ast::Severity;
+
+    use crate::config::GuardingConfig;
+    use crate::custom::{CustomRule, CustomRules};
+    use crate::report::{CheckReport, Violation};
+
+    struct NoGodClasses;
+
+    impl CustomRule for NoGodClasses {
+        fn name(&self) -> &str {
+            "no-god-classes"
+        }
+
+        fn evaluate(&self, project: &CodeProject) -> Vec<Violation> {
+            project.files.iter()
+                .flat_map(|file| file.classes.iter())
+                .filter(|class| class.functions.len() + class.fields.len() > 1)
+                .map(|class| Violation::new("too many members").with_item(class.name.clone(), None))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn should_add_custom_rules_to_reports() {
+        let mut file = CodeFile::default();
+        file.classes.push(CodeClass { name: "Orders".to_string(), ..Default::default() });
+        file.classes[0].fields = vec![Default::default(), Default::default()];
+        let project = CodeProject::new(vec![file]);
+
+        let mut custom = CustomRules::default();
+        custom.register(NoGodClasses);
+        let config = GuardingConfig::from_toml("[severity]\nno-god-classes = \"warning\"", std::env::temp_dir()).unwrap();
+
+        let mut report = CheckReport { rules: vec![Default::default()], errors: vec![], files: vec![] };
+        let mut found = vec![];
+        custom.evaluate(&config, &project).append_to(&mut report, &mut |violation: &Violation| found.push(violation.clone()));
+
+        assert_eq!(1, found.len());
+        let violations = report.violations();
+        assert_eq!(1, violations.len());
+        assert_eq!("no-god-classes", violations[0].rule);
+        assert_eq!(1, violations[0].rule_index);
+        assert_eq!(Severity::Warning, violations[0].severity);
+        assert_eq!(Some("Orders".to_string()), violations[0].item);
+
+        let config = GuardingConfig::from_toml("skip_rules = [\"no-god-classes\"]", std::env::temp_dir()).unwrap();
+        let mut report = CheckReport { rules: vec![], errors: vec![], files: vec![] };
+        custom.evaluate(&config, &project).append_to(&mut report, &mut |_: &Violation| {});
+        assert!(report.rules.is_empty());
+    }
+}
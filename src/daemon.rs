@@ -0,0 +1,158 @@
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+
+use serde_json::{json, Value};
+use tracing::debug;
+
+use crate::format::json::JsonReport;
+use crate::lsp::{read_message, write_message};
+use crate::query::run_query;
+use crate::watch::{ViolationDelta, WatchSession};
+
+const INVALID_PARAMS: i64 = -32602;
+const METHOD_NOT_FOUND: i64 = -32601;
+/// a request which was understood but failed, like a rule file which doesn't parse anymore
+const REQUEST_FAILED: i64 = -32000;
+
+/// Answers JSON-RPC requests on a project parsed once, so a check after an edit only re-parses
+/// the changed files:
+///
+/// - `check`, with the `changed` paths since the last request, returns the report like
+///   `guarding check --format json` with the `added` and `fixed` violations
+/// - `refresh` re-reads the rules and every source root, when it's unknown what changed
+/// - `query` returns what a scope like `class(resideIn "..service..")` selects
+/// - `shutdown`, then `exit`
+pub struct Daemon {
+    session: WatchSession,
+}
+
+impl Daemon {
+    pub fn new(session: WatchSession) -> Daemon {
+        Daemon { session }
+    }
+
+    /// the answers to `message`, none for notifications; `None` after `exit`
+    pub fn handle(&mut self, message: &Value) -> Option<Vec<Value>> {
+        let method = message["method"].as_str().unwrap_or("");
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        debug!("daemon request {}", method);
+
+        let result = match method {
+            "check" => self.check(params),
+            "refresh" => self.session.refresh()
+                .map(|delta| self.report(&delta))
+                .map_err(|err| (REQUEST_FAILED, err)),
+            "query" => self.query(params),
+            "shutdown" => Ok(Value::Null),
+            "exit" => return None,
+            _ => Err((METHOD_NOT_FOUND, format!("unknown method {}", method))),
+        };
+
+        Some(match (id, result) {
+            (Some(id), Ok(result)) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            (Some(id), Err((code, message))) => vec![json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } })],
+            (None, _) => vec![]
+        })
+    }
+
+    fn check(&mut self, params: &Value) -> Result<Value, (i64, String)> {
+        let changed: Vec<PathBuf> = match &params["changed"] {
+            Value::Null => vec![],
+            Value::Array(paths) => paths.iter().filter_map(|path| path.as_str()).map(absolute).collect(),
+            _ => return Err((INVALID_PARAMS, "`changed` isn't a list of paths".to_string())),
+        };
+
+        let delta = self.session.changed(&changed).map_err(|err| (REQUEST_FAILED, err))?;
+        Ok(self.report(&delta.unwrap_or_default()))
+    }
+
+    fn query(&self, params: &Value) -> Result<Value, (i64, String)> {
+        let query = params["query"].as_str().ok_or_else(|| (INVALID_PARAMS, "no `query`".to_string()))?;
+        let matches = run_query(query, self.session.project()).map_err(|err| (INVALID_PARAMS, err))?;
+        Ok(json!(matches))
+    }
+
+    fn report(&self, delta: &ViolationDelta) -> Value {
+        let report = self.session.report();
+        let mut result = json!(JsonReport::from_report(report));
+        result["failed"] = json!(report.has_failures());
+        result["added"] = json!(delta.added);
+        result["fixed"] = json!(delta.fixed);
+        result
+    }
+}
+
+/// answers the `Content-Length` framed requests of `input`, like the language server, until
+/// `exit` or the end of the input
+pub fn serve(session: WatchSession, input: &mut dyn BufRead, output: &mut dyn Write) -> Result<(), String> {
+    let mut daemon = Daemon::new(session);
+    while let Some(message) = read_message(input)? {
+        let answers = match daemon.handle(&message) {
+            Some(answers) => answers,
+            None => return Ok(())
+        };
+        for answer in answers {
+            write_message(output, &answer)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// the session's roots are canonical, a removed file only has a canonical directory
+fn absolute(path: &str) -> PathBuf {
+    let path = Path::new(path);
+    if let Ok(path) = fs::canonicalize(path) {
+        return path;
+    }
+
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new(".")
+    };
+    match (fs::canonicalize(dir), path.file_name()) {
+        (Ok(dir), Some(name)) => dir.join(name),
+        _ => path.to_path_buf()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use serde_json::json;
+
+    use crate::config::GuardingConfig;
+    use crate::daemon::Daemon;
+    use crate::watch::WatchSession;
+
+    #[test]
+    fn should_check_and_query_a_warm_project() {
+        let dir = env::temp_dir().join("guarding").join("daemon");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src").join("OrderService.java"), "package shop.service;\nclass OrderService {}").unwrap();
+        fs::write(dir.join("guarding.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Service\";").unwrap();
+
+        let config = GuardingConfig { dir: dir.clone(), ..Default::default() };
+        let mut daemon = Daemon::new(WatchSession::new(config, Some(vec![dir.join("src")]), None).unwrap());
+
+        let order = dir.join("src").join("Order.java");
+        fs::write(&order, "package shop.service;\nclass Order {}").unwrap();
+        let answer = daemon.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "check", "params": { "changed": [order.display().to_string()] } })).unwrap().remove(0);
+        assert_eq!(1, answer["id"]);
+        assert_eq!(true, answer["result"]["failed"]);
+        assert_eq!(1, answer["result"]["added"].as_array().unwrap().len());
+        assert_eq!("naming", answer["result"]["violations"][0]["rule"]);
+
+        let answer = daemon.handle(&json!({ "jsonrpc": "2.0", "id": 2, "method": "query", "params": { "query": "class(resideIn \"..service..\")" } })).unwrap().remove(0);
+        assert_eq!(2, answer["result"].as_array().unwrap().len());
+
+        let answer = daemon.handle(&json!({ "jsonrpc": "2.0", "id": 3, "method": "format" })).unwrap().remove(0);
+        assert_eq!(-32601, answer["error"]["code"]);
+        assert!(daemon.handle(&json!({ "jsonrpc": "2.0", "method": "initialized" })).unwrap().is_empty());
+        assert!(daemon.handle(&json!({ "jsonrpc": "2.0", "method": "exit" })).is_none());
+    }
+}
@@ -0,0 +1,182 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use tracing::debug;
+
+use guarding_parser::ast::GuardRule;
+
+use crate::baseline::Baseline;
+use crate::changed::git;
+use crate::check_project;
+use crate::config::GuardingConfig;
+use crate::report::CheckReport;
+
+/// The revision a change set started from, like `origin/main` for a pull request, to report only
+/// the violations the change set adds.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DiffBase {
+    /// the top directory of the repository
+    top: PathBuf,
+    /// where the branch left the base
+    commit: String,
+    /// the old and new path of every file renamed since, relative to `top`
+    renames: Vec<(String, String)>,
+}
+
+impl DiffBase {
+    pub fn from_git(dir: &Path, base: &str) -> Result<DiffBase, String> {
+        let top = PathBuf::from(git(dir, &["rev-parse", "--show-toplevel"])?.trim());
+        let commit = git(&top, &["merge-base", base, "HEAD"])?.trim().to_string();
+        let renames = git(&top, &["diff", "--name-status", "-M", "--diff-filter=R", &commit])?
+            .lines()
+            .filter_map(|line| {
+                let mut parts = line.split('\t').skip(1);
+                Some((parts.next()?.to_string(), parts.next()?.to_string()))
+            })
+            .collect();
+
+        Ok(DiffBase { top, commit, renames })
+    }
+
+    /// checks `rules` on the roots at the base commit, in a worktree which is removed after;
+    /// files unchanged since are taken from the model cache. The paths of the report are the
+    /// ones of the roots, renamed files are at their new path.
+    pub fn check(&self, config: &GuardingConfig, roots: &[PathBuf], rules: Vec<GuardRule>) -> Result<CheckReport, String> {
+        let worktree = env::temp_dir()
+            .join("guarding-diff-base")
+            .join(format!("{}-{}", process::id(), &self.commit[..self.commit.len().min(12)]));
+        let _ = fs::remove_dir_all(&worktree);
+        if let Some(parent) = worktree.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("can't create {}: {}", parent.display(), err))?;
+        }
+
+        let mut mapped = vec![];
+        for root in roots {
+            let relative = fs::canonicalize(root).ok()
+                .and_then(|canonical| canonical.strip_prefix(&self.top).ok().map(|relative| relative.to_path_buf()))
+                .ok_or_else(|| format!("{} isn't in the git repository {}", root.display(), self.top.display()))?;
+            mapped.push((root.clone(), relative));
+        }
+
+        debug!("checking {} in {}", self.commit, worktree.display());
+        // created before the worktree, so a half-added one is removed too
+        let guard = Worktree { top: &self.top, dir: worktree.clone() };
+        git(&self.top, &["worktree", "add", "--detach", "--quiet", &worktree.display().to_string(), &self.commit])?;
+        let base_roots: Vec<PathBuf> = mapped.iter().map(|(_, relative)| worktree.join(relative)).collect();
+        let report = check_project(rules, config.build_project(&base_roots));
+        drop(guard);
+
+        let mut paths: Vec<(String, String)> = vec![];
+        for ((root, relative), base_root) in mapped.iter().zip(&base_roots) {
+            for (old, new) in &self.renames {
+                if let (Ok(old), Ok(new)) = (Path::new(old).strip_prefix(relative), Path::new(new).strip_prefix(relative)) {
+                    paths.push((base_root.join(old).display().to_string(), root.join(new).display().to_string()));
+                }
+            }
+        }
+        for ((root, _), base_root) in mapped.iter().zip(&base_roots) {
+            paths.push((base_root.display().to_string(), root.display().to_string()));
+        }
+        Ok(move_paths(report, &paths))
+    }
+
+    /// the violations of `report` which aren't in `base`, the report of the base; violations
+    /// are matched by fingerprint, so a violation moved to other lines isn't new
+    pub fn new_violations(base: &CheckReport, report: CheckReport) -> CheckReport {
+        Baseline::of_report(base).filter(report)
+    }
+}
+
+/// A worktree of the base, removed when dropped, also when the check panics.
+struct Worktree<'a> {
+    top: &'a Path,
+    dir: PathBuf,
+}
+
+impl Drop for Worktree<'_> {
+    fn drop(&mut self) {
+        let _ = git(self.top, &["worktree", "remove", "--force", &self.dir.display().to_string()]);
+        let _ = fs::remove_dir_all(&self.dir);
+        // forgets the worktree if `git worktree add` failed after registering it
+        let _ = git(self.top, &["worktree", "prune"]);
+    }
+}
+
+/// replaces the first of each `(from, to)` the items and locations start with, renamed files
+/// before their roots
+fn move_paths(mut report: CheckReport, paths: &[(String, String)]) -> CheckReport {
+    let move_path = |path: &str| -> String {
+        paths.iter()
+            .find(|(from, _)| path.starts_with(from.as_str()))
+            .map(|(from, to)| format!("{}{}", to, &path[from.len()..]))
+            .unwrap_or_else(|| path.to_string())
+    };
+
+    for error in report.errors.iter_mut() {
+        for item in error.items.iter_mut() {
+            *item = move_path(item);
+        }
+        for location in error.locations.iter_mut() {
+            location.path = move_path(&location.path);
+        }
+    }
+    for file in report.files.iter_mut() {
+        file.path = move_path(&file.path);
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::path::Path;
+
+    use crate::changed::git;
+    use crate::config::GuardingConfig;
+    use crate::diff_base::DiffBase;
+    use crate::{check_project, parse_rules};
+
+    fn commit(dir: &Path, message: &str) {
+        git(dir, &["add", "-A"]).unwrap();
+        git(dir, &["-c", "user.name=guarding", "-c", "user.email=guarding@example.com", "commit", "--quiet", "-m", message]).unwrap();
+    }
+
+    #[test]
+    fn should_report_only_the_violations_of_the_change_set() {
+        let dir = env::temp_dir().join("guarding").join("diff-base");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        git(&dir, &["init", "--quiet"]).unwrap();
+        fs::write(dir.join("src").join("Order.java"), "package shop;\nclass Order {}").unwrap();
+        commit(&dir, "order");
+        git(&dir, &["branch", "base"]).unwrap();
+
+        fs::create_dir_all(dir.join("src").join("orders")).unwrap();
+        fs::rename(dir.join("src").join("Order.java"), dir.join("src").join("orders").join("Order.java")).unwrap();
+        fs::write(dir.join("src").join("Cart.java"), "package shop;\nclass Cart {}").unwrap();
+        commit(&dir, "cart");
+
+        let rules = parse_rules("class::name should endsWith \"Service\";").unwrap();
+        let config = GuardingConfig { dir: dir.clone(), ..Default::default() };
+        let roots = vec![dir.join("src")];
+        let report = check_project(rules.clone(), config.build_project(&roots));
+        assert_eq!(2, report.violations().len());
+
+        let base = DiffBase::from_git(&dir, "base").unwrap();
+        assert_eq!(vec![("src/Order.java".to_string(), "src/orders/Order.java".to_string())], base.renames);
+        let base_report = base.check(&config, &roots, rules).unwrap();
+        assert_eq!(dir.join("src").join("orders").join("Order.java").display().to_string(), base_report.files[0].path);
+
+        let new = DiffBase::new_violations(&base_report, report);
+        let violations = new.violations();
+        assert_eq!(1, violations.len());
+        assert!(violations[0].item.as_ref().unwrap().contains("Cart"));
+        assert_eq!(1, git(&dir, &["worktree", "list"]).unwrap().lines().count());
+
+        let missing = DiffBase { commit: "0".repeat(40), ..base };
+        assert!(missing.check(&config, &roots, vec![]).is_err());
+        assert_eq!(1, git(&dir, &["worktree", "list"]).unwrap().lines().count());
+    }
+}
@@ -0,0 +1,173 @@
+use guarding_parser::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope, Severity};
+
+use crate::export::{ExportOptions, ExportedRules};
+use crate::import::slug;
+
+pub const TOOL: &str = "archunit";
+
+/// a JUnit 5 test class with an `@ArchTest` per rule ArchUnit has an equivalent for, the
+/// others as comments and skipped
+pub fn to_test_class(rules: &[GuardRule], options: &ExportOptions) -> ExportedRules {
+    let mut fields = String::new();
+    let mut skipped = vec![];
+    let mut names: Vec<String> = vec![];
+
+    for (index, rule) in rules.iter().enumerate() {
+        let mut name = if rule.name.is_empty() { format!("rule_{}", index + 1) } else { slug(&rule.name).replace('-', "_") };
+        if name.starts_with(|c: char| c.is_ascii_digit()) {
+            name = format!("rule_{}", name);
+        }
+        while names.contains(&name) {
+            name = format!("{}_{}", name, index + 1);
+        }
+
+        fields.push_str(&format!("\n    // guarding: {}\n", rule.origin));
+        match to_arch_rule(rule) {
+            Some(arch_rule) => {
+                if rule.severity != Severity::Error {
+                    fields.push_str(&format!("    // a {} in guarding, fails the tests here\n", rule.severity.name()));
+                }
+                let because = if rule.because.is_empty() { String::new() } else { format!("\n            .because({})", java_string(&rule.because)) };
+                fields.push_str(&format!("    @ArchTest\n    static final ArchRule {} = {}{};\n", name, arch_rule, because));
+                names.push(name);
+            }
+            None => {
+                fields.push_str("    // not exported, ArchUnit has no equivalent\n");
+                skipped.push(rule.origin.clone());
+            }
+        }
+    }
+
+    let mut content = String::new();
+    if !options.package.is_empty() {
+        content.push_str(&format!("package {};\n\n", options.package));
+    }
+    content.push_str("import com.tngtech.archunit.core.importer.ImportOption;
+import com.tngtech.archunit.junit.AnalyzeClasses;
+import com.tngtech.archunit.junit.ArchTest;
+import com.tngtech.archunit.lang.ArchRule;
+
+import static com.tngtech.archunit.lang.syntax.ArchRuleDefinition.classes;
+import static com.tngtech.archunit.lang.syntax.ArchRuleDefinition.noClasses;
+
+");
+    content.push_str(&format!("/** Generated by `guarding export archunit` from {}, change the rules there. */\n", options.source));
+    let packages = if options.package.is_empty() { String::new() } else { format!("packages = {}, ", java_string(&options.package)) };
+    content.push_str(&format!("@AnalyzeClasses({}importOptions = ImportOption.DoNotIncludeTests.class)\n", packages));
+    content.push_str(&format!("public class {} {{{}}}\n", options.class_name, fields));
+
+    ExportedRules { content, skipped }
+}
+
+/// the rule in the ArchUnit fluent API, `None` without an equivalent
+pub fn to_arch_rule(rule: &GuardRule) -> Option<String> {
+    let (negated, operator) = match rule.ops.as_slice() {
+        [Operator::Not, operator] => (true, operator),
+        [operator] => (false, operator),
+        _ => return None,
+    };
+    let is_name = rule.expr == Expr::PropsCall(vec!["name".to_string()]);
+    let is_class = rule.expr == Expr::Identifier("".to_string()) || rule.expr == Expr::PropsCall(vec![]);
+    if rule.level != RuleLevel::Class && rule.level != RuleLevel::Package {
+        return None;
+    }
+
+    match (operator, &rule.assert) {
+        (Operator::Accessed, RuleAssert::ArrayStringed(sources)) if is_class || rule.level == RuleLevel::Package => {
+            let target = match &rule.scope {
+                RuleScope::PathDefine(target) if target != "." => target,
+                _ => return None,
+            };
+            let sources: Vec<String> = sources.iter().map(|source| java_string(source)).collect();
+            Some(if negated {
+                format!("noClasses().that().resideInAnyPackage({})\n            .should().dependOnClassesThat().resideInAPackage({})",
+                        sources.join(", "), java_string(target))
+            } else {
+                format!("classes().that().resideInAPackage({})\n            .should().onlyBeAccessed().byAnyPackage({})",
+                        java_string(target), sources.join(", "))
+            })
+        }
+        (Operator::Endswith, RuleAssert::Stringed(text)) if is_name && rule.level == RuleLevel::Class => {
+            should(&rule.scope, if negated { "haveSimpleNameNotEndingWith" } else { "haveSimpleNameEndingWith" }, text)
+        }
+        (Operator::StartsWith, RuleAssert::Stringed(text)) if is_name && rule.level == RuleLevel::Class => {
+            should(&rule.scope, if negated { "haveSimpleNameNotStartingWith" } else { "haveSimpleNameStartingWith" }, text)
+        }
+        (Operator::Contains, RuleAssert::Stringed(text)) if is_name && rule.level == RuleLevel::Class => {
+            should(&rule.scope, if negated { "haveSimpleNameNotContaining" } else { "haveSimpleNameContaining" }, text)
+        }
+        (Operator::ResideIn, RuleAssert::Leveled(RuleLevel::Package, package)) | (Operator::ResideIn, RuleAssert::Stringed(package))
+        if is_class && rule.level == RuleLevel::Class => {
+            should(&rule.scope, if negated { "resideOutsideOfPackage" } else { "resideInAPackage" }, package)
+        }
+        _ => None,
+    }
+}
+
+/// `classes().that()...should().method(argument)`
+fn should(scope: &RuleScope, method: &str, argument: &str) -> Option<String> {
+    let that = match scope {
+        RuleScope::All => String::new(),
+        RuleScope::PathDefine(path) if path == "." => String::new(),
+        RuleScope::PathDefine(path) => format!(".that().resideInAPackage({})", java_string(path)),
+        RuleScope::Implementation(name) => format!(".that().implement({})", java_string(name)),
+        RuleScope::Extend(name) | RuleScope::Assignable(name) => format!(".that().areAssignableTo({})", java_string(name)),
+        RuleScope::MatchRegex(regex) => format!(".that().haveNameMatching({})", java_string(regex)),
+        RuleScope::Kind(kind) => {
+            let method = match kind.as_str() {
+                "interface" => "areInterfaces",
+                "!interface" => "areNotInterfaces",
+                "enum" => "areEnums",
+                "!enum" => "areNotEnums",
+                "annotation" => "areAnnotations",
+                "!annotation" => "areNotAnnotations",
+                _ => return None,
+            };
+            format!(".that().{}()", method)
+        }
+    };
+
+    Some(format!("classes(){}\n            .should().{}({})", that, method, java_string(argument)))
+}
+
+fn java_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::export::archunit::{to_arch_rule, to_test_class};
+    use crate::export::ExportOptions;
+    use crate::parse_rules;
+
+    #[test]
+    fn should_convert_rules_to_the_fluent_api() {
+        let rules = parse_rules("class(resideIn \"..service..\")::name should endsWith \"Service\";
+class(\"..domain..\") only accessed([\"..service..\", \"..domain..\"]);
+class(\"..web..\") should not accessed([\"..domain..\"]);
+class(implementation \"Repository\") should not resideIn package(\"..web..\");").unwrap();
+        let converted: Vec<String> = rules.iter().map(|rule| to_arch_rule(rule).unwrap().replace("\n            ", "")).collect();
+
+        assert_eq!(vec![
+            "classes().that().resideInAPackage(\"..service..\").should().haveSimpleNameEndingWith(\"Service\")",
+            "classes().that().resideInAPackage(\"..domain..\").should().onlyBeAccessed().byAnyPackage(\"..service..\", \"..domain..\")",
+            "noClasses().that().resideInAnyPackage(\"..domain..\").should().dependOnClassesThat().resideInAPackage(\"..web..\")",
+            "classes().that().implement(\"Repository\").should().resideOutsideOfPackage(\"..web..\")",
+        ], converted);
+    }
+
+    #[test]
+    fn should_write_a_test_class() {
+        let rules = parse_rules("@name(\"service-naming\")\n@because(\"found by suffix\")\nclass(resideIn \"..service..\")::name should endsWith \"Service\";
+class::len should < 20;").unwrap();
+        let options = ExportOptions { package: "com.shop".to_string(), class_name: "ArchitectureTest".to_string(), source: "guarding.guarding".to_string() };
+        let exported = to_test_class(&rules, &options);
+
+        assert!(exported.content.starts_with("package com.shop;"));
+        assert!(exported.content.contains("@AnalyzeClasses(packages = \"com.shop\", "));
+        assert!(exported.content.contains("public class ArchitectureTest {"));
+        assert!(exported.content.contains("static final ArchRule service_naming = classes()"));
+        assert!(exported.content.contains(".because(\"found by suffix\");"));
+        assert_eq!(vec!["class::len should < 20;"], exported.skipped);
+    }
+}
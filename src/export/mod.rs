@@ -0,0 +1,45 @@
+use guarding_parser::ast::GuardRule;
+
+use crate::import::dependency_cruiser;
+
+pub mod archunit;
+
+/// the tools `export_rules` writes the config of
+pub const TOOLS: [&str; 2] = [dependency_cruiser::SOURCE, archunit::TOOL];
+
+/// The config of another tool for the rules it can express, with the other rules.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ExportedRules {
+    pub content: String,
+    /// the origins of the rules the tool has no equivalent for
+    pub skipped: Vec<String>,
+}
+
+/// for the generated code, like the package and name of an ArchUnit test class
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportOptions {
+    /// empty for the default package
+    pub package: String,
+    pub class_name: String,
+    /// the rule file, mentioned in the generated code
+    pub source: String,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        ExportOptions {
+            package: "".to_string(),
+            class_name: "GuardingArchitectureTest".to_string(),
+            source: "guarding.guarding".to_string(),
+        }
+    }
+}
+
+/// the config of `tool` for `rules`, like a `.dependency-cruiser.json` or an ArchUnit test
+pub fn export_rules(tool: &str, rules: &[GuardRule], options: &ExportOptions) -> Result<ExportedRules, String> {
+    match tool {
+        "dependency-cruiser" => Ok(dependency_cruiser::to_config(rules)),
+        "archunit" => Ok(archunit::to_test_class(rules, options)),
+        _ => Err(format!("unknown tool `{}`, one of: {}", tool, TOOLS.join(", "))),
+    }
+}
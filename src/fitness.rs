@@ -0,0 +1,189 @@
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use guarding_parser::ast::Severity;
+
+use crate::report::CheckReport;
+use crate::stats::ProjectStats;
+
+pub const HISTORY_FILE: &'static str = "guarding-history.json";
+
+/// how much a violated rule weighs in the score by its severity
+fn weight_of(severity: Severity) -> f64 {
+    match severity {
+        Severity::Error => 3.0,
+        Severity::Warning => 2.0,
+        Severity::Info => 1.0,
+    }
+}
+
+/// The fitness of the architecture at one check, kept in the history to follow its trend.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct RunSummary {
+    /// seconds since the Unix epoch
+    pub timestamp: u64,
+    /// from 0 to 100, see [`RunSummary::of_report`]
+    pub score: f64,
+    pub rules: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+    pub files: usize,
+    pub cycles: usize,
+    /// of the packages of the project
+    pub average_instability: f64,
+}
+
+impl RunSummary {
+    /// the score is 70% the rules, 20% the cycles and 10% the stability of the packages: a rule
+    /// with `n` violations is `1 / (1 + n)` healthy, weighted 3, 2 or 1 by its severity, the
+    /// cycles are `1 / (1 + cycles)` and the stability is `1 - average instability`
+    pub fn of_report(report: &CheckReport) -> RunSummary {
+        let stats = ProjectStats::of_files(&report.files, 0);
+        let violations = report.violations();
+
+        let (mut healthy, mut total) = (0.0, 0.0);
+        for (index, rule) in report.rules.iter().enumerate() {
+            let count = violations.iter().filter(|violation| violation.rule_index == index).count();
+            let weight = weight_of(rule.severity);
+            healthy += weight / (1.0 + count as f64);
+            total += weight;
+        }
+        let rules_health = if report.rules.is_empty() { 1.0 } else { healthy / total };
+
+        let packages: Vec<f64> = stats.packages.iter()
+            .filter(|package| !package.name.is_empty())
+            .map(|package| package.instability)
+            .collect();
+        let average_instability = if packages.is_empty() { 0.0 } else { packages.iter().sum::<f64>() / packages.len() as f64 };
+        let cycles_health = 1.0 / (1.0 + stats.cycles.len() as f64);
+
+        let score = 100.0 * (0.7 * rules_health + 0.2 * cycles_health + 0.1 * (1.0 - average_instability));
+        let count_of = |severity: Severity| violations.iter().filter(|violation| violation.severity == severity).count();
+        RunSummary {
+            timestamp: SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs()).unwrap_or(0),
+            score: round(score),
+            rules: report.rules.len(),
+            errors: count_of(Severity::Error),
+            warnings: count_of(Severity::Warning),
+            infos: count_of(Severity::Info),
+            files: stats.files,
+            cycles: stats.cycles.len(),
+            average_instability: (average_instability * 100.0).round() / 100.0,
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        format!("fitness {:.1}: {} errors, {} warnings, {} infos, {} cycles, average instability {:.2}",
+                self.score, self.errors, self.warnings, self.infos, self.cycles, self.average_instability)
+    }
+}
+
+/// The summaries of the past checks, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct FitnessHistory {
+    pub version: u32,
+    pub runs: Vec<RunSummary>,
+}
+
+impl FitnessHistory {
+    /// an empty history when there is no file yet
+    pub fn load(path: &Path) -> Result<FitnessHistory, String> {
+        if !path.exists() {
+            return Ok(FitnessHistory { version: 1, runs: vec![] });
+        }
+
+        let content = fs::read_to_string(path).map_err(|err| format!("can't read history {}: {}", path.display(), err))?;
+        serde_json::from_str(&content).map_err(|err| format!("invalid history {}: {}", path.display(), err))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        let content = format!("{}\n", serde_json::to_string_pretty(self).unwrap());
+        fs::write(path, content).map_err(|err| format!("can't write history {}: {}", path.display(), err))
+    }
+
+    pub fn last(&self) -> Option<&RunSummary> {
+        self.runs.last()
+    }
+
+    /// keeps the `limit` latest runs when there are more
+    pub fn push(&mut self, run: RunSummary, limit: usize) {
+        self.runs.push(run);
+        if self.runs.len() > limit {
+            self.runs.drain(..self.runs.len() - limit);
+        }
+    }
+
+    /// `current` against the last run and the first one of the history
+    pub fn trend_of(&self, current: &RunSummary) -> String {
+        let mut text = format!("{}\n", current.to_text());
+        if let Some(last) = self.last() {
+            text.push_str(&format!("since the last run: {}\n", FitnessHistory::delta(last, current)));
+        }
+        if let (Some(first), true) = (self.runs.first(), self.runs.len() > 1) {
+            text.push_str(&format!("over {} runs: {}\n", self.runs.len() + 1, FitnessHistory::delta(first, current)));
+        }
+        text
+    }
+
+    fn delta(from: &RunSummary, to: &RunSummary) -> String {
+        let direction = if to.score > from.score {
+            "improving"
+        } else if to.score < from.score {
+            "eroding"
+        } else {
+            "stable"
+        };
+        format!("{} fitness {:+.1}, errors {:+}, warnings {:+}, cycles {:+}, average instability {:+.2}",
+                direction,
+                to.score - from.score,
+                to.errors as i64 - from.errors as i64,
+                to.warnings as i64 - from.warnings as i64,
+                to.cycles as i64 - from.cycles as i64,
+                to.average_instability - from.average_instability)
+    }
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10.0).round() / 10.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fitness::{FitnessHistory, RunSummary};
+    use crate::testing::check_sources;
+
+    #[test]
+    fn should_score_by_severity_and_violations() {
+        let rules = "class::name should endsWith \"Service\";\n@severity(\"info\")\nclass::name should startsWith \"Shop\";";
+        let clean = check_sources(rules, &[("OrderService.java", "class OrderService {\n}")]).unwrap();
+        let violated = check_sources(rules, &[("Order.java", "class Order {\n}"), ("Cart.java", "class Cart {\n}")]).unwrap();
+
+        let clean = RunSummary::of_report(&clean);
+        let violated = RunSummary::of_report(&violated);
+        assert_eq!(2, violated.errors);
+        assert_eq!(2, violated.infos);
+        assert!(violated.score < clean.score);
+        assert!(clean.score <= 100.0);
+    }
+
+    #[test]
+    fn should_report_the_trend_of_the_runs() {
+        let mut history = FitnessHistory { version: 1, runs: vec![] };
+        let first = RunSummary { score: 60.0, errors: 5, ..Default::default() };
+        history.push(first, 2);
+        history.push(RunSummary { score: 70.0, errors: 3, ..Default::default() }, 2);
+
+        let trend = history.trend_of(&RunSummary { score: 65.0, errors: 4, ..Default::default() });
+        assert!(trend.contains("since the last run: eroding fitness -5.0, errors +1"));
+        assert!(trend.contains("over 3 runs: improving fitness +5.0, errors -1"));
+
+        history.push(RunSummary { score: 65.0, ..Default::default() }, 2);
+        assert_eq!(2, history.runs.len());
+        assert_eq!(3, history.runs[0].errors);
+    }
+}
@@ -0,0 +1,91 @@
+use crate::format::relative_path;
+use crate::report::CheckReport;
+use crate::stats::ProjectStats;
+
+/// one row per violation, to load into spreadsheets; the fingerprint follows a violation
+/// between runs
+pub fn render(report: &CheckReport) -> String {
+    let mut csv = String::from("rule,severity,message,item,path,line,column,fingerprint\n");
+    for violation in report.violations() {
+        let (path, line, column) = match &violation.location {
+            Some(location) => (relative_path(&location.path), (location.start.row + 1).to_string(), (location.start.column + 1).to_string()),
+            None => (String::new(), String::new(), String::new())
+        };
+
+        push_row(&mut csv, &[
+            report.rule_id(violation.rule_index),
+            violation.severity.name().to_string(),
+            violation.message.clone(),
+            violation.item.clone().unwrap_or_default(),
+            path,
+            line,
+            column,
+            violation.fingerprint.clone(),
+        ]);
+    }
+    csv
+}
+
+/// one row per package
+pub fn render_stats(stats: &ProjectStats) -> String {
+    let mut csv = String::from("package,files,classes,abstract_classes,afferent,efferent,instability\n");
+    for package in &stats.packages {
+        push_row(&mut csv, &[
+            package.name.clone(),
+            package.files.to_string(),
+            package.classes.to_string(),
+            package.abstract_classes.to_string(),
+            package.afferent.to_string(),
+            package.efferent.to_string(),
+            format!("{:.2}", package.instability),
+        ]);
+    }
+    csv
+}
+
+fn push_row(csv: &mut String, cells: &[String]) {
+    let cells: Vec<String> = cells.iter().map(|cell| escape(cell)).collect();
+    csv.push_str(&cells.join(","));
+    csv.push('\n');
+}
+
+/// RFC 4180: quoted when it has a separator, quote or line break, quotes doubled
+fn escape(cell: &str) -> String {
+    if cell.contains(|c| c == ',' || c == '"' || c == '\n' || c == '\r') {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::GuardRule;
+
+    use crate::format::csv::render;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_render_violation_rows() {
+        let mut naming = GuardRule::default();
+        naming.name = "naming".to_string();
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        error.push_item("name: Order".to_string(), ErrorLocation {
+            path: "src/Order.java".to_string(),
+            start: CodePoint { row: 2, column: 4 },
+            end: CodePoint { row: 4, column: 1 },
+        });
+
+        let report = CheckReport { rules: vec![naming], errors: vec![error], files: vec![] };
+        let csv = render(&report);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!("rule,severity,message,item,path,line,column,fingerprint", lines[0]);
+        assert_eq!(format!("naming,error,\"endsWith: \"\"Service\"\"\",name: Order,src/Order.java,3,5,{}", report.violations()[0].fingerprint), lines[1]);
+    }
+}
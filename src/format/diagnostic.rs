@@ -0,0 +1,206 @@
+use guarding_core::rule_executor::rule_error::ErrorLocation;
+use guarding_parser::ast::Severity;
+
+use crate::format::{relative_path, SourceLines};
+use crate::report::{CheckReport, Violation};
+
+const RESET: &'static str = "\x1b[0m";
+const BOLD: &'static str = "\x1b[1m";
+const GUTTER: &'static str = "\x1b[1;34m";
+
+/// compiler-like diagnostics, one per violation with the violating code underlined:
+///
+/// ```text
+/// error[naming]: endsWith: "Service"
+///  --> src/Order.java:3:7
+///   |
+/// 3 | class Order {
+///   |       ^^^^^ name: Order
+///   |
+///   = rule: class::name should endsWith "Service";
+///   = because: services are found by name
+/// ```
+pub fn render(report: &CheckReport, color: bool) -> String {
+    let paint = Paint { color };
+    let mut sources = SourceLines::default();
+
+    let mut text = String::new();
+    for violation in report.violations() {
+        text.push_str(&render_violation(report, &violation, &paint, &mut sources));
+        text.push('\n');
+    }
+
+    text.push_str(&paint.apply(BOLD, &report.summary()));
+    text.push('\n');
+    text
+}
+
+/// one line per violation like `src/Order.java:3:7: error[naming]: endsWith: "Service" - name: Order`,
+/// for scripts and editors matching lines, never colored
+pub fn render_machine(report: &CheckReport) -> String {
+    let mut text = String::new();
+    for violation in report.violations() {
+        if let Some(location) = &violation.location {
+            text.push_str(&format!("{}:{}:{}: ", relative_path(&location.path), location.start.row + 1, location.start.column + 1));
+        }
+        text.push_str(&format!("{}[{}]: {}", violation.severity.name(), violation.rule, violation.message));
+        if let Some(item) = &violation.item {
+            text.push_str(&format!(" - {}", item));
+        }
+        if let Some(suggestion) = &violation.suggestion {
+            text.push_str(&format!(" (rename to {})", suggestion));
+        }
+        text.push('\n');
+    }
+
+    text.push_str(&report.summary());
+    text.push('\n');
+    text
+}
+
+fn render_violation(report: &CheckReport, violation: &Violation, paint: &Paint, sources: &mut SourceLines) -> String {
+    let severity = violation.severity;
+    let mut text = format!("{}{}\n",
+                           paint.apply(severity_color(severity), &format!("{}[{}]", severity.name(), violation.rule)),
+                           paint.apply(BOLD, &format!(": {}", violation.message)));
+
+    let lines = violation.location.as_ref().and_then(|location| sources.of(&location.path));
+    let row = violation.location.as_ref().map(|location| location.start.row + 1).unwrap_or(0);
+    let width = row.to_string().len();
+    let gutter = paint.apply(GUTTER, &format!("{} |", " ".repeat(width)));
+
+    if let Some(location) = &violation.location {
+        text.push_str(&format!("{}{} {}:{}:{}\n", " ".repeat(width), paint.apply(GUTTER, "-->"),
+                               relative_path(&location.path), location.start.row + 1, location.start.column + 1));
+    }
+
+    match (&violation.location, lines.and_then(|lines| lines.get(row.saturating_sub(1)))) {
+        (Some(location), Some(line)) => {
+            let (start, end) = underline_range(line, location);
+            text.push_str(&format!("{}\n", gutter));
+            text.push_str(&format!("{} {}\n", paint.apply(GUTTER, &format!("{} |", row)), line));
+            text.push_str(&format!("{} {}{} {}\n", gutter, " ".repeat(start),
+                                   paint.apply(severity_color(severity), &"^".repeat((end - start).max(1))),
+                                   violation.item.clone().unwrap_or_default()));
+        }
+        _ => {
+            if let Some(item) = &violation.item {
+                text.push_str(&format!("{} {}\n", gutter, item));
+            }
+        }
+    }
+
+    if let Some(rule) = report.rules.get(violation.rule_index) {
+        let note = paint.apply(GUTTER, &format!("{} =", " ".repeat(width)));
+        text.push_str(&format!("{}\n", gutter));
+        text.push_str(&format!("{} rule: {}\n", note, rule.origin));
+        if !rule.because.is_empty() {
+            text.push_str(&format!("{} because: {}\n", note, rule.because));
+        }
+        if let Some(suggestion) = &violation.suggestion {
+            text.push_str(&format!("{} help: rename to {}\n", note, suggestion));
+        }
+    }
+
+    text
+}
+
+/// in chars of the first line, till its end when the violation spans more lines
+fn underline_range(line: &str, location: &ErrorLocation) -> (usize, usize) {
+    let chars_before = |column: usize| {
+        let mut column = column.min(line.len());
+        while !line.is_char_boundary(column) {
+            column -= 1;
+        }
+        line[..column].chars().count()
+    };
+
+    let start = chars_before(location.start.column);
+    let end = if location.end.row > location.start.row { line.chars().count() } else { chars_before(location.end.column) };
+    (start, end.max(start))
+}
+
+fn severity_color(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "\x1b[1;31m",
+        Severity::Warning => "\x1b[1;33m",
+        Severity::Info => "\x1b[1;36m",
+    }
+}
+
+struct Paint {
+    color: bool,
+}
+
+impl Paint {
+    fn apply(&self, style: &str, text: &str) -> String {
+        if self.color {
+            format!("{}{}{}", style, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::GuardRule;
+
+    use crate::format::diagnostic::{render, render_machine};
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_underline_violating_code() {
+        let dir = env::temp_dir().join("guarding").join("diagnostic");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Order.java");
+        fs::write(&path, "package shop;\n\nclass Order {\n}\n").unwrap();
+
+        let mut naming = GuardRule::default();
+        naming.name = "naming".to_string();
+        naming.origin = "class::name should endsWith \"Service\";".to_string();
+        naming.because = "services are found by name".to_string();
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        error.push_item("name: Order".to_string(), ErrorLocation {
+            path: path.display().to_string(),
+            start: CodePoint { row: 2, column: 6 },
+            end: CodePoint { row: 2, column: 11 },
+        });
+
+        let report = CheckReport { rules: vec![naming], errors: vec![error], files: vec![] };
+        let text = render(&report, false);
+
+        assert!(text.starts_with("error[naming]: endsWith: \"Service\"\n"));
+        assert!(text.contains(":3:7\n"));
+        assert!(text.contains("3 | class Order {\n  |       ^^^^^ name: Order\n"));
+        assert!(text.contains("  = because: services are found by name\n"));
+        assert!(!text.contains("\x1b["));
+        assert!(render(&report, true).contains("\x1b[1;31merror[naming]"));
+    }
+
+    #[test]
+    fn should_render_a_line_per_violation() {
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        error.push_item("name: Order".to_string(), ErrorLocation {
+            path: "src/Order.java".to_string(),
+            start: CodePoint { row: 2, column: 6 },
+            end: CodePoint { row: 2, column: 11 },
+        });
+        let mut size = RuleErrorMsg::new(MismatchType::FileSize, 1);
+        size.msg = "file.len = 3".to_string();
+
+        let report = CheckReport { rules: vec![GuardRule::default(), GuardRule::default()], errors: vec![error, size], files: vec![] };
+        assert_eq!("src/Order.java:3:7: error[rule #1]: endsWith: \"Service\" - name: Order
+error[rule #2]: file.len = 3
+2 rules, 0 files: 2 errors, 0 warnings, 0 infos
+", render_machine(&report));
+    }
+}
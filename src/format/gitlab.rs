@@ -0,0 +1,93 @@
+use serde_json::{json, Value};
+
+use guarding_parser::ast::Severity;
+
+use crate::format::relative_path;
+use crate::report::CheckReport;
+
+/// where GitLab points violations without a file, like a file count of a package
+const PROJECT_PATH: &'static str = "guarding.guarding";
+
+/// the code quality artifact of GitLab, shown inline on merge requests when uploaded with
+/// `artifacts:reports:codequality`
+pub fn render(report: &CheckReport) -> String {
+    serde_json::to_string_pretty(&to_code_quality(report)).unwrap()
+}
+
+pub fn to_code_quality(report: &CheckReport) -> Value {
+    let issues: Vec<Value> = report.violations().iter()
+        .map(|violation| {
+            let description = match &violation.item {
+                Some(item) => format!("{}: {}", violation.message, item),
+                None => violation.message.clone()
+            };
+            let (path, begin, end) = match &violation.location {
+                Some(location) => (relative_path(&location.path), location.start.row + 1, location.end.row.max(location.start.row) + 1),
+                None => (PROJECT_PATH.to_string(), 1, 1)
+            };
+
+            json!({
+                "type": "issue",
+                "check_name": report.rule_id(violation.rule_index),
+                "description": description,
+                "categories": ["Style"],
+                "severity": severity(violation.severity),
+                "fingerprint": violation.fingerprint,
+                "location": {
+                    "path": path,
+                    "lines": { "begin": begin, "end": end },
+                },
+            })
+        })
+        .collect();
+
+    Value::Array(issues)
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "major",
+        Severity::Warning => "minor",
+        Severity::Info => "info",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use crate::format::gitlab::to_code_quality;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_render_code_quality_issues() {
+        let mut naming = GuardRule::default();
+        naming.name = "naming".to_string();
+        let mut size = GuardRule::default();
+        size.severity = Severity::Warning;
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        error.push_item("name: Order".to_string(), ErrorLocation {
+            path: "src/Order.java".to_string(),
+            start: CodePoint { row: 2, column: 0 },
+            end: CodePoint { row: 4, column: 1 },
+        });
+        let mut warning = RuleErrorMsg::new(MismatchType::FileSize, 1);
+        warning.msg = "file.len = 3".to_string();
+
+        let report = CheckReport { rules: vec![naming, size], errors: vec![error, warning], files: vec![] };
+        let issues = to_code_quality(&report);
+
+        assert_eq!("naming", issues[0]["check_name"]);
+        assert_eq!("major", issues[0]["severity"]);
+        assert_eq!("src/Order.java", issues[0]["location"]["path"]);
+        assert_eq!(3, issues[0]["location"]["lines"]["begin"]);
+        assert_eq!(report.violations()[0].fingerprint, issues[0]["fingerprint"]);
+        assert_eq!("minor", issues[1]["severity"]);
+        assert_eq!("guarding.guarding", issues[1]["location"]["path"]);
+    }
+}
@@ -0,0 +1,190 @@
+use guarding_core::resolver::DependencyGraph;
+use guarding_core::rule_executor::rule_error::ErrorLocation;
+use guarding_parser::ast::Severity;
+
+use crate::format::{escape_xml, SourceLines};
+use crate::report::{CheckReport, Violation};
+
+/// lines shown before and after a violation
+const SNIPPET_CONTEXT: usize = 2;
+
+const STYLE: &'static str = "body { font-family: -apple-system, \"Segoe UI\", sans-serif; margin: 2em auto; max-width: 72em; color: #24292e; }
+h1, h2, h3 { font-weight: 600; }
+.summary { display: flex; gap: 1em; }
+.summary div { border: 1px solid #e1e4e8; border-radius: 6px; padding: 0.8em 1.2em; }
+.summary strong { display: block; font-size: 1.6em; }
+.rule { border-top: 1px solid #e1e4e8; margin-top: 1.5em; }
+.severity { border-radius: 4px; color: #fff; font-size: 0.8em; padding: 0.1em 0.5em; }
+.error { background: #d73a49; }
+.warning { background: #e36209; }
+.info { background: #0366d6; }
+.passed { background: #28a745; }
+pre, code { font-family: SFMono-Regular, Menlo, monospace; font-size: 0.9em; }
+pre { background: #f6f8fa; border-radius: 6px; padding: 0.6em; overflow-x: auto; }
+pre .hit { background: #fff5b1; display: block; }
+table { border-collapse: collapse; }
+td, th { border: 1px solid #e1e4e8; padding: 0.3em 0.8em; text-align: left; vertical-align: top; }
+";
+
+/// one standalone page, without scripts or external resources, to attach or mail around
+pub fn render(report: &CheckReport) -> String {
+    let violations = report.violations();
+    let mut sources = SourceLines::default();
+
+    let mut html = String::from("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Guarding report</title>\n");
+    html.push_str(&format!("<style>\n{}</style>\n</head>\n<body>\n<h1>Guarding report</h1>\n", STYLE));
+
+    html.push_str("<div class=\"summary\">\n");
+    for (label, count) in &[
+        ("rules", report.rules.len()),
+        ("files", report.files.len()),
+        ("errors", report.count_of(Severity::Error)),
+        ("warnings", report.count_of(Severity::Warning)),
+        ("infos", report.count_of(Severity::Info))
+    ] {
+        html.push_str(&format!("<div><strong>{}</strong>{}</div>\n", count, label));
+    }
+    html.push_str("</div>\n");
+
+    html.push_str("<h2>Rules</h2>\n");
+    for (index, rule) in report.rules.iter().enumerate() {
+        let of_rule: Vec<&Violation> = violations.iter().filter(|violation| violation.rule_index == index).collect();
+        let status = if of_rule.is_empty() { "passed" } else { rule.severity.name() };
+
+        html.push_str("<section class=\"rule\">\n");
+        html.push_str(&format!("<h3>{} <span class=\"severity {}\">{}</span></h3>\n",
+                               escape_xml(&report.rule_id(index)), status, status));
+        html.push_str(&format!("<pre>{}</pre>\n", escape_xml(&rule.origin)));
+        if !rule.because.is_empty() {
+            html.push_str(&format!("<p>because {}</p>\n", escape_xml(&rule.because)));
+        }
+        if !rule.tags.is_empty() {
+            html.push_str(&format!("<p>tags: {}</p>\n", escape_xml(&rule.tags.join(", "))));
+        }
+
+        if !of_rule.is_empty() {
+            html.push_str("<ul>\n");
+            for violation in of_rule {
+                html.push_str(&render_violation(violation, &mut sources));
+            }
+            html.push_str("</ul>\n");
+        }
+        html.push_str("</section>\n");
+    }
+
+    html.push_str(&render_dependencies(&DependencyGraph::build(&report.files)));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_violation(violation: &Violation, sources: &mut SourceLines) -> String {
+    let mut html = format!("<li>{}", escape_xml(&violation.message));
+    if let Some(item) = &violation.item {
+        html.push_str(&format!(" &mdash; <code>{}</code>", escape_xml(item)));
+    }
+
+    if let Some(location) = &violation.location {
+        html.push_str(&format!("<br><code>{}:{}</code>\n", escape_xml(&location.path), location.start.row + 1));
+        if let Some(lines) = sources.of(&location.path) {
+            html.push_str(&snippet(lines, location));
+        }
+    }
+
+    html.push_str("</li>\n");
+    html
+}
+
+/// the violating lines with a few around them, each numbered
+fn snippet(lines: &[String], location: &ErrorLocation) -> String {
+    if location.start.row >= lines.len() {
+        return String::new();
+    }
+
+    let first = location.start.row.saturating_sub(SNIPPET_CONTEXT);
+    let last = (location.end.row.max(location.start.row) + SNIPPET_CONTEXT).min(lines.len() - 1);
+
+    let mut html = String::from("<pre>");
+    for row in first..=last {
+        let line = format!("{:>5} | {}", row + 1, escape_xml(&lines[row]));
+        if row >= location.start.row && row <= location.end.row.max(location.start.row) {
+            html.push_str(&format!("<span class=\"hit\">{}</span>", line));
+        } else {
+            html.push_str(&format!("{}\n", line));
+        }
+    }
+    html.push_str("</pre>\n");
+    html
+}
+
+/// every package with what it depends on and what depends on it
+fn render_dependencies(graph: &DependencyGraph) -> String {
+    let mut html = String::from("<h2>Dependencies</h2>\n");
+    let packages: Vec<&str> = graph.nodes().into_iter().filter(|package| !package.is_empty()).collect();
+    if packages.is_empty() {
+        html.push_str("<p>no packages</p>\n");
+        return html;
+    }
+
+    html.push_str("<table>\n<tr><th>package</th><th>depends on</th><th>used by</th></tr>\n");
+    for package in packages {
+        html.push_str(&format!("<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+                               escape_xml(package),
+                               escape_xml(&graph.dependencies_of(package).join(", ")),
+                               escape_xml(&graph.dependents_of(package).join(", "))));
+    }
+    html.push_str("</table>\n");
+    html
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use guarding_core::domain::CodePoint;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_import::CodeImport;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::GuardRule;
+
+    use crate::format::html::render;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_render_rules_snippets_and_dependencies() {
+        let dir = env::temp_dir().join("guarding").join("html");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Order.java");
+        fs::write(&path, "package shop;\n\nclass Order<T> {\n}\n").unwrap();
+
+        let mut naming = GuardRule::default();
+        naming.name = "naming".to_string();
+        naming.origin = "class::name should endsWith \"Service\";".to_string();
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        let location = ErrorLocation {
+            path: path.display().to_string(),
+            start: CodePoint { row: 2, column: 0 },
+            end: CodePoint { row: 2, column: 14 },
+        };
+        error.push_item("name: <Order>".to_string(), location);
+
+        let mut file = CodeFile::default();
+        file.package = "shop".to_string();
+        let mut import = CodeImport::default();
+        import.package = "shop.util".to_string();
+        file.imports.push(import);
+
+        let report = CheckReport { rules: vec![naming, GuardRule::default()], errors: vec![error], files: vec![file] };
+        let html = render(&report);
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<strong>1</strong>errors"));
+        assert!(html.contains("naming <span class=\"severity error\">error</span>"));
+        assert!(html.contains("rule-2 <span class=\"severity passed\">passed</span>"));
+        assert!(html.contains("<span class=\"hit\">    3 | class Order&lt;T&gt; {</span>"));
+        assert!(html.contains("<tr><td><code>shop</code></td><td>shop.util</td><td></td></tr>"));
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use crate::report::CheckReport;
+
+/// changes on every change of the document, so consumers can tell what they read
+pub const JSON_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JsonReport {
+    pub schema_version: u32,
+    pub summary: JsonSummary,
+    pub violations: Vec<JsonViolation>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JsonSummary {
+    pub rules: usize,
+    pub files: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub infos: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JsonViolation {
+    pub rule: String,
+    pub severity: String,
+    pub fingerprint: String,
+    /// empty for violations of the whole project, like a file count
+    pub file: String,
+    pub range: Option<JsonRange>,
+    pub message: String,
+    pub item: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+}
+
+/// 1-based lines and columns, like editors show them
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JsonRange {
+    pub start_line: usize,
+    pub start_column: usize,
+    pub end_line: usize,
+    pub end_column: usize,
+}
+
+impl JsonReport {
+    pub fn from_report(report: &CheckReport) -> JsonReport {
+        let violations: Vec<JsonViolation> = report.violations().into_iter()
+            .map(|violation| JsonViolation {
+                rule: violation.rule,
+                severity: violation.severity.name().to_string(),
+                fingerprint: violation.fingerprint,
+                file: violation.location.as_ref().map(|location| location.path.clone()).unwrap_or_default(),
+                range: violation.location.as_ref().map(|location| JsonRange {
+                    start_line: location.start.row + 1,
+                    start_column: location.start.column + 1,
+                    end_line: location.end.row + 1,
+                    end_column: location.end.column + 1,
+                }),
+                message: violation.message,
+                item: violation.item,
+                suggestion: violation.suggestion,
+            })
+            .collect();
+
+        let count = |severity: &str| violations.iter().filter(|violation| violation.severity == severity).count();
+        let summary = JsonSummary {
+            rules: report.rules.len(),
+            files: report.files.len(),
+            errors: count("error"),
+            warnings: count("warning"),
+            infos: count("info"),
+        };
+
+        JsonReport { schema_version: JSON_SCHEMA_VERSION, summary, violations }
+    }
+}
+
+pub fn render(report: &CheckReport) -> String {
+    serde_json::to_string_pretty(&JsonReport::from_report(report)).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::GuardRule;
+
+    use crate::format::json::{JsonRange, JsonReport};
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_flatten_items_with_locations() {
+        let mut rule = GuardRule::default();
+        rule.name = "naming".to_string();
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        let location = ErrorLocation {
+            path: "src/Order.java".to_string(),
+            start: CodePoint { row: 2, column: 0 },
+            end: CodePoint { row: 4, column: 1 },
+        };
+        error.push_item("path: shop, name: Order".to_string(), location);
+        error.push_item("path: shop, name: Cart".to_string(), ErrorLocation::default());
+
+        let report = CheckReport { rules: vec![rule], errors: vec![error], files: vec![] };
+        let json = JsonReport::from_report(&report);
+
+        assert_eq!(1, json.schema_version);
+        assert_eq!(2, json.summary.errors);
+        assert_eq!("src/Order.java", json.violations[0].file);
+        assert_eq!(Some(JsonRange { start_line: 3, start_column: 1, end_line: 5, end_column: 2 }), json.violations[0].range);
+        assert_eq!("", json.violations[1].file);
+        assert_eq!(None, json.violations[1].range);
+    }
+}
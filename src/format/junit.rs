@@ -0,0 +1,79 @@
+use guarding_parser::ast::Severity;
+
+use crate::format::escape_xml;
+use crate::report::CheckReport;
+
+/// one test case per rule; violations of error rules are failures, the others are only
+/// printed as output, like they don't fail `guarding check`
+pub fn render(report: &CheckReport) -> String {
+    let violations = report.violations();
+    let failures = violations.iter().filter(|violation| violation.severity == Severity::Error).count();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    xml.push_str(&format!("<testsuites name=\"guarding\" tests=\"{}\" failures=\"{}\">\n", report.rules.len(), failures));
+    xml.push_str(&format!("  <testsuite name=\"guarding\" tests=\"{}\" failures=\"{}\" errors=\"0\">\n", report.rules.len(), failures));
+
+    for (index, rule) in report.rules.iter().enumerate() {
+        let name = escape_xml(&report.rule_id(index));
+        let of_rule: Vec<_> = violations.iter().filter(|violation| violation.rule_index == index).collect();
+        if of_rule.is_empty() {
+            xml.push_str(&format!("    <testcase name=\"{}\" classname=\"guarding\"/>\n", name));
+            continue;
+        }
+
+        xml.push_str(&format!("    <testcase name=\"{}\" classname=\"guarding\">\n", name));
+        let lines: Vec<String> = of_rule.iter()
+            .map(|violation| {
+                let location = violation.location.as_ref()
+                    .map(|location| format!("{}:{}: ", location.path, location.start.row + 1))
+                    .unwrap_or_default();
+                format!("{}{}", location, violation.item.clone().unwrap_or_else(|| violation.message.clone()))
+            })
+            .collect();
+        let body = escape_xml(&format!("{}\n{}", rule.origin, lines.join("\n")));
+
+        if rule.severity == Severity::Error {
+            xml.push_str(&format!("      <failure message=\"{}\" type=\"{}\">{}</failure>\n",
+                                  escape_xml(&of_rule[0].message), rule.severity.name(), body));
+        } else {
+            xml.push_str(&format!("      <system-out>{}</system-out>\n", body));
+        }
+        xml.push_str("    </testcase>\n");
+    }
+
+    xml.push_str("  </testsuite>\n</testsuites>\n");
+    xml
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use crate::format::junit::render;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_map_rules_to_test_cases() {
+        let mut naming = GuardRule::default();
+        naming.name = "naming".to_string();
+        let mut size = GuardRule::default();
+        size.severity = Severity::Warning;
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        error.push_item("name: <Order>".to_string(), ErrorLocation { path: "Order.java".to_string(), ..Default::default() });
+        let mut warning = RuleErrorMsg::new(MismatchType::FileSize, 1);
+        warning.msg = "file.len = 3".to_string();
+
+        let report = CheckReport { rules: vec![naming, size, GuardRule::default()], errors: vec![error, warning], files: vec![] };
+        let xml = render(&report);
+
+        assert!(xml.contains("<testsuite name=\"guarding\" tests=\"3\" failures=\"1\""));
+        assert!(xml.contains("<failure message=\"endsWith: &quot;Service&quot;\" type=\"error\">"));
+        assert!(xml.contains("Order.java:1: name: &lt;Order&gt;"));
+        assert!(xml.contains("<system-out>"));
+        assert!(xml.contains("<testcase name=\"rule-3\" classname=\"guarding\"/>"));
+    }
+}
@@ -0,0 +1,51 @@
+use crate::graph::{ArchGraph, GraphLevel};
+use crate::report::CheckReport;
+
+/// markdown for wikis and merge requests: the counts, the violated rules and a flowchart of
+/// the package dependencies, with the violating ones in red
+pub fn render(report: &CheckReport) -> String {
+    let mut markdown = format!("## Guarding\n\n{}\n\n", report.summary());
+
+    let violations = report.violations();
+    if !violations.is_empty() {
+        markdown.push_str("| rule | severity | violations |\n|---|---|---|\n");
+        for (index, rule) in report.rules.iter().enumerate() {
+            let count = violations.iter().filter(|violation| violation.rule_index == index).count();
+            if count > 0 {
+                markdown.push_str(&format!("| `{}` | {} | {} |\n", report.rule_id(index), rule.severity.name(), count));
+            }
+        }
+        markdown.push('\n');
+    }
+
+    markdown.push_str(&ArchGraph::from_report(report, GraphLevel::Package, None).to_mermaid());
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::MismatchType;
+    use guarding_parser::ast::GuardRule;
+
+    use crate::format::mermaid::render;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_render_markdown_with_flowchart() {
+        let mut file = CodeFile::default();
+        file.package = "shop.service".to_string();
+
+        let report = CheckReport {
+            rules: vec![GuardRule::default()],
+            errors: vec![RuleErrorMsg::new(MismatchType::FileSize, 0)],
+            files: vec![file],
+        };
+        let markdown = render(&report);
+
+        assert!(markdown.starts_with("## Guarding\n\n1 rules, 1 files: 1 errors"));
+        assert!(markdown.contains("| `rule-1` | error | 1 |\n"));
+        assert!(markdown.contains("```mermaid\nflowchart TD\n    subgraph service\n        n0[\"shop.service\"]\n    end\n```\n"));
+    }
+}
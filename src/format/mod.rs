@@ -0,0 +1,103 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::report::CheckReport;
+
+pub mod csv;
+pub mod diagnostic;
+pub mod gitlab;
+pub mod html;
+pub mod json;
+pub mod junit;
+pub mod mermaid;
+pub mod sarif;
+pub mod teamcity;
+
+/// how `guarding check` prints the report
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutputFormat {
+    Text,
+    Json,
+    Sarif,
+    Junit,
+    Html,
+    Gitlab,
+    Teamcity,
+    Mermaid,
+    Csv,
+}
+
+impl OutputFormat {
+    pub fn from_name(name: &str) -> Option<OutputFormat> {
+        match name {
+            "text" => Some(OutputFormat::Text),
+            "json" => Some(OutputFormat::Json),
+            "sarif" => Some(OutputFormat::Sarif),
+            "junit" => Some(OutputFormat::Junit),
+            "html" => Some(OutputFormat::Html),
+            "gitlab" => Some(OutputFormat::Gitlab),
+            "teamcity" => Some(OutputFormat::Teamcity),
+            "mermaid" => Some(OutputFormat::Mermaid),
+            "csv" => Some(OutputFormat::Csv),
+            _ => None
+        }
+    }
+
+    pub fn names() -> &'static [&'static str] {
+        &["text", "json", "sarif", "junit", "html", "gitlab", "teamcity", "mermaid", "csv"]
+    }
+
+    pub fn render(&self, report: &CheckReport) -> String {
+        self.render_colored(report, false)
+    }
+
+    /// `color` only changes the text, the other formats are read by tools
+    pub fn render_colored(&self, report: &CheckReport, color: bool) -> String {
+        match self {
+            OutputFormat::Text => diagnostic::render(report, color),
+            OutputFormat::Json => json::render(report),
+            OutputFormat::Sarif => sarif::render(report),
+            OutputFormat::Junit => junit::render(report),
+            OutputFormat::Html => html::render(report),
+            OutputFormat::Gitlab => gitlab::render(report),
+            OutputFormat::Teamcity => teamcity::render(report),
+            OutputFormat::Mermaid => mermaid::render(report),
+            OutputFormat::Csv => csv::render(report),
+        }
+    }
+}
+
+/// for text and attribute values of XML and HTML
+pub(crate) fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// relative to the working directory, which is the repository root in CI, with `/`
+pub(crate) fn relative_path(path: &str) -> String {
+    let path = Path::new(path);
+    let relative = env::current_dir().ok()
+        .and_then(|cwd| path.strip_prefix(cwd).ok().map(|relative| relative.to_path_buf()))
+        .unwrap_or_else(|| path.to_path_buf());
+
+    relative.to_string_lossy().replace('\\', "/")
+}
+
+/// the lines of the files with violations, each file read once
+#[derive(Default)]
+pub(crate) struct SourceLines {
+    files: BTreeMap<String, Option<Vec<String>>>,
+}
+
+impl SourceLines {
+    pub(crate) fn of(&mut self, path: &str) -> Option<&Vec<String>> {
+        self.files.entry(path.to_string())
+            .or_insert_with(|| fs::read_to_string(path).ok().map(|code| code.lines().map(|line| line.to_string()).collect()))
+            .as_ref()
+    }
+}
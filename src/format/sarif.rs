@@ -0,0 +1,117 @@
+use serde_json::{json, Value};
+
+use guarding_parser::ast::Severity;
+
+use crate::format::relative_path;
+use crate::report::CheckReport;
+
+pub const SARIF_VERSION: &'static str = "2.1.0";
+pub const SARIF_SCHEMA: &'static str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// `guarding/v1` partial fingerprints let code scanning keep a result open across commits
+pub fn render(report: &CheckReport) -> String {
+    serde_json::to_string_pretty(&to_sarif(report)).unwrap()
+}
+
+pub fn to_sarif(report: &CheckReport) -> Value {
+    let rules: Vec<Value> = report.rules.iter().enumerate()
+        .map(|(index, rule)| json!({
+            "id": report.rule_id(index),
+            "name": report.rule_id(index),
+            "shortDescription": { "text": rule.origin },
+            "defaultConfiguration": { "level": level(rule.severity) },
+            "properties": { "tags": rule.tags },
+        }))
+        .collect();
+
+    let results: Vec<Value> = report.violations().iter()
+        .map(|violation| {
+            let text = match &violation.item {
+                Some(item) => format!("{}: {}", violation.message, item),
+                None => violation.message.clone()
+            };
+            let locations: Vec<Value> = violation.location.iter()
+                .map(|location| json!({
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": relative_path(&location.path) },
+                        "region": {
+                            "startLine": location.start.row + 1,
+                            "startColumn": location.start.column + 1,
+                            "endLine": location.end.row + 1,
+                            "endColumn": location.end.column + 1,
+                        }
+                    }
+                }))
+                .collect();
+
+            json!({
+                "ruleId": report.rule_id(violation.rule_index),
+                "ruleIndex": violation.rule_index,
+                "level": level(violation.severity),
+                "message": { "text": text },
+                "locations": locations,
+                "partialFingerprints": { "guarding/v1": violation.fingerprint },
+            })
+        })
+        .collect();
+
+    json!({
+        "$schema": SARIF_SCHEMA,
+        "version": SARIF_VERSION,
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "guarding",
+                    "informationUri": "https://github.com/inherd/guarding",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    })
+}
+
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info => "note",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use crate::format::sarif::to_sarif;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_render_rules_and_results() {
+        let mut rule = GuardRule::default();
+        rule.severity = Severity::Info;
+        rule.origin = "class::name should endsWith \"Service\";".to_string();
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: \"Service\"".to_string();
+        error.push_item("name: Order".to_string(), ErrorLocation {
+            path: "src/Order.java".to_string(),
+            start: CodePoint { row: 0, column: 0 },
+            end: CodePoint { row: 1, column: 1 },
+        });
+
+        let report = CheckReport { rules: vec![rule], errors: vec![error], files: vec![] };
+        let sarif = to_sarif(&report);
+
+        assert_eq!("2.1.0", sarif["version"]);
+        let run = &sarif["runs"][0];
+        assert_eq!("rule-1", run["tool"]["driver"]["rules"][0]["id"]);
+        assert_eq!("note", run["results"][0]["level"]);
+        assert_eq!("src/Order.java", run["results"][0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"]);
+        assert_eq!(2, run["results"][0]["locations"][0]["physicalLocation"]["region"]["endLine"]);
+    }
+}
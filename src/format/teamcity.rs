@@ -0,0 +1,105 @@
+use guarding_parser::ast::Severity;
+
+use crate::format::relative_path;
+use crate::report::CheckReport;
+
+/// service messages, read by TeamCity from the build log: an inspection type per rule, an
+/// inspection per violation and statistic values for failure conditions on the counts
+pub fn render(report: &CheckReport) -> String {
+    let mut text = String::new();
+    for (index, rule) in report.rules.iter().enumerate() {
+        text.push_str(&format!("##teamcity[inspectionType id='{}' name='{}' category='guarding' description='{}']\n",
+                               escape(&report.rule_id(index)),
+                               escape(&report.rule_id(index)),
+                               escape(&rule.origin)));
+    }
+
+    for violation in report.violations() {
+        let message = match &violation.item {
+            Some(item) => format!("{}: {}", violation.message, item),
+            None => violation.message.clone()
+        };
+        let (file, line) = match &violation.location {
+            Some(location) => (relative_path(&location.path), location.start.row + 1),
+            None => (String::new(), 0)
+        };
+
+        let mut inspection = format!("##teamcity[inspection typeId='{}' message='{}' file='{}'",
+                                     escape(&report.rule_id(violation.rule_index)), escape(&message), escape(&file));
+        if line > 0 {
+            inspection.push_str(&format!(" line='{}'", line));
+        }
+        inspection.push_str(&format!(" SEVERITY='{}']\n", severity(violation.severity)));
+        text.push_str(&inspection);
+    }
+
+    for (key, severity) in &[("guardingErrors", Severity::Error), ("guardingWarnings", Severity::Warning), ("guardingInfos", Severity::Info)] {
+        text.push_str(&format!("##teamcity[buildStatisticValue key='{}' value='{}']\n", key, report.count_of(*severity)));
+    }
+
+    text
+}
+
+fn severity(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "ERROR",
+        Severity::Warning => "WARNING",
+        Severity::Info => "INFO",
+    }
+}
+
+/// the `|` escapes of service message values
+fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+    for char in text.chars() {
+        match char {
+            '|' => escaped.push_str("||"),
+            '\'' => escaped.push_str("|'"),
+            '\n' => escaped.push_str("|n"),
+            '\r' => escaped.push_str("|r"),
+            '[' => escaped.push_str("|["),
+            ']' => escaped.push_str("|]"),
+            _ => escaped.push(char)
+        }
+    }
+
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use crate::format::teamcity::render;
+    use crate::report::CheckReport;
+
+    #[test]
+    fn should_render_inspections_and_statistics() {
+        let mut naming = GuardRule::default();
+        naming.name = "naming".to_string();
+        naming.origin = "class::name should endsWith \"Service\";".to_string();
+        let mut size = GuardRule::default();
+        size.severity = Severity::Warning;
+
+        let mut error = RuleErrorMsg::new(MismatchType::FileName, 0);
+        error.msg = "endsWith: 'Service'".to_string();
+        error.push_item("name: [Order]".to_string(), ErrorLocation {
+            path: "src/Order.java".to_string(),
+            start: CodePoint { row: 2, column: 0 },
+            end: CodePoint { row: 4, column: 1 },
+        });
+        let mut warning = RuleErrorMsg::new(MismatchType::FileSize, 1);
+        warning.msg = "file.len = 3".to_string();
+
+        let report = CheckReport { rules: vec![naming, size], errors: vec![error, warning], files: vec![] };
+        let text = render(&report);
+
+        assert!(text.contains("##teamcity[inspectionType id='naming' name='naming' category='guarding' description='class::name should endsWith \"Service\";']"));
+        assert!(text.contains("##teamcity[inspection typeId='naming' message='endsWith: |'Service|': name: |[Order|]' file='src/Order.java' line='3' SEVERITY='ERROR']"));
+        assert!(text.contains("##teamcity[inspection typeId='rule-2' message='file.len = 3' file='' SEVERITY='WARNING']"));
+        assert!(text.contains("##teamcity[buildStatisticValue key='guardingErrors' value='1']"));
+    }
+}
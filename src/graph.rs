@@ -0,0 +1,350 @@
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::resolver::DependencyGraph;
+use guarding_core::rule_executor::package_matcher::is_package_match;
+use guarding_core::rule_executor::rule_error::MismatchType;
+
+use crate::init::LAYERS;
+use crate::report::CheckReport;
+
+/// what the nodes of a graph are
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GraphLevel {
+    Package,
+    Class,
+}
+
+impl GraphLevel {
+    pub fn from_name(name: &str) -> Option<GraphLevel> {
+        match name {
+            "package" => Some(GraphLevel::Package),
+            "class" => Some(GraphLevel::Class),
+            _ => None
+        }
+    }
+}
+
+/// The dependencies between the packages or classes of the project, with the ones violating
+/// an access rule marked.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ArchGraph {
+    pub nodes: BTreeSet<String>,
+    pub edges: BTreeSet<(String, String)>,
+    pub violated: BTreeSet<(String, String)>,
+}
+
+impl ArchGraph {
+    /// only the code of the project, the edges start in `scope` when given
+    pub fn from_report(report: &CheckReport, level: GraphLevel, scope: Option<&str>) -> ArchGraph {
+        let mut graph = ArchGraph::default();
+        match level {
+            GraphLevel::Package => graph.add_packages(&report.files),
+            GraphLevel::Class => graph.add_classes(&report.files),
+        }
+        graph.mark_violations(report, level);
+
+        if let Some(scope) = scope {
+            graph.edges.retain(|(from, _)| is_package_match(scope.to_string(), from));
+            let nodes: BTreeSet<String> = graph.edges.iter().flat_map(|(from, to)| vec![from.clone(), to.clone()]).collect();
+            graph.nodes.retain(|node| is_package_match(scope.to_string(), node) || nodes.contains(node));
+        }
+        let edges = graph.edges.clone();
+        graph.violated.retain(|edge| edges.contains(edge));
+
+        graph
+    }
+
+    fn add_packages(&mut self, files: &[CodeFile]) {
+        let packages: BTreeSet<&str> = files.iter().map(|file| file.package.as_str()).filter(|package| !package.is_empty()).collect();
+        let dependencies = DependencyGraph::build(files);
+
+        self.nodes.extend(packages.iter().map(|package| package.to_string()));
+        for (from, to) in dependencies.edges() {
+            if packages.contains(from) && packages.contains(to) {
+                self.edges.insert((from.to_string(), to.to_string()));
+            }
+        }
+    }
+
+    /// from the imports and the resolved supertypes and field types
+    fn add_classes(&mut self, files: &[CodeFile]) {
+        let classes: BTreeSet<String> = files.iter().flat_map(|file| file.classes.iter().map(|class| class.full_name())).collect();
+        self.nodes.extend(classes.iter().cloned());
+
+        for file in files {
+            for class in &file.classes {
+                let from = class.full_name();
+                let types = file.imports.iter().map(|import| &import.source)
+                    .chain(class.extends.iter())
+                    .chain(class.implements.iter())
+                    .chain(class.fields.iter().map(|field| &field.typ));
+
+                for typ in types {
+                    let name = typ.split(|c| c == '<' || c == '[').next().unwrap_or(typ);
+                    if name != from && classes.contains(name) {
+                        self.edges.insert((from.clone(), name.to_string()));
+                    }
+                }
+            }
+        }
+    }
+
+    /// access violations point to the violating import
+    fn mark_violations(&mut self, report: &CheckReport, level: GraphLevel) {
+        let files: BTreeMap<&str, &CodeFile> = report.files.iter().map(|file| (file.path.as_str(), file)).collect();
+        for error in report.errors.iter().filter(|error| error.mismatch_type == MismatchType::Access) {
+            for location in &error.locations {
+                let file = match files.get(location.path.as_str()) {
+                    Some(file) => file,
+                    None => continue
+                };
+
+                for import in file.imports.iter().filter(|import| import.start == location.start) {
+                    match level {
+                        GraphLevel::Package => {
+                            self.violated.insert((file.package.clone(), import.package.clone()));
+                        }
+                        GraphLevel::Class => {
+                            for class in &file.classes {
+                                self.violated.insert((class.full_name(), import.source.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// the node of a full name, or of a class or package name only one node ends with
+    pub fn find_node(&self, name: &str) -> Option<&str> {
+        if let Some(node) = self.nodes.get(name) {
+            return Some(node.as_str());
+        }
+
+        let suffix = format!(".{}", name);
+        let mut found = self.nodes.iter().filter(|node| node.ends_with(&suffix));
+        match (found.next(), found.next()) {
+            (Some(node), None) => Some(node.as_str()),
+            _ => None
+        }
+    }
+
+    /// the shortest ways from `from` to `to` along the edges, at most `limit` of them, none
+    /// when `from` doesn't depend on `to`
+    pub fn shortest_paths(&self, from: &str, to: &str, limit: usize) -> Vec<Vec<String>> {
+        let mut targets: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for (source, target) in &self.edges {
+            targets.entry(source.as_str()).or_default().push(target.as_str());
+        }
+
+        // breadth first, keeping every node one step closer to `from`
+        let mut distances: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut previous: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        let mut queue = VecDeque::new();
+        distances.insert(from, 0);
+        queue.push_back(from);
+        while let Some(node) = queue.pop_front() {
+            let distance = distances[node];
+            for &target in targets.get(node).map(|targets| targets.as_slice()).unwrap_or(&[]) {
+                match distances.get(target) {
+                    None => {
+                        distances.insert(target, distance + 1);
+                        previous.entry(target).or_default().push(node);
+                        queue.push_back(target);
+                    }
+                    Some(&found) if found == distance + 1 => previous.entry(target).or_default().push(node),
+                    _ => {}
+                }
+            }
+        }
+
+        if !distances.contains_key(to) {
+            return vec![];
+        }
+
+        let mut paths = vec![];
+        collect_paths(&previous, from, vec![to], limit, &mut paths);
+        paths
+    }
+
+    /// Graphviz, violated edges in red
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph guarding {\n    rankdir=LR;\n    node [shape=box, fontname=\"Helvetica\"];\n");
+        for node in &self.nodes {
+            dot.push_str(&format!("    {};\n", dot_id(node)));
+        }
+        for edge in &self.edges {
+            if self.violated.contains(edge) {
+                dot.push_str(&format!("    {} -> {} [color=red, penwidth=2];\n", dot_id(&edge.0), dot_id(&edge.1)));
+            } else {
+                dot.push_str(&format!("    {} -> {};\n", dot_id(&edge.0), dot_id(&edge.1)));
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// a fenced flowchart for markdown, the packages of a layer like `service` in a subgraph
+    pub fn to_mermaid(&self) -> String {
+        let ids: BTreeMap<&str, String> = self.nodes.iter().enumerate().map(|(index, node)| (node.as_str(), format!("n{}", index))).collect();
+        let mut layers: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        let mut outside = vec![];
+        for node in &self.nodes {
+            match layer_of(node) {
+                Some(layer) => layers.entry(layer).or_default().push(node),
+                None => outside.push(node.as_str()),
+            }
+        }
+
+        let mut mermaid = String::from("```mermaid\nflowchart TD\n");
+        for (layer, nodes) in &layers {
+            mermaid.push_str(&format!("    subgraph {}\n", LAYERS[*layer][0]));
+            for node in nodes {
+                mermaid.push_str(&format!("        {}[\"{}\"]\n", ids[node], mermaid_label(node)));
+            }
+            mermaid.push_str("    end\n");
+        }
+        for node in outside {
+            mermaid.push_str(&format!("    {}[\"{}\"]\n", ids[node], mermaid_label(node)));
+        }
+
+        let mut violated = vec![];
+        for (index, edge) in self.edges.iter().enumerate() {
+            mermaid.push_str(&format!("    {} --> {}\n", ids[edge.0.as_str()], ids[edge.1.as_str()]));
+            if self.violated.contains(edge) {
+                violated.push(index.to_string());
+            }
+        }
+        if !violated.is_empty() {
+            mermaid.push_str(&format!("    linkStyle {} stroke:red,stroke-width:2px\n", violated.join(",")));
+        }
+
+        mermaid.push_str("```\n");
+        mermaid
+    }
+}
+
+/// walks back from the end of `path` to `from`
+fn collect_paths(previous: &BTreeMap<&str, Vec<&str>>, from: &str, path: Vec<&str>, limit: usize, paths: &mut Vec<Vec<String>>) {
+    if paths.len() >= limit {
+        return;
+    }
+
+    let last = path[path.len() - 1];
+    if last == from {
+        paths.push(path.iter().rev().map(|node| node.to_string()).collect());
+        return;
+    }
+    for &node in previous.get(last).map(|nodes| nodes.as_slice()).unwrap_or(&[]) {
+        let mut longer = path.clone();
+        longer.push(node);
+        collect_paths(previous, from, longer, limit, paths);
+    }
+}
+
+/// the first layer of `LAYERS` one of the parts of the name is in
+fn layer_of(name: &str) -> Option<usize> {
+    let segments: Vec<String> = name.split('.').map(|segment| segment.to_lowercase()).collect();
+    LAYERS.iter().position(|names| {
+        segments.iter().any(|segment| names.iter().any(|layer| segment == layer || *segment == format!("{}s", layer)))
+    })
+}
+
+fn mermaid_label(name: &str) -> String {
+    name.replace('"', "#quot;")
+}
+
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::domain::code_class::CodeClass;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_import::CodeImport;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+
+    use crate::graph::{ArchGraph, GraphLevel};
+    use crate::report::CheckReport;
+
+    fn report() -> CheckReport {
+        let file = |package: &str, class: &str, imports: &[&str]| {
+            let mut file = CodeFile::default();
+            file.package = package.to_string();
+            file.path = format!("{}.java", class);
+            let mut code_class = CodeClass::default();
+            code_class.name = class.to_string();
+            code_class.package = package.to_string();
+            file.classes.push(code_class);
+            for (row, import) in imports.iter().enumerate() {
+                let mut import = CodeImport::symbol(import, ".");
+                import.start = CodePoint { row, column: 0 };
+                file.imports.push(import);
+            }
+            file
+        };
+
+        let mut error = RuleErrorMsg::new(MismatchType::Access, 0);
+        error.push_item("Order.java".to_string(), ErrorLocation {
+            path: "Order.java".to_string(),
+            start: CodePoint { row: 1, column: 0 },
+            end: Default::default(),
+        });
+
+        CheckReport {
+            rules: vec![],
+            errors: vec![error],
+            files: vec![
+                file("shop.order", "Order", &["shop.core.Entity", "shop.web.OrderController", "java.util.List"]),
+                file("shop.core", "Entity", &[]),
+                file("shop.web", "OrderController", &["shop.order.Order"]),
+            ],
+        }
+    }
+
+    #[test]
+    fn should_export_packages_to_dot() {
+        let graph = ArchGraph::from_report(&report(), GraphLevel::Package, None);
+        assert_eq!(3, graph.nodes.len());
+        assert_eq!(3, graph.edges.len());
+
+        let dot = graph.to_dot();
+        assert!(dot.starts_with("digraph guarding {"));
+        assert!(dot.contains("    \"shop.order\" -> \"shop.core\";\n"));
+        assert!(dot.contains("    \"shop.order\" -> \"shop.web\" [color=red, penwidth=2];\n"));
+        assert!(!dot.contains("java.util"));
+    }
+
+    #[test]
+    fn should_export_layers_to_mermaid() {
+        let mermaid = ArchGraph::from_report(&report(), GraphLevel::Package, None).to_mermaid();
+        assert!(mermaid.starts_with("```mermaid\nflowchart TD\n"));
+        assert!(mermaid.contains("    subgraph controller\n        n2[\"shop.web\"]\n    end\n"));
+        assert!(mermaid.contains("    n0[\"shop.core\"]\n"));
+        assert!(mermaid.contains("    n1 --> n2\n"));
+        assert!(mermaid.contains("    linkStyle 1 stroke:red,stroke-width:2px\n"));
+    }
+
+    #[test]
+    fn should_find_shortest_paths() {
+        let graph = ArchGraph::from_report(&report(), GraphLevel::Class, None);
+        assert_eq!(Some("shop.web.OrderController"), graph.find_node("OrderController"));
+
+        let paths = graph.shortest_paths("shop.web.OrderController", "shop.core.Entity", 10);
+        assert_eq!(vec![vec!["shop.web.OrderController", "shop.order.Order", "shop.core.Entity"]], paths);
+        assert!(graph.shortest_paths("shop.core.Entity", "shop.order.Order", 10).is_empty());
+        assert_eq!(vec![vec!["shop.core.Entity"]], graph.shortest_paths("shop.core.Entity", "shop.core.Entity", 10));
+    }
+
+    #[test]
+    fn should_export_classes_in_scope() {
+        let graph = ArchGraph::from_report(&report(), GraphLevel::Class, Some("..web.."));
+        assert_eq!(vec![("shop.web.OrderController".to_string(), "shop.order.Order".to_string())], graph.edges.into_iter().collect::<Vec<_>>());
+        assert!(graph.violated.is_empty());
+        assert_eq!(2, graph.nodes.len());
+    }
+}
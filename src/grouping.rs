@@ -0,0 +1,170 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use guarding_core::rule_executor::RuleErrorMsg;
+use guarding_parser::ast::Severity;
+
+use crate::report::CheckReport;
+
+/// What the violations of a report are grouped by; every format then shows them in that order.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupBy {
+    Rule,
+    File,
+    Package,
+    Severity,
+}
+
+impl GroupBy {
+    pub fn from_name(name: &str) -> Option<GroupBy> {
+        match name {
+            "rule" => Some(GroupBy::Rule),
+            "file" => Some(GroupBy::File),
+            "package" => Some(GroupBy::Package),
+            "severity" => Some(GroupBy::Severity),
+            _ => None
+        }
+    }
+
+    pub fn names() -> Vec<&'static str> {
+        vec!["rule", "file", "package", "severity"]
+    }
+}
+
+/// The order of the groups: by their key, like the file path, or the most violations first.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SortOrder {
+    Key,
+    Count,
+}
+
+impl SortOrder {
+    pub fn from_name(name: &str) -> Option<SortOrder> {
+        match name {
+            "key" => Some(SortOrder::Key),
+            "count" => Some(SortOrder::Count),
+            _ => None
+        }
+    }
+}
+
+/// the report with its violations in groups; by file or package a violation of a rule is split
+/// into one per group, so each keeps its rule and message
+pub fn group_report(mut report: CheckReport, by: GroupBy, sort: SortOrder) -> CheckReport {
+    let packages: BTreeMap<&str, &str> = report.files.iter().map(|file| (file.path.as_str(), file.package.as_str())).collect();
+    let mut groups: BTreeMap<GroupKey, Vec<RuleErrorMsg>> = BTreeMap::new();
+
+    for error in &report.errors {
+        match by {
+            GroupBy::Rule => groups.entry(GroupKey::Rule(error.rule_index)).or_default().push(error.clone()),
+            GroupBy::Severity => groups.entry(GroupKey::Severity(Reverse(report.severity_of(error)))).or_default().push(error.clone()),
+            GroupBy::File | GroupBy::Package => {
+                let key_of = |index: usize| {
+                    let path = error.location_of(index).map(|location| location.path.as_str()).unwrap_or("");
+                    let key = if by == GroupBy::File { path } else { packages.get(path).copied().unwrap_or("") };
+                    GroupKey::Name(key.to_string())
+                };
+                if error.items.is_empty() {
+                    groups.entry(GroupKey::Name(String::new())).or_default().push(error.clone());
+                    continue;
+                }
+
+                let mut parts: BTreeMap<GroupKey, RuleErrorMsg> = BTreeMap::new();
+                for (index, item) in error.items.iter().enumerate() {
+                    let part = parts.entry(key_of(index)).or_insert_with(|| RuleErrorMsg { items: vec![], locations: vec![], suggestions: vec![], ..error.clone() });
+                    part.items.push(item.clone());
+                    if let Some(location) = error.locations.get(index) {
+                        part.locations.push(location.clone());
+                    }
+                    if let Some(suggestion) = error.suggestions.get(index) {
+                        part.suggestions.push(suggestion.clone());
+                    }
+                }
+                for (key, part) in parts {
+                    groups.entry(key).or_default().push(part);
+                }
+            }
+        }
+    }
+
+    let mut groups: Vec<(GroupKey, Vec<RuleErrorMsg>)> = groups.into_iter().collect();
+    if sort == SortOrder::Count {
+        let count = |errors: &Vec<RuleErrorMsg>| errors.iter().map(|error| error.items.len().max(1)).sum::<usize>();
+        // stable, so groups with as many violations keep the order of their keys
+        groups.sort_by(|(_, a), (_, b)| count(b).cmp(&count(a)));
+    }
+
+    report.errors = groups.into_iter().flat_map(|(_, errors)| errors).collect();
+    report
+}
+
+/// most severe first, then by rule or name
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum GroupKey {
+    Severity(Reverse<Severity>),
+    Rule(usize),
+    Name(String),
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::{ErrorLocation, MismatchType};
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use crate::grouping::{group_report, GroupBy, SortOrder};
+    use crate::report::CheckReport;
+
+    fn report() -> CheckReport {
+        let location = |path: &str| ErrorLocation { path: path.to_string(), start: CodePoint::default(), end: CodePoint::default() };
+        let mut naming = RuleErrorMsg::new(MismatchType::FileName, 0);
+        naming.push_item("Order".to_string(), location("src/shop/order/Order.java"));
+        naming.push_item("User".to_string(), location("src/shop/user/User.java"));
+        let mut access = RuleErrorMsg::new(MismatchType::Access, 1);
+        access.push_item("OrderLine".to_string(), location("src/shop/order/OrderLine.java"));
+        access.push_item("Order".to_string(), location("src/shop/order/Order.java"));
+
+        let file = |package: &str, path: &str| {
+            let mut file = CodeFile::default();
+            file.package = package.to_string();
+            file.path = path.to_string();
+            file
+        };
+        let mut warning = GuardRule::default();
+        warning.severity = Severity::Warning;
+
+        CheckReport {
+            rules: vec![warning, GuardRule::default()],
+            errors: vec![naming, access],
+            files: vec![
+                file("shop.order", "src/shop/order/Order.java"),
+                file("shop.order", "src/shop/order/OrderLine.java"),
+                file("shop.user", "src/shop/user/User.java"),
+            ],
+        }
+    }
+
+    #[test]
+    fn should_group_by_file_and_package() {
+        let grouped = group_report(report(), GroupBy::File, SortOrder::Key);
+        let parts: Vec<(usize, Vec<String>)> = grouped.errors.iter().map(|error| (error.rule_index, error.items.clone())).collect();
+        assert_eq!(vec![
+            (0, vec!["Order".to_string()]),
+            (1, vec!["Order".to_string()]),
+            (1, vec!["OrderLine".to_string()]),
+            (0, vec!["User".to_string()]),
+        ], parts);
+        assert_eq!(4, grouped.violations().len());
+
+        let grouped = group_report(report(), GroupBy::Package, SortOrder::Count);
+        assert_eq!(vec![1, 3, 1], grouped.errors.iter().map(|error| error.items.len() + error.rule_index).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn should_put_severe_rules_first() {
+        let grouped = group_report(report(), GroupBy::Severity, SortOrder::Key);
+        assert_eq!(vec![1, 0], grouped.errors.iter().map(|error| error.rule_index).collect::<Vec<usize>>());
+    }
+}
@@ -0,0 +1,89 @@
+use std::mem;
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_core::rule_executor::{RuleErrorMsg, RuleExecutor};
+use guarding_parser::ast::GuardRule;
+
+use crate::report::{CheckReport, Violation};
+
+/// Gets the violations of a check while it runs, rule after rule, like an IDE plugin showing them
+/// before the whole report is done; a closure taking a `&Violation` is one.
+pub trait ViolationHandler {
+    fn on_violation(&mut self, violation: &Violation);
+
+    /// after each rule, with how many violations it had
+    fn on_rule_done(&mut self, _rule: &GuardRule, _violations: usize) {}
+}
+
+impl<F: FnMut(&Violation)> ViolationHandler for F {
+    fn on_violation(&mut self, violation: &Violation) {
+        self(violation)
+    }
+}
+
+/// like `check_project`, handing every violation to `handler` as soon as its rule ran; the parse
+/// warnings aren't printed, they are in the files of the report
+pub fn check_project_with(rules: Vec<GuardRule>, project: CodeProject, handler: &mut dyn ViolationHandler) -> CheckReport {
+    // for the names and severities of the violations, the errors come rule by rule
+    let partial = CheckReport { rules: rules.clone(), errors: vec![], files: vec![] };
+
+    let mut executor = RuleExecutor::from_project(project, rules.clone());
+    executor.run_with(&mut |index, errors| hand_over(&partial, index, &errors, handler));
+
+    let errors = mem::take(&mut executor.errors);
+    CheckReport { rules, errors, files: executor.into_files() }
+}
+
+/// the violations of the rule at `index` of `report`, then its end
+pub(crate) fn hand_over(report: &CheckReport, index: usize, errors: &[RuleErrorMsg], handler: &mut dyn ViolationHandler) {
+    let violations: Vec<Violation> = errors.iter().flat_map(|error| report.violations_of(error)).collect();
+    for violation in &violations {
+        handler.on_violation(violation);
+    }
+    if let Some(rule) = report.rules.get(index) {
+        handler.on_rule_done(rule, violations.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::code_class::CodeClass;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_project::CodeProject;
+    use guarding_parser::ast::GuardRule;
+
+    use crate::handler::{check_project_with, ViolationHandler};
+    use crate::parse_rules;
+    use crate::report::Violation;
+
+    #[derive(Default)]
+    struct Events(Vec<String>);
+
+    impl ViolationHandler for Events {
+        fn on_violation(&mut self, violation: &Violation) {
+            self.0.push(format!("{}: {}", violation.rule, violation.item.clone().unwrap_or_default()));
+        }
+
+        fn on_rule_done(&mut self, rule: &GuardRule, violations: usize) {
+            self.0.push(format!("{} done, {}", rule.name, violations));
+        }
+    }
+
+    #[test]
+    fn should_hand_violations_over_rule_by_rule() {
+        let mut file = CodeFile::default();
+        file.package = "shop.service".to_string();
+        file.path = "shop/service/Orders.java".to_string();
+        file.classes.push(CodeClass { name: "Orders".to_string(), package: "shop.service".to_string(), ..Default::default() });
+        let rules = parse_rules("@name(\"naming\")\nclass(\"..service..\")::name should endsWith \"Service\";\n@name(\"size\")\nclass::len should < 10;").unwrap();
+
+        let mut events = Events::default();
+        let report = check_project_with(rules.clone(), CodeProject::new(vec![file.clone()]), &mut events);
+        assert_eq!(vec!["naming: path: shop.service, name: Orders", "naming done, 1", "size done, 0"], events.0);
+        assert_eq!(1, report.violations().len());
+
+        let mut count = 0;
+        check_project_with(rules, CodeProject::new(vec![file]), &mut |_: &Violation| count += 1);
+        assert_eq!(1, count);
+    }
+}
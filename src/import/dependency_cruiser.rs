@@ -0,0 +1,225 @@
+use serde_json::{json, Value};
+
+use guarding_parser::ast::{GuardRule, Operator, RuleAssert, RuleScope, Severity};
+
+use crate::export::ExportedRules;
+use crate::import::{access_rule, ImportedRules};
+
+pub const SOURCE: &str = "dependency-cruiser";
+
+/// the `forbidden` and `allowed` rules of a `.dependency-cruiser.json`, or of a `.js` one exporting plain JSON
+pub fn from_config(content: &str) -> Result<ImportedRules, String> {
+    let config = parse_config(content)?;
+    let mut imported = ImportedRules::new(SOURCE);
+
+    for (index, rule) in array_of(&config, "forbidden").iter().enumerate() {
+        let name = rule.get("name").and_then(Value::as_str).map(|name| name.to_string())
+            .unwrap_or_else(|| format!("forbidden-{}", index + 1));
+        let severity = rule.get("severity").and_then(Value::as_str).unwrap_or("warn");
+        if severity == "ignore" {
+            continue;
+        }
+
+        let (from, to) = (rule.get("from").unwrap_or(&Value::Null), rule.get("to").unwrap_or(&Value::Null));
+        let unsupported: Vec<&String> = keys_of(to).into_iter().filter(|key| key.as_str() != "path").collect();
+        if !unsupported.is_empty() {
+            let keys: Vec<&str> = unsupported.iter().map(|key| key.as_str()).collect();
+            imported.skipped.push(format!("\"{}\", `to` with {}", name, keys.join(", ")));
+            continue;
+        }
+
+        let targets = match patterns_of(to, "path") {
+            Some(targets) if !targets.is_empty() => targets,
+            _ => {
+                imported.skipped.push(format!("\"{}\", `to` without a path guarding can match", name));
+                continue;
+            }
+        };
+        // `pathNot` sources allow only the other modules, like `only accessed`
+        let (sources, only) = match (patterns_of(from, "path"), patterns_of(from, "pathNot")) {
+            (Some(sources), None) if !sources.is_empty() => (sources, false),
+            (None, Some(sources)) if !sources.is_empty() => (sources, true),
+            _ => {
+                imported.skipped.push(format!("\"{}\", `from` without a single path or pathNot guarding can match", name));
+                continue;
+            }
+        };
+
+        let comment = rule.get("comment").and_then(Value::as_str).unwrap_or("");
+        for target in &targets {
+            let imported_rule = imported.push(&name, comment, access_rule(target, &sources, only));
+            imported_rule.severity = severity_of(severity);
+        }
+    }
+
+    if !array_of(&config, "allowed").is_empty() {
+        imported.skipped.push("`allowed`, list what's forbidden instead".to_string());
+    }
+
+    Ok(imported)
+}
+
+/// the `accessed` rules as a `.dependency-cruiser.json`, the other rules as skipped
+pub fn to_config(rules: &[GuardRule]) -> ExportedRules {
+    let mut forbidden: Vec<Value> = vec![];
+    let mut skipped: Vec<String> = vec![];
+
+    for (index, rule) in rules.iter().enumerate() {
+        let (negated, operator) = match rule.ops.as_slice() {
+            [Operator::Not, operator, ..] => (true, operator),
+            [operator, ..] => (false, operator),
+            [] => (false, &Operator::Eq),
+        };
+        let converted = match (&rule.scope, operator, &rule.assert) {
+            (RuleScope::PathDefine(target), Operator::Accessed, RuleAssert::ArrayStringed(sources)) => {
+                to_regex(target).zip(sources.iter().map(|source| to_regex(source)).collect::<Option<Vec<String>>>())
+            }
+            _ => None,
+        };
+
+        let (target, sources) = match converted {
+            Some(converted) => converted,
+            None => {
+                skipped.push(rule.origin.clone());
+                continue;
+            }
+        };
+
+        let name = if rule.name.is_empty() { format!("guarding-{}", index + 1) } else { rule.name.clone() };
+        let from_key = if negated { "path" } else { "pathNot" };
+        let mut config = json!({
+            "name": name,
+            "severity": match rule.severity { Severity::Error => "error", Severity::Warning => "warn", Severity::Info => "info" },
+            "from": { from_key: sources.join("|") },
+            "to": { "path": target },
+        });
+        if !rule.because.is_empty() {
+            config["comment"] = Value::String(rule.because.clone());
+        }
+        forbidden.push(config);
+    }
+
+    let content = serde_json::to_string_pretty(&json!({ "forbidden": forbidden })).unwrap();
+    ExportedRules { content: format!("{}\n", content), skipped }
+}
+
+/// a guarding pattern as a path regex: `..shared..` is `shared`, `src.app..` is `^src/app`
+pub fn to_regex(pattern: &str) -> Option<String> {
+    let anchored = !pattern.starts_with("..");
+    let path = pattern.trim_start_matches("..").trim_end_matches("..");
+    if path.is_empty() || !path.chars().all(|c| c.is_alphanumeric() || "._-/@".contains(c)) {
+        return None;
+    }
+
+    let path = path.replace('.', "/");
+    Some(if anchored { format!("^{}", path) } else { path })
+}
+
+/// a path regex as a guarding pattern, `None` for regexes which aren't a plain path like `\.spec\.ts$`
+pub fn to_pattern(regex: &str) -> Option<String> {
+    let anchored = regex.starts_with('^');
+    let path = regex.trim_start_matches('^')
+        .trim_end_matches("(/|$)")
+        .trim_end_matches('$')
+        .trim_end_matches('/');
+    if path.is_empty() || !path.chars().all(|c| c.is_alphanumeric() || "_-/@".contains(c)) {
+        return None;
+    }
+
+    let path = path.replace('/', ".");
+    Some(if anchored { format!("{}..", path) } else { format!("..{}..", path) })
+}
+
+fn parse_config(content: &str) -> Result<Value, String> {
+    let json = content.trim()
+        .trim_start_matches("module.exports")
+        .trim_start()
+        .trim_start_matches('=')
+        .trim_end_matches(';');
+    serde_json::from_str(json).map_err(|err| format!(
+        "not a JSON config ({}), convert it with `node -e 'console.log(JSON.stringify(require(\"./.dependency-cruiser.js\")))'`",
+        err
+    ))
+}
+
+fn array_of<'a>(config: &'a Value, key: &str) -> &'a [Value] {
+    config.get(key).and_then(Value::as_array).map(|values| values.as_slice()).unwrap_or(&[])
+}
+
+fn keys_of(value: &Value) -> Vec<&String> {
+    value.as_object().map(|object| object.keys().collect()).unwrap_or_default()
+}
+
+/// the patterns of a `path` given as a regex or a list of them, `None` when missing or not a plain path
+fn patterns_of(value: &Value, key: &str) -> Option<Vec<String>> {
+    let regexes: Vec<&str> = match value.get(key)? {
+        Value::String(regex) => regex.split('|').collect(),
+        Value::Array(regexes) => regexes.iter().filter_map(Value::as_str).flat_map(|regex| regex.split('|')).collect(),
+        _ => return Some(vec![]),
+    };
+    regexes.iter().map(|regex| to_pattern(regex.trim_start_matches('(').trim_end_matches(')'))).collect()
+}
+
+fn severity_of(name: &str) -> Severity {
+    Severity::from_name(name).unwrap_or(Severity::Warning)
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_parser::parser::parse;
+
+    use crate::import::dependency_cruiser::{from_config, to_config, to_pattern};
+
+    const CONFIG: &str = r#"module.exports = {
+  "forbidden": [
+    {
+      "name": "no-ui-to-db",
+      "comment": "the ui goes through the services",
+      "severity": "error",
+      "from": { "path": "^src/ui" },
+      "to": { "path": "^src/db" }
+    },
+    {
+      "name": "shared-only-from-features",
+      "severity": "warn",
+      "from": { "pathNot": "^src/features" },
+      "to": { "path": "shared" }
+    },
+    { "name": "no-circular", "severity": "warn", "from": {}, "to": { "circular": true } }
+  ]
+};"#;
+
+    #[test]
+    fn should_convert_path_regexes() {
+        assert_eq!(Some("src.ui..".to_string()), to_pattern("^src/ui/"));
+        assert_eq!(Some("..shared..".to_string()), to_pattern("shared(/|$)"));
+        assert_eq!(None, to_pattern("\\.spec\\.ts$"));
+    }
+
+    #[test]
+    fn should_import_forbidden_rules() {
+        let imported = from_config(CONFIG).unwrap();
+        let rules: Vec<&str> = imported.rules.iter().map(|rule| rule.rule.as_str()).collect();
+        assert_eq!(vec![
+            "class(\"src.db..\") should not accessed([\"src.ui..\"]);",
+            "class(\"..shared..\") only accessed([\"src.features..\"]);",
+        ], rules);
+        assert_eq!(1, imported.skipped.len());
+
+        let rules = parse(&imported.to_guarding()).unwrap();
+        assert_eq!("the ui goes through the services", rules[0].because);
+        assert_eq!("warning", rules[1].severity.name());
+    }
+
+    #[test]
+    fn should_export_accessed_rules_back() {
+        let rules = parse(&from_config(CONFIG).unwrap().to_guarding()).unwrap();
+        let exported = to_config(&rules);
+        assert!(exported.skipped.is_empty());
+
+        let imported = from_config(&exported.content).unwrap();
+        assert_eq!(2, imported.rules.len());
+        assert_eq!("class(\"src.db..\") should not accessed([\"src.ui..\"]);", imported.rules[0].rule);
+        assert_eq!("no-ui-to-db", imported.rules[0].name);
+    }
+}
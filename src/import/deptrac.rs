@@ -0,0 +1,194 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde_yaml::Value;
+
+use crate::import::{access_rule, ImportedRules};
+
+pub const SOURCE: &str = "deptrac";
+
+/// A layer and the packages of its collectors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Layer {
+    pub name: String,
+    pub patterns: Vec<String>,
+}
+
+/// the layers and ruleset of a `deptrac.yaml`, or of an older `depfile.yaml` without the `deptrac` key
+pub fn from_config(content: &str) -> Result<ImportedRules, String> {
+    let config: Value = serde_yaml::from_str(content).map_err(|err| format!("{}", err))?;
+    let config = config.get("deptrac").unwrap_or(&config);
+    let mut imported = ImportedRules::new(SOURCE);
+
+    let layers: Vec<Layer> = sequence_of(config, "layers").iter()
+        .filter_map(|layer| to_layer(layer, &mut imported.skipped))
+        .collect();
+    if layers.is_empty() {
+        return Err("no layers found".to_string());
+    }
+
+    let ruleset = ruleset_of(config);
+    for key in ["skip_violations", "skipViolations"] {
+        if config.get(key).is_some() {
+            imported.skipped.push(format!("`{}`, run `guarding baseline generate` instead", key));
+        }
+    }
+
+    for target in &layers {
+        // the layers which may not use `target`, a layer always uses itself
+        let users: Vec<&Layer> = layers.iter()
+            .filter(|layer| layer.name != target.name)
+            .filter(|layer| !allowed_of(&ruleset, &layer.name).contains(&target.name))
+            .collect();
+        if users.is_empty() {
+            continue;
+        }
+
+        let sources: Vec<String> = users.iter().flat_map(|layer| layer.patterns.clone()).collect();
+        let allowed: Vec<&str> = layers.iter()
+            .filter(|layer| layer.name != target.name && !users.iter().any(|user| user.name == layer.name))
+            .map(|layer| layer.name.as_str())
+            .collect();
+        let because = if allowed.is_empty() {
+            format!("no other layer may use {}", target.name)
+        } else {
+            format!("only {} may use {}", allowed.join(", "), target.name)
+        };
+        for pattern in &target.patterns {
+            imported.push(&target.name, &because, access_rule(pattern, &sources, false));
+        }
+    }
+
+    Ok(imported)
+}
+
+/// a collector regex as a package: `App\Controller\.*` is `App.Controller..`, `.*Controller.*` is `..Controller..`
+pub fn to_pattern(regex: &str) -> Option<String> {
+    let regex = regex.trim();
+    // `#...#i`, a regex with delimiters and flags
+    let regex = match regex.rfind('#') {
+        Some(end) if end > 0 && regex.starts_with('#') => &regex[1..end],
+        _ => regex,
+    };
+    let anchored = !regex.starts_with(".*");
+    let path = regex.trim_start_matches('^')
+        .trim_start_matches(".*")
+        .trim_end_matches('$')
+        .trim_end_matches(".*")
+        .trim_end_matches(|c: char| c == '\\' || c == '/');
+
+    let path = path.replace("\\\\", ".").replace('\\', ".").replace('/', ".");
+    if path.is_empty() || !path.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '.') {
+        return None;
+    }
+    Some(if anchored { format!("{}..", path) } else { format!("..{}..", path) })
+}
+
+fn to_layer(layer: &Value, skipped: &mut Vec<String>) -> Option<Layer> {
+    let name = layer.get("name").and_then(Value::as_str)?.to_string();
+    let mut patterns = vec![];
+    for collector in sequence_of(layer, "collectors") {
+        let kind = collector.get("type").and_then(Value::as_str).unwrap_or("");
+        let regex = collector.get("value").or_else(|| collector.get("regex")).and_then(Value::as_str);
+        let supported = ["className", "classNameRegex", "classLike", "namespace", "directory"].contains(&kind);
+        match regex.filter(|_| supported).and_then(to_pattern) {
+            Some(pattern) => patterns.push(pattern),
+            None => skipped.push(format!("a `{}` collector of {}", kind, name)),
+        }
+    }
+
+    if patterns.is_empty() {
+        skipped.push(format!("the layer {}, none of its collectors has a package", name));
+        return None;
+    }
+    Some(Layer { name, patterns })
+}
+
+/// the layers each layer may use, with `+Layer` kept for `allowed_of`
+fn ruleset_of(config: &Value) -> BTreeMap<String, Vec<String>> {
+    let mut ruleset = BTreeMap::new();
+    if let Some(rules) = config.get("ruleset").and_then(Value::as_mapping) {
+        for (layer, allowed) in rules {
+            if let Some(layer) = layer.as_str() {
+                let allowed = allowed.as_sequence()
+                    .map(|allowed| allowed.iter().filter_map(Value::as_str).map(|name| name.to_string()).collect())
+                    .unwrap_or_default();
+                ruleset.insert(layer.to_string(), allowed);
+            }
+        }
+    }
+    ruleset
+}
+
+/// the layers `layer` may use, following the ones of a `+Layer`
+fn allowed_of(ruleset: &BTreeMap<String, Vec<String>>, layer: &str) -> BTreeSet<String> {
+    let mut allowed = BTreeSet::new();
+    let mut pending: Vec<(String, bool)> = vec![(layer.to_string(), true)];
+    let mut visited = BTreeSet::new();
+    while let Some((current, follow)) = pending.pop() {
+        if !follow || !visited.insert(current.clone()) {
+            continue;
+        }
+        for name in ruleset.get(&current).into_iter().flatten() {
+            let transitive = name.starts_with('+');
+            let name = name.trim_start_matches('+').to_string();
+            allowed.insert(name.clone());
+            pending.push((name, transitive));
+        }
+    }
+    allowed
+}
+
+fn sequence_of<'a>(value: &'a Value, key: &str) -> &'a [Value] {
+    value.get(key).and_then(Value::as_sequence).map(|values| values.as_slice()).unwrap_or(&[])
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_parser::parser::parse;
+
+    use crate::import::deptrac::{from_config, to_pattern};
+
+    const CONFIG: &str = r#"
+deptrac:
+  paths: ["./src"]
+  layers:
+    - name: Controller
+      collectors:
+        - type: classLike
+          value: App\\Controller\\.*
+    - name: Service
+      collectors:
+        - type: className
+          regex: .*Service.*
+    - name: Repository
+      collectors:
+        - type: namespace
+          value: '#^App\\Repository\\#'
+        - type: bool
+  ruleset:
+    Controller: [+Service]
+    Service: [Repository]
+    Repository: ~
+"#;
+
+    #[test]
+    fn should_convert_collector_regexes() {
+        assert_eq!(Some("App.Controller..".to_string()), to_pattern("App\\\\Controller\\\\.*"));
+        assert_eq!(Some("..Service..".to_string()), to_pattern(".*Service.*"));
+        assert_eq!(Some("App.Repository..".to_string()), to_pattern("#^App\\\\Repository\\\\#"));
+        assert_eq!(None, to_pattern(".*(Controller|Action)$"));
+    }
+
+    #[test]
+    fn should_convert_the_ruleset() {
+        let imported = from_config(CONFIG).unwrap();
+        let rules: Vec<&str> = imported.rules.iter().map(|rule| rule.rule.as_str()).collect();
+        assert_eq!(vec![
+            "class(\"App.Controller..\") should not accessed([\"..Service..\", \"App.Repository..\"]);",
+            "class(\"..Service..\") should not accessed([\"App.Repository..\"]);",
+        ], rules);
+        assert_eq!("only Controller may use Service", imported.rules[1].because);
+        assert_eq!(1, imported.skipped.len());
+        assert_eq!(2, parse(&imported.to_guarding()).unwrap().len());
+    }
+}
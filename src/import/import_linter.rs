@@ -0,0 +1,265 @@
+use std::collections::HashMap;
+
+use crate::import::{forbid_access, ImportedRules};
+
+pub const SOURCE: &str = "import-linter";
+
+/// a `[importlinter:contract:...]` section, or a `[[tool.importlinter.contracts]]` table
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Contract {
+    pub name: String,
+    pub kind: String,
+    pub fields: HashMap<String, Vec<String>>,
+}
+
+impl Contract {
+    fn values(&self, key: &str) -> Vec<String> {
+        self.fields.get(key).cloned().unwrap_or_default()
+    }
+}
+
+/// the contracts of a `.importlinter` or `setup.cfg`
+pub fn from_ini(content: &str) -> Result<ImportedRules, String> {
+    Ok(to_rules(&parse_ini(content)?))
+}
+
+/// the contracts of the `[tool.importlinter]` of a `pyproject.toml`
+pub fn from_pyproject(content: &str) -> Result<ImportedRules, String> {
+    let value: toml::Value = toml::from_str(content).map_err(|err| format!("{}", err))?;
+    let contracts = value.get("tool")
+        .and_then(|tool| tool.get("importlinter"))
+        .and_then(|linter| linter.get("contracts"))
+        .and_then(|contracts| contracts.as_array())
+        .ok_or_else(|| "no [[tool.importlinter.contracts]] found".to_string())?;
+
+    let contracts: Vec<Contract> = contracts.iter()
+        .filter_map(|contract| contract.as_table())
+        .map(|table| {
+            let mut contract = Contract::default();
+            for (key, value) in table {
+                let values: Vec<String> = match value {
+                    toml::Value::String(value) => vec![value.clone()],
+                    toml::Value::Array(values) => values.iter().filter_map(|value| value.as_str()).map(|value| value.to_string()).collect(),
+                    other => vec![other.to_string()],
+                };
+                match key.as_str() {
+                    "name" => contract.name = values.join(" "),
+                    "type" => contract.kind = values.join(" "),
+                    _ => { contract.fields.insert(key.clone(), values); }
+                }
+            }
+            contract
+        })
+        .collect();
+
+    Ok(to_rules(&contracts))
+}
+
+/// the contract sections, with the values of a key on its indented lines after it
+pub fn parse_ini(content: &str) -> Result<Vec<Contract>, String> {
+    let mut contracts: Vec<Contract> = vec![];
+    let mut in_contract = false;
+    let mut key: Option<String> = None;
+
+    for (index, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+            continue;
+        }
+
+        if trimmed.starts_with('[') {
+            in_contract = trimmed.starts_with("[importlinter:contract:");
+            if in_contract {
+                contracts.push(Contract::default());
+            }
+            key = None;
+            continue;
+        }
+        if !in_contract {
+            continue;
+        }
+
+        let contract = contracts.last_mut().unwrap();
+        if line.starts_with(|c: char| c.is_whitespace()) {
+            match &key {
+                Some(key) => contract.fields.entry(key.clone()).or_default().push(trimmed.to_string()),
+                None => return Err(format!("line {}: a value without a key", index + 1)),
+            }
+            continue;
+        }
+
+        let (name, value) = match trimmed.split_once(|c: char| c == '=' || c == ':') {
+            Some((name, value)) => (name.trim().to_string(), value.trim().to_string()),
+            None => return Err(format!("line {}: expected `key = value`", index + 1)),
+        };
+        match name.as_str() {
+            "name" => contract.name = value,
+            "type" => contract.kind = value,
+            _ => {
+                let values = contract.fields.entry(name.clone()).or_default();
+                if !value.is_empty() {
+                    values.push(value);
+                }
+            }
+        }
+        key = Some(name);
+    }
+
+    Ok(contracts)
+}
+
+/// the rules of the contracts guarding can check, the other contracts and settings as skipped
+pub fn to_rules(contracts: &[Contract]) -> ImportedRules {
+    let mut imported = ImportedRules::new(SOURCE);
+    for contract in contracts {
+        let name = if contract.name.is_empty() { contract.kind.clone() } else { contract.name.clone() };
+        for key in ["ignore_imports", "unmatched_ignore_imports_alerting"] {
+            if contract.fields.contains_key(key) {
+                imported.skipped.push(format!("`{}` of \"{}\"", key, name));
+            }
+        }
+
+        match contract.kind.as_str() {
+            "layers" => layers(&mut imported, &name, contract),
+            "forbidden" => {
+                let sources = contract.values("source_modules");
+                for forbidden in contract.values("forbidden_modules") {
+                    imported.push(&name, &contract.name, forbid_access(&forbidden, &sources));
+                }
+            }
+            "independence" => {
+                let modules = contract.values("modules");
+                for module in &modules {
+                    let others: Vec<String> = modules.iter().filter(|other| *other != module).cloned().collect();
+                    imported.push(&name, &contract.name, forbid_access(module, &others));
+                }
+            }
+            other => imported.skipped.push(format!("\"{}\", contracts of type `{}`", name, other)),
+        }
+    }
+
+    imported
+}
+
+/// a layer may use the ones below it, not the ones above; `a | b` layers are independent
+/// of each other, `a : b` ones aren't
+fn layers(imported: &mut ImportedRules, name: &str, contract: &Contract) {
+    let mut containers = contract.values("containers");
+    if containers.is_empty() {
+        containers.push(String::new());
+    }
+
+    let layers: Vec<Vec<String>> = contract.values("layers").iter()
+        .rev()
+        .map(|layer| layer.split(|c: char| c == '|' || c == ':')
+            .map(|module| module.trim().trim_start_matches('(').trim_end_matches(')').trim().to_string())
+            .filter(|module| !module.is_empty())
+            .collect())
+        .collect();
+
+    for container in &containers {
+        let full = |module: &String| if container.is_empty() { module.clone() } else { format!("{}.{}", container, module) };
+        // import-linter lists the highest layer first, reversed above
+        for (index, layer) in layers.iter().enumerate() {
+            let lower: Vec<String> = layers[..index].iter().flatten().map(full).collect();
+            for module in layer {
+                if !lower.is_empty() {
+                    imported.push(name, &contract.name, forbid_access(&full(module), &lower));
+                }
+            }
+        }
+
+        for layer in contract.values("layers").iter().filter(|layer| layer.contains('|')) {
+            let siblings: Vec<String> = layer.split('|')
+                .map(|module| full(&module.trim().trim_start_matches('(').trim_end_matches(')').trim().to_string()))
+                .collect();
+            for module in &siblings {
+                let others: Vec<String> = siblings.iter().filter(|other| *other != module).cloned().collect();
+                imported.push(name, &contract.name, forbid_access(module, &others));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_parser::parser::parse;
+
+    use crate::import::import_linter::{from_ini, from_pyproject, parse_ini};
+
+    const CONTRACTS: &str = "[importlinter]
+root_package = shop
+
+[importlinter:contract:layers]
+name = Shop layers
+type = layers
+layers =
+    shop.api
+    shop.domain
+ignore_imports =
+    shop.domain.legacy -> shop.api
+
+[importlinter:contract:2]
+name = No django in the domain
+type = forbidden
+source_modules =
+    shop.domain
+forbidden_modules =
+    django
+
+[importlinter:contract:3]
+name = Independent apps
+type = independence
+modules = shop.orders
+    shop.users
+
+[importlinter:contract:4]
+name = Custom
+type = shop.contracts.Custom
+";
+
+    #[test]
+    fn should_read_the_contract_sections() {
+        let contracts = parse_ini(CONTRACTS).unwrap();
+        assert_eq!(4, contracts.len());
+        assert_eq!("layers", contracts[0].kind);
+        assert_eq!(vec!["shop.api", "shop.domain"], contracts[0].fields["layers"]);
+        assert_eq!(vec!["shop.orders", "shop.users"], contracts[2].fields["modules"]);
+    }
+
+    #[test]
+    fn should_convert_contracts_to_rules() {
+        let imported = from_ini(CONTRACTS).unwrap();
+        let rules: Vec<&str> = imported.rules.iter().map(|rule| rule.rule.as_str()).collect();
+        assert_eq!(vec![
+            "class(\"shop.api..\") should not accessed([\"shop.domain..\"]);",
+            "class(\"django..\") should not accessed([\"shop.domain..\"]);",
+            "class(\"shop.orders..\") should not accessed([\"shop.users..\"]);",
+            "class(\"shop.users..\") should not accessed([\"shop.orders..\"]);",
+        ], rules);
+        assert_eq!("independent-apps-2", imported.rules[3].name);
+        assert_eq!(2, imported.skipped.len());
+
+        let rules = parse(&imported.to_guarding()).unwrap();
+        assert_eq!(4, rules.len());
+        assert_eq!(vec!["import-linter"], rules[0].tags);
+    }
+
+    #[test]
+    fn should_convert_the_contracts_of_pyproject() {
+        let content = "[tool.importlinter]
+root_package = \"shop\"
+
+[[tool.importlinter.contracts]]
+name = \"Layers\"
+type = \"layers\"
+containers = [\"shop.orders\"]
+layers = [\"api\", \"views | tasks\", \"models\"]
+";
+        let imported = from_pyproject(content).unwrap();
+        let rules: Vec<&str> = imported.rules.iter().map(|rule| rule.rule.as_str()).collect();
+        assert!(rules.contains(&"class(\"shop.orders.api..\") should not accessed([\"shop.orders.models..\", \"shop.orders.views..\", \"shop.orders.tasks..\"]);"));
+        assert!(rules.contains(&"class(\"shop.orders.views..\") should not accessed([\"shop.orders.tasks..\"]);"));
+        assert_eq!(5, rules.len());
+    }
+}
@@ -0,0 +1,108 @@
+use std::fs;
+use std::path::Path;
+
+use guarding_parser::ast::Severity;
+
+pub mod dependency_cruiser;
+pub mod deptrac;
+pub mod import_linter;
+
+/// the tools `import_file` reads the config of
+pub const TOOLS: [&str; 3] = [import_linter::SOURCE, dependency_cruiser::SOURCE, deptrac::SOURCE];
+
+/// The rules converted from the config of another tool, with what guarding can't express.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportedRules {
+    /// the tool, for the header and the tag of the rules
+    pub source: String,
+    pub rules: Vec<ImportedRule>,
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportedRule {
+    pub name: String,
+    /// the contract or rule it comes from
+    pub because: String,
+    pub severity: Severity,
+    pub rule: String,
+}
+
+impl ImportedRules {
+    pub fn new(source: &str) -> ImportedRules {
+        ImportedRules { source: source.to_string(), ..Default::default() }
+    }
+
+    /// adds a rule named after `name`, numbered when it's taken
+    pub fn push(&mut self, name: &str, because: &str, rule: String) -> &mut ImportedRule {
+        let base = slug(name);
+        let mut name = base.clone();
+        let mut count = 1;
+        while self.rules.iter().any(|imported| imported.name == name) {
+            count += 1;
+            name = format!("{}-{}", base, count);
+        }
+        self.rules.push(ImportedRule { name, because: because.to_string(), severity: Severity::Error, rule });
+        self.rules.last_mut().unwrap()
+    }
+
+    /// a rule file, with the skipped parts as comments
+    pub fn to_guarding(&self) -> String {
+        let mut text = format!("// imported from {}\n", self.source);
+        for skipped in &self.skipped {
+            text.push_str(&format!("// skipped: {}\n", skipped));
+        }
+        for imported in &self.rules {
+            text.push_str(&format!("\n@name({:?})\n@tag({:?})\n", imported.name, self.source));
+            if imported.severity != Severity::Error {
+                text.push_str(&format!("@severity({:?})\n", imported.severity.name()));
+            }
+            if !imported.because.is_empty() {
+                text.push_str(&format!("@because({:?})\n", imported.because));
+            }
+            text.push_str(&imported.rule);
+            text.push('\n');
+        }
+        text
+    }
+}
+
+/// the rules of the config of `tool` at `path`, like `import-linter` and a `.importlinter`
+pub fn import_file(tool: &str, path: &Path) -> Result<ImportedRules, String> {
+    let content = fs::read_to_string(path).map_err(|err| format!("can't read {}: {}", path.display(), err))?;
+    let is_toml = path.extension().map(|extension| extension == "toml").unwrap_or(false);
+    match tool {
+        "import-linter" if is_toml => import_linter::from_pyproject(&content),
+        "import-linter" => import_linter::from_ini(&content),
+        "dependency-cruiser" => dependency_cruiser::from_config(&content),
+        "deptrac" => deptrac::from_config(&content),
+        _ => Err(format!("unknown tool `{}`, one of: {}", tool, TOOLS.join(", "))),
+    }
+}
+
+/// a module or namespace with everything below it, like `shop.order..`
+pub fn package_pattern(module: &str) -> String {
+    format!("{}..", module.trim().trim_end_matches(".*").trim_end_matches(".**"))
+}
+
+/// `class("b..") should not accessed(["a.."])`, the classes of `target` used by the ones of `sources`
+pub fn forbid_access(target: &str, sources: &[String]) -> String {
+    let sources: Vec<String> = sources.iter().map(|source| package_pattern(source)).collect();
+    access_rule(&package_pattern(target), &sources, false)
+}
+
+/// an `accessed` rule on patterns, `only accessed` for `only` and `should not accessed` otherwise
+pub fn access_rule(target: &str, sources: &[String], only: bool) -> String {
+    let sources: Vec<String> = sources.iter().map(|source| format!("{:?}", source)).collect();
+    let operator = if only { "only" } else { "should not" };
+    format!("class({:?}) {} accessed([{}]);", target, operator, sources.join(", "))
+}
+
+/// lowercase words joined by `-`, for rule names
+pub fn slug(name: &str) -> String {
+    let words: Vec<String> = name.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| word.to_lowercase())
+        .collect();
+    if words.is_empty() { "rule".to_string() } else { words.join("-") }
+}
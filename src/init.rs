@@ -0,0 +1,167 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_ident::{LanguageRegistry, ModelBuilder, ProjectScanner};
+use guarding_ident::support::build_metadata::find_build_files;
+
+/// package names and the suffix of the classes in them
+const NAMING: &[(&str, &str)] = &[
+    ("controller", "Controller"),
+    ("service", "Service"),
+    ("repository", "Repository"),
+    ("dao", "Dao"),
+    ("handler", "Handler"),
+];
+
+/// from the outside in: each layer is only used by itself and the layers before it
+pub(crate) const LAYERS: &[&[&str]] = &[
+    &["controller", "web", "api", "rest"],
+    &["service", "application", "usecase"],
+    &["repository", "dao", "persistence", "infrastructure"],
+];
+
+/// What `guarding init` found in a project.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ProjectSurvey {
+    /// language -> file count
+    pub languages: BTreeMap<String, usize>,
+    pub build_files: Vec<String>,
+    /// the package every other one is in, like `com.example.shop`
+    pub root_package: String,
+    /// every part of every package name, like `service` of `com.example.shop.service`
+    pub segments: BTreeSet<String>,
+}
+
+impl ProjectSurvey {
+    pub fn of_dir(code_dir: &Path) -> ProjectSurvey {
+        let registry = LanguageRegistry::default();
+        let scanner = ProjectScanner::new(&registry);
+        let paths = scanner.scan(code_dir);
+
+        let mut languages = BTreeMap::new();
+        for path in &paths {
+            if let Some(language) = registry.detect(path) {
+                *languages.entry(language.name().to_string()).or_insert(0) += 1;
+            }
+        }
+
+        let mut files = scanner.parse(&paths);
+        ModelBuilder::resolve_models(&registry, code_dir, &mut files);
+
+        let mut survey = ProjectSurvey::of_files(&files);
+        survey.languages = languages;
+        survey.build_files = find_build_files(code_dir).iter()
+            .filter_map(|path| path.file_name().map(|name| name.to_string_lossy().to_string()))
+            .collect();
+        survey
+    }
+
+    pub fn of_files(files: &[CodeFile]) -> ProjectSurvey {
+        let packages: Vec<Vec<&str>> = files.iter()
+            .map(|file| package_segments(&file.package))
+            .filter(|segments| !segments.is_empty())
+            .collect();
+
+        let mut root: Vec<&str> = packages.first().cloned().unwrap_or_default();
+        for segments in &packages {
+            let common = root.iter().zip(segments.iter()).take_while(|(a, b)| a == b).count();
+            root.truncate(common);
+        }
+
+        ProjectSurvey {
+            root_package: root.join("."),
+            segments: packages.iter().flatten().map(|segment| segment.to_lowercase()).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn has_segment(&self, name: &str) -> bool {
+        self.segments.contains(name) || self.segments.contains(&format!("{}s", name))
+    }
+
+    /// a rules file with comments on what was found and why each rule is there
+    pub fn starter_rules(&self) -> String {
+        let mut rules = String::from("// guarding rules, see https://github.com/inherd/guarding\n");
+        rules.push_str("// generated by `guarding init`, edit them as the project needs\n");
+        if !self.languages.is_empty() {
+            let languages: Vec<String> = self.languages.iter().map(|(name, count)| format!("{} ({} files)", name, count)).collect();
+            rules.push_str(&format!("// languages: {}\n", languages.join(", ")));
+        }
+        if !self.build_files.is_empty() {
+            rules.push_str(&format!("// build files: {}\n", self.build_files.join(", ")));
+        }
+        if !self.root_package.is_empty() {
+            rules.push_str(&format!("// root package: {}\n", self.root_package));
+        }
+
+        rules.push_str("\n// packages stay small enough to understand\n");
+        rules.push_str("@name(\"file-count\")\npackage(\".\")::file.len should < 200;\n");
+
+        rules.push_str("\n// packages don't depend on each other in circles\n");
+        rules.push_str("@name(\"no-cycles\")\npackage(\".\")::cycle.len should = 0;\n");
+
+        let naming: Vec<&(&str, &str)> = NAMING.iter().filter(|(package, _)| self.has_segment(package)).collect();
+        if !naming.is_empty() {
+            rules.push_str("\n// classes tell their role by name\n");
+        }
+        for (package, suffix) in naming {
+            rules.push_str(&format!("@name(\"{}-naming\")\n@severity(\"warning\")\n@tag(\"naming\")\n", package));
+            rules.push_str(&format!("class(resideIn \"..{}..\")::name should endsWith \"{}\";\n", package, suffix));
+        }
+
+        let layers: Vec<&str> = LAYERS.iter()
+            .filter_map(|names| names.iter().find(|name| self.has_segment(name)).copied())
+            .collect();
+        if layers.len() > 1 {
+            rules.push_str(&format!("\n// layers, from the outside in: {}\n", layers.join(" -> ")));
+            for (index, layer) in layers.iter().enumerate() {
+                let allowed: Vec<String> = layers[..=index].iter().map(|name| format!("\"..{}..\"", name)).collect();
+                rules.push_str(&format!("@name(\"{}-layer\")\n@tag(\"layers\")\n", layer));
+                rules.push_str(&format!("class(\"..{}..\") only accessed([{}]);\n", layer, allowed.join(", ")));
+            }
+        } else {
+            rules.push_str("\n// layers, like controllers only used by controllers:\n");
+            rules.push_str("// class(\"..controller..\") only accessed([\"..controller..\"]);\n");
+        }
+
+        rules
+    }
+}
+
+fn package_segments(package: &str) -> Vec<&str> {
+    package.split(|c| c == '.' || c == '/' || c == ':')
+        .filter(|segment| !segment.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::code_file::CodeFile;
+
+    use crate::init::ProjectSurvey;
+    use crate::parse_rules;
+
+    #[test]
+    fn should_tailor_rules_to_packages() {
+        let files: Vec<CodeFile> = ["com.shop.controller", "com.shop.service", "com.shop.repository.jpa"].iter()
+            .map(|package| {
+                let mut file = CodeFile::default();
+                file.package = package.to_string();
+                file
+            })
+            .collect();
+
+        let survey = ProjectSurvey::of_files(&files);
+        assert_eq!("com.shop", survey.root_package);
+
+        let rules = survey.starter_rules();
+        assert!(rules.contains("class(resideIn \"..service..\")::name should endsWith \"Service\";"));
+        assert!(rules.contains("class(\"..service..\") only accessed([\"..controller..\", \"..service..\"]);"));
+        assert!(!rules.contains("Dao"));
+
+        let parsed = parse_rules(&rules).unwrap();
+        assert_eq!("no-cycles", parsed[1].name);
+        assert_eq!(8, parsed.len());
+    }
+}
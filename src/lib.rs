@@ -1,28 +1,162 @@
 extern crate serde;
 
-use std::path::PathBuf;
+use std::io;
+use std::mem;
+use std::path::{Path, PathBuf};
+
+use tracing::warn;
+use tracing_subscriber::EnvFilter;
 
 use guarding_core::domain::code_file::CodeFile;
-use guarding_ident::ModelBuilder;
+pub use guarding_core::domain::code_project::CodeProject;
+pub use guarding_core::rule_executor::RuleSummary;
+use guarding_ident::{LanguageRegistry, ModelBuilder};
 use guarding_core::rule_executor::{RuleErrorMsg, RuleExecutor};
-use guarding_parser::ast::GuardRule;
+pub use guarding_parser::ast::GuardRule;
+use guarding_parser::ast::RuleLevel;
 use guarding_parser::parser;
 
+use crate::config::GuardingConfig;
+use crate::timings::PhaseTimings;
+
+pub mod api;
+pub mod baseline;
+pub mod cache;
+pub mod cargo;
+pub mod changed;
+pub mod cli;
+pub mod config;
+pub mod custom;
+pub mod daemon;
+pub mod diff_base;
+pub mod export;
+pub mod fitness;
+pub mod format;
+pub mod graph;
+pub mod grouping;
+pub mod handler;
+pub mod import;
+pub mod init;
+pub mod lint;
+pub mod lsp;
+pub mod monorepo;
+pub mod packs;
+pub mod query;
+pub mod repl;
+pub mod report;
+pub mod rule_list;
+pub mod rules;
+pub mod server;
+pub mod stats;
+pub mod structured;
+pub mod suggest;
+pub mod testing;
+pub mod timings;
+pub mod watch;
+
+pub use api::{AnalysisReport, Guarding};
+pub use custom::CustomRule;
+pub use handler::ViolationHandler;
+pub use report::{CheckReport, Violation};
+
+/// the rules of the `guarding init` template
+pub const INIT_RULES: &'static str = "// guarding rules, see https://github.com/inherd/guarding
+@name(\"file-count\")
+package(\".\")::file.len should < 200;
+";
+
+/// diagnostics on stderr, warnings only unless `RUST_LOG` asks for more, like
+/// `RUST_LOG=guarding_ident=trace`
+pub fn init_logging() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn"));
+    let _ = tracing_subscriber::fmt().with_env_filter(filter).with_writer(io::stderr).try_init();
+}
+
 pub fn exec_guarding(rule_content: String, code_dir: PathBuf) -> Vec<RuleErrorMsg> {
     match parser::parse(rule_content.as_str()) {
         Err(e) => {
-            println!("{}", e);
+            warn!("{}", e);
             vec![]
         },
         Ok(rules) => {
-            let models = ModelBuilder::build_models_by_dir(code_dir);
-            exec(rules, models)
+            let project = ModelBuilder::build_project_by_dir(code_dir);
+            print_parse_warnings(&project.files);
+            exec(rules, project)
+        }
+    }
+}
+
+/// parses the rules and runs them on `code_dir`, an invalid rule file is an error
+pub fn check(rule_content: &str, code_dir: PathBuf) -> Result<CheckReport, String> {
+    let rules = parse_rules(rule_content)?;
+    let project = ModelBuilder::build_project_by_dir(code_dir);
+    Ok(check_project(rules, project))
+}
+
+/// with the settings of `guarding.toml`: its source roots unless `roots` are given, its rule
+/// files and severity overrides
+pub fn check_with_config(config: &GuardingConfig, roots: Option<Vec<PathBuf>>, rule_content: Option<String>) -> Result<CheckReport, String> {
+    check_timed(config, roots, rule_content, &mut PhaseTimings::default(), &|_, _| {})
+}
+
+/// `check_with_config` adding the time of each phase to `timings`, `progress` is called while
+/// parsing like in `GuardingConfig::build_project_timed`
+pub fn check_timed(config: &GuardingConfig, roots: Option<Vec<PathBuf>>, rule_content: Option<String>,
+                   timings: &mut PhaseTimings, progress: &(dyn Fn(usize, usize) + Sync)) -> Result<CheckReport, String> {
+    let rules = match rule_content {
+        Some(content) => {
+            let mut rules = parse_rules(&content)?;
+            config.apply_rule_settings(&mut rules);
+            rules
+        }
+        None => config.load_rules()?.into_iter().map(|(_, rule)| rule).collect()
+    };
+
+    let roots = roots.unwrap_or_else(|| config.source_roots());
+    let project = config.build_project_timed(&roots, timings, progress);
+    Ok(timings.time("rules", || check_project(rules, project)))
+}
+
+pub fn check_project(rules: Vec<GuardRule>, project: CodeProject) -> CheckReport {
+    print_parse_warnings(&project.files);
+
+    let mut executor = RuleExecutor::from_project(project, rules.clone());
+    executor.run();
+    let errors = mem::take(&mut executor.errors);
+    CheckReport { rules, errors, files: executor.into_files() }
+}
+
+/// checks `code` as the content of `path`, like an editor buffer before it's saved; package
+/// rules need the other files of the package and are left out
+pub fn check_source(registry: &LanguageRegistry, rules: Vec<GuardRule>, path: &Path, code: &str) -> Result<CheckReport, String> {
+    let language = registry.detect(path).ok_or_else(|| format!("no language for {}", path.display()))?;
+    let mut file = ModelBuilder::identify(language, code, path).map_err(|err| err.to_string())?;
+    file.path = ModelBuilder::format_path(path);
+    file.file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+    file.language = language.name().to_string();
+
+    // the warnings are in the report, stdout is only for the report
+    let rules: Vec<GuardRule> = rules.into_iter().filter(|rule| rule.level != RuleLevel::Package).collect();
+    let files = vec![file];
+    let errors = exec(rules.clone(), CodeProject::new(files.clone()));
+    Ok(CheckReport { rules, errors, files })
+}
+
+pub fn parse_rules(rule_content: &str) -> Result<Vec<GuardRule>, String> {
+    parser::parse(rule_content).map_err(|err| format!("{}", err))
+}
+
+/// on stderr through `tracing`, stdout is only for the report
+pub(crate) fn print_parse_warnings(models: &Vec<CodeFile>) {
+    for file in models {
+        for warning in &file.warnings {
+            warn!("{}:{}:{} {}", file.path, warning.start.row + 1, warning.start.column + 1, warning.message);
         }
     }
 }
 
-fn exec(rules: Vec<GuardRule>, models: Vec<CodeFile>) -> Vec<RuleErrorMsg> {
-    let mut executor = RuleExecutor::new(models, rules);
+fn exec(rules: Vec<GuardRule>, project: CodeProject) -> Vec<RuleErrorMsg> {
+    let mut executor = RuleExecutor::from_project(project, rules);
     executor.run();
 
     return executor.errors;
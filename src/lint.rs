@@ -0,0 +1,331 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use regex::Regex;
+
+use guarding_core::rule_executor::package_matcher::convert_to_regex;
+use guarding_parser::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope, RuleType, Severity};
+
+use crate::parse_rules;
+use crate::structured::parse_rule_file;
+
+const RULE_METAS: &[&str] = &["name", "severity", "tag", "because"];
+const CLASS_KINDS: &[&str] = &["class", "interface", "enum", "record", "struct", "trait", "object"];
+
+/// A problem of a rule file found without running it. Errors are rules which can't work,
+/// warnings are rules which likely don't do what they say.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LintIssue {
+    pub severity: Severity,
+    /// `None` for problems of the file
+    pub rule_index: Option<usize>,
+    pub message: String,
+}
+
+impl LintIssue {
+    fn error(rule_index: usize, message: String) -> LintIssue {
+        LintIssue { severity: Severity::Error, rule_index: Some(rule_index), message }
+    }
+
+    fn warning(rule_index: usize, message: String) -> LintIssue {
+        LintIssue { severity: Severity::Warning, rule_index: Some(rule_index), message }
+    }
+
+    pub fn to_text(&self) -> String {
+        match self.rule_index {
+            Some(index) => format!("{}[rule #{}]: {}", self.severity.name(), index + 1, self.message),
+            None => format!("{}: {}", self.severity.name(), self.message)
+        }
+    }
+}
+
+/// the problems of a rule file, sorted by rule; a syntax error is an `Err`
+pub fn lint_rules(content: &str) -> Result<Vec<LintIssue>, String> {
+    let rules = parse_rules(content)?;
+
+    let mut issues = lint_metas(content);
+    issues.extend(lint_parsed(rules));
+    Ok(sorted(issues))
+}
+
+/// like `lint_rules`, for the rule file at `path`, YAML and TOML ones by their extension
+pub fn lint_rule_file(path: &Path, content: &str) -> Result<Vec<LintIssue>, String> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml") | Some("yml") | Some("toml") => Ok(lint_parsed(parse_rule_file(path, content)?)),
+        _ => lint_rules(content),
+    }
+}
+
+/// the problems of parsed rules, sorted by rule
+pub fn lint_parsed(rules: Vec<GuardRule>) -> Vec<LintIssue> {
+    let mut issues = vec![];
+    let mut names: BTreeMap<&str, usize> = BTreeMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        if !rule.name.is_empty() {
+            match names.get(rule.name.as_str()) {
+                Some(first) => issues.push(LintIssue::error(index, format!("duplicate name {:?}, already used by rule #{}", rule.name, first + 1))),
+                None => {
+                    names.insert(&rule.name, index);
+                }
+            }
+        }
+
+        issues.extend(unsupported(rule).map(|message| LintIssue::error(index, message)));
+        issues.extend(never_matching(rule).map(|message| LintIssue::warning(index, message)));
+    }
+    issues.extend(conflicts(&rules));
+
+    sorted(issues)
+}
+
+fn sorted(mut issues: Vec<LintIssue>) -> Vec<LintIssue> {
+    issues.sort_by_key(|issue| issue.rule_index.map(|index| index + 1).unwrap_or(0));
+    issues
+}
+
+/// metas the parser only prints a note of
+fn lint_metas(content: &str) -> Vec<LintIssue> {
+    let mut issues = vec![];
+    let meta = Regex::new(r#"@(\w+)\s*\(\s*["']([^"']*)["']"#).unwrap();
+    for capture in meta.captures_iter(content) {
+        let (key, value) = (&capture[1], &capture[2]);
+        if !RULE_METAS.contains(&key) {
+            issues.push(LintIssue { severity: Severity::Warning, rule_index: None, message: format!("unknown rule meta @{}, ignored", key) });
+        } else if key == "severity" && Severity::from_name(value).is_none() {
+            issues.push(LintIssue { severity: Severity::Error, rule_index: None, message: format!("unknown severity {:?}", value) });
+        }
+    }
+
+    issues
+}
+
+/// rules the executor has no check for, which would silently never fail
+fn unsupported(rule: &GuardRule) -> Option<String> {
+    if rule.ty == RuleType::Layer {
+        return Some("layer rules are not run yet".to_string());
+    }
+
+    let operator = match rule.ops.iter().find(|op| **op != Operator::Not) {
+        Some(operator) => operator,
+        None => return Some("rule without an operator".to_string())
+    };
+    let property = match &rule.expr {
+        Expr::PropsCall(props) => props.join("."),
+        Expr::Identifier(ident) => ident.clone(),
+    };
+
+    let supported = match (&rule.level, property.as_str()) {
+        (RuleLevel::Function, _) => return Some("function rules are not run yet".to_string()),
+        (RuleLevel::Struct, _) => return Some("struct rules are not run yet".to_string()),
+        (RuleLevel::Class, "") => matches!(operator, Operator::Accessed)
+            || matches!(rule.assert, RuleAssert::Leveled(_, _)),
+        (RuleLevel::Class, "len") => is_size(operator),
+        (RuleLevel::Class, "name") => is_string(operator),
+        (RuleLevel::Class, "doc") | (RuleLevel::Class, "function.doc") => *operator == Operator::Empty,
+        (RuleLevel::Package, "len") | (RuleLevel::Package, "file.len")
+        | (RuleLevel::Package, "external.import.len") | (RuleLevel::Package, "cycle.len") => is_size(operator),
+        (RuleLevel::Package, "external.import") => *operator == Operator::Inside,
+        _ => return Some(format!("`{}` of a {} is not checked", property, rule.level.name()))
+    };
+
+    if !supported {
+        return Some(format!("{:?} is not an operator of `{}` of a {}", operator, property, rule.level.name()));
+    }
+    if is_size(operator) && !matches!(rule.assert, RuleAssert::Sized(_)) {
+        return Some(format!("{:?} needs a number", operator));
+    }
+
+    None
+}
+
+/// scopes which can be written but select nothing
+fn never_matching(rule: &GuardRule) -> Option<String> {
+    match &rule.scope {
+        RuleScope::PathDefine(pattern) => {
+            if pattern.trim().is_empty() {
+                Some("empty package pattern matches nothing".to_string())
+            } else if pattern.contains("...") || pattern.contains(char::is_whitespace) {
+                Some(format!("package pattern {:?} matches no package name", pattern))
+            } else if pattern != "." && Regex::new(&convert_to_regex(pattern.clone())).is_err() {
+                Some(format!("package pattern {:?} is not valid", pattern))
+            } else {
+                None
+            }
+        }
+        RuleScope::MatchRegex(regex) => match Regex::new(regex) {
+            Err(err) => Some(format!("invalid regex {:?}: {}", regex, err)),
+            Ok(_) => Some("match scopes select nothing yet".to_string())
+        },
+        RuleScope::Kind(kind) => {
+            let name = kind.trim_start_matches('!');
+            if CLASS_KINDS.contains(&name) {
+                None
+            } else {
+                Some(format!("unknown kind {:?}, kinds are: {}", name, CLASS_KINDS.join(", ")))
+            }
+        }
+        RuleScope::Implementation(name) | RuleScope::Extend(name) | RuleScope::Assignable(name) if name.trim().is_empty() => {
+            Some("empty type name matches nothing".to_string())
+        }
+        _ => None
+    }
+}
+
+/// rules of the same things which can't pass together, like `len < 10` and `len > 20`
+fn conflicts(rules: &[GuardRule]) -> Vec<LintIssue> {
+    let mut groups: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, rule) in rules.iter().enumerate() {
+        groups.entry(format!("{:?} {:?} {:?}", rule.level, rule.scope, rule.expr)).or_default().push(index);
+    }
+
+    let mut issues = vec![];
+    for indices in groups.values() {
+        let mut lower = 0;
+        let mut upper = usize::MAX;
+        for (position, &index) in indices.iter().enumerate() {
+            let rule = &rules[index];
+            let (negated, operator) = split_not(&rule.ops);
+
+            if let (Some(operator), RuleAssert::Sized(size)) = (operator, &rule.assert) {
+                let (low, high) = size_range(operator, negated, *size);
+                lower = lower.max(low);
+                upper = upper.min(high);
+                if lower > upper {
+                    issues.push(LintIssue::error(index, "conflicts with the sizes of the rules before it on the same scope".to_string()));
+                    break;
+                }
+            }
+
+            for &other in &indices[..position] {
+                let (other_negated, other_operator) = split_not(&rules[other].ops);
+                if operator.is_some() && operator == other_operator && negated != other_negated && rule.assert == rules[other].assert {
+                    issues.push(LintIssue::error(index, format!("contradicts rule #{}", other + 1)));
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+fn split_not(ops: &[Operator]) -> (bool, Option<&Operator>) {
+    match ops.first() {
+        Some(Operator::Not) => (true, ops.get(1)),
+        first => (false, first)
+    }
+}
+
+/// the allowed sizes, both ends included
+fn size_range(operator: &Operator, negated: bool, size: usize) -> (usize, usize) {
+    let operator = match (operator, negated) {
+        (Operator::Gt, true) => &Operator::Lte,
+        (Operator::Gte, true) => &Operator::Lt,
+        (Operator::Lt, true) => &Operator::Gte,
+        (Operator::Lte, true) => &Operator::Gt,
+        (_, true) => return (0, usize::MAX),
+        (operator, false) => operator,
+    };
+
+    match operator {
+        Operator::Gt => (size.saturating_add(1), usize::MAX),
+        Operator::Gte => (size, usize::MAX),
+        Operator::Lt if size == 0 => (1, 0),
+        Operator::Lt => (0, size - 1),
+        Operator::Lte => (0, size),
+        Operator::Eq => (size, size),
+        _ => (0, usize::MAX)
+    }
+}
+
+fn is_size(operator: &Operator) -> bool {
+    matches!(operator, Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte | Operator::Eq)
+}
+
+fn is_string(operator: &Operator) -> bool {
+    matches!(operator, Operator::StartsWith | Operator::Endswith | Operator::Contains)
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_parser::ast::Severity;
+
+    use std::path::Path;
+
+    use crate::lint::{lint_rule_file, lint_rules};
+
+    #[test]
+    fn should_pass_valid_rules() {
+        let content = "@name(\"size\")
+package(\".\")::file.len should < 200;
+class(\"..service..\")::name should endsWith \"Service\";
+class(\"java.util.Map\") only accessed([\"com.phodal.pepper.refactor.staticclass\"]);";
+        assert_eq!(Vec::<String>::new(), lint_rules(content).unwrap().iter().map(|issue| issue.to_text()).collect::<Vec<String>>());
+    }
+
+    #[test]
+    fn should_report_rule_problems() {
+        let content = "@name(\"size\")
+package(\".\")::file.len should < 10;
+@name(\"size\") @owner(\"team\")
+package(\".\")::file.len should > 20;
+class(\"..service..\")::name should endsWith \"Service\";
+class(\"..service..\")::name should not endsWith \"Service\";
+package(\".\")::file.name should endsWith \"Service\";
+class(kind \"module\")::len should < 20;
+function::name should contains \"get\";";
+        let issues: Vec<String> = lint_rules(content).unwrap().iter().map(|issue| issue.to_text()).collect();
+
+        assert_eq!(vec![
+            "warning: unknown rule meta @owner, ignored",
+            "error[rule #2]: duplicate name \"size\", already used by rule #1",
+            "error[rule #2]: conflicts with the sizes of the rules before it on the same scope",
+            "error[rule #4]: contradicts rule #3",
+            "error[rule #5]: `file.name` of a package is not checked",
+            "warning[rule #6]: unknown kind \"module\", kinds are: class, interface, enum, record, struct, trait, object",
+            "error[rule #7]: function rules are not run yet",
+        ], issues);
+    }
+
+    #[test]
+    fn should_report_layer_rules() {
+        let content = "@name(\"onion\")
+layer(\"onion\")
+    ::domainModel(\"..domain..\")
+    ::adapter(\"..adapter..\");";
+        let issues: Vec<String> = lint_rules(content).unwrap().iter().map(|issue| issue.to_text()).collect();
+
+        assert_eq!(vec!["error[rule #1]: layer rules are not run yet"], issues);
+    }
+
+    #[test]
+    fn should_fail_on_syntax_errors() {
+        assert!(lint_rules("class(\"..\") only something;").is_err());
+        let issues = lint_rules("@severity(\"fatal\")\nclass::len should < 2;").unwrap();
+        assert_eq!(Severity::Error, issues[0].severity);
+    }
+
+    #[test]
+    fn should_lint_yaml_rules() {
+        let content = "rules:
+  - name: size
+    level: package
+    scope: { resideIn: \".\" }
+    expr: { props: [file, len] }
+    ops: [lt]
+    assert: { size: 10 }
+  - name: size
+    level: package
+    scope: { resideIn: \".\" }
+    expr: { props: [file, name] }
+    ops: [endsWith]
+    assert: { string: Service }
+";
+        let issues: Vec<String> = lint_rule_file(Path::new("rules.yaml"), content).unwrap().iter().map(|issue| issue.to_text()).collect();
+
+        assert_eq!(vec![
+            "error[rule #2]: duplicate name \"size\", already used by rule #1",
+            "error[rule #2]: `file.name` of a package is not checked",
+        ], issues);
+        assert!(lint_rule_file(Path::new("rules.yaml"), "rules: [").is_err());
+    }
+}
@@ -0,0 +1,580 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::sync::OnceLock;
+use std::thread;
+
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, CONTROLS};
+use regex::Regex;
+use serde_json::{json, Value};
+use tracing::debug;
+
+use guarding_parser::ast::Severity;
+use guarding_parser::syntax::{syntax_tree, tokenize, TokenKind};
+
+use crate::config::GuardingConfig;
+use crate::lint::lint_rules;
+use crate::parse_rules;
+
+const LEVELS: [&str; 5] = ["package", "class", "struct", "function", "file"];
+const SCOPES: [&str; 6] = ["resideIn", "implementation", "extends", "assignable", "kind", "match"];
+const PROPERTIES: [&str; 7] = ["name", "len", "doc", "function", "file", "cycle", "external"];
+const METAS: [&str; 4] = ["@name", "@severity", "@tag", "@because"];
+/// the semantic token types, by the index the tokens refer to
+const TOKEN_TYPES: [&str; 8] = ["keyword", "type", "operator", "string", "number", "property", "decorator", "comment"];
+/// what is escaped of a path in a `file://` URI
+const URI_PATH: &AsciiSet = &CONTROLS.add(b' ').add(b'"').add(b'#').add(b'%').add(b'<').add(b'>').add(b'?').add(b'`').add(b'{').add(b'}');
+
+/// the operators and keywords with what they do, for completions and hovers
+const OPERATORS: [(&str, &str); 13] = [
+    ("should", "starts the assert of a rule"),
+    ("only", "`only accessed([...])`, the packages allowed to use the selection"),
+    ("not", "negates the operator after it, like `should not endsWith \"Impl\"`"),
+    ("endsWith", "the name ends with the text"),
+    ("startsWith", "the name starts with the text"),
+    ("contains", "the name contains the text"),
+    ("resideIn", "the classes are in the packages of the pattern, like `resideIn package(\"..service..\")`"),
+    ("inside", "the external imports come from the packages or libraries of the patterns"),
+    ("accessed", "the selection is only used from the packages of the patterns"),
+    ("dependBy", "the selection is used by the packages of the pattern"),
+    ("empty", "the property has no value, like `::doc should not empty`"),
+    ("implementation", "the classes implementing an interface, like `class(implementation \"Repository\")`"),
+    ("assignable", "the direct or indirect subtypes of a type, like `class(assignable \"Entity\")`"),
+];
+
+/// A language server for rule files on stdin and stdout: diagnostics of the syntax and of
+/// `guarding validate`, completions, hovers, going to the rule files of a `guarding.toml`, semantic
+/// tokens and selection ranges.
+pub struct LanguageServer {
+    root: Option<PathBuf>,
+    documents: BTreeMap<String, String>,
+    /// of the project at `root`, parsed once on a thread so completions don't wait for it
+    packages: Option<Vec<String>>,
+    parsing: Option<Receiver<Vec<String>>>,
+}
+
+impl LanguageServer {
+    pub fn new() -> LanguageServer {
+        LanguageServer { root: None, documents: BTreeMap::new(), packages: None, parsing: None }
+    }
+
+    /// the responses and notifications of one message of the client; `None` after `exit`
+    pub fn handle(&mut self, message: &Value) -> Option<Vec<Value>> {
+        let method = message["method"].as_str().unwrap_or("");
+        let id = message.get("id").cloned();
+        let params = &message["params"];
+        debug!("lsp message {}", method);
+
+        let result = match method {
+            "initialize" => {
+                self.root = params["rootUri"].as_str().map(path_of_uri)
+                    .or_else(|| params["rootPath"].as_str().map(PathBuf::from));
+                self.packages = None;
+                self.parsing = Some(parse_packages(self.root.clone().unwrap_or_else(|| PathBuf::from("."))));
+                json!({
+                    "capabilities": {
+                        "textDocumentSync": 1,
+                        "completionProvider": { "triggerCharacters": ["\"", ":", "@", "("] },
+                        "hoverProvider": true,
+                        "definitionProvider": true,
+                        "selectionRangeProvider": true,
+                        "semanticTokensProvider": { "legend": { "tokenTypes": TOKEN_TYPES, "tokenModifiers": [] }, "full": true }
+                    },
+                    "serverInfo": { "name": "guarding", "version": env!("CARGO_PKG_VERSION") }
+                })
+            }
+            "shutdown" => Value::Null,
+            "exit" => return None,
+            "textDocument/didOpen" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+                let text = params["textDocument"]["text"].as_str().unwrap_or("").to_string();
+                return Some(self.update(uri, text));
+            }
+            "textDocument/didChange" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+                // full syncs, the last change is the whole text
+                let text = params["contentChanges"].as_array()
+                    .and_then(|changes| changes.last())
+                    .and_then(|change| change["text"].as_str())
+                    .unwrap_or("")
+                    .to_string();
+                return Some(self.update(uri, text));
+            }
+            "textDocument/didClose" => {
+                let uri = params["textDocument"]["uri"].as_str().unwrap_or("").to_string();
+                self.documents.remove(&uri);
+                return Some(vec![publish(&uri, vec![])]);
+            }
+            "textDocument/completion" => self.completion(params),
+            "textDocument/hover" => self.hover(params),
+            "textDocument/definition" => self.definition(params),
+            "textDocument/semanticTokens/full" => self.semantic_tokens(params),
+            "textDocument/selectionRange" => self.selection_ranges(params),
+            _ => {
+                return Some(match id {
+                    // requests need an answer, notifications like `initialized` don't
+                    Some(id) => vec![json!({ "jsonrpc": "2.0", "id": id, "error": { "code": -32601, "message": format!("unknown method {}", method) } })],
+                    None => vec![]
+                });
+            }
+        };
+
+        Some(match id {
+            Some(id) => vec![json!({ "jsonrpc": "2.0", "id": id, "result": result })],
+            None => vec![]
+        })
+    }
+
+    fn update(&mut self, uri: String, text: String) -> Vec<Value> {
+        let diagnostics = if uri.ends_with(".guarding") { diagnostics(&text) } else { vec![] };
+        self.documents.insert(uri.clone(), text);
+        vec![publish(&uri, diagnostics)]
+    }
+
+    fn completion(&mut self, params: &Value) -> Value {
+        // the start of the line up to the cursor
+        let before = match self.position(params) {
+            Some((text, offset)) => {
+                let line_start = text[..offset].rfind('\n').map(|index| index + 1).unwrap_or(0);
+                text[line_start..offset].to_string()
+            }
+            None => return json!([])
+        };
+        let in_string = before.matches('"').count() % 2 == 1;
+
+        let mut items = vec![];
+        if in_string {
+            for package in self.packages() {
+                items.push(json!({ "label": format!("..{}..", package), "kind": 9, "detail": "package" }));
+            }
+        } else if before.trim_end().ends_with("::") || before.ends_with('.') {
+            items.extend(PROPERTIES.iter().map(|property| json!({ "label": property, "kind": 10 })));
+        } else if before.trim_start().starts_with('@') {
+            items.extend(METAS.iter().map(|meta| json!({ "label": meta, "kind": 14, "insertText": format!("{}(\"\")", &meta[1..]) })));
+        } else if before.ends_with('(') {
+            items.extend(SCOPES.iter().map(|scope| json!({ "label": scope, "kind": 14 })));
+        } else {
+            items.extend(LEVELS.iter().map(|level| json!({ "label": level, "kind": 14 })));
+            items.extend(OPERATORS.iter().map(|(operator, doc)| json!({ "label": operator, "kind": 24, "documentation": doc })));
+            items.extend(METAS.iter().map(|meta| json!({ "label": meta, "kind": 14 })));
+        }
+
+        json!(items)
+    }
+
+    fn hover(&self, params: &Value) -> Value {
+        let word = self.position(params).map(|(text, offset)| word_at(text, offset)).unwrap_or_default();
+        match OPERATORS.iter().find(|(operator, _)| *operator == word) {
+            Some((operator, doc)) => json!({ "contents": { "kind": "markdown", "value": format!("`{}`: {}", operator, doc) } }),
+            None => Value::Null
+        }
+    }
+
+    /// the rule file of a string like `"rules/naming.guarding"`, relative to the document
+    fn definition(&self, params: &Value) -> Value {
+        let uri = params["textDocument"]["uri"].as_str().unwrap_or("");
+        let (text, offset) = match self.position(params) {
+            Some(found) => found,
+            None => return Value::Null
+        };
+
+        let path = match string_at(text, offset) {
+            Some(path) if path.ends_with(".guarding") => path,
+            _ => return Value::Null
+        };
+        let dir = path_of_uri(uri).parent().map(|dir| dir.to_path_buf()).unwrap_or_default();
+        let target = dir.join(path);
+        if !target.is_file() {
+            return Value::Null;
+        }
+
+        json!({ "uri": uri_of_path(&target), "range": range(0, 0, 0, 0) })
+    }
+
+    fn semantic_tokens(&self, params: &Value) -> Value {
+        let text = match params["textDocument"]["uri"].as_str().and_then(|uri| self.documents.get(uri)) {
+            Some(text) => text,
+            None => return Value::Null
+        };
+        json!({ "data": semantic_tokens(text) })
+    }
+
+    /// the rules, their parts and so on up to the file around every position
+    fn selection_ranges(&self, params: &Value) -> Value {
+        let text = match params["textDocument"]["uri"].as_str().and_then(|uri| self.documents.get(uri)) {
+            Some(text) => text,
+            None => return Value::Null
+        };
+        let positions = params["positions"].as_array().cloned().unwrap_or_default();
+        let tree = syntax_tree(text).ok();
+
+        let ranges: Vec<Value> = positions.iter()
+            .map(|position| {
+                let line = position["line"].as_u64().unwrap_or(0) as usize;
+                let character = position["character"].as_u64().unwrap_or(0) as usize;
+                let offset = offset_of(text, line, character);
+                let spans: Vec<(usize, usize)> = match &tree {
+                    Some(tree) => tree.path_to(offset).iter().map(|node| (node.start, node.end)).collect(),
+                    // without a tree while the file doesn't parse, the whole file
+                    None => vec![(0, text.len())]
+                };
+
+                spans.iter().rev().fold(Value::Null, |parent, (start, end)| {
+                    let ((start_line, start_column), (end_line, end_column)) = (position_of(text, *start), position_of(text, *end));
+                    let mut selection = json!({ "range": range(start_line, start_column, end_line, end_column) });
+                    if !parent.is_null() {
+                        selection["parent"] = parent;
+                    }
+                    selection
+                })
+            })
+            .collect();
+        json!(ranges)
+    }
+
+    /// the text of the document and the byte offset of the position of `params`
+    fn position(&self, params: &Value) -> Option<(&str, usize)> {
+        let text = self.documents.get(params["textDocument"]["uri"].as_str()?)?;
+        let line = params["position"]["line"].as_u64()? as usize;
+        let character = params["position"]["character"].as_u64()? as usize;
+        Some((text.as_str(), offset_of(text, line, character)))
+    }
+
+    /// the packages of the project, none while it's still parsed
+    fn packages(&mut self) -> Vec<String> {
+        if self.packages.is_none() {
+            let root = self.root.clone().unwrap_or_else(|| PathBuf::from("."));
+            let parsing = self.parsing.get_or_insert_with(|| parse_packages(root));
+            match parsing.try_recv() {
+                Ok(packages) => self.packages = Some(packages),
+                Err(TryRecvError::Empty) => return vec![],
+                Err(TryRecvError::Disconnected) => self.packages = Some(vec![]),
+            }
+            self.parsing = None;
+        }
+
+        self.packages.clone().unwrap_or_default()
+    }
+}
+
+/// the packages of the project at `root` on another thread, through the model cache of its config
+fn parse_packages(root: PathBuf) -> Receiver<Vec<String>> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let config = match GuardingConfig::discover(&root) {
+            Some(path) => GuardingConfig::load(&path).ok(),
+            None => GuardingConfig::from_toml("", root.clone()).ok()
+        };
+        let packages = config.map(|config| {
+            let roots = if config.roots.is_empty() { vec![root] } else { config.source_roots() };
+            let project = config.build_project(&roots);
+            let packages: BTreeSet<String> = project.files.iter()
+                .map(|file| file.package.clone())
+                .filter(|package| !package.is_empty())
+                .collect();
+            packages.into_iter().collect()
+        });
+        let _ = sender.send(packages.unwrap_or_default());
+    });
+    receiver
+}
+
+impl Default for LanguageServer {
+    fn default() -> Self {
+        LanguageServer::new()
+    }
+}
+
+/// the syntax error, or the issues `guarding validate` finds, at their rules
+pub fn diagnostics(text: &str) -> Vec<Value> {
+    let issues = match lint_rules(text) {
+        Ok(issues) => issues,
+        Err(err) => {
+            static POSITION: OnceLock<Regex> = OnceLock::new();
+            let position = POSITION.get_or_init(|| Regex::new(r"--> (\d+):(\d+)").unwrap());
+            let (line, column) = position.captures(&err)
+                .map(|capture| (capture[1].parse::<usize>().unwrap_or(1), capture[2].parse::<usize>().unwrap_or(1)))
+                .unwrap_or((1, 1));
+            // the last line of a pest error is what was expected
+            let message = err.lines().last().unwrap_or(&err).trim_start_matches(|c: char| c == ' ' || c == '=').trim();
+            return vec![diagnostic(range(line - 1, column - 1, line - 1, column), Severity::Error, message)];
+        }
+    };
+
+    let rules = parse_rules(text).unwrap_or_default();
+    let mut ranges = vec![];
+    let mut from = 0;
+    for rule in &rules {
+        let start = text[from..].find(&rule.origin).map(|index| from + index).unwrap_or(from);
+        let end = start + rule.origin.len();
+        ranges.push((position_of(text, start), position_of(text, end)));
+        from = end.min(text.len());
+    }
+
+    issues.iter()
+        .map(|issue| {
+            let ((start_line, start_column), (end_line, end_column)) = issue.rule_index
+                .and_then(|index| ranges.get(index).copied())
+                .unwrap_or(((0, 0), (0, 0)));
+            diagnostic(range(start_line, start_column, end_line, end_column), issue.severity, &issue.message)
+        })
+        .collect()
+}
+
+/// the tokens of `text` as the relative numbers of the protocol, a token per line of a comment
+pub fn semantic_tokens(text: &str) -> Vec<usize> {
+    let mut data = vec![];
+    let (mut last_line, mut last_column) = (0, 0);
+    for token in tokenize(text) {
+        let token_type = match token.kind {
+            TokenKind::Keyword => 0,
+            TokenKind::Scope => 1,
+            TokenKind::Operator => 2,
+            TokenKind::String => 3,
+            TokenKind::Number => 4,
+            TokenKind::Identifier => 5,
+            TokenKind::Meta => 6,
+            TokenKind::Comment => 7,
+            TokenKind::Punctuation | TokenKind::Whitespace | TokenKind::Unknown => continue,
+        };
+
+        for (index, part) in token.text(text).split('\n').enumerate() {
+            let (line, column) = if index == 0 { (token.line, token.column) } else { (token.line + index, 0) };
+            let length = part.trim_end_matches('\r').chars().count();
+            if length == 0 {
+                continue;
+            }
+
+            let delta_column = if line == last_line { column - last_column } else { column };
+            data.extend_from_slice(&[line - last_line, delta_column, length, token_type, 0]);
+            last_line = line;
+            last_column = column;
+        }
+    }
+
+    data
+}
+
+/// reads messages from `input` and writes the answers to `output` until the client exits
+pub fn serve(input: &mut dyn BufRead, output: &mut dyn Write) -> Result<(), String> {
+    let mut server = LanguageServer::new();
+    while let Some(message) = read_message(input)? {
+        let answers = match server.handle(&message) {
+            Some(answers) => answers,
+            None => return Ok(())
+        };
+        for answer in answers {
+            write_message(output, &answer)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// a `Content-Length` framed message, `None` at the end of the input
+pub fn read_message(input: &mut dyn BufRead) -> Result<Option<Value>, String> {
+    let mut length = None;
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).map_err(|err| format!("can't read the message: {}", err))? == 0 {
+            return Ok(None);
+        }
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+        if let Some(value) = header.strip_prefix("Content-Length:") {
+            length = value.trim().parse::<usize>().ok();
+        }
+    }
+
+    let length = length.ok_or_else(|| "a message without Content-Length".to_string())?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body).map_err(|err| format!("can't read the message: {}", err))?;
+    serde_json::from_slice(&body).map(Some).map_err(|err| format!("invalid message: {}", err))
+}
+
+pub fn write_message(output: &mut dyn Write, message: &Value) -> Result<(), String> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+        .and_then(|_| output.flush())
+        .map_err(|err| format!("can't write the message: {}", err))
+}
+
+fn publish(uri: &str, diagnostics: Vec<Value>) -> Value {
+    json!({ "jsonrpc": "2.0", "method": "textDocument/publishDiagnostics", "params": { "uri": uri, "diagnostics": diagnostics } })
+}
+
+fn diagnostic(range: Value, severity: Severity, message: &str) -> Value {
+    let severity = match severity {
+        Severity::Error => 1,
+        Severity::Warning => 2,
+        Severity::Info => 3,
+    };
+    json!({ "range": range, "severity": severity, "source": "guarding", "message": message })
+}
+
+fn range(start_line: usize, start_column: usize, end_line: usize, end_column: usize) -> Value {
+    json!({ "start": { "line": start_line, "character": start_column }, "end": { "line": end_line, "character": end_column } })
+}
+
+/// by characters, which is the UTF-16 offset of the protocol for most rule files
+fn offset_of(text: &str, line: usize, character: usize) -> usize {
+    let line_start: usize = text.split_inclusive('\n').take(line).map(|line| line.len()).sum();
+    let line_text = text[line_start.min(text.len())..].lines().next().unwrap_or("");
+    let column: usize = line_text.chars().take(character).map(|c| c.len_utf8()).sum();
+    (line_start + column).min(text.len())
+}
+
+fn position_of(text: &str, offset: usize) -> (usize, usize) {
+    let before = &text[..offset.min(text.len())];
+    let line = before.matches('\n').count();
+    let column = before.rsplit('\n').next().unwrap_or("").chars().count();
+    (line, column)
+}
+
+fn word_at(text: &str, offset: usize) -> String {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let start = text[..offset].char_indices().rev()
+        .find(|(_, c)| !is_word(*c))
+        .map(|(index, c)| index + c.len_utf8())
+        .unwrap_or(0);
+    let end = text[offset..].find(|c: char| !is_word(c)).map(|index| offset + index).unwrap_or(text.len());
+    text[start..end].to_string()
+}
+
+/// the content of the double quoted string around `offset`
+fn string_at(text: &str, offset: usize) -> Option<&str> {
+    let line_start = text[..offset].rfind('\n').map(|index| index + 1).unwrap_or(0);
+    let before = &text[line_start..offset];
+    if before.matches('"').count() % 2 == 0 {
+        return None;
+    }
+
+    let start = line_start + before.rfind('"')? + 1;
+    let end = offset + text[offset..].find(|c: char| c == '"' || c == '\n')?;
+    Some(&text[start..end])
+}
+
+fn path_of_uri(uri: &str) -> PathBuf {
+    let path = uri.strip_prefix("file://").unwrap_or(uri);
+    PathBuf::from(percent_decode_str(path).decode_utf8_lossy().to_string())
+}
+
+fn uri_of_path(path: &Path) -> String {
+    format!("file://{}", utf8_percent_encode(&path.display().to_string(), URI_PATH))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::io::Cursor;
+
+    use serde_json::json;
+
+    use std::path::{Path, PathBuf};
+    use std::thread;
+    use std::time::Duration;
+
+    use crate::lsp::{diagnostics, path_of_uri, read_message, semantic_tokens, uri_of_path, write_message, LanguageServer};
+
+    fn open(server: &mut LanguageServer, uri: &str, text: &str) {
+        server.handle(&json!({ "method": "textDocument/didOpen", "params": { "textDocument": { "uri": uri, "text": text } } }));
+    }
+
+    fn request(server: &mut LanguageServer, method: &str, uri: &str, line: usize, character: usize) -> serde_json::Value {
+        let message = json!({ "id": 1, "method": method, "params": { "textDocument": { "uri": uri }, "position": { "line": line, "character": character } } });
+        server.handle(&message).unwrap()[0]["result"].clone()
+    }
+
+    #[test]
+    fn should_report_syntax_errors_and_issues_at_their_rules() {
+        let errors = diagnostics("class::name should");
+        assert_eq!(1, errors.len());
+        assert_eq!(0, errors[0]["range"]["start"]["line"]);
+
+        let issues = diagnostics("package(\".\")::file.len should < 200;\n@name(\"a\")\nclass::name should endsWith \"\";\n@name(\"a\")\nclass::len should < 2;");
+        assert!(!issues.is_empty());
+        assert!(issues.iter().all(|issue| issue["range"]["start"]["line"].as_u64().unwrap() > 0));
+    }
+
+    #[test]
+    fn should_complete_hover_and_go_to_rule_files() {
+        let dir = std::env::temp_dir().join("guarding").join("lsp");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("rules")).unwrap();
+        fs::write(dir.join("rules/naming.guarding"), "class::name should endsWith \"Service\";").unwrap();
+
+        let mut server = LanguageServer::new();
+        let rules = "file:///rules.guarding";
+        open(&mut server, rules, "class::name should endsWith \"Service\";");
+
+        let completions = request(&mut server, "textDocument/completion", rules, 0, 7);
+        assert!(completions.as_array().unwrap().iter().any(|item| item["label"] == "name"));
+        let hover = request(&mut server, "textDocument/hover", rules, 0, 22);
+        assert!(hover["contents"]["value"].as_str().unwrap().contains("endsWith"));
+        // a separator of more than one byte right before the word
+        open(&mut server, rules, "class::name should \u{bb}endsWith\u{3000}\"Service\";");
+        let hover = request(&mut server, "textDocument/hover", rules, 0, 20);
+        assert!(hover["contents"]["value"].as_str().unwrap().contains("endsWith"));
+        let hover = request(&mut server, "textDocument/hover", rules, 0, 28);
+        assert!(hover["contents"]["value"].as_str().unwrap().contains("endsWith"));
+
+        let config = format!("file://{}", dir.join("guarding.toml").display());
+        open(&mut server, &config, "rules = [\"rules/naming.guarding\"]");
+        let definition = request(&mut server, "textDocument/definition", &config, 0, 14);
+        assert!(definition["uri"].as_str().unwrap().ends_with("rules/naming.guarding"));
+    }
+
+    #[test]
+    fn should_decode_escaped_uris() {
+        assert_eq!(PathBuf::from("/work/my shop/#1/caf\u{e9}.guarding"), path_of_uri("file:///work/my%20shop/%231/caf%C3%A9.guarding"));
+        assert_eq!("file:///work/my%20shop/%231/caf%C3%A9.guarding", uri_of_path(Path::new("/work/my shop/#1/caf\u{e9}.guarding")));
+    }
+
+    #[test]
+    fn should_complete_packages_once_parsed() {
+        let dir = std::env::temp_dir().join("guarding").join("lsp packages");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("Order.java"), "package shop.order;\nclass Order {}").unwrap();
+
+        let mut server = LanguageServer::new();
+        server.handle(&json!({ "id": 1, "method": "initialize", "params": { "rootUri": uri_of_path(&dir) } }));
+        let rules = "file:///rules.guarding";
+        open(&mut server, rules, "class(resideIn \"");
+
+        let labels = (0..100)
+            .map(|_| {
+                let completions = request(&mut server, "textDocument/completion", rules, 0, 16);
+                thread::sleep(Duration::from_millis(20));
+                completions
+            })
+            .find(|completions| !completions.as_array().unwrap().is_empty())
+            .unwrap();
+        assert_eq!("..shop.order..", labels[0]["label"]);
+    }
+
+    #[test]
+    fn should_encode_semantic_tokens() {
+        let data = semantic_tokens("class::name\n  should < 20;");
+        // `class`, `::`, `name`, then `should`, `<` and `20` on the next line
+        assert_eq!(vec![
+            0, 0, 5, 0, 0,
+            0, 5, 2, 2, 0,
+            0, 2, 4, 5, 0,
+            1, 2, 6, 0, 0,
+            0, 7, 1, 2, 0,
+            0, 2, 2, 4, 0,
+        ], data);
+    }
+
+    #[test]
+    fn should_frame_messages() {
+        let mut output = vec![];
+        write_message(&mut output, &json!({ "id": 1 })).unwrap();
+        let message = read_message(&mut Cursor::new(output)).unwrap().unwrap();
+        assert_eq!(1, message["id"]);
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::Path;
+
+use crate::check_project;
+use crate::config::GuardingConfig;
+use crate::report::CheckReport;
+
+/// A project of a monorepo, like a service, with its config merged over the one of the repo.
+#[derive(Debug, Clone)]
+pub struct RepoProject {
+    /// the directory relative to the repo, like `services/billing`
+    pub name: String,
+    pub config: GuardingConfig,
+}
+
+/// the `projects` of `repo`, each with [`GuardingConfig::for_project`]
+pub fn repo_projects(repo: &GuardingConfig) -> Result<Vec<RepoProject>, String> {
+    repo.project_dirs().iter()
+        .map(|dir| {
+            let config = repo.for_project(dir).map_err(|err| format!("{}, in project {}", err, dir.display()))?;
+            Ok(RepoProject { name: project_name(&repo.dir, dir), config })
+        })
+        .collect()
+}
+
+/// checks every project with its own rules and roots, in one report
+pub fn check_projects(repo: &GuardingConfig) -> Result<CheckReport, String> {
+    let mut reports = vec![];
+    for project in repo_projects(repo)? {
+        let rules = project.config.load_rules()?.into_iter().map(|(_, rule)| rule).collect();
+        let report = check_project(rules, project.config.build_project(&project.config.source_roots()));
+        reports.push((project.name, report));
+    }
+
+    Ok(merge_reports(reports))
+}
+
+/// one report of the reports of the projects; the rules are named after their project, like
+/// `services/billing/naming` or `services/billing/rule-2`, so each violation tells its project
+pub fn merge_reports(reports: Vec<(String, CheckReport)>) -> CheckReport {
+    let mut merged = CheckReport { rules: vec![], errors: vec![], files: vec![] };
+    for (name, report) in reports {
+        let offset = merged.rules.len();
+        for (index, rule) in report.rules.iter().enumerate() {
+            let mut rule = rule.clone();
+            rule.name = format!("{}/{}", name, report.rule_id(index));
+            merged.rules.push(rule);
+        }
+
+        merged.errors.extend(report.errors.into_iter().map(|mut error| {
+            error.rule_index += offset;
+            error
+        }));
+        merged.files.extend(report.files);
+    }
+
+    merged
+}
+
+fn project_name(repo: &Path, dir: &Path) -> String {
+    let relative = dir.strip_prefix(repo).unwrap_or(dir);
+    relative.iter().filter_map(|part| part.to_str()).collect::<Vec<&str>>().join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use guarding_parser::ast::Severity;
+
+    use crate::config::GuardingConfig;
+    use crate::monorepo::{check_projects, repo_projects};
+
+    #[test]
+    fn should_check_each_project_with_merged_rules() {
+        let dir = env::temp_dir().join("guarding").join("monorepo");
+        let _ = fs::remove_dir_all(&dir);
+        let billing = dir.join("services").join("billing");
+        let orders = dir.join("services").join("orders");
+        fs::create_dir_all(billing.join("src")).unwrap();
+        fs::create_dir_all(orders.join("src")).unwrap();
+
+        fs::write(dir.join("guarding.toml"), "projects = [\"services/*\"]\nskip_rules = [\"size\"]\n\n[severity]\nnaming = \"warning\"\n").unwrap();
+        fs::write(dir.join("guarding.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Service\";\n@name(\"size\")\nclass::len should < 1;").unwrap();
+        fs::write(billing.join("guarding.toml"), "roots = [\"src\"]\n\n[severity]\nnaming = \"error\"\n").unwrap();
+        fs::write(billing.join("guarding.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Billing\";").unwrap();
+        fs::write(billing.join("src").join("Invoice.java"), "package billing;\nclass Invoice {}").unwrap();
+        fs::write(orders.join("src").join("Order.java"), "package orders;\nclass Order {}").unwrap();
+
+        let repo = GuardingConfig::load(&dir.join("guarding.toml")).unwrap();
+        assert_eq!(vec![dir.join("guarding.guarding")], repo.rule_files());
+        let projects = repo_projects(&repo).unwrap();
+        assert_eq!(vec!["services/billing", "services/orders"], projects.iter().map(|project| project.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(vec![billing.join("src")], projects[0].config.source_roots());
+
+        let report = check_projects(&repo).unwrap();
+        let violations = report.violations();
+        assert_eq!(2, violations.len());
+        assert_eq!(("services/billing/naming", Severity::Error), (violations[0].rule.as_str(), violations[0].severity));
+        assert!(violations[0].origin.contains("Billing"));
+        assert_eq!(("services/orders/naming", Severity::Warning), (violations[1].rule.as_str(), violations[1].severity));
+        assert_eq!(2, report.files.len());
+    }
+
+    #[test]
+    fn should_reject_jobs_of_a_project() {
+        let dir = env::temp_dir().join("guarding").join("monorepo-jobs");
+        let _ = fs::remove_dir_all(&dir);
+        let billing = dir.join("services").join("billing");
+        fs::create_dir_all(&billing).unwrap();
+        fs::write(dir.join("guarding.toml"), "projects = [\"services/*\"]\njobs = 2\n").unwrap();
+        fs::write(billing.join("guarding.toml"), "jobs = 4\n").unwrap();
+
+        let repo = GuardingConfig::load(&dir.join("guarding.toml")).unwrap();
+        let err = repo_projects(&repo).err().unwrap();
+        assert!(err.contains("jobs is only read from the repo's config"), "{}", err);
+
+        fs::write(billing.join("guarding.toml"), "roots = [\"src\"]\n").unwrap();
+        assert_eq!(Some(2), repo_projects(&repo).unwrap()[0].config.jobs);
+    }
+}
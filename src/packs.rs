@@ -0,0 +1,211 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+use tracing::debug;
+
+use guarding_parser::ast::GuardRule;
+
+use crate::changed::git;
+use crate::report::fnv1a;
+
+/// A rule pack shared between projects, the `*.guarding` files of a git repository:
+///
+/// ```toml
+/// [packs.clean]
+/// git = "https://github.com/acme/guarding-rules"
+/// ref = "v1.2.0"
+/// path = "clean-architecture"
+/// ```
+///
+/// The rules of the pack `clean` are named like `clean/layers`, so `skip_rules = ["clean/layers"]`
+/// disables one, `[severity]` changes it and a rule of the project named `clean/layers` replaces it.
+#[derive(Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct RulePack {
+    /// the URL of the repository, anything `git clone` takes
+    pub git: String,
+    /// a branch, tag or commit, the default branch when not set
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    /// the directory of the rules in the repository, all of it when not set
+    pub path: Option<String>,
+}
+
+impl RulePack {
+    /// where the pack is checked out under `packs_dir`, another URL or ref is another checkout
+    pub fn checkout_dir(&self, name: &str, packs_dir: &Path) -> PathBuf {
+        let key = format!("{}@{}", self.git, self.git_ref.as_deref().unwrap_or(""));
+        packs_dir.join(format!("{}-{:016x}", name, fnv1a(key.as_bytes())))
+    }
+
+    /// the checkout of the pack, cloned when it isn't there yet; `update` fetches it again, for
+    /// refs which move like branches
+    pub fn fetch(&self, name: &str, packs_dir: &Path, update: bool) -> Result<PathBuf, String> {
+        let checkout = self.checkout_dir(name, packs_dir);
+        let fetched = if !checkout.join(".git").exists() {
+            self.clone_into(&checkout)
+        } else if update {
+            self.update(&checkout)
+        } else {
+            Ok(())
+        };
+
+        fetched.map_err(|err| format!("can't fetch the rule pack {} from {}: {}", name, self.git, err))?;
+        Ok(checkout)
+    }
+
+    /// the `*.guarding` files of the checkout, by path
+    pub fn rule_files(&self, checkout: &Path) -> Result<Vec<PathBuf>, String> {
+        let dir = match &self.path {
+            Some(path) => checkout.join(path),
+            None => checkout.to_path_buf(),
+        };
+        if !dir.is_dir() {
+            return Err(format!("the rule pack has no directory {}", self.path.as_deref().unwrap_or(".")));
+        }
+
+        let mut files = vec![];
+        collect_rule_files(&dir, &mut files);
+        files.sort();
+        Ok(files)
+    }
+
+    /// the commit the checkout is at
+    pub fn revision(checkout: &Path) -> Result<String, String> {
+        git(checkout, &["rev-parse", "HEAD"]).map(|commit| commit.trim().to_string())
+    }
+
+    fn clone_into(&self, checkout: &Path) -> Result<(), String> {
+        let parent = checkout.parent().unwrap_or_else(|| Path::new("."));
+        fs::create_dir_all(parent).map_err(|err| format!("can't create {}: {}", parent.display(), err))?;
+        // a clone which failed half-way would be taken for a checkout
+        let _ = fs::remove_dir_all(checkout);
+
+        debug!("cloning {} into {}", self.git, checkout.display());
+        git(parent, &["clone", "--quiet", "--", &self.git, &checkout.display().to_string()])?;
+        match &self.git_ref {
+            Some(git_ref) => checkout_ref(checkout, git_ref),
+            None => Ok(())
+        }
+    }
+
+    fn update(&self, checkout: &Path) -> Result<(), String> {
+        debug!("fetching {} in {}", self.git, checkout.display());
+        git(checkout, &["fetch", "--quiet", "--tags", "origin"])?;
+        match &self.git_ref {
+            // a branch is at the fetched commit only as `origin/<branch>`
+            Some(git_ref) => checkout_ref(checkout, &format!("origin/{}", git_ref))
+                .or_else(|_| checkout_ref(checkout, git_ref)),
+            None => checkout_ref(checkout, "origin/HEAD")
+        }
+    }
+}
+
+/// `git_ref` is a revision and never a path or an option, the config refuses the ones starting
+/// with `-`
+fn checkout_ref(checkout: &Path, git_ref: &str) -> Result<(), String> {
+    git(checkout, &["checkout", "--quiet", "--detach", git_ref, "--"]).map(|_| ())
+}
+
+/// names the rules of the pack `name` like `name/layers`, unnamed ones stay unnamed
+pub fn namespace_rules(name: &str, rules: &mut Vec<GuardRule>) {
+    for rule in rules.iter_mut() {
+        if !rule.name.is_empty() {
+            rule.name = format!("{}/{}", name, rule.name);
+        }
+    }
+}
+
+fn collect_rule_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if path.file_name().map(|name| name != ".git").unwrap_or(false) {
+                collect_rule_files(&path, files);
+            }
+        } else if path.extension().map(|extension| extension == "guarding").unwrap_or(false) {
+            files.push(path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+    use std::path::Path;
+
+    use guarding_parser::ast::Severity;
+
+    use crate::changed::git;
+    use crate::config::GuardingConfig;
+    use crate::packs::RulePack;
+
+    fn commit(dir: &Path, message: &str) {
+        git(dir, &["add", "-A"]).unwrap();
+        git(dir, &["-c", "user.name=guarding", "-c", "user.email=guarding@example.com", "commit", "--quiet", "-m", message]).unwrap();
+    }
+
+    #[test]
+    fn should_fetch_the_rules_of_a_pack_at_a_ref() {
+        let dir = env::temp_dir().join("guarding").join("packs");
+        let _ = fs::remove_dir_all(&dir);
+        let remote = dir.join("remote");
+        fs::create_dir_all(remote.join("clean")).unwrap();
+        git(&remote, &["init", "--quiet"]).unwrap();
+        fs::write(remote.join("clean").join("layers.guarding"), "@name(\"layers\")\nclass::name should endsWith \"Service\";").unwrap();
+        fs::write(remote.join("README.md"), "rules").unwrap();
+        commit(&remote, "layers");
+        git(&remote, &["tag", "v1"]).unwrap();
+        fs::write(remote.join("clean").join("naming.guarding"), "class::len should < 20;").unwrap();
+        commit(&remote, "naming");
+
+        let pack = RulePack {
+            git: remote.display().to_string(),
+            git_ref: Some("v1".to_string()),
+            path: Some("clean".to_string()),
+        };
+        let checkout = pack.fetch("clean", &dir.join("cache"), false).unwrap();
+        let files = pack.rule_files(&checkout).unwrap();
+        assert_eq!(vec![checkout.join("clean").join("layers.guarding")], files);
+        assert_eq!(checkout, pack.fetch("clean", &dir.join("cache"), true).unwrap());
+
+        let latest = RulePack { git_ref: None, ..pack.clone() };
+        assert_ne!(checkout, latest.checkout_dir("clean", &dir.join("cache")));
+        let checkout = latest.fetch("clean", &dir.join("cache"), false).unwrap();
+        assert_eq!(2, latest.rule_files(&checkout).unwrap().len());
+        assert_eq!(40, RulePack::revision(&checkout).unwrap().len());
+    }
+
+    #[test]
+    fn should_namespace_pack_rules_for_overriding_them() {
+        let dir = env::temp_dir().join("guarding").join("pack-rules");
+        let _ = fs::remove_dir_all(&dir);
+        let remote = dir.join("remote");
+        fs::create_dir_all(&remote).unwrap();
+        git(&remote, &["init", "--quiet"]).unwrap();
+        fs::write(remote.join("clean.guarding"), "@name(\"layers\")\nclass::len should < 20;\n@name(\"naming\")\nclass::name should endsWith \"Service\";\n@name(\"size\")\nclass::len should < 10;").unwrap();
+        commit(&remote, "clean");
+
+        let project = dir.join("project");
+        fs::create_dir_all(&project).unwrap();
+        fs::write(project.join("guarding.guarding"), "@name(\"clean/size\")\nclass::len should < 50;").unwrap();
+        let toml = format!("skip_rules = [\"clean/layers\"]\n\n[severity]\n\"clean/naming\" = \"warning\"\n\n[packs.clean]\ngit = {:?}\n", remote.display().to_string());
+        let config = GuardingConfig::from_toml(&toml, project.clone()).unwrap();
+
+        let rules: Vec<_> = config.load_rules().unwrap().into_iter().map(|(path, rule)| (path, rule.name, rule.severity)).collect();
+        assert_eq!(2, rules.len());
+        assert_eq!((project.join("guarding.guarding"), "clean/size".to_string(), Severity::Error), rules[0]);
+        assert_eq!(("clean/naming".to_string(), Severity::Warning), (rules[1].1.clone(), rules[1].2));
+        assert!(project.join(".guarding").join("packs").is_dir());
+
+        assert!(GuardingConfig::from_toml("[packs.\"acme/clean\"]\ngit = \"x\"", project.clone()).is_err());
+        assert!(GuardingConfig::from_toml("[packs.clean]\nref = \"v1\"", project.clone()).is_err());
+        assert!(GuardingConfig::from_toml("[packs.clean]\ngit = \"x\"\nref = \"--foo\"", project.clone()).is_err());
+        assert!(GuardingConfig::from_toml("[packs.clean]\ngit = \"--upload-pack=touch /tmp/x\"", project).is_err());
+    }
+}
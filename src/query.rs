@@ -0,0 +1,113 @@
+use serde::{Deserialize, Serialize};
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_core::rule_executor::RuleExecutor;
+use guarding_parser::ast::{Expr, RuleLevel};
+use guarding_parser::parser;
+
+use crate::format::relative_path;
+
+/// One class, function or file selected by a query.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct QueryMatch {
+    /// `class`, `function` or `file`
+    pub kind: String,
+    pub name: String,
+    pub path: String,
+    /// 1-based, 0 for whole files
+    pub line: usize,
+}
+
+impl QueryMatch {
+    pub fn to_text(&self) -> String {
+        if self.line == 0 {
+            format!("{} {}  {}", self.kind, self.name, self.path)
+        } else {
+            format!("{} {}  {}:{}", self.kind, self.name, self.path, self.line)
+        }
+    }
+}
+
+/// runs the selection part of a rule, like `class(resideIn "..service..")` or
+/// `class(implementation "Parser")::function`, and returns what it selects
+pub fn run_query(query: &str, project: CodeProject) -> Result<Vec<QueryMatch>, String> {
+    let rule = parser::parse_query(query).map_err(|err| format!("{}", err))?;
+    let property = match &rule.expr {
+        Expr::PropsCall(props) => props.join("."),
+        Expr::Identifier(ident) => ident.clone(),
+    };
+
+    let mut executor = RuleExecutor::from_project(project, vec![]);
+    let mut matches = vec![];
+    match (&rule.level, property.as_str()) {
+        (RuleLevel::Class, "") | (RuleLevel::Struct, "") => {
+            for (location, class) in executor.select_classes(&rule) {
+                let name = if class.package.is_empty() { class.name.clone() } else { class.full_name() };
+                matches.push(QueryMatch { kind: class.kind.clone(), name, path: relative_path(&location.path), line: class.start.row + 1 });
+            }
+        }
+        (RuleLevel::Class, "function") | (RuleLevel::Struct, "function") => {
+            for (location, class) in executor.select_classes(&rule) {
+                for function in &class.functions {
+                    let name = format!("{}.{}", class.name, function.name);
+                    matches.push(QueryMatch { kind: "function".to_string(), name, path: relative_path(&location.path), line: function.start.row + 1 });
+                }
+            }
+        }
+        (RuleLevel::Package, "") | (RuleLevel::Package, "file") => {
+            for file in executor.select_files(&rule) {
+                matches.push(QueryMatch { kind: "file".to_string(), name: file.package.clone(), path: relative_path(&file.path), line: 0 });
+            }
+        }
+        _ => return Err(format!("can't query `{}` of a {}, only classes, their functions and package files", property, rule.level.name()))
+    }
+
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::code_class::CodeClass;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_function::CodeFunction;
+    use guarding_core::domain::code_project::CodeProject;
+
+    use crate::query::run_query;
+
+    fn project() -> CodeProject {
+        let file = |package: &str, path: &str, class: &str| {
+            let mut file = CodeFile::default();
+            file.package = package.to_string();
+            file.path = path.to_string();
+            let mut code_class = CodeClass::default();
+            code_class.name = class.to_string();
+            code_class.package = package.to_string();
+            let mut function = CodeFunction::default();
+            function.name = "handle".to_string();
+            code_class.functions.push(function);
+            file.classes.push(code_class);
+            file
+        };
+
+        CodeProject::new(vec![
+            file("shop.service", "src/OrderService.java", "OrderService"),
+            file("shop.web", "src/OrderController.java", "OrderController"),
+        ])
+    }
+
+    #[test]
+    fn should_query_classes_and_functions() {
+        let matches = run_query("class(resideIn \"..service..\")", project()).unwrap();
+        assert_eq!(1, matches.len());
+        assert_eq!("class shop.service.OrderService  src/OrderService.java:1", matches[0].to_text());
+
+        let matches = run_query("class::function", project()).unwrap();
+        assert_eq!(vec!["OrderService.handle", "OrderController.handle"], matches.iter().map(|found| found.name.as_str()).collect::<Vec<&str>>());
+
+        let matches = run_query("package(\"..web..\")", project()).unwrap();
+        assert_eq!("file shop.web  src/OrderController.java", matches[0].to_text());
+
+        assert!(run_query("class::doc", project()).is_err());
+        assert!(run_query("class(resideIn", project()).is_err());
+    }
+}
@@ -0,0 +1,151 @@
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+
+use guarding_core::domain::code_project::CodeProject;
+
+use crate::{check_project, parse_rules};
+use crate::config::GuardingConfig;
+use crate::format::OutputFormat;
+use crate::query::run_query;
+
+const HELP: &'static str = "a rule ending with `;` prints its violations, like
+  class(resideIn \"..service..\")::name should endsWith \"Service\";
+a scope prints what it selects, like
+  class(implementation \"Parser\")::function
+a rule can span lines until its `;`
+
+:files   the number of parsed files
+:reload  parses the project again
+:help    this text
+:quit    leaves, like end of input
+";
+
+/// The project parsed once, to try rules and scopes against it one after the other.
+pub struct ReplSession {
+    config: GuardingConfig,
+    roots: Vec<PathBuf>,
+    project: CodeProject,
+}
+
+impl ReplSession {
+    pub fn new(config: GuardingConfig, roots: Option<Vec<PathBuf>>) -> ReplSession {
+        let roots = roots.unwrap_or_else(|| config.source_roots());
+        let project = config.build_project(&roots);
+        ReplSession { config, roots, project }
+    }
+
+    pub fn project(&self) -> &CodeProject {
+        &self.project
+    }
+
+    /// the answer to one complete input, see `is_complete`; `None` to quit
+    pub fn eval(&mut self, input: &str, color: bool) -> Option<Result<String, String>> {
+        let input = input.trim();
+        let answer = match input {
+            "" => Ok(String::new()),
+            ":quit" | ":q" => return None,
+            ":help" | ":h" => Ok(HELP.to_string()),
+            ":files" => Ok(format!("{} files\n", self.project.files.len())),
+            ":reload" => {
+                self.project = self.config.build_project(&self.roots);
+                Ok(format!("reloaded {} files\n", self.project.files.len()))
+            }
+            _ if input.starts_with(':') => Err(format!("unknown command {}, :help lists them", input)),
+            _ if input.ends_with(';') => self.check(input, color),
+            _ => self.query(input),
+        };
+
+        Some(answer)
+    }
+
+    fn check(&self, rules: &str, color: bool) -> Result<String, String> {
+        let mut rules = parse_rules(rules)?;
+        self.config.apply_rule_settings(&mut rules);
+        let report = check_project(rules, self.project.clone());
+        Ok(OutputFormat::Text.render_colored(&report, color))
+    }
+
+    fn query(&self, query: &str) -> Result<String, String> {
+        let matches = run_query(query, self.project.clone())?;
+        let mut text: String = matches.iter().map(|found| format!("{}\n", found.to_text())).collect();
+        text.push_str(&format!("{} matches\n", matches.len()));
+        Ok(text)
+    }
+}
+
+/// a command, a rule up to its `;` or a scope without `should`; anything else waits for more lines
+pub fn is_complete(input: &str) -> bool {
+    let input = input.trim();
+    input.is_empty() || input.starts_with(':') || input.ends_with(';') || !input.contains("should")
+}
+
+/// reads inputs from stdin until `:quit` or its end, printing every answer
+pub fn repl(mut session: ReplSession, color: bool) -> Result<(), String> {
+    println!("{} files loaded, :help for help", session.project().files.len());
+    let stdin = io::stdin();
+    let mut input = String::new();
+    prompt("guarding> ");
+
+    for line in stdin.lock().lines() {
+        let line = line.map_err(|err| format!("can't read stdin: {}", err))?;
+        input.push_str(&line);
+        input.push('\n');
+        if !is_complete(&input) {
+            prompt("   ...> ");
+            continue;
+        }
+
+        match session.eval(&input, color) {
+            Some(Ok(answer)) => print!("{}", answer),
+            Some(Err(err)) => eprintln!("{}", err),
+            None => return Ok(()),
+        }
+        input.clear();
+        prompt("guarding> ");
+    }
+
+    Ok(())
+}
+
+fn prompt(text: &str) {
+    print!("{}", text);
+    let _ = io::stdout().flush();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::config::GuardingConfig;
+    use crate::repl::{is_complete, ReplSession};
+
+    #[test]
+    fn should_answer_rules_and_queries() {
+        let dir = std::env::temp_dir().join("guarding").join("repl");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("shop/service")).unwrap();
+        fs::write(dir.join("shop/service/OrderService.java"), "package shop.service;\nclass OrderService {}").unwrap();
+        fs::write(dir.join("shop/service/Orders.java"), "package shop.service;\nclass Orders {}").unwrap();
+
+        let config = GuardingConfig::from_toml("", dir.clone()).unwrap();
+        let mut session = ReplSession::new(config, None);
+
+        let matches = session.eval("class(resideIn \"..service..\")", false).unwrap().unwrap();
+        assert!(matches.ends_with("2 matches\n"));
+
+        let violations = session.eval("class(resideIn \"..service..\")::name should endsWith \"Service\";", false).unwrap().unwrap();
+        assert!(violations.contains("Orders"));
+        assert!(!violations.contains("OrderService.java"));
+
+        assert!(session.eval(":unknown", false).unwrap().is_err());
+        assert!(session.eval(":quit", false).is_none());
+    }
+
+    #[test]
+    fn should_wait_for_the_end_of_a_rule() {
+        assert!(!is_complete("class::name\n  should endsWith \"Service\"\n"));
+        assert!(is_complete("class::name\n  should endsWith \"Service\";\n"));
+        assert!(is_complete("class(resideIn \"..service..\")\n"));
+        assert!(is_complete(":files\n"));
+    }
+}
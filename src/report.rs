@@ -0,0 +1,337 @@
+use serde::Deserialize;
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::rule_executor::RuleErrorMsg;
+use guarding_core::rule_executor::rule_error::ErrorLocation;
+use guarding_parser::ast::{GuardRule, Severity};
+
+/// The result of checking a project: the rules, what they found, and the parsed files.
+pub struct CheckReport {
+    pub rules: Vec<GuardRule>,
+    pub errors: Vec<RuleErrorMsg>,
+    pub files: Vec<CodeFile>,
+}
+
+/// When a check fails: on violations of `fail_on` or more severe rules, and only when there are
+/// more than `max_violations` of them if set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FailPolicy {
+    pub fail_on: Severity,
+    pub max_violations: Option<usize>,
+}
+
+impl Default for FailPolicy {
+    fn default() -> Self {
+        FailPolicy { fail_on: Severity::Error, max_violations: None }
+    }
+}
+
+/// The exit code of a check by its outcome, so scripts tell a broken architecture from a broken
+/// setup; `parse_error` is 0 by default, files which didn't parse are warnings only.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct ExitCodes {
+    /// the check fails by the fail policy
+    pub violations: i32,
+    /// the config or a rule file can't be read or has invalid rules
+    pub rule_error: i32,
+    /// some sources have syntax errors
+    pub parse_error: i32,
+    /// guarding itself failed
+    pub internal_error: i32,
+}
+
+impl Default for ExitCodes {
+    fn default() -> Self {
+        ExitCodes { violations: 1, rule_error: 2, parse_error: 0, internal_error: 101 }
+    }
+}
+
+impl ExitCodes {
+    /// violations win over parse errors, and 0 when neither happened
+    pub fn of_report(&self, report: &CheckReport, policy: &FailPolicy) -> i32 {
+        if report.fails(policy) {
+            self.violations
+        } else if report.unparsed_count() > 0 {
+            self.parse_error
+        } else {
+            0
+        }
+    }
+}
+
+/// One violated item of a rule, or the whole violation for rules without items, like sizes.
+#[derive(Debug, Clone)]
+pub struct Violation {
+    pub rule: String,
+    pub rule_index: usize,
+    /// the rule as written
+    pub origin: String,
+    pub severity: Severity,
+    pub message: String,
+    pub item: Option<String>,
+    pub location: Option<ErrorLocation>,
+    /// with `--suggest`, like the name a naming rule expects
+    pub suggestion: Option<String>,
+    /// stays the same while the rule and the violating code do, even when lines move
+    pub fingerprint: String,
+}
+
+impl Violation {
+    /// a violation of a custom rule, which guarding fills the rule fields of
+    pub fn new(message: impl Into<String>) -> Violation {
+        Violation {
+            rule: String::new(),
+            rule_index: 0,
+            origin: String::new(),
+            severity: Severity::Error,
+            message: message.into(),
+            item: None,
+            location: None,
+            suggestion: None,
+            fingerprint: String::new(),
+        }
+    }
+
+    /// the violating item, like a class, and where it is if known
+    pub fn with_item(mut self, item: impl Into<String>, location: Option<ErrorLocation>) -> Violation {
+        self.item = Some(item.into());
+        self.location = location;
+        self
+    }
+}
+
+impl CheckReport {
+    pub fn violations(&self) -> Vec<Violation> {
+        self.errors.iter().flat_map(|error| self.violations_of(error)).collect()
+    }
+
+    /// the violations of one error, which can be of another report with the same rules
+    pub fn violations_of(&self, error: &RuleErrorMsg) -> Vec<Violation> {
+        let rule = self.rule_name(error);
+        let origin = self.rule_of(error).map(|rule| rule.origin.clone()).unwrap_or_default();
+        let violation = |item: Option<&String>, location: Option<&ErrorLocation>, suggestion: Option<&String>| {
+            let key = format!("{}\0{}\0{}", rule, error.msg, item.map(|item| item.as_str()).unwrap_or(""));
+            Violation {
+                rule: rule.clone(),
+                rule_index: error.rule_index,
+                origin: origin.clone(),
+                severity: self.severity_of(error),
+                message: error.msg.clone(),
+                item: item.cloned(),
+                location: location.cloned(),
+                suggestion: suggestion.filter(|suggestion| !suggestion.is_empty()).cloned(),
+                fingerprint: fingerprint(&key),
+            }
+        };
+
+        if error.items.is_empty() {
+            return vec![violation(None, None, None)];
+        }
+        error.items.iter().enumerate()
+            .map(|(index, item)| violation(Some(item), error.location_of(index), error.suggestions.get(index)))
+            .collect()
+    }
+
+    pub fn rule_of(&self, error: &RuleErrorMsg) -> Option<&GuardRule> {
+        self.rules.get(error.rule_index)
+    }
+
+    pub fn severity_of(&self, error: &RuleErrorMsg) -> Severity {
+        self.rule_of(error).map(|rule| rule.severity).unwrap_or(Severity::Error)
+    }
+
+    /// `@name` of the rule, or its position like `rule #2` for unnamed ones
+    pub fn rule_name(&self, error: &RuleErrorMsg) -> String {
+        match self.rule_of(error) {
+            Some(rule) if !rule.name.is_empty() => rule.name.clone(),
+            _ => format!("rule #{}", error.rule_index + 1)
+        }
+    }
+
+    /// the `@name`, or `rule-2` for the second rule when it has none, usable as an identifier
+    pub fn rule_id(&self, index: usize) -> String {
+        match self.rules.get(index) {
+            Some(rule) if !rule.name.is_empty() => rule.name.clone(),
+            _ => format!("rule-{}", index + 1)
+        }
+    }
+
+    /// violations of error rules, warnings and infos don't fail a check
+    pub fn has_failures(&self) -> bool {
+        self.fails(&FailPolicy::default())
+    }
+
+    pub fn fails(&self, policy: &FailPolicy) -> bool {
+        let failing = self.failing_count(policy);
+        match policy.max_violations {
+            Some(max_violations) => failing > max_violations,
+            None => failing > 0
+        }
+    }
+
+    /// the violated items of rules as severe as `fail_on` or more
+    pub fn failing_count(&self, policy: &FailPolicy) -> usize {
+        self.violations().iter().filter(|violation| violation.severity >= policy.fail_on).count()
+    }
+
+    /// the files with parse warnings, like syntax errors
+    pub fn unparsed_count(&self) -> usize {
+        self.files.iter().filter(|file| !file.warnings.is_empty()).count()
+    }
+
+    /// the path and error of every file which couldn't be identified at all
+    pub fn ident_errors(&self) -> Vec<(String, String)> {
+        self.files.iter()
+            .filter_map(|file| file.ident_error.as_ref().map(|err| (file.path.clone(), err.clone())))
+            .collect()
+    }
+
+    pub fn count_of(&self, severity: Severity) -> usize {
+        self.errors.iter().filter(|error| self.severity_of(error) == severity).count()
+    }
+
+    /// one line per violated item, or per violation without items, to compare two reports
+    pub fn violation_lines(&self) -> Vec<String> {
+        let mut lines = vec![];
+        for error in &self.errors {
+            let head = format!("{}[{}]: {}", self.severity_of(error).name(), self.rule_name(error), error.msg);
+            if error.items.is_empty() {
+                lines.push(head);
+            } else {
+                lines.extend(error.items.iter().map(|item| format!("{} - {}", head, item)));
+            }
+        }
+
+        lines
+    }
+
+    /// one line per violation and one indented line per item
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for error in &self.errors {
+            text.push_str(&format!("{}[{}]: {}\n", self.severity_of(error).name(), self.rule_name(error), error.msg));
+            if let Some(rule) = self.rule_of(error) {
+                text.push_str(&format!("  --> {}\n", rule.origin));
+            }
+            for item in &error.items {
+                text.push_str(&format!("  - {}\n", item));
+            }
+        }
+        for (path, err) in self.ident_errors() {
+            text.push_str(&format!("failed[{}]: {}\n", path, err));
+        }
+
+        text.push_str(&self.summary());
+        text.push('\n');
+        text
+    }
+
+    pub fn summary(&self) -> String {
+        let summary = format!("{} rules, {} files: {} errors, {} warnings, {} infos",
+                              self.rules.len(),
+                              self.files.len(),
+                              self.count_of(Severity::Error),
+                              self.count_of(Severity::Warning),
+                              self.count_of(Severity::Info));
+        match self.ident_errors().len() {
+            0 => summary,
+            failed => format!("{}, {} files failed", summary, failed)
+        }
+    }
+}
+
+fn fingerprint(key: &str) -> String {
+    format!("{:016x}", fnv1a(key.as_bytes()))
+}
+
+/// FNV-1a, stable between versions and platforms unlike the std hasher
+pub(crate) fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::rule_executor::RuleErrorMsg;
+    use guarding_core::rule_executor::rule_error::MismatchType;
+    use guarding_parser::ast::{GuardRule, Severity};
+
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::parse_warning::ParseWarning;
+
+    use crate::report::{CheckReport, ExitCodes, FailPolicy};
+
+    #[test]
+    fn should_only_fail_on_error_rules() {
+        let mut warning = GuardRule::default();
+        warning.name = "naming".to_string();
+        warning.severity = Severity::Warning;
+
+        let mut report = CheckReport {
+            rules: vec![warning, GuardRule::default()],
+            errors: vec![RuleErrorMsg::new(MismatchType::FileName, 0)],
+            files: vec![],
+        };
+        assert!(!report.has_failures());
+        assert!(report.to_text().starts_with("warning[naming]"));
+
+        report.errors.push(RuleErrorMsg::new(MismatchType::FileSize, 1));
+        assert!(report.has_failures());
+        assert_eq!("rule #2", report.rule_name(&report.errors[1]));
+
+        let violations = report.violations();
+        assert_eq!(2, violations.len());
+        assert_eq!(Severity::Warning, violations[0].severity);
+        assert_ne!(violations[0].fingerprint, violations[1].fingerprint);
+        assert_eq!(violations[0].fingerprint, report.violations()[0].fingerprint);
+    }
+
+    #[test]
+    fn should_fail_by_policy() {
+        let mut warning = GuardRule::default();
+        warning.severity = Severity::Warning;
+        let report = CheckReport {
+            rules: vec![warning],
+            errors: vec![RuleErrorMsg::new(MismatchType::FileName, 0), RuleErrorMsg::new(MismatchType::FileName, 0)],
+            files: vec![],
+        };
+
+        assert!(!report.fails(&FailPolicy::default()));
+        assert!(report.fails(&FailPolicy { fail_on: Severity::Warning, max_violations: None }));
+        assert!(report.fails(&FailPolicy { fail_on: Severity::Info, max_violations: Some(1) }));
+        assert!(!report.fails(&FailPolicy { fail_on: Severity::Warning, max_violations: Some(2) }));
+    }
+
+    #[test]
+    fn should_map_outcomes_to_exit_codes() {
+        let codes = ExitCodes { violations: 3, parse_error: 4, ..Default::default() };
+        let mut file = CodeFile::default();
+        file.warnings.push(ParseWarning::default());
+        let mut report = CheckReport { rules: vec![GuardRule::default()], errors: vec![], files: vec![CodeFile::default()] };
+        assert_eq!(0, codes.of_report(&report, &FailPolicy::default()));
+
+        report.files.push(file);
+        assert_eq!(4, codes.of_report(&report, &FailPolicy::default()));
+        assert_eq!(0, ExitCodes::default().of_report(&report, &FailPolicy::default()));
+
+        report.errors.push(RuleErrorMsg::new(MismatchType::FileName, 0));
+        assert_eq!(3, codes.of_report(&report, &FailPolicy::default()));
+    }
+
+    #[test]
+    fn should_report_files_which_failed() {
+        let mut failed = CodeFile::failed("identifier failed: java");
+        failed.path = "src/Order.java".to_string();
+        let report = CheckReport { rules: vec![], errors: vec![], files: vec![CodeFile::default(), failed] };
+
+        assert_eq!(vec![("src/Order.java".to_string(), "identifier failed: java".to_string())], report.ident_errors());
+        assert_eq!(1, report.unparsed_count());
+        assert!(report.summary().ends_with(", 1 files failed"));
+        assert!(report.to_text().contains("failed[src/Order.java]: identifier failed: java"));
+    }
+}
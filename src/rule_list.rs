@@ -0,0 +1,130 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use guarding_parser::ast::{GuardRule, RuleScope};
+
+use crate::format::relative_path;
+
+/// One loaded rule as `guarding rules list` shows it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RuleEntry {
+    /// the `@name`, or `rule-2` like in the reports
+    pub id: String,
+    pub file: String,
+    pub level: String,
+    pub scope: String,
+    pub severity: String,
+    pub tags: Vec<String>,
+    pub rule: String,
+}
+
+impl RuleEntry {
+    pub fn of(file: &Path, index: usize, rule: &GuardRule) -> RuleEntry {
+        RuleEntry {
+            id: if rule.name.is_empty() { format!("rule-{}", index + 1) } else { rule.name.clone() },
+            file: relative_path(&file.display().to_string()),
+            level: rule.level.name().to_string(),
+            scope: scope_text(&rule.scope),
+            severity: rule.severity.name().to_string(),
+            tags: rule.tags.clone(),
+            rule: rule.origin.clone(),
+        }
+    }
+}
+
+pub fn list_rules(rules: &[(PathBuf, GuardRule)]) -> Vec<RuleEntry> {
+    rules.iter().enumerate().map(|(index, (file, rule))| RuleEntry::of(file, index, rule)).collect()
+}
+
+/// the scope as written in a rule
+pub fn scope_text(scope: &RuleScope) -> String {
+    match scope {
+        RuleScope::All => "*".to_string(),
+        RuleScope::PathDefine(path) => path.clone(),
+        RuleScope::Extend(name) => format!("extends {}", name),
+        RuleScope::Assignable(name) => format!("assignable {}", name),
+        RuleScope::Implementation(name) => format!("implementation {}", name),
+        RuleScope::Kind(kind) => format!("kind {}", kind),
+        RuleScope::MatchRegex(regex) => format!("match({})", regex),
+    }
+}
+
+/// aligned columns, one line per rule
+pub fn to_table(entries: &[RuleEntry]) -> String {
+    let header = ["ID", "LEVEL", "SCOPE", "SEVERITY", "TAGS", "FILE"];
+    let rows: Vec<[String; 6]> = entries.iter()
+        .map(|entry| [
+            entry.id.clone(),
+            entry.level.clone(),
+            entry.scope.clone(),
+            entry.severity.clone(),
+            entry.tags.join(","),
+            entry.file.clone(),
+        ])
+        .collect();
+
+    let mut widths: Vec<usize> = header.iter().map(|title| title.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row.iter()) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells.iter().zip(widths.iter()).map(|(cell, width)| format!("{:<width$}", cell, width = width)).collect();
+        format!("{}\n", padded.join("  ").trim_end())
+    };
+
+    let mut table = line(header.to_vec());
+    for row in &rows {
+        table.push_str(&line(row.iter().map(|cell| cell.as_str()).collect()));
+    }
+    table
+}
+
+/// a line per rule file with the rules it contributed, in the order they were loaded
+pub fn to_sources(entries: &[RuleEntry]) -> String {
+    let mut files: Vec<(&str, Vec<&str>)> = vec![];
+    for entry in entries {
+        match files.iter_mut().find(|(file, _)| *file == entry.file) {
+            Some((_, ids)) => ids.push(&entry.id),
+            None => files.push((&entry.file, vec![&entry.id])),
+        }
+    }
+
+    files.iter()
+        .map(|(file, ids)| format!("{}: {}\n", file, ids.join(", ")))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use crate::parse_rules;
+    use crate::rule_list::{list_rules, to_sources, to_table};
+
+    #[test]
+    fn should_list_rules() {
+        let rules = parse_rules("@name(\"service-naming\")
+@severity(\"warning\")
+@tag(\"naming\")
+class(resideIn \"..service..\")::name should endsWith \"Service\";
+package(\".\")::file.len should < 200;").unwrap();
+        let rules: Vec<(PathBuf, _)> = rules.into_iter().enumerate()
+            .map(|(index, rule)| (PathBuf::from(if index == 0 { "guarding.guarding" } else { "rules/size.guarding" }), rule))
+            .collect();
+
+        let entries = list_rules(&rules);
+        assert_eq!("service-naming", entries[0].id);
+        assert_eq!("..service..", entries[0].scope);
+        assert_eq!("rule-2", entries[1].id);
+
+        let table = to_table(&entries);
+        assert_eq!("ID              LEVEL    SCOPE        SEVERITY  TAGS    FILE", table.lines().next().unwrap());
+        assert_eq!("rule-2          package  .            error             rules/size.guarding", table.lines().nth(2).unwrap());
+
+        assert_eq!("guarding.guarding: service-naming\nrules/size.guarding: rule-2\n", to_sources(&entries));
+    }
+}
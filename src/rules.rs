@@ -0,0 +1,217 @@
+use guarding_parser::ast::{Expr, GuardRule, Operator, RuleAssert, RuleLevel, RuleScope};
+
+/// Rules built in code instead of the guarding DSL, for architecture tests in `#[test]` functions:
+///
+/// ```
+/// use guarding::rules::classes;
+///
+/// let rule = classes().that().reside_in("..service..").should().have_name_ending_with("Service");
+/// assert_eq!("class(resideIn \"..service..\")::name should endsWith \"Service\";", rule.origin);
+/// ```
+///
+/// The rules can be checked with `Guarding::with_rule`; their `origin` is the same rule in the
+/// DSL, so reports show them like rules of a file.
+pub fn classes() -> Classes {
+    Classes
+}
+
+/// every class, until `that` narrows them
+pub struct Classes;
+
+impl Classes {
+    pub fn that(self) -> ClassesThat {
+        ClassesThat
+    }
+
+    pub fn should(self) -> ClassesShould {
+        GivenClasses { scope: RuleScope::All }.should()
+    }
+}
+
+/// the one filter of a rule, a rule can't have more than one
+pub struct ClassesThat;
+
+impl ClassesThat {
+    /// in the packages of a pattern like `..service..`
+    pub fn reside_in(self, package: &str) -> GivenClasses {
+        GivenClasses { scope: RuleScope::PathDefine(package.to_string()) }
+    }
+
+    pub fn implement(self, name: &str) -> GivenClasses {
+        GivenClasses { scope: RuleScope::Implementation(name.to_string()) }
+    }
+
+    pub fn extend(self, name: &str) -> GivenClasses {
+        GivenClasses { scope: RuleScope::Extend(name.to_string()) }
+    }
+
+    /// direct or indirect subtypes of `name`
+    pub fn are_assignable_to(self, name: &str) -> GivenClasses {
+        GivenClasses { scope: RuleScope::Assignable(name.to_string()) }
+    }
+
+    /// `interface`, `enum`..., like the `kind` scope
+    pub fn are_of_kind(self, kind: &str) -> GivenClasses {
+        GivenClasses { scope: RuleScope::Kind(kind.to_string()) }
+    }
+
+    pub fn are_not_of_kind(self, kind: &str) -> GivenClasses {
+        GivenClasses { scope: RuleScope::Kind(format!("!{}", kind)) }
+    }
+}
+
+pub struct GivenClasses {
+    scope: RuleScope,
+}
+
+impl GivenClasses {
+    pub fn should(self) -> ClassesShould {
+        ClassesShould { scope: self.scope, negated: false }
+    }
+}
+
+/// the assert ending a rule, every method returns the rule
+pub struct ClassesShould {
+    scope: RuleScope,
+    negated: bool,
+}
+
+impl ClassesShould {
+    /// negates the assert, like `should not endsWith`
+    pub fn not(mut self) -> ClassesShould {
+        self.negated = !self.negated;
+        self
+    }
+
+    pub fn have_name_ending_with(self, suffix: &str) -> GuardRule {
+        self.name(Operator::Endswith, suffix)
+    }
+
+    pub fn have_name_starting_with(self, prefix: &str) -> GuardRule {
+        self.name(Operator::StartsWith, prefix)
+    }
+
+    pub fn have_name_containing(self, text: &str) -> GuardRule {
+        self.name(Operator::Contains, text)
+    }
+
+    /// like `class(implementation "Repository") resideIn package("..persistence..")`
+    pub fn reside_in(self, package: &str) -> GuardRule {
+        let assert = RuleAssert::Leveled(RuleLevel::Package, package.to_string());
+        self.rule(Expr::Identifier("".to_string()), Operator::ResideIn, assert)
+    }
+
+    pub fn have_doc(self) -> GuardRule {
+        self.not().rule(Expr::PropsCall(vec!["doc".to_string()]), Operator::Empty, RuleAssert::Empty)
+    }
+
+    /// only used from the packages of the patterns, for a `reside_in` filter
+    pub fn only_be_accessed_by(self, packages: &[&str]) -> GuardRule {
+        let packages = packages.iter().map(|package| package.to_string()).collect();
+        self.rule(Expr::Identifier("".to_string()), Operator::Accessed, RuleAssert::ArrayStringed(packages))
+    }
+
+    fn name(self, op: Operator, text: &str) -> GuardRule {
+        self.rule(Expr::PropsCall(vec!["name".to_string()]), op, RuleAssert::Stringed(text.to_string()))
+    }
+
+    fn rule(self, expr: Expr, op: Operator, assert: RuleAssert) -> GuardRule {
+        let mut ops = vec![];
+        if self.negated {
+            ops.push(Operator::Not);
+        }
+        ops.push(op);
+
+        let mut rule = GuardRule {
+            level: RuleLevel::Class,
+            scope: self.scope,
+            expr,
+            ops,
+            assert,
+            ..Default::default()
+        };
+        rule.origin = to_dsl(&rule);
+        rule
+    }
+}
+
+/// the rule in the guarding DSL without its metas, for the rules the builder makes and the
+/// ones of YAML and TOML files
+pub(crate) fn to_dsl(rule: &GuardRule) -> String {
+    let mut text = rule.level.name().to_string();
+    match &rule.scope {
+        RuleScope::All => {}
+        RuleScope::PathDefine(path) => text.push_str(&format!("(resideIn {})", quote(path))),
+        RuleScope::Extend(name) => text.push_str(&format!("(extends {})", quote(name))),
+        RuleScope::Assignable(name) => text.push_str(&format!("(assignable {})", quote(name))),
+        RuleScope::Implementation(name) => text.push_str(&format!("(implementation {})", quote(name))),
+        RuleScope::Kind(kind) => text.push_str(&format!("(kind {})", quote(kind))),
+        RuleScope::MatchRegex(regex) => text.push_str(&format!("(match({}))", quote(regex))),
+    }
+    if let Expr::PropsCall(props) = &rule.expr {
+        text.push_str(&format!("::{}", props.join(".")));
+    }
+
+    // `should not accessed` forbids instead of allowing
+    let only = rule.ops.first() == Some(&Operator::Accessed);
+    text.push_str(if only { " only" } else { " should" });
+    for op in &rule.ops {
+        text.push_str(match op {
+            Operator::Not => " not",
+            Operator::Endswith => " endsWith",
+            Operator::StartsWith => " startsWith",
+            Operator::Contains => " contains",
+            Operator::ResideIn => " resideIn",
+            Operator::Accessed => " accessed",
+            Operator::Empty => " empty",
+            Operator::Inside => " inside",
+            Operator::DependBy => " dependBy",
+            Operator::Gt => " >",
+            Operator::Gte => " >=",
+            Operator::Lt => " <",
+            Operator::Lte => " <=",
+            Operator::Eq => " =",
+            _ => ""
+        });
+    }
+
+    match &rule.assert {
+        RuleAssert::Stringed(string) => text.push_str(&format!(" {}", quote(string))),
+        RuleAssert::Leveled(level, string) => text.push_str(&format!(" {}({})", level.name(), quote(string))),
+        RuleAssert::ArrayStringed(strings) => {
+            let strings: Vec<String> = strings.iter().map(|string| quote(string)).collect();
+            text.push_str(&format!("([{}])", strings.join(", ")));
+        }
+        RuleAssert::Sized(size) => text.push_str(&format!(" {}", size)),
+        RuleAssert::Empty => {}
+    }
+    text.push(';');
+    text
+}
+
+/// the DSL has no escapes, so a string with `"` is single quoted
+fn quote(string: &str) -> String {
+    if string.contains('"') { format!("'{}'", string) } else { format!("\"{}\"", string) }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_rules;
+    use crate::rules::classes;
+
+    #[test]
+    fn should_build_the_rules_of_the_dsl() {
+        let rules = vec![
+            classes().that().reside_in("..service..").should().have_name_ending_with("Service"),
+            classes().that().are_not_of_kind("enum").should().not().have_name_starting_with("Abstract"),
+            classes().should().have_name_containing("Order"),
+            classes().that().implement("Repository").should().reside_in("..persistence.."),
+            classes().that().reside_in("..api..").should().have_doc(),
+            classes().that().reside_in("..service..").should().only_be_accessed_by(&["..controller..", "..service.."]),
+        ];
+
+        for rule in rules {
+            assert_eq!(vec![rule.clone()], parse_rules(&rule.origin).unwrap(), "{}", rule.origin);
+        }
+    }
+}
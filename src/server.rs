@@ -0,0 +1,281 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+use crate::format::json::JsonReport;
+use crate::graph::{ArchGraph, GraphLevel};
+use crate::report::CheckReport;
+use crate::stats::ProjectStats;
+use crate::watch::WatchSession;
+
+/// the largest classes and functions in `/stats` without a `?top=`
+const DEFAULT_TOP: usize = 10;
+/// how long a client has to send its request and read the answer
+const IO_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Answers the HTTP `GET`s of dashboards polling the architecture of a project, in JSON:
+///
+/// - `/violations`, the report like `guarding check --format json` with `failed`
+/// - `/rules`, each rule with its severity, how many violations it has and whether it passed
+/// - `/stats`, like `guarding stats`, `?top=20` for more of the largest classes and functions
+/// - `/graph`, the package dependencies with the violated ones, `?level=class` for classes
+/// - `/health`
+///
+/// The project is checked again by a request once the last check is `refresh_every` old, so
+/// polling doesn't re-parse it each time.
+pub struct HttpServer {
+    session: WatchSession,
+    refresh_every: Duration,
+    checked: Instant,
+}
+
+impl HttpServer {
+    pub fn new(session: WatchSession, refresh_every: Duration) -> HttpServer {
+        HttpServer { session, refresh_every, checked: Instant::now() }
+    }
+
+    /// the status and the body answering `method` on `target`, like `/graph?level=class`
+    pub fn respond(&mut self, method: &str, target: &str) -> (u16, Value) {
+        if method != "GET" {
+            return (405, json!({ "error": format!("{} isn't supported, only GET", method) }));
+        }
+
+        let (path, query) = match target.split_once('?') {
+            Some((path, query)) => (path, query),
+            None => (target, "")
+        };
+        if path == "/health" {
+            return (200, json!({ "status": "ok" }));
+        }
+
+        if self.checked.elapsed() >= self.refresh_every {
+            if let Err(err) = self.session.refresh() {
+                return (500, json!({ "error": err }));
+            }
+            self.checked = Instant::now();
+        }
+
+        let report = self.session.report();
+        match path {
+            "/violations" => {
+                let mut body = json!(JsonReport::from_report(report));
+                body["failed"] = json!(report.has_failures());
+                (200, body)
+            }
+            "/rules" => (200, rule_status(report)),
+            "/stats" => match query_param(query, "top").map(str::parse::<usize>) {
+                None => (200, json!(ProjectStats::of_files(&report.files, DEFAULT_TOP))),
+                Some(Ok(top)) => (200, json!(ProjectStats::of_files(&report.files, top))),
+                Some(Err(_)) => (400, json!({ "error": "`top` isn't a number" })),
+            },
+            "/graph" => match GraphLevel::from_name(query_param(query, "level").unwrap_or("package")) {
+                Some(level) => (200, graph_json(&ArchGraph::from_report(report, level, None))),
+                None => (400, json!({ "error": "`level` is `package` or `class`" })),
+            },
+            _ => (404, json!({ "error": format!("no {}", path) })),
+        }
+    }
+
+    /// reads one request of `input` and writes its answer to `output`; the headers are skipped,
+    /// a `GET` has no body
+    pub fn answer(&mut self, input: &mut dyn BufRead, output: &mut dyn Write) -> Result<(), String> {
+        let (status, body) = match read_request(input)? {
+            Some((method, target)) => self.respond(&method, &target),
+            None => bad_request(),
+        };
+        write_response(output, status, &body)
+    }
+}
+
+/// answers the requests on `address`, like `127.0.0.1:7878`, until killed; each connection is
+/// read on its own thread, so a slow client doesn't hold up the others, and the answers are
+/// made one at a time
+pub fn serve(server: HttpServer, address: &str) -> Result<(), String> {
+    let listener = TcpListener::bind(address).map_err(|err| format!("can't listen on {}: {}", address, err))?;
+    eprintln!("serving on http://{}", address);
+
+    let server = Arc::new(Mutex::new(server));
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                warn!("can't accept a connection: {}", err);
+                continue;
+            }
+        };
+
+        let server = Arc::clone(&server);
+        thread::spawn(move || {
+            if let Err(err) = answer_connection(&server, stream) {
+                warn!("{}", err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn answer_connection(server: &Mutex<HttpServer>, mut stream: TcpStream) -> Result<(), String> {
+    stream.set_read_timeout(Some(IO_TIMEOUT))
+        .and_then(|_| stream.set_write_timeout(Some(IO_TIMEOUT)))
+        .map_err(|err| format!("can't set the timeouts of a connection: {}", err))?;
+    let mut input = BufReader::new(stream.try_clone().map_err(|err| format!("can't read a connection: {}", err))?);
+
+    let (status, body) = match read_request(&mut input)? {
+        // a request which panicked left the last report, it's still worth answering
+        Some((method, target)) => server.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).respond(&method, &target),
+        None => bad_request(),
+    };
+    write_response(&mut stream, status, &body)
+}
+
+/// the method and target of the request of `input`, `None` when it isn't HTTP; the headers are
+/// skipped, a `GET` has no body
+fn read_request(input: &mut dyn BufRead) -> Result<Option<(String, String)>, String> {
+    let mut line = String::new();
+    input.read_line(&mut line).map_err(|err| format!("can't read the request: {}", err))?;
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Ok(None),
+    };
+
+    loop {
+        let mut header = String::new();
+        let read = input.read_line(&mut header).map_err(|err| format!("can't read the request: {}", err))?;
+        if read == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    debug!("http {} {}", method, target);
+    Ok(Some((method, target)))
+}
+
+fn bad_request() -> (u16, Value) {
+    (400, json!({ "error": "not an HTTP request" }))
+}
+
+fn rule_status(report: &CheckReport) -> Value {
+    let mut counts = vec![0; report.rules.len()];
+    for violation in report.violations() {
+        if let Some(count) = counts.get_mut(violation.rule_index) {
+            *count += 1;
+        }
+    }
+
+    let rules: Vec<Value> = report.rules.iter().enumerate().map(|(index, rule)| {
+        let violations = counts[index];
+        let name = if rule.name.is_empty() { format!("rule #{}", index + 1) } else { rule.name.clone() };
+        json!({ "name": name, "severity": rule.severity, "violations": violations, "passed": violations == 0 })
+    }).collect();
+    json!(rules)
+}
+
+fn graph_json(graph: &ArchGraph) -> Value {
+    let edges: Vec<Value> = graph.edges.iter()
+        .map(|(from, to)| json!({ "from": from, "to": to, "violated": graph.violated.contains(&(from.clone(), to.clone())) }))
+        .collect();
+    json!({ "nodes": graph.nodes, "edges": edges })
+}
+
+fn query_param<'a>(query: &'a str, name: &str) -> Option<&'a str> {
+    query.split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value)
+}
+
+fn write_response(output: &mut dyn Write, status: u16, body: &Value) -> Result<(), String> {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    write!(output, "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+           status, reason, body.len(), body)
+        .and_then(|_| output.flush())
+        .map_err(|err| format!("can't answer: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::Duration;
+    use std::{env, fs};
+
+    use crate::config::GuardingConfig;
+    use crate::server::{serve, HttpServer};
+    use crate::watch::WatchSession;
+
+    #[test]
+    fn should_serve_the_health_of_a_project() {
+        let dir = env::temp_dir().join("guarding").join("server");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src").join("Order.java"), "package shop.service;\nclass Order {}").unwrap();
+        fs::write(dir.join("guarding.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Service\";").unwrap();
+
+        let config = GuardingConfig { dir: dir.clone(), ..Default::default() };
+        let session = WatchSession::new(config, Some(vec![dir.join("src")]), None).unwrap();
+        let mut server = HttpServer::new(session, Duration::from_secs(60));
+
+        let (status, body) = server.respond("GET", "/rules");
+        assert_eq!(200, status);
+        assert_eq!("naming", body[0]["name"]);
+        assert_eq!(1, body[0]["violations"]);
+        assert_eq!(false, body[0]["passed"]);
+
+        assert_eq!(true, server.respond("GET", "/violations").1["failed"]);
+        assert_eq!(1, server.respond("GET", "/stats?top=1").1["files"]);
+        assert_eq!(400, server.respond("GET", "/graph?level=module").0);
+        assert_eq!(404, server.respond("GET", "/reports").0);
+        assert_eq!(405, server.respond("POST", "/rules").0);
+
+        let mut output = vec![];
+        server.answer(&mut Cursor::new("GET /health HTTP/1.1\r\nHost: localhost\r\n\r\n"), &mut output).unwrap();
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(output.ends_with("{\"status\":\"ok\"}"));
+
+        let mut output = vec![];
+        server.answer(&mut Cursor::new("\r\n"), &mut output).unwrap();
+        assert!(String::from_utf8(output).unwrap().starts_with("HTTP/1.1 400 Bad Request\r\n"));
+    }
+
+    #[test]
+    fn should_answer_while_another_client_is_idle() {
+        let dir = env::temp_dir().join("guarding").join("server-idle");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let config = GuardingConfig { dir: dir.clone(), ..Default::default() };
+        let server = HttpServer::new(WatchSession::new(config, Some(vec![dir.clone()]), None).unwrap(), Duration::from_secs(60));
+
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let serving = address.clone();
+        thread::spawn(move || serve(server, &serving));
+
+        let connect = || (0..50)
+            .find_map(|_| TcpStream::connect(&address).ok().or_else(|| {
+                thread::sleep(Duration::from_millis(20));
+                None
+            }))
+            .unwrap();
+        let _idle = connect();
+        let mut client = connect();
+        client.write_all(b"GET /health HTTP/1.1\r\n\r\n").unwrap();
+        let mut answer = String::new();
+        client.read_to_string(&mut answer).unwrap();
+        assert!(answer.ends_with("{\"status\":\"ok\"}"));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::resolver::DependencyGraph;
+
+use crate::format::relative_path;
+
+/// The size and coupling of a project, from the model the rules run on.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ProjectStats {
+    pub files: usize,
+    pub classes: usize,
+    pub functions: usize,
+    pub packages: Vec<PackageStats>,
+    pub largest_classes: Vec<SizeEntry>,
+    pub largest_functions: Vec<SizeEntry>,
+    /// the packages depending on each other in circles
+    pub cycles: Vec<Vec<String>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PackageStats {
+    pub name: String,
+    pub files: usize,
+    pub classes: usize,
+    pub abstract_classes: usize,
+    /// the packages of the project using this one
+    pub afferent: usize,
+    /// the packages of the project this one uses
+    pub efferent: usize,
+    /// `efferent / (afferent + efferent)`, 0 for a package only used, 1 for one only using
+    pub instability: f64,
+}
+
+/// a class by its lines or a function by its lines of code
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct SizeEntry {
+    pub name: String,
+    pub path: String,
+    pub line: usize,
+    pub size: usize,
+}
+
+impl ProjectStats {
+    /// `top` of the largest classes and functions
+    pub fn of_files(files: &[CodeFile], top: usize) -> ProjectStats {
+        let graph = DependencyGraph::build(files);
+        let mut packages: BTreeMap<&str, PackageStats> = BTreeMap::new();
+        let mut classes = vec![];
+        let mut functions = vec![];
+
+        for file in files {
+            let package = packages.entry(file.package.as_str()).or_insert_with(|| PackageStats { name: file.package.clone(), ..Default::default() });
+            package.files += 1;
+            package.classes += file.classes.len();
+            package.abstract_classes += file.classes.iter().filter(|class| class.is_abstract).count();
+
+            for class in &file.classes {
+                classes.push(SizeEntry {
+                    name: class.full_name(),
+                    path: relative_path(&file.path),
+                    line: class.start.row + 1,
+                    size: class.end.row.saturating_sub(class.start.row) + 1,
+                });
+                for function in &class.functions {
+                    functions.push(SizeEntry {
+                        name: format!("{}.{}", class.name, function.name),
+                        path: relative_path(&file.path),
+                        line: function.start.row + 1,
+                        size: function.loc,
+                    });
+                }
+            }
+            for function in &file.functions {
+                functions.push(SizeEntry {
+                    name: function.name.clone(),
+                    path: relative_path(&file.path),
+                    line: function.start.row + 1,
+                    size: function.loc,
+                });
+            }
+        }
+
+        let names: Vec<&str> = packages.keys().copied().collect();
+        for (name, package) in packages.iter_mut() {
+            package.efferent = graph.dependencies_of(name).iter().filter(|other| names.contains(other)).count();
+            package.afferent = graph.dependents_of(name).iter().filter(|other| names.contains(other)).count();
+            let coupling = package.afferent + package.efferent;
+            package.instability = if coupling == 0 { 0.0 } else { package.efferent as f64 / coupling as f64 };
+        }
+
+        ProjectStats {
+            files: files.len(),
+            classes: classes.len(),
+            functions: functions.len(),
+            packages: packages.into_iter().map(|(_, package)| package).collect(),
+            largest_classes: largest(classes, top),
+            largest_functions: largest(functions, top),
+            cycles: graph.cycles().iter().map(|cycle| cycle.iter().map(|name| name.to_string()).collect()).collect(),
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{} files, {} classes, {} functions, {} packages, {} cycles\n",
+                               self.files, self.classes, self.functions, self.packages.len(), self.cycles.len());
+
+        text.push_str("\npackages (files, classes, abstract, used by, uses, instability):\n");
+        let width = self.packages.iter().map(|package| package.name.chars().count()).max().unwrap_or(0);
+        for package in &self.packages {
+            let name = if package.name.is_empty() { "(default)" } else { package.name.as_str() };
+            text.push_str(&format!("  {:<width$}  {:>4} {:>4} {:>4} {:>4} {:>4}  {:.2}\n", name,
+                                   package.files, package.classes, package.abstract_classes,
+                                   package.afferent, package.efferent, package.instability, width = width.max(9)));
+        }
+
+        text.push_str("\nlargest classes (lines):\n");
+        for class in &self.largest_classes {
+            text.push_str(&format!("  {:>5}  {}  {}:{}\n", class.size, class.name, class.path, class.line));
+        }
+        text.push_str("\nlargest functions (lines of code):\n");
+        for function in &self.largest_functions {
+            text.push_str(&format!("  {:>5}  {}  {}:{}\n", function.size, function.name, function.path, function.line));
+        }
+
+        if !self.cycles.is_empty() {
+            text.push_str("\ncycles:\n");
+            for cycle in &self.cycles {
+                text.push_str(&format!("  {}\n", cycle.join(" <-> ")));
+            }
+        }
+        text
+    }
+}
+
+fn largest(mut entries: Vec<SizeEntry>, top: usize) -> Vec<SizeEntry> {
+    entries.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.name.cmp(&b.name)));
+    entries.truncate(top);
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use guarding_core::domain::CodePoint;
+    use guarding_core::domain::code_class::CodeClass;
+    use guarding_core::domain::code_file::CodeFile;
+    use guarding_core::domain::code_function::CodeFunction;
+    use guarding_core::domain::code_import::CodeImport;
+
+    use crate::stats::ProjectStats;
+
+    fn file(package: &str, class: &str, lines: usize, imports: &[&str]) -> CodeFile {
+        let mut file = CodeFile::default();
+        file.package = package.to_string();
+        file.path = format!("{}.java", class);
+        let mut code_class = CodeClass::default();
+        code_class.name = class.to_string();
+        code_class.package = package.to_string();
+        code_class.end = CodePoint { row: lines - 1, column: 0 };
+        let mut function = CodeFunction::default();
+        function.name = "run".to_string();
+        function.loc = lines / 2;
+        code_class.functions.push(function);
+        file.classes.push(code_class);
+        file.imports = imports.iter().map(|import| CodeImport::symbol(import, ".")).collect();
+        file
+    }
+
+    #[test]
+    fn should_count_sizes_coupling_and_cycles() {
+        let files = vec![
+            file("shop.order", "Order", 40, &["shop.core.Entity", "shop.web.OrderController"]),
+            file("shop.core", "Entity", 10, &[]),
+            file("shop.web", "OrderController", 25, &["shop.order.Order", "java.util.List"]),
+        ];
+        let stats = ProjectStats::of_files(&files, 2);
+
+        assert_eq!(3, stats.classes);
+        let order = stats.packages.iter().find(|package| package.name == "shop.order").unwrap();
+        assert_eq!((1, 2), (order.afferent, order.efferent));
+        let core = stats.packages.iter().find(|package| package.name == "shop.core").unwrap();
+        assert_eq!(0.0, core.instability);
+
+        assert_eq!(vec!["shop.order.Order", "shop.web.OrderController"], stats.largest_classes.iter().map(|class| class.name.as_str()).collect::<Vec<&str>>());
+        assert_eq!(20, stats.largest_functions[0].size);
+        assert_eq!(1, stats.cycles.len());
+        assert!(stats.to_text().starts_with("3 files, 3 classes, 3 functions, 3 packages, 1 cycles\n"));
+    }
+}
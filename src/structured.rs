@@ -0,0 +1,109 @@
+use std::path::Path;
+
+use serde::Deserialize;
+
+use guarding_parser::ast::{GuardRule, RuleType};
+
+use crate::parse_rules;
+use crate::rules::to_dsl;
+
+/// A YAML or TOML rule file, a `rules` list of `GuardRule`s:
+///
+/// ```yaml
+/// rules:
+///   - name: service-naming
+///     scope: { resideIn: "..service.." }
+///     expr: { props: [name] }
+///     ops: [endsWith]
+///     assert: { string: Service }
+/// ```
+#[derive(Deserialize, Debug, Default)]
+struct RuleFile {
+    #[serde(default)]
+    rules: Vec<GuardRule>,
+}
+
+/// the rules of a rule file, YAML or TOML by the extension and the DSL otherwise
+pub fn parse_rule_file(path: &Path, content: &str) -> Result<Vec<GuardRule>, String> {
+    match path.extension().and_then(|extension| extension.to_str()) {
+        Some("yaml") | Some("yml") => from_yaml(content),
+        Some("toml") => from_toml(content),
+        _ => parse_rules(content),
+    }
+}
+
+pub fn from_yaml(content: &str) -> Result<Vec<GuardRule>, String> {
+    let file: RuleFile = serde_yaml::from_str(content).map_err(|err| format!("{}", err))?;
+    finish(file.rules)
+}
+
+pub fn from_toml(content: &str) -> Result<Vec<GuardRule>, String> {
+    let file: RuleFile = toml::from_str(content).map_err(|err| format!("{}", err))?;
+    finish(file.rules)
+}
+
+/// the DSL as the origin for the reports, and the rules the executor can't run as errors
+fn finish(mut rules: Vec<GuardRule>) -> Result<Vec<GuardRule>, String> {
+    for (index, rule) in rules.iter_mut().enumerate() {
+        if rule.ty == RuleType::Normal && rule.ops.is_empty() {
+            let name = if rule.name.is_empty() { format!("rule {}", index + 1) } else { format!("rule {:?}", rule.name) };
+            return Err(format!("{} has no `ops`", name));
+        }
+        if rule.origin.is_empty() {
+            rule.origin = to_dsl(rule);
+        }
+    }
+
+    Ok(rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::parse_rules;
+    use crate::structured::{from_toml, from_yaml, parse_rule_file};
+
+    #[test]
+    fn should_read_the_same_rules_as_the_dsl() {
+        let dsl = parse_rules("@name(\"service-naming\")\n@tag(\"naming\")\nclass(resideIn \"..service..\")::name should endsWith \"Service\";\n\
+            @severity(\"warning\")\nclass(\"..domain..\") only accessed([\"..service..\"]);").unwrap();
+
+        let yaml = from_yaml("rules:
+  - name: service-naming
+    tags: [naming]
+    scope: { resideIn: \"..service..\" }
+    expr: { props: [name] }
+    ops: [endsWith]
+    assert: { string: Service }
+  - severity: warning
+    scope: { resideIn: \"..domain..\" }
+    ops: [accessed]
+    assert: { strings: [\"..service..\"] }
+").unwrap();
+        assert_eq!(dsl[0], yaml[0]);
+        assert_eq!(dsl[1].ops, yaml[1].ops);
+        assert_eq!(dsl[1].assert, yaml[1].assert);
+        assert_eq!("warning", yaml[1].severity.name());
+
+        let toml = from_toml("[[rules]]
+name = \"service-naming\"
+tags = [\"naming\"]
+scope = { resideIn = \"..service..\" }
+expr = { props = [\"name\"] }
+ops = [\"endsWith\"]
+assert = { string = \"Service\" }
+").unwrap();
+        assert_eq!(yaml[0], toml[0]);
+    }
+
+    #[test]
+    fn should_pick_the_format_by_extension() {
+        let content = "rules:\n  - expr: { props: [name] }\n    ops: [endsWith]\n    assert: { string: Service }\n";
+        let rules = parse_rule_file(Path::new("rules.yml"), content).unwrap();
+        assert_eq!("class::name should endsWith \"Service\";", rules[0].origin);
+
+        assert!(parse_rule_file(Path::new("rules.yaml"), "rules:\n  - name: empty\n").unwrap_err().contains("empty"));
+        assert!(parse_rule_file(Path::new("rules.guarding"), content).is_err());
+    }
+}
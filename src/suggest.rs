@@ -0,0 +1,95 @@
+use guarding_parser::ast::{Expr, GuardRule, Operator};
+
+use crate::report::CheckReport;
+
+/// the words ending or starting a name by its role, replaced instead of kept when the rule
+/// expects another one, like `OrderMgr` to `OrderService`
+const ROLE_SUFFIXES: [&'static str; 18] = [
+    "Manager", "Mgr", "Service", "Svc", "Controller", "Ctrl", "Repository", "Repo", "Dao",
+    "Handler", "Helper", "Utils", "Util", "Impl", "Dto", "Factory", "Provider", "Adapter",
+];
+const ROLE_PREFIXES: [&'static str; 3] = ["Abstract", "Base", "Default"];
+
+/// a name `name` of a rule like `class::name should endsWith "Service"` expects, `None` for
+/// other rules or when there is no better name
+pub fn suggest_name(name: &str, rule: &GuardRule) -> Option<String> {
+    let is_name = matches!(&rule.expr, Expr::PropsCall(props) if props.first().map(|prop| prop == "name").unwrap_or(false));
+    let expected = GuardRule::assert_string(rule);
+    if !is_name || expected.is_empty() {
+        return None;
+    }
+
+    let (negated, op) = match rule.ops.as_slice() {
+        [Operator::Not, op, ..] => (true, op),
+        [op, ..] => (false, op),
+        [] => return None
+    };
+
+    let suggested = match (op, negated) {
+        (Operator::Endswith, false) => {
+            let stem = ROLE_SUFFIXES.iter().find(|suffix| name.len() > suffix.len() && name.ends_with(*suffix))
+                .map(|suffix| &name[..name.len() - suffix.len()])
+                .unwrap_or(name);
+            // `OrderServiceImpl` only loses its `Impl` for `endsWith "Service"`
+            if stem.ends_with(expected.as_str()) { stem.to_string() } else { format!("{}{}", stem, expected) }
+        }
+        (Operator::StartsWith, false) => {
+            let stem = ROLE_PREFIXES.iter().find(|prefix| name.len() > prefix.len() && name.starts_with(*prefix))
+                .map(|prefix| &name[prefix.len()..])
+                .unwrap_or(name);
+            format!("{}{}", expected, stem)
+        }
+        (Operator::Contains, false) => format!("{}{}", name, expected),
+        (Operator::Endswith, true) => name.strip_suffix(expected.as_str())?.to_string(),
+        (Operator::StartsWith, true) => name.strip_prefix(expected.as_str())?.to_string(),
+        (Operator::Contains, true) => name.replacen(expected.as_str(), "", 1),
+        _ => return None
+    };
+
+    if suggested.is_empty() || suggested == name {
+        None
+    } else {
+        Some(suggested)
+    }
+}
+
+/// fills the suggestions of every violated item of a naming rule, the items end with the name
+/// like `path: shop.order, name: OrderMgr`
+pub fn add_suggestions(report: &mut CheckReport) {
+    let rules = report.rules.clone();
+    for error in report.errors.iter_mut() {
+        let rule = match rules.get(error.rule_index) {
+            Some(rule) => rule,
+            None => continue
+        };
+
+        error.suggestions = error.items.iter()
+            .map(|item| {
+                let name = item.rsplit("name: ").next().unwrap_or(item);
+                suggest_name(name, rule).unwrap_or_default()
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse_rules;
+    use crate::suggest::suggest_name;
+
+    fn suggest(name: &str, rule: &str) -> Option<String> {
+        suggest_name(name, &parse_rules(rule).unwrap()[0])
+    }
+
+    #[test]
+    fn should_suggest_names_following_the_rule() {
+        assert_eq!(Some("OrderService".to_string()), suggest("OrderMgr", "class::name should endsWith \"Service\";"));
+        assert_eq!(Some("OrderManager".to_string()), suggest("Order", "class::name should endsWith \"Manager\";"));
+        assert_eq!(Some("IOrder".to_string()), suggest("Order", "class::name should startsWith \"I\";"));
+        assert_eq!(Some("AbstractParser".to_string()), suggest("BaseParser", "class::name should startsWith \"Abstract\";"));
+        assert_eq!(Some("OrderService".to_string()), suggest("OrderServiceImpl", "class::name should not endsWith \"Impl\";"));
+        assert_eq!(Some("OrderService".to_string()), suggest("OrderServiceImpl", "class::name should endsWith \"Service\";"));
+        assert_eq!(None, suggest("Impl", "class::name should not endsWith \"Impl\";"));
+        assert_eq!(None, suggest("Order", "class::len should < 20;"));
+    }
+}
@@ -0,0 +1,124 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use guarding_core::domain::code_project::CodeProject;
+use guarding_ident::{LanguageRegistry, ModelBuilder};
+
+use crate::report::CheckReport;
+use crate::{check_project, parse_rules};
+
+static FIXTURES: AtomicUsize = AtomicUsize::new(0);
+
+/// checks `rules` on inline sources, like `[("src/shop/Order.java", "package shop; class Order {}")]`;
+/// they are written to a temporary directory and parsed like a project, so package and module
+/// resolution work, the paths in the report are the given ones
+pub fn check_sources(rules: &str, sources: &[(&str, &str)]) -> Result<CheckReport, String> {
+    let rules = parse_rules(rules)?;
+    let dir = env::temp_dir()
+        .join("guarding-fixtures")
+        .join(format!("{}-{}", process::id(), FIXTURES.fetch_add(1, Ordering::Relaxed)));
+
+    let written = write_sources(&dir, sources);
+    let models = written.map(|_| ModelBuilder::build_models_with(&LanguageRegistry::default(), dir.clone()));
+    let _ = fs::remove_dir_all(&dir);
+
+    let mut models = models?;
+    for file in models.iter_mut() {
+        if let Ok(path) = Path::new(&file.path).strip_prefix(&dir) {
+            file.path = ModelBuilder::format_path(path);
+        }
+    }
+    Ok(check_project(rules, CodeProject::new(models)))
+}
+
+/// panics with the violations when `rules` find any in `sources`, of any severity
+///
+/// ```
+/// guarding::testing::assert_rule_passes(
+///     "class::name should endsWith \"Service\";",
+///     &[("OrderService.java", "class OrderService {}")],
+/// );
+/// ```
+pub fn assert_rule_passes(rules: &str, sources: &[(&str, &str)]) {
+    let report = check_or_panic(rules, sources);
+    if !report.violations().is_empty() {
+        panic!("expected no violations, found:\n{}", report.to_text());
+    }
+}
+
+/// panics when `rules` find nothing in `sources`; the report is returned for asserting on
+/// which items were found
+///
+/// ```
+/// let report = guarding::testing::assert_rule_violates(
+///     "class::name should endsWith \"Service\";",
+///     &[("Order.java", "class Order {}")],
+/// );
+/// assert!(report.has_failures());
+/// ```
+pub fn assert_rule_violates(rules: &str, sources: &[(&str, &str)]) -> CheckReport {
+    let report = check_or_panic(rules, sources);
+    if report.violations().is_empty() {
+        panic!("expected violations, found none in {} files", report.files.len());
+    }
+    report
+}
+
+fn check_or_panic(rules: &str, sources: &[(&str, &str)]) -> CheckReport {
+    match check_sources(rules, sources) {
+        Ok(report) => report,
+        Err(err) => panic!("can't check the rules: {}", err)
+    }
+}
+
+fn write_sources(dir: &Path, sources: &[(&str, &str)]) -> Result<(), String> {
+    for (path, code) in sources {
+        let path = dir.join(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|err| format!("can't create {}: {}", parent.display(), err))?;
+        }
+        fs::write(&path, code).map_err(|err| format!("can't write {}: {}", path.display(), err))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::testing::{assert_rule_passes, assert_rule_violates, check_sources};
+
+    const LAYERS: &str = "class(\"..repository..\") should not accessed([\"..controller..\"]);";
+
+    #[test]
+    fn should_check_inline_sources_by_their_paths() {
+        let report = assert_rule_violates(LAYERS, &[
+            ("src/shop/repository/OrderRepository.java", "package shop.repository;\nclass OrderRepository {}"),
+            ("src/shop/controller/OrderController.java", "package shop.controller;\nimport shop.repository.OrderRepository;\nclass OrderController {}"),
+        ]);
+
+        let violation = &report.violations()[0];
+        assert_eq!("src/shop/controller/OrderController.java", violation.location.as_ref().unwrap().path);
+    }
+
+    #[test]
+    fn should_pass_without_violations() {
+        assert_rule_passes(LAYERS, &[
+            ("src/shop/repository/OrderRepository.java", "package shop.repository;\nclass OrderRepository {}"),
+            ("src/shop/service/OrderService.java", "package shop.service;\nimport shop.repository.OrderRepository;\nclass OrderService {}"),
+        ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected violations")]
+    fn should_panic_when_nothing_violates() {
+        assert_rule_violates(LAYERS, &[("Order.java", "class Order {}")]);
+    }
+
+    #[test]
+    fn should_refuse_broken_rules() {
+        assert!(check_sources("class::name should", &[]).is_err());
+    }
+}
@@ -1,8 +1,11 @@
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use guarding_core::rule_executor::rule_error::MismatchType;
-use crate::exec_guarding;
+use guarding_ident::LanguageRegistry;
+
+use crate::{check, check_source, exec_guarding, INIT_RULES, parse_rules};
+use crate::testing::check_sources;
 
 fn test_dir() -> PathBuf {
     PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -10,6 +13,13 @@ fn test_dir() -> PathBuf {
         .join("java")
 }
 
+fn ts_dir() -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("_fixtures")
+        .join("ts")
+        .join("src")
+}
+
 #[test]
 fn should_working_in_process() {
     let path = test_dir().join("size.guarding");
@@ -43,7 +53,7 @@ fn should_support_filter() {
 
 #[test]
 fn should_support_for_class_filter() {
-    let content = "class(\".\")::len should < 25;
+    let content = "class(\".\")::len should < 30;
 class(\".\")::len should > 20;";
     let errors = exec_guarding(content.to_string(), test_dir());
 
@@ -58,6 +68,36 @@ fn should_support_for_extends_count() {
     assert_eq!(0, errors.len());
 }
 
+#[test]
+fn should_support_for_assignable_count() {
+    let content = "class(assignable \"BaseParser\")::len = 2";
+    let errors = exec_guarding(content.to_string(), test_dir());
+
+    assert_eq!(0, errors.len());
+}
+
+#[test]
+fn should_support_kind_scope() {
+    let content = "class(kind \"interface\")::len = 4;
+class(kind \"!interface\")::len = 22;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+
+    assert_eq!(0, errors.len());
+}
+
+#[test]
+fn should_check_external_imports() {
+    let content = "package(\".\")::external.import.len should = 24;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(0, errors.len());
+
+    let content = "package(\"..pepper..\")::external.import should inside([\"java..\", \"org.springframework..\"]);";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(1, errors.len());
+    assert_eq!(1, errors[0].items.len());
+    assert!(errors[0].items[0].contains("org.reflections.Reflections"));
+}
+
 #[test]
 fn should_support_for_extends_ends_with() {
     let content = "class(implementation \"BaseParser\")::name should endsWith \"Parser2\";";
@@ -135,3 +175,105 @@ fn should_support_for_accessed() {
     assert_eq!(1, errors.len());
     assert!(errors[0].items[0].contains("MyDictionary.java"))
 }
+
+#[test]
+fn should_support_not_accessed() {
+    let content = "class(\"java.util.Map\") should not accessed([\"com.phodal.pepper.refactor.staticclass\"]);";
+    let errors = exec_guarding(content.to_string(), test_dir());
+
+    assert_eq!(1, errors.len());
+    let items = errors[0].items.join(" ");
+    assert!(items.contains("LogFileHandle.java"));
+    assert!(items.contains("FileVerify.java"));
+    assert!(!items.contains("MyDictionary.java"));
+}
+
+#[test]
+fn should_support_ts_alias_for_accessed() {
+    let content = "class(\"..shared..\") only accessed([\"..features..\"]);";
+    let errors = exec_guarding(content.to_string(), ts_dir());
+
+    assert_eq!(1, errors.len());
+    assert!(errors[0].items[0].contains("main.ts"))
+}
+
+#[test]
+fn should_support_doc_empty() {
+    let content = "class(resideIn \"com.phodal.pepper.refactor.parser\")::doc should not empty;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+
+    assert_eq!(1, errors.len());
+    assert_eq!(MismatchType::Documentation, errors[0].mismatch_type);
+    // `JsonParser`, `XmlParser` and the `BaseParser` interface
+    assert_eq!(3, errors[0].items.len());
+
+    let content = "class(resideIn \"com.phodal.pepper.refactor.parser\")::doc should empty;";
+    let errors = exec_guarding(content.to_string(), test_dir());
+    assert_eq!(0, errors.len());
+}
+
+#[test]
+fn should_check_with_severity() {
+    let content = "@severity(\"warning\")
+package(\".\")::file.len should > 50;";
+    let report = check(content, test_dir()).unwrap();
+
+    assert_eq!(1, report.errors.len());
+    assert!(!report.has_failures());
+
+    let report = check("package(\".\")::file.len should > 50;", test_dir()).unwrap();
+    assert!(report.has_failures());
+    assert!(check("class(\"..\") only something;", test_dir()).is_err());
+}
+
+#[test]
+fn should_parse_init_rules() {
+    let rules = parse_rules(INIT_RULES).unwrap();
+    assert_eq!("file-count", rules[0].name);
+}
+
+#[test]
+fn should_check_package_cycles() {
+    let dir = std::env::temp_dir().join("guarding").join("cycles");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("Order.java"), "package shop.order;\nimport shop.payment.Payment;\nclass Order {}").unwrap();
+    fs::write(dir.join("Payment.java"), "package shop.payment;\nimport shop.order.Order;\nclass Payment {}").unwrap();
+    fs::write(dir.join("User.java"), "package shop.user;\nclass User {}").unwrap();
+
+    let report = check("package(\".\")::cycle.len should = 0;", dir.clone()).unwrap();
+    assert_eq!(1, report.errors.len());
+    assert_eq!(vec!["shop.order <-> shop.payment".to_string()], report.errors[0].items);
+
+    let report = check("package(\"..user..\")::cycle.len should = 0;", dir).unwrap();
+    assert_eq!(0, report.errors.len());
+}
+
+#[test]
+fn should_check_source_from_editor() {
+    let rules = parse_rules("class(resideIn \"..service..\")::name should endsWith \"Service\";
+package(\".\")::file.len should > 50;").unwrap();
+    let code = "package shop.service;\nclass Order {}";
+
+    let mut registry = LanguageRegistry::default();
+    let report = check_source(&registry, rules.clone(), Path::new("src/shop/service/Order.java"), code).unwrap();
+    assert_eq!(1, report.rules.len());
+    assert_eq!(1, report.errors.len());
+    assert_eq!("src/shop/service/Order.java", report.files[0].path);
+
+    assert!(check_source(&registry, vec![], Path::new("notes.txt"), "").is_err());
+    registry.map_extension("jsp", "java").unwrap();
+    assert_eq!(1, check_source(&registry, rules, Path::new("src/shop/service/order.jsp"), code).unwrap().errors.len());
+}
+
+#[test]
+fn should_locate_same_named_classes_in_their_files() {
+    let report = check_sources("class::name should endsWith \"Service\";", &[
+        ("billing/Order.java", "package billing;\nclass Order {}"),
+        ("shop/Order.java", "package shop;\nclass Order {}"),
+    ]).unwrap();
+
+    let mut paths: Vec<&str> = report.errors[0].locations.iter().map(|location| location.path.as_str()).collect();
+    paths.sort();
+    assert_eq!(vec!["billing/Order.java", "shop/Order.java"], paths);
+}
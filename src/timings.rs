@@ -0,0 +1,91 @@
+use std::time::{Duration, Instant};
+
+/// How long each phase of a check took, in the order they ran; a phase running again, like
+/// parsing of a second source root, adds to its time.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PhaseTimings {
+    pub phases: Vec<(String, Duration)>,
+    /// the parsed files, for the files per second
+    pub files: usize,
+}
+
+impl PhaseTimings {
+    pub fn time<T>(&mut self, phase: &str, run: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = run();
+        self.add(phase, start.elapsed());
+        result
+    }
+
+    pub fn add(&mut self, phase: &str, duration: Duration) {
+        match self.phases.iter_mut().find(|(name, _)| name == phase) {
+            Some((_, total)) => *total += duration,
+            None => self.phases.push((phase.to_string(), duration)),
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, duration)| *duration).sum()
+    }
+
+    /// one line, like `checked 4210 files in 2.31s`
+    pub fn summary(&self) -> String {
+        format!("checked {} files in {:.2}s", self.files, self.total().as_secs_f64())
+    }
+
+    /// a line per phase with its share of the total
+    pub fn to_text(&self) -> String {
+        let total = self.total().as_secs_f64();
+        let mut text = String::new();
+        for (name, duration) in &self.phases {
+            let seconds = duration.as_secs_f64();
+            let share = if total > 0.0 { seconds / total * 100.0 } else { 0.0 };
+            text.push_str(&format!("{:<8} {:>8.3}s {:>5.1}%", name, seconds, share));
+            if name == "parse" && seconds > 0.0 {
+                text.push_str(&format!("  {:.0} files/s", self.files as f64 / seconds));
+            }
+            text.push('\n');
+        }
+        text.push_str(&format!("{:<8} {:>8.3}s\n", "total", total));
+        text
+    }
+}
+
+/// `[=========>          ] 1200/4210 files`
+pub fn progress_bar(done: usize, total: usize, width: usize) -> String {
+    let filled = if total == 0 { width } else { done.min(total) * width / total };
+    let bar = if filled >= width {
+        "=".repeat(width)
+    } else {
+        format!("{}>{}", "=".repeat(filled), " ".repeat(width - filled - 1))
+    };
+    format!("[{}] {}/{} files", bar, done, total)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use crate::timings::{PhaseTimings, progress_bar};
+
+    #[test]
+    fn should_sum_phases() {
+        let mut timings = PhaseTimings::default();
+        timings.add("scan", Duration::from_millis(100));
+        timings.add("parse", Duration::from_millis(250));
+        timings.add("parse", Duration::from_millis(250));
+        timings.add("rules", Duration::from_millis(400));
+        timings.files = 1000;
+
+        assert_eq!(Duration::from_millis(1000), timings.total());
+        assert_eq!("checked 1000 files in 1.00s", timings.summary());
+        assert_eq!("parse       0.500s  50.0%  2000 files/s", timings.to_text().lines().nth(1).unwrap());
+    }
+
+    #[test]
+    fn should_draw_progress() {
+        assert_eq!("[=====>    ] 50/100 files", progress_bar(50, 100, 10));
+        assert_eq!("[==========] 100/100 files", progress_bar(100, 100, 10));
+        assert_eq!("[>         ] 0/100 files", progress_bar(0, 100, 10));
+    }
+}
@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::channel;
+use std::time::Duration;
+
+use notify::{DebouncedEvent, RecursiveMode, Watcher, watcher};
+
+use guarding_core::domain::code_file::CodeFile;
+use guarding_core::domain::code_project::CodeProject;
+use guarding_ident::{IncrementalParser, LanguageRegistry, ModelBuilder};
+use guarding_parser::ast::{GuardRule, Severity};
+
+use crate::config::GuardingConfig;
+use crate::handler::check_project_with;
+use crate::print_parse_warnings;
+use crate::report::{CheckReport, Violation};
+
+/// The violations which appeared or disappeared between two checks.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ViolationDelta {
+    pub added: Vec<String>,
+    pub fixed: Vec<String>,
+}
+
+impl ViolationDelta {
+    pub fn between(old: &CheckReport, new: &CheckReport) -> ViolationDelta {
+        let old: BTreeSet<String> = old.violation_lines().into_iter().collect();
+        let new: BTreeSet<String> = new.violation_lines().into_iter().collect();
+
+        ViolationDelta {
+            added: new.difference(&old).cloned().collect(),
+            fixed: old.difference(&new).cloned().collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.fixed.is_empty()
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        for line in &self.added {
+            text.push_str(&format!("+ {}\n", line));
+        }
+        for line in &self.fixed {
+            text.push_str(&format!("- {}\n", line));
+        }
+
+        text
+    }
+}
+
+/// Keeps the parsed files of the roots between checks, so a change only re-parses the
+/// changed files (incrementally) and a change of a rule file only re-reads the rules.
+pub struct WatchSession {
+    config: GuardingConfig,
+    registry: LanguageRegistry,
+    parser: IncrementalParser,
+    roots: Vec<PathBuf>,
+    rule_files: Vec<PathBuf>,
+    /// the rules of the config's packs too, unless a rule file was given
+    with_packs: bool,
+    rules: Vec<GuardRule>,
+    /// the files of every root, before the packages and imports are resolved
+    sources: Vec<BTreeMap<PathBuf, CodeFile>>,
+    report: CheckReport,
+}
+
+impl WatchSession {
+    pub fn new(config: GuardingConfig, roots: Option<Vec<PathBuf>>, rule_file: Option<PathBuf>) -> Result<WatchSession, String> {
+        let roots = roots.unwrap_or_else(|| config.source_roots()).iter()
+            .map(|root| fs::canonicalize(root).map_err(|err| format!("can't watch {}: {}", root.display(), err)))
+            .collect::<Result<Vec<PathBuf>, String>>()?;
+        let with_packs = rule_file.is_none();
+        let rule_files = rule_file.map(|file| vec![file]).unwrap_or_else(|| config.rule_files()).iter()
+            .map(|file| fs::canonicalize(file).map_err(|err| format!("can't read rules {}: {}", file.display(), err)))
+            .collect::<Result<Vec<PathBuf>, String>>()?;
+
+        let registry = config.registry();
+        let mut session = WatchSession {
+            config,
+            registry,
+            parser: IncrementalParser::default(),
+            sources: roots.iter().map(|_| BTreeMap::new()).collect(),
+            roots,
+            rule_files,
+            with_packs,
+            rules: vec![],
+            report: CheckReport { rules: vec![], errors: vec![], files: vec![] },
+        };
+
+        session.load_rules()?;
+        for index in 0..session.roots.len() {
+            let paths = session.config.scanner(&session.registry).scan(&session.roots[index]);
+            for path in paths {
+                session.parse(index, &path);
+            }
+        }
+
+        session.report = session.run();
+        Ok(session)
+    }
+
+    pub fn report(&self) -> &CheckReport {
+        &self.report
+    }
+
+    /// the files and directories to watch
+    pub fn watched(&self) -> Vec<PathBuf> {
+        self.roots.iter().chain(self.rule_files.iter()).cloned().collect()
+    }
+
+    /// re-checks after `paths` changed, `None` when none of them is a rule or source file
+    pub fn changed(&mut self, paths: &[PathBuf]) -> Result<Option<ViolationDelta>, String> {
+        let mut affected = false;
+        for path in paths {
+            if self.rule_files.contains(path) {
+                self.load_rules()?;
+                affected = true;
+                continue;
+            }
+
+            let index = match self.roots.iter().position(|root| path.starts_with(root)) {
+                Some(index) => index,
+                None => continue
+            };
+
+            if self.sources[index].contains_key(path) || self.is_scanned(index, path) {
+                if path.is_file() {
+                    self.parse(index, path);
+                } else {
+                    self.sources[index].remove(path);
+                    self.parser.forget(path);
+                }
+                affected = true;
+            }
+        }
+
+        if !affected {
+            return Ok(None);
+        }
+
+        let report = self.run();
+        let delta = ViolationDelta::between(&self.report, &report);
+        self.report = report;
+        Ok(Some(delta))
+    }
+
+    fn load_rules(&mut self) -> Result<(), String> {
+        let mut rules = self.config.load_rule_files(&self.rule_files)?;
+        if self.with_packs {
+            self.config.load_pack_rules(&mut rules)?;
+        }
+        self.rules = rules.into_iter().map(|(_, rule)| rule).collect();
+        Ok(())
+    }
+
+    /// a new file counts only when the scanner would take it, so ignored files stay ignored
+    fn is_scanned(&self, index: usize, path: &Path) -> bool {
+        self.config.scanner(&self.registry).scan(&self.roots[index]).iter().any(|scanned| scanned == path)
+    }
+
+    fn parse(&mut self, index: usize, path: &Path) {
+        let max_file_size = self.config.scanner(&self.registry).max_file_size();
+        let file = match ModelBuilder::read_source(path, max_file_size) {
+            Ok(code) => self.parser.parse(path, code),
+            Err(message) => Some(CodeFile::unparsed(&message)),
+        };
+
+        if let Some(mut file) = file {
+            file.path = ModelBuilder::format_path(path);
+            file.file_name = path.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            self.sources[index].insert(path.to_path_buf(), file);
+        }
+    }
+
+    /// the resolved models of the parsed files, without parsing anything
+    pub fn project(&self) -> CodeProject {
+        let mut files = vec![];
+        for (root, sources) in self.roots.iter().zip(self.sources.iter()) {
+            let mut models: Vec<CodeFile> = sources.values().cloned().collect();
+            ModelBuilder::resolve_models(self.parser.registry(), root, &mut models);
+            files.extend(models);
+        }
+
+        CodeProject::new(files)
+    }
+
+    /// re-reads the rules and every source root, for when it's unknown what changed, like after
+    /// a checkout; the trees of the files are reused, so unchanged ones are parsed again quickly
+    pub fn refresh(&mut self) -> Result<ViolationDelta, String> {
+        self.load_rules()?;
+        for index in 0..self.roots.len() {
+            let paths: BTreeSet<PathBuf> = self.config.scanner(&self.registry).scan(&self.roots[index]).into_iter().collect();
+            let removed: Vec<PathBuf> = self.sources[index].keys().filter(|path| !paths.contains(*path)).cloned().collect();
+            for path in removed {
+                self.sources[index].remove(&path);
+                self.parser.forget(&path);
+            }
+            for path in paths {
+                self.parse(index, &path);
+            }
+        }
+
+        let report = self.run();
+        let delta = ViolationDelta::between(&self.report, &report);
+        self.report = report;
+        Ok(delta)
+    }
+
+    /// the parse warnings are in the files of the report, stdout may be a protocol
+    fn run(&self) -> CheckReport {
+        check_project_with(self.rules.clone(), self.project(), &mut |_: &Violation| {})
+    }
+}
+
+/// checks on every change of the watched files until the process is stopped, printing what
+/// was added and fixed since the last check
+pub fn watch(mut session: WatchSession) -> Result<(), String> {
+    let (sender, receiver) = channel();
+    let mut watcher = watcher(sender, Duration::from_millis(300)).map_err(|err| format!("{}", err))?;
+    for path in session.watched() {
+        watcher.watch(&path, RecursiveMode::Recursive).map_err(|err| format!("can't watch {}: {}", path.display(), err))?;
+    }
+
+    print_parse_warnings(&session.report().files);
+    print!("{}", session.report().to_text());
+    println!("watching for changes...");
+
+    for event in receiver {
+        let paths = match event {
+            DebouncedEvent::Create(path) | DebouncedEvent::Write(path) | DebouncedEvent::Remove(path) => vec![path],
+            DebouncedEvent::Rename(from, to) => vec![from, to],
+            DebouncedEvent::Error(err, _) => {
+                eprintln!("watch error: {}", err);
+                continue;
+            }
+            _ => continue
+        };
+
+        match session.changed(&paths) {
+            Ok(Some(delta)) => {
+                print_parse_warnings(&session.report().files);
+                if delta.is_empty() {
+                    println!("no change in violations");
+                } else {
+                    print!("{}", delta.to_text());
+                }
+                let report = session.report();
+                println!("{} errors, {} warnings", report.count_of(Severity::Error), report.count_of(Severity::Warning));
+            }
+            Ok(None) => {}
+            Err(err) => eprintln!("{}", err),
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use crate::config::GuardingConfig;
+    use crate::watch::WatchSession;
+
+    #[test]
+    fn should_report_delta_of_changed_files() {
+        let dir = env::temp_dir().join("guarding").join("watch");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(dir.join("src")).unwrap();
+        fs::write(dir.join("src").join("OrderService.java"), "class OrderService {}").unwrap();
+        fs::write(dir.join("guarding.guarding"), "@name(\"naming\")\nclass::name should endsWith \"Service\";").unwrap();
+
+        let config = GuardingConfig { dir: dir.clone(), ..Default::default() };
+        let mut session = WatchSession::new(config, Some(vec![dir.join("src")]), None).unwrap();
+        assert_eq!(0, session.report().errors.len());
+
+        let order = fs::canonicalize(dir.join("src")).unwrap().join("Order.java");
+        fs::write(&order, "class Order {}").unwrap();
+        let delta = session.changed(&[order.clone()]).unwrap().unwrap();
+        assert_eq!(1, delta.added.len());
+        assert!(delta.added[0].contains("naming"));
+
+        fs::remove_file(&order).unwrap();
+        let delta = session.changed(&[order]).unwrap().unwrap();
+        assert_eq!(1, delta.fixed.len());
+
+        let unrelated = fs::canonicalize(&dir).unwrap().join("notes.txt");
+        assert!(session.changed(&[unrelated]).unwrap().is_none());
+    }
+}